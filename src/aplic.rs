@@ -0,0 +1,604 @@
+use crate::{
+    bus::{BusError, Device, Platform},
+    cause::Interrupt,
+    imsic::Level,
+};
+
+// Where QEMU's virt machine places the M-level domain of its APLIC, and the number of sources
+// it has.
+pub const DEFAULT_APLIC_BASE: u64 = 0x0c00_0000;
+pub const DEFAULT_APLIC_SOURCES: u32 = 95;
+pub const MAX_APLIC_SOURCES: u32 = 1023;
+
+// The S-level domain, a child of the M-level one, follows it 16 MiB above, as on QEMU's virt
+// machine. A domain has room for the interrupt delivery controls of 512 harts.
+pub const SUPERVISOR_DOMAIN: u64 = 0x100_0000;
+pub const DOMAIN_SIZE: u64 = 0x8000;
+
+// The registers of a domain.
+const DOMAINCFG: u64 = 0x0000;
+const SOURCECFG_BASE: u64 = 0x0004;
+const MMSIADDRCFG: u64 = 0x1bc0;
+const MMSIADDRCFGH: u64 = 0x1bc4;
+const SMSIADDRCFG: u64 = 0x1bc8;
+const SMSIADDRCFGH: u64 = 0x1bcc;
+const SETIP_BASE: u64 = 0x1c00;
+const SETIPNUM: u64 = 0x1cdc;
+const IN_CLRIP_BASE: u64 = 0x1d00;
+const CLRIPNUM: u64 = 0x1ddc;
+const SETIE_BASE: u64 = 0x1e00;
+const SETIENUM: u64 = 0x1edc;
+const CLRIE_BASE: u64 = 0x1f00;
+const CLRIENUM: u64 = 0x1fdc;
+const SETIPNUM_LE: u64 = 0x2000;
+const SETIPNUM_BE: u64 = 0x2004;
+const GENMSI: u64 = 0x3000;
+const TARGET_BASE: u64 = 0x3004;
+// the interrupt delivery control of each hart, in direct delivery mode
+const IDC_BASE: u64 = 0x4000;
+const IDC_SIZE: u64 = 0x20;
+const IDELIVERY: u64 = 0x00;
+const IFORCE: u64 = 0x04;
+const ITHRESHOLD: u64 = 0x08;
+const TOPI: u64 = 0x18;
+const CLAIMI: u64 = 0x1c;
+
+// the most significant byte of domaincfg reads as 0x80, which tells its endianness apart
+const DOMAINCFG_FIXED: u32 = 0x80 << 24;
+const DOMAINCFG_IE: u32 = 1 << 8;
+const DOMAINCFG_DM: u32 = 1 << 2;
+
+const SOURCECFG_D: u32 = 1 << 10;
+
+// The source modes: how the rectified input is taken from the line, if at all.
+const INACTIVE: u32 = 0;
+const DETACHED: u32 = 1;
+const EDGE1: u32 = 4;
+const EDGE0: u32 = 5;
+const LEVEL1: u32 = 6;
+const LEVEL0: u32 = 7;
+
+// The writable fields of the MSI address configuration: the lock, the widths and shifts of the
+// hart and group indices in the address, and the page number of the interrupt file of hart 0.
+const MMSIADDRCFGH_MASK: u32 = 0x9f77_7fff;
+const SMSIADDRCFGH_MASK: u32 = 0x0070_0fff;
+const MSIADDRCFGH_L: u32 = 1 << 31;
+
+// The fields of target and genmsi.
+const TARGET_HART_SHIFT: u32 = 18;
+const TARGET_IPRIO: u32 = 0xff;
+const TARGET_EIID: u32 = 0x7ff;
+
+#[derive(Clone, Default)]
+struct Source {
+    // to the S-level domain
+    delegated: bool,
+    mode: u32,
+    pending: bool,
+    enabled: bool,
+    target: u32,
+    // whether the device asserts the line
+    input: bool,
+}
+
+impl Source {
+    fn level(&self) -> Level {
+        match self.delegated {
+            true => Level::Supervisor,
+            false => Level::Machine,
+        }
+    }
+
+    fn rectified(&self) -> bool {
+        match self.mode {
+            EDGE1 | LEVEL1 => self.input,
+            EDGE0 | LEVEL0 => !self.input,
+            _ => false,
+        }
+    }
+
+    fn level_sensitive(&self) -> bool {
+        matches!(self.mode, LEVEL1 | LEVEL0)
+    }
+
+    // A rising edge of the rectified input, as the line or the mode changes, sets the pending
+    // bit, unless the source follows a level in direct delivery mode.
+    fn detect_edge(&mut self, before: bool, msi: bool) {
+        if !before && self.rectified() && (!self.level_sensitive() || msi) {
+            self.pending = true;
+        }
+    }
+
+    fn hart(&self) -> usize {
+        (self.target >> TARGET_HART_SHIFT) as usize
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Idc {
+    delivery: bool,
+    force: bool,
+    threshold: u32,
+}
+
+struct Domain {
+    enabled: bool,
+    // whether interrupts are delivered as MSIs, or else directly to the harts
+    msi: bool,
+    genmsi: u32,
+    idcs: Vec<Idc>,
+    // whether each hart is interrupted in direct delivery mode, kept up to date
+    interrupt: Vec<bool>,
+}
+
+impl Domain {
+    fn new(harts: usize) -> Self {
+        Self {
+            enabled: false,
+            msi: false,
+            genmsi: 0,
+            idcs: vec![Idc::default(); harts],
+            interrupt: vec![false; harts],
+        }
+    }
+}
+
+// The advanced platform-level interrupt controller, with a domain for M-mode and a child domain
+// for S-mode, to which the M-level domain delegates sources. Each domain delivers the interrupts
+// of its sources either directly to the harts, through MEIP or SEIP, or as MSIs to the interrupt
+// files of an IMSIC.
+pub struct Aplic {
+    // by source, where source 0 is unused
+    sources: Vec<Source>,
+    machine: Domain,
+    supervisor: Domain,
+    mmsiaddrcfg: u32,
+    mmsiaddrcfgh: u32,
+    smsiaddrcfg: u32,
+    smsiaddrcfgh: u32,
+    // the lines asserted at the last step
+    lines: Vec<u32>,
+    messages: Vec<(u64, u32)>,
+}
+
+impl Aplic {
+    pub fn new(harts: usize, sources: u32) -> Self {
+        let sources = sources.min(MAX_APLIC_SOURCES);
+        Self {
+            sources: vec![Source::default(); sources as usize + 1],
+            machine: Domain::new(harts),
+            supervisor: Domain::new(harts),
+            mmsiaddrcfg: 0,
+            mmsiaddrcfgh: 0,
+            smsiaddrcfg: 0,
+            smsiaddrcfgh: 0,
+            lines: Vec::new(),
+            messages: Vec::new(),
+        }
+    }
+
+    fn domain(&self, level: Level) -> &Domain {
+        match level {
+            Level::Machine => &self.machine,
+            Level::Supervisor => &self.supervisor,
+        }
+    }
+
+    fn domain_mut(&mut self, level: Level) -> &mut Domain {
+        match level {
+            Level::Machine => &mut self.machine,
+            Level::Supervisor => &mut self.supervisor,
+        }
+    }
+
+    // The source, if it exists and belongs to the domain. A domain sees the sources that it
+    // does not own as inactive.
+    fn source(&mut self, level: Level, source: u32) -> Option<&mut Source> {
+        match self.sources.get_mut(source as usize) {
+            Some(s) if source != 0 && s.level() == level => Some(s),
+            _ => None,
+        }
+    }
+
+    // The address of the interrupt file of a hart at a level, from the MSI address
+    // configuration.
+    fn msi_address(&self, level: Level, hart: usize) -> u64 {
+        let high = self.mmsiaddrcfgh as u64;
+        let lhxw = (high >> 12) & 0xf;
+        let hhxw = (high >> 16) & 0x7;
+        let hhxs = (high >> 24) & 0x1f;
+        let (ppn, lhxs) = match level {
+            Level::Machine => (
+                (high & 0xfff) << 32 | self.mmsiaddrcfg as u64,
+                (high >> 20) & 0x7,
+            ),
+            Level::Supervisor => {
+                let high = self.smsiaddrcfgh as u64;
+                (
+                    (high & 0xfff) << 32 | self.smsiaddrcfg as u64,
+                    (high >> 20) & 0x7,
+                )
+            }
+        };
+        let hart = hart as u64;
+        let group = (hart >> lhxw) & ((1 << hhxw) - 1);
+        let index = hart & ((1 << lhxw) - 1);
+        (ppn | group << (hhxs + 12) | index << lhxs) << 12
+    }
+
+    fn send_msi(&mut self, level: Level, hart: usize, eiid: u32) {
+        let address = self.msi_address(level, hart);
+        self.messages.push((address, eiid));
+    }
+
+    // Software can set the pending bit of a source, except when it follows a level in direct
+    // delivery mode, or a low level in MSI delivery mode.
+    fn set_pending(&mut self, level: Level, source: u32) {
+        let msi = self.domain(level).msi;
+        if let Some(s) = self.source(level, source) {
+            let allowed = match s.mode {
+                INACTIVE => false,
+                LEVEL1 | LEVEL0 => msi && s.rectified(),
+                _ => true,
+            };
+            s.pending |= allowed;
+        }
+    }
+
+    fn clear_pending(&mut self, level: Level, source: u32) {
+        let msi = self.domain(level).msi;
+        if let Some(s) = self.source(level, source) {
+            if !s.level_sensitive() || msi {
+                s.pending = false;
+            }
+        }
+    }
+
+    fn set_enabled(&mut self, level: Level, source: u32, enabled: bool) {
+        if let Some(s) = self.source(level, source) {
+            s.enabled = enabled && s.mode != INACTIVE;
+        }
+    }
+
+    // The enabled pending source targeting the hart with the highest priority, which is the
+    // lowest number, above the threshold if one is set. Returns the source and its priority.
+    fn top(&self, level: Level, hart: usize) -> Option<(u32, u32)> {
+        let domain = self.domain(level);
+        let threshold = domain.idcs.get(hart)?.threshold;
+        (1..self.sources.len() as u32)
+            .filter_map(|source| {
+                let s = &self.sources[source as usize];
+                let priority = s.target & TARGET_IPRIO;
+                let eligible = s.level() == level
+                    && s.pending
+                    && s.enabled
+                    && s.hart() == hart
+                    && (threshold == 0 || priority < threshold);
+                eligible.then_some((source, priority))
+            })
+            .min_by_key(|&(source, priority)| (priority, source))
+    }
+
+    fn topi(&self, level: Level, hart: usize) -> u32 {
+        match self.top(level, hart) {
+            Some((source, priority)) => source << 16 | priority,
+            None => 0,
+        }
+    }
+
+    // Claims the top interrupt of the hart in direct delivery mode, or else clears iforce.
+    fn claim(&mut self, level: Level, hart: usize) -> u32 {
+        let topi = self.topi(level, hart);
+        match topi >> 16 {
+            0 => {
+                if let Some(idc) = self.domain_mut(level).idcs.get_mut(hart) {
+                    idc.force = false;
+                }
+            }
+            source => self.sources[source as usize].pending = false,
+        }
+        topi
+    }
+
+    // Brings the pending bits in line with the levels of the lines, forwards the pending
+    // interrupts of the domains in MSI delivery mode, and finds which harts are interrupted by
+    // the domains in direct delivery mode.
+    fn update(&mut self) {
+        for source in 1..self.sources.len() {
+            let msi = self.domain(self.sources[source].level()).msi;
+            let s = &mut self.sources[source];
+            if s.level_sensitive() {
+                // a level is pending while it is asserted in direct delivery mode, and is
+                // cleared when it is deasserted in MSI delivery mode
+                if !s.rectified() {
+                    s.pending = false;
+                } else if !msi {
+                    s.pending = true;
+                }
+            }
+        }
+        for level in [Level::Machine, Level::Supervisor] {
+            let domain = self.domain(level);
+            if domain.enabled && domain.msi {
+                let mut forwarded = Vec::new();
+                for s in &mut self.sources[1..] {
+                    if s.level() == level && s.pending && s.enabled {
+                        s.pending = false;
+                        forwarded.push((s.hart(), s.target & TARGET_EIID));
+                    }
+                }
+                for (hart, eiid) in forwarded {
+                    self.send_msi(level, hart, eiid);
+                }
+            }
+            let harts = self.domain(level).idcs.len();
+            for hart in 0..harts {
+                let domain = self.domain(level);
+                let idc = domain.idcs[hart];
+                let interrupt = domain.enabled
+                    && !domain.msi
+                    && idc.delivery
+                    && (idc.force || self.top(level, hart).is_some());
+                self.domain_mut(level).interrupt[hart] = interrupt;
+            }
+        }
+    }
+
+    // Samples the lines.
+    fn sample(&mut self, lines: &[u32]) {
+        for source in 1..self.sources.len() {
+            let msi = self.domain(self.sources[source].level()).msi;
+            let s = &mut self.sources[source];
+            let before = s.rectified();
+            s.input = lines.contains(&(source as u32));
+            s.detect_edge(before, msi);
+        }
+    }
+
+    fn read_sourcecfg(&self, level: Level, source: u32) -> u32 {
+        let Some(s) = self.sources.get(source as usize) else {
+            return 0;
+        };
+        match (level, s.delegated) {
+            // the S-level domain is child 0
+            (Level::Machine, true) => SOURCECFG_D,
+            (Level::Machine, false) | (Level::Supervisor, true) => s.mode,
+            (Level::Supervisor, false) => 0,
+        }
+    }
+
+    fn write_sourcecfg(&mut self, level: Level, source: u32, value: u32) {
+        if source as usize >= self.sources.len() {
+            return;
+        }
+        let msi = self.domain(level).msi;
+        let s = &mut self.sources[source as usize];
+        // only the M-level domain has a child to delegate to, and a source is reset when it
+        // moves between the domains
+        if level == Level::Machine {
+            let delegated = value & SOURCECFG_D != 0;
+            if delegated != s.delegated {
+                *s = Source {
+                    delegated,
+                    input: s.input,
+                    ..Default::default()
+                };
+            }
+        }
+        if s.level() != level {
+            return;
+        }
+        let before = s.rectified();
+        s.mode = match value & 0b111 {
+            mode @ (DETACHED | EDGE1 | EDGE0 | LEVEL1 | LEVEL0) => mode,
+            _ => INACTIVE,
+        };
+        if s.mode == INACTIVE {
+            s.pending = false;
+            s.enabled = false;
+        }
+        s.detect_edge(before, msi);
+    }
+
+    fn write_target(&mut self, level: Level, source: u32, value: u32) {
+        let msi = self.domain(level).msi;
+        if let Some(s) = self.source(level, source) {
+            let hart = value & !((1 << TARGET_HART_SHIFT) - 1);
+            s.target = match msi {
+                // the guest index is zero, as the harts have no guest interrupt files
+                true => hart | value & TARGET_EIID,
+                // priority 0 is taken as 1
+                false => hart | (value & TARGET_IPRIO).max(1),
+            };
+        }
+    }
+
+    // Applies `f` to each source with its bit set in a word of a bit set register.
+    fn for_bits(&mut self, word: u64, value: u32, mut f: impl FnMut(&mut Self, u32)) {
+        for bit in 0..32 {
+            if value >> bit & 1 == 1 {
+                f(self, 32 * word as u32 + bit);
+            }
+        }
+    }
+
+    fn read_bits(&self, level: Level, word: u64, bit: impl Fn(&Source) -> bool) -> u32 {
+        (0..32).fold(0, |value, i| {
+            let source = 32 * word as usize + i;
+            let set = source != 0
+                && self
+                    .sources
+                    .get(source)
+                    .is_some_and(|s| s.level() == level && bit(s));
+            value | (set as u32) << i
+        })
+    }
+
+    fn read_register(&mut self, level: Level, offset: u64) -> u32 {
+        let root = level == Level::Machine;
+        let word = |base: u64| (offset - base) / 4;
+        match offset {
+            DOMAINCFG => {
+                let domain = self.domain(level);
+                let ie = if domain.enabled { DOMAINCFG_IE } else { 0 };
+                let dm = if domain.msi { DOMAINCFG_DM } else { 0 };
+                DOMAINCFG_FIXED | ie | dm
+            }
+            SOURCECFG_BASE..MMSIADDRCFG => {
+                self.read_sourcecfg(level, word(SOURCECFG_BASE) as u32 + 1)
+            }
+            MMSIADDRCFG if root => self.mmsiaddrcfg,
+            MMSIADDRCFGH if root => self.mmsiaddrcfgh,
+            SMSIADDRCFG if root => self.smsiaddrcfg,
+            SMSIADDRCFGH if root => self.smsiaddrcfgh,
+            SETIP_BASE..SETIPNUM => self.read_bits(level, word(SETIP_BASE), |s| s.pending),
+            IN_CLRIP_BASE..CLRIPNUM => {
+                self.read_bits(level, word(IN_CLRIP_BASE), Source::rectified)
+            }
+            SETIE_BASE..SETIENUM => self.read_bits(level, word(SETIE_BASE), |s| s.enabled),
+            // MSIs are sent at once, so genmsi is never busy
+            GENMSI => self.domain(level).genmsi,
+            TARGET_BASE..IDC_BASE => {
+                let source = word(TARGET_BASE) as u32 + 1;
+                self.source(level, source).map_or(0, |s| s.target)
+            }
+            IDC_BASE.. => {
+                let hart = ((offset - IDC_BASE) / IDC_SIZE) as usize;
+                let Some(&idc) = self.domain(level).idcs.get(hart) else {
+                    return 0;
+                };
+                match (offset - IDC_BASE) % IDC_SIZE {
+                    IDELIVERY => idc.delivery as u32,
+                    IFORCE => idc.force as u32,
+                    ITHRESHOLD => idc.threshold,
+                    TOPI => self.topi(level, hart),
+                    CLAIMI => self.claim(level, hart),
+                    _ => 0,
+                }
+            }
+            _ => 0,
+        }
+    }
+
+    fn write_register(&mut self, level: Level, offset: u64, value: u32) {
+        let root = level == Level::Machine;
+        let locked = self.mmsiaddrcfgh & MSIADDRCFGH_L != 0;
+        let word = |base: u64| (offset - base) / 4;
+        match offset {
+            DOMAINCFG => {
+                let domain = self.domain_mut(level);
+                domain.enabled = value & DOMAINCFG_IE != 0;
+                domain.msi = value & DOMAINCFG_DM != 0;
+            }
+            SOURCECFG_BASE..MMSIADDRCFG => {
+                self.write_sourcecfg(level, word(SOURCECFG_BASE) as u32 + 1, value)
+            }
+            MMSIADDRCFG if root && !locked => self.mmsiaddrcfg = value,
+            MMSIADDRCFGH if root && !locked => self.mmsiaddrcfgh = value & MMSIADDRCFGH_MASK,
+            SMSIADDRCFG if root && !locked => self.smsiaddrcfg = value,
+            SMSIADDRCFGH if root && !locked => self.smsiaddrcfgh = value & SMSIADDRCFGH_MASK,
+            SETIP_BASE..SETIPNUM => {
+                self.for_bits(word(SETIP_BASE), value, |a, s| a.set_pending(level, s))
+            }
+            SETIPNUM | SETIPNUM_LE => self.set_pending(level, value),
+            SETIPNUM_BE => self.set_pending(level, value.swap_bytes()),
+            IN_CLRIP_BASE..CLRIPNUM => {
+                self.for_bits(word(IN_CLRIP_BASE), value, |a, s| a.clear_pending(level, s))
+            }
+            CLRIPNUM => self.clear_pending(level, value),
+            SETIE_BASE..SETIENUM => self.for_bits(word(SETIE_BASE), value, |a, s| {
+                a.set_enabled(level, s, true)
+            }),
+            SETIENUM => self.set_enabled(level, value, true),
+            CLRIE_BASE..CLRIENUM => self.for_bits(word(CLRIE_BASE), value, |a, s| {
+                a.set_enabled(level, s, false)
+            }),
+            CLRIENUM => self.set_enabled(level, value, false),
+            GENMSI => {
+                let genmsi = value & (!((1 << TARGET_HART_SHIFT) - 1) | TARGET_EIID);
+                self.domain_mut(level).genmsi = genmsi;
+                if self.domain(level).msi {
+                    let hart = (genmsi >> TARGET_HART_SHIFT) as usize;
+                    self.send_msi(level, hart, genmsi & TARGET_EIID);
+                }
+            }
+            TARGET_BASE..IDC_BASE => self.write_target(level, word(TARGET_BASE) as u32 + 1, value),
+            IDC_BASE.. => {
+                let hart = ((offset - IDC_BASE) / IDC_SIZE) as usize;
+                let register = (offset - IDC_BASE) % IDC_SIZE;
+                let Some(idc) = self.domain_mut(level).idcs.get_mut(hart) else {
+                    return;
+                };
+                match register {
+                    IDELIVERY => idc.delivery = value & 1 == 1,
+                    IFORCE => idc.force = value & 1 == 1,
+                    ITHRESHOLD => idc.threshold = value & TARGET_IPRIO,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // The domain of the register at `offset`, and its offset within the domain.
+    fn domain_offset(offset: u64) -> Option<(Level, u64)> {
+        let (level, offset) = match offset.checked_sub(SUPERVISOR_DOMAIN) {
+            Some(offset) => (Level::Supervisor, offset),
+            None => (Level::Machine, offset),
+        };
+        (offset < DOMAIN_SIZE).then_some((level, offset))
+    }
+}
+
+impl Device for Aplic {
+    fn size(&self) -> u64 {
+        SUPERVISOR_DOMAIN + DOMAIN_SIZE
+    }
+
+    // The registers are accessed as 32-bit words, at the aligned address of the access.
+    fn read(&mut self, offset: u64, _: usize) -> Result<u64, BusError> {
+        let Some((level, offset)) = Self::domain_offset(offset & !0b11) else {
+            return Ok(0);
+        };
+        let value = self.read_register(level, offset);
+        self.update();
+        Ok(value as u64)
+    }
+
+    fn write(&mut self, offset: u64, _: usize, value: u64) -> Result<(), BusError> {
+        if let Some((level, offset)) = Self::domain_offset(offset & !0b11) {
+            self.write_register(level, offset, value as u32);
+            self.update();
+        }
+        Ok(())
+    }
+}
+
+impl Platform for Aplic {
+    fn set_interrupt_lines(&mut self, lines: &[u32]) {
+        // on most steps, no line is asserted
+        if lines.is_empty() && self.lines.is_empty() {
+            return;
+        }
+        if self.lines != lines {
+            self.lines = lines.to_vec();
+            self.sample(lines);
+            self.update();
+        }
+    }
+
+    fn hart_interrupts(&self, hart: usize) -> u64 {
+        let (Some(&machine), Some(&supervisor)) = (
+            self.machine.interrupt.get(hart),
+            self.supervisor.interrupt.get(hart),
+        ) else {
+            return 0;
+        };
+        (machine as u64) << Interrupt::MachineExternal.to_primitive()
+            | (supervisor as u64) << Interrupt::SupervisorExternal.to_primitive()
+    }
+
+    fn take_messages(&mut self) -> Vec<(u64, u32)> {
+        std::mem::take(&mut self.messages)
+    }
+}
@@ -1,3 +1,4 @@
+pub const MASK_5BIT: u64 = 0b11111;
 pub const MASK_6BIT: u64 = 0b111111;
 pub const MASK_12BIT: u64 = 0b111111111111;
 
@@ -0,0 +1,48 @@
+use crate::xlen::Xlen;
+
+pub const BOOT_ROM_BASE: u64 = 0x1000;
+
+// The struct fw_dynamic_info that OpenSBI's fw_dynamic finds at a2: its magic number, version 2,
+// the address and mode of the next stage, which is the kernel in S-mode, no options, and any hart
+// to boot on.
+const FW_DYNAMIC_INFO_MAGIC: u64 = 0x4942_534f;
+const FW_DYNAMIC_INFO_VERSION: u64 = 2;
+const FW_DYNAMIC_INFO_NEXT_MODE_S: u64 = 1;
+
+// The code of the boot ROM, where the harts start: it jumps to the entry point of the payload,
+// stored after the code, with the hart ID in a0, the address of the device tree, stored after
+// the entry point, in a1, and the information for fw_dynamic about the kernel, stored last, in
+// a2, as Spike and QEMU do.
+pub fn boot_rom(xlen: Xlen, entry: u64, fdt: u64, kernel: u64) -> Vec<u8> {
+    let (load_fdt, load_entry) = match xlen {
+        // lw a1, 32(t0); lw t0, 24(t0)
+        Xlen::Rv32 => (0x0202a583, 0x0182a283),
+        // ld a1, 32(t0); ld t0, 24(t0)
+        Xlen::Rv64 => (0x0202b583, 0x0182b283),
+    };
+    let code: [u32; 6] = [
+        // auipc t0, 0
+        0x00000297, // addi a2, t0, 40
+        0x02828613, // csrr a0, mhartid
+        0xf1402573, load_fdt, load_entry, // jr t0
+        0x00028067,
+    ];
+    let mut rom: Vec<u8> = code.iter().flat_map(|i| i.to_le_bytes()).collect();
+    rom.extend(entry.to_le_bytes());
+    rom.extend(fdt.to_le_bytes());
+    let info = [
+        FW_DYNAMIC_INFO_MAGIC,
+        FW_DYNAMIC_INFO_VERSION,
+        kernel,
+        FW_DYNAMIC_INFO_NEXT_MODE_S,
+        0,
+        xlen.zero_extend(u64::MAX),
+    ];
+    for word in info {
+        match xlen {
+            Xlen::Rv32 => rom.extend((word as u32).to_le_bytes()),
+            Xlen::Rv64 => rom.extend(word.to_le_bytes()),
+        }
+    }
+    rom
+}
@@ -0,0 +1,415 @@
+use std::{collections::HashMap, mem};
+
+use crate::{
+    cause::{Cause, Exception},
+    imsic::{InterruptFile, Level},
+    memory::Memory,
+    mmu::{Access, Translation, PAGE_SIZE},
+};
+
+// A device refused an access, which the hart takes as an access fault.
+#[derive(Debug)]
+pub struct BusError;
+
+// A memory-mapped device. The bus passes it the accesses that fall within its `size` bytes, as
+// offsets from its base address.
+pub trait Device {
+    fn size(&self) -> u64;
+
+    // Reads `size` bytes (1, 2, 4 or 8), which may have side effects such as popping a FIFO.
+    fn read(&mut self, offset: u64, size: usize) -> Result<u64, BusError>;
+
+    // Writes the low `size` bytes (1, 2, 4 or 8) of `value`.
+    fn write(&mut self, offset: u64, size: usize, value: u64) -> Result<(), BusError>;
+
+    // Advances the device by a step, in which each hart runs an instruction.
+    fn tick(&mut self) {}
+
+    // Whether the device asserts its interrupt line, which is level-triggered.
+    fn interrupt(&self) -> bool {
+        false
+    }
+}
+
+// The devices of the platform, which reach past their registers: into the memory, to the harts
+// and to the other devices.
+pub(crate) trait Platform: Device {
+    // Accesses the memory directly after each step, as a device that masters the bus does, such
+    // as to process the buffers that a driver has made available.
+    fn dma(&mut self, _memory: &mut Memory) {}
+
+    // The interrupts that the device raises directly at a hart, as bits of mip, such as the
+    // timer and software interrupts of a CLINT.
+    fn hart_interrupts(&self, _hart: usize) -> u64 {
+        0
+    }
+
+    // Samples the interrupt lines that the devices assert after each step, as an interrupt
+    // controller does.
+    fn set_interrupt_lines(&mut self, _lines: &[u32]) {}
+
+    // The time of the platform that the time CSR shows, if the device keeps it.
+    fn time(&self) -> Option<u64> {
+        None
+    }
+
+    // The interrupt file of a hart at a privilege level, which the hart accesses through its
+    // CSRs, if the device is an IMSIC.
+    fn interrupt_file(&mut self, _hart: usize, _level: Level) -> Option<&mut InterruptFile> {
+        None
+    }
+
+    // The MSIs that the device has sent since the last step, as the addresses and data of
+    // 32-bit writes, which the bus performs after the step.
+    fn take_messages(&mut self) -> Vec<(u64, u32)> {
+        Vec::new()
+    }
+}
+
+// A device attached from outside the crate, which is no more than its registers.
+struct External(Box<dyn Device>);
+
+impl Device for External {
+    fn size(&self) -> u64 {
+        self.0.size()
+    }
+
+    fn read(&mut self, offset: u64, size: usize) -> Result<u64, BusError> {
+        self.0.read(offset, size)
+    }
+
+    fn write(&mut self, offset: u64, size: usize, value: u64) -> Result<(), BusError> {
+        self.0.write(offset, size, value)
+    }
+
+    fn tick(&mut self) {
+        self.0.tick()
+    }
+
+    fn interrupt(&self) -> bool {
+        self.0.interrupt()
+    }
+}
+
+impl Platform for External {}
+
+struct Slot {
+    base: u64,
+    device: Box<dyn Platform>,
+    // the interrupt line of the device, if it is wired to one
+    irq: Option<u32>,
+}
+
+impl Slot {
+    fn contains(&self, address: u64, size: usize) -> bool {
+        address >= self.base
+            && address
+                .checked_add(size as u64)
+                .is_some_and(|end| end <= self.base + self.device.size())
+    }
+}
+
+// Routes the physical addresses to the memory, or to the devices between its regions.
+pub struct Bus {
+    memory: Memory,
+    slots: Vec<Slot>,
+    // a doubleword whose stores are recorded, and which of its words, low and high, they wrote
+    watch: Option<u64>,
+    watched: (bool, bool),
+    // the physical address reserved by the last LR of each hart, which a store to its
+    // doubleword clears
+    reservations: HashMap<u64, u64>,
+    // the translation of the hart that steps
+    translation: Option<Translation>,
+}
+
+impl Bus {
+    pub fn new(memory: Memory) -> Self {
+        Self {
+            memory,
+            slots: Vec::new(),
+            watch: None,
+            watched: (false, false),
+            reservations: HashMap::new(),
+            translation: None,
+        }
+    }
+
+    // Records the stores to the doubleword at `address`, such as tohost, which RV32 writes in
+    // halves.
+    pub fn watch(&mut self, address: u64) {
+        self.watch = Some(address);
+    }
+
+    // Which words of the watched doubleword, low and high, have been stored to since the last
+    // call.
+    pub fn take_watched(&mut self) -> (bool, bool) {
+        mem::take(&mut self.watched)
+    }
+
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    pub fn memory_mut(&mut self) -> &mut Memory {
+        &mut self.memory
+    }
+
+    // Maps `device` at `base`, with its interrupt line wired to `irq`. Fails if it overlaps the
+    // memory or another device.
+    pub fn attach(
+        &mut self,
+        base: u64,
+        device: Box<dyn Device>,
+        irq: Option<u32>,
+    ) -> Result<(), String> {
+        self.attach_platform(base, Box::new(External(device)), irq)
+    }
+
+    pub(crate) fn attach_platform(
+        &mut self,
+        base: u64,
+        device: Box<dyn Platform>,
+        irq: Option<u32>,
+    ) -> Result<(), String> {
+        let end = base
+            .checked_add(device.size())
+            .ok_or(format!("the device at {:#x} is too large", base))?;
+        let overlaps_memory = self.memory.overlaps(base, end);
+        let overlaps_device = self
+            .slots
+            .iter()
+            .any(|slot| slot.base < end && base < slot.base + slot.device.size());
+        if overlaps_memory || overlaps_device {
+            return Err(format!(
+                "the device at {:#x} overlaps the memory or another device",
+                base
+            ));
+        }
+        self.slots.push(Slot { base, device, irq });
+        Ok(())
+    }
+
+    fn slot(&mut self, address: u64, size: usize) -> Option<&mut Slot> {
+        self.slots
+            .iter_mut()
+            .find(|slot| slot.contains(address, size))
+    }
+
+    // Sets the translation of the virtual addresses of the hart that is about to step, or None
+    // for Bare, in which they are the physical addresses.
+    pub(crate) fn set_translation(&mut self, translation: Option<Translation>) {
+        self.translation = translation;
+    }
+
+    fn translate(&mut self, address: u64, access: Access) -> Result<u64, Cause> {
+        match &self.translation {
+            Some(translation) => translation.translate(&mut self.memory, address, access),
+            None => Ok(address),
+        }
+    }
+
+    // Translates the `size` bytes at `address`, which a misaligned access may spread over two
+    // pages. Returns the physical address of the first byte, the number of bytes that are on its
+    // page, and the physical address of the rest on the next page.
+    fn translate_bytes(
+        &mut self,
+        address: u64,
+        size: usize,
+        access: Access,
+    ) -> Result<(u64, usize, u64), Cause> {
+        let physical = self.translate(address, access)?;
+        let split = (PAGE_SIZE - address % PAGE_SIZE) as usize;
+        if self.translation.is_none() || split >= size {
+            return Ok((physical, size, physical));
+        }
+        let next = self.translate(address.wrapping_add(split as u64), access)?;
+        Ok((physical, split, next))
+    }
+
+    // Loads `size` bytes (1, 2, 4 or 8) in little-endian order.
+    pub fn load_bytes(&mut self, address: u64, size: usize) -> Result<u64, Cause> {
+        let (physical, split, next) = self.translate_bytes(address, size, Access::Load)?;
+        let fault = |_| Access::Load.access_fault(address);
+        if split == size {
+            return self.load_physical(physical, size).map_err(fault);
+        }
+        // an access that crosses into another page is made a byte at a time
+        (0..size).rev().try_fold(0, |value, i| {
+            let byte = match i < split {
+                true => self.load_physical(physical + i as u64, 1),
+                false => self.load_physical(next + (i - split) as u64, 1),
+            };
+            Ok(value << 8 | byte.map_err(fault)?)
+        })
+    }
+
+    // Stores the low `size` bytes (1, 2, 4 or 8) of `value` in little-endian order.
+    pub fn store_bytes(&mut self, address: u64, size: usize, value: u64) -> Result<(), Cause> {
+        let (physical, split, next) = self.translate_bytes(address, size, Access::Store)?;
+        let fault = |_| Access::Store.access_fault(address);
+        if split == size {
+            return self.store_physical(physical, size, value).map_err(fault);
+        }
+        for i in 0..size {
+            let byte = value >> (8 * i);
+            match i < split {
+                true => self.store_physical(physical + i as u64, 1, byte),
+                false => self.store_physical(next + (i - split) as u64, 1, byte),
+            }
+            .map_err(fault)?;
+        }
+        Ok(())
+    }
+
+    fn load_physical(&mut self, address: u64, size: usize) -> Result<u64, Cause> {
+        if let Ok(value) = self.memory.load_bytes(address, size) {
+            return Ok(value);
+        }
+        let slot = self
+            .slot(address, size)
+            .ok_or(Cause::Exception(Exception::LoadAccessFault(address)))?;
+        slot.device
+            .read(address - slot.base, size)
+            .map_err(|_| Cause::Exception(Exception::LoadAccessFault(address)))
+    }
+
+    fn store_physical(&mut self, address: u64, size: usize, value: u64) -> Result<(), Cause> {
+        if let Some(watch) = self.watch {
+            let end = address.saturating_add(size as u64);
+            self.watched.0 |= address < watch + 4 && watch < end;
+            self.watched.1 |= address < watch + 8 && watch + 4 < end;
+        }
+        let end = address.saturating_add(size as u64);
+        self.reservations
+            .retain(|_, reserved| end <= *reserved & !7 || (*reserved & !7) + 8 <= address);
+        if self.memory.store_bytes(address, size, value).is_ok() {
+            return Ok(());
+        }
+        let slot = self
+            .slot(address, size)
+            .ok_or(Cause::Exception(Exception::StoreAmoAccessFault(address)))?;
+        slot.device
+            .write(address - slot.base, size, value)
+            .map_err(|_| Cause::Exception(Exception::StoreAmoAccessFault(address)))
+    }
+
+    // Loads as LR does, reserving the physical address for the hart. The atomic accesses are
+    // aligned, so they stay within a page.
+    pub fn load_reserved(&mut self, hart: u64, address: u64, size: usize) -> Result<u64, Cause> {
+        let physical = self.translate(address, Access::Load)?;
+        let value = self
+            .load_physical(physical, size)
+            .map_err(|_| Access::Load.access_fault(address))?;
+        self.reservations.insert(hart, physical);
+        Ok(value)
+    }
+
+    // Stores as SC does, if the hart still holds the reservation of the address, which it gives
+    // up either way. Returns whether the value has been stored.
+    pub fn store_conditional(
+        &mut self,
+        hart: u64,
+        address: u64,
+        size: usize,
+        value: u64,
+    ) -> Result<bool, Cause> {
+        let physical = self.translate(address, Access::Store)?;
+        if self.reservations.remove(&hart) != Some(physical) {
+            return Ok(false);
+        }
+        self.store_physical(physical, size, value)
+            .map_err(|_| Access::Store.access_fault(address))?;
+        Ok(true)
+    }
+
+    // Replaces the value at `address` with `operation` applied to it, as an AMO does, and
+    // returns the value that was there. A fault is a store fault, even on the load.
+    pub fn amo(
+        &mut self,
+        address: u64,
+        size: usize,
+        operation: impl FnOnce(u64) -> u64,
+    ) -> Result<u64, Cause> {
+        let physical = self.translate(address, Access::Store)?;
+        let fault = |_| Access::Store.access_fault(address);
+        let value = self.load_physical(physical, size).map_err(fault)?;
+        self.store_physical(physical, size, operation(value))
+            .map_err(fault)?;
+        Ok(value)
+    }
+
+    // Instructions are fetched from the memory only, 2 or 4 bytes at a time, which stay within
+    // a page.
+    pub fn fetch(&mut self, address: u64, size: usize) -> Result<u32, Cause> {
+        let physical = self.translate(address, Access::Fetch)?;
+        self.memory
+            .fetch(physical, size)
+            .map_err(|_| Access::Fetch.access_fault(address))
+    }
+
+    // Cache-block operations apply to the memory only, and fault as stores. Managing a block
+    // needs the permission to load from it, and zeroing it that to store to it.
+    pub fn check_block(&mut self, address: u64) -> Result<(), Cause> {
+        let physical = self
+            .translate(address, Access::Load)
+            .map_err(|cause| match cause {
+                Cause::Exception(Exception::LoadPageFault(_)) => Access::Store.page_fault(address),
+                _ => Access::Store.access_fault(address),
+            })?;
+        self.memory
+            .check_block(physical)
+            .map_err(|_| Access::Store.access_fault(address))
+    }
+
+    pub fn zero_block(&mut self, address: u64) -> Result<(), Cause> {
+        let physical = self.translate(address, Access::Store)?;
+        self.memory
+            .zero_block(physical)
+            .map_err(|_| Access::Store.access_fault(address))
+    }
+
+    pub fn tick(&mut self) {
+        for slot in &mut self.slots {
+            slot.device.tick();
+            slot.device.dma(&mut self.memory);
+        }
+        let lines: Vec<u32> = self.interrupt_lines().collect();
+        for slot in &mut self.slots {
+            slot.device.set_interrupt_lines(&lines);
+        }
+        let messages: Vec<(u64, u32)> = self
+            .slots
+            .iter_mut()
+            .flat_map(|slot| slot.device.take_messages())
+            .collect();
+        for (address, data) in messages {
+            // a message to no device is lost
+            let _ = self.store_physical(address, 4, data as u64);
+        }
+    }
+
+    pub fn hart_interrupts(&self, hart: usize) -> u64 {
+        self.slots.iter().fold(0, |pending, slot| {
+            pending | slot.device.hart_interrupts(hart)
+        })
+    }
+
+    pub fn interrupt_file(&mut self, hart: usize, level: Level) -> Option<&mut InterruptFile> {
+        self.slots
+            .iter_mut()
+            .find_map(|slot| slot.device.interrupt_file(hart, level))
+    }
+
+    pub fn time(&self) -> Option<u64> {
+        self.slots.iter().find_map(|slot| slot.device.time())
+    }
+
+    // The interrupt lines asserted by the devices.
+    pub fn interrupt_lines(&self) -> impl Iterator<Item = u32> + '_ {
+        self.slots
+            .iter()
+            .filter(|slot| slot.device.interrupt())
+            .filter_map(|slot| slot.irq)
+    }
+}
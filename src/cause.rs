@@ -1,35 +1,80 @@
 pub enum Cause {
     Exception(Exception),
+    Interrupt(Interrupt),
     ExceptionReturn(ExceptionReturn),
 }
 
 pub enum Exception {
+    InstructionAddressMisaligned(u64),
+    InstructionAccessFault(u64),
     IllegalInstruction,
+    Breakpoint,
+    LoadAddressMisaligned(u64),
+    LoadAccessFault(u64),
+    StoreAmoAddressMisaligned(u64),
+    StoreAmoAccessFault(u64),
     EnvironmentCallFromUserMode,
     EnvironmentCallFromSupervisorMode,
     EnvironmentCallFromMachineMode,
+    InstructionPageFault(u64),
+    LoadPageFault(u64),
+    StoreAmoPageFault(u64),
+}
+
+#[derive(Clone, Copy)]
+pub enum Interrupt {
+    SupervisorSoftware,
+    MachineSoftware,
+    SupervisorTimer,
+    MachineTimer,
+    SupervisorExternal,
+    MachineExternal,
 }
 
 pub enum ExceptionReturn {
     Machine,
+    Supervisor,
 }
 
 impl Cause {
+    // The exception code, without the interrupt bit that xcause adds at the top of XLEN.
     pub fn to_primitive(&self) -> u64 {
         match self {
             Self::Exception(exception) => exception.to_primitive(),
+            Self::Interrupt(interrupt) => interrupt.to_primitive(),
             Self::ExceptionReturn(_) => panic!(),
         }
     }
 
     pub fn is_interrupt(&self) -> bool {
-        false
+        matches!(self, Self::Interrupt(_))
     }
 
     pub fn exception_code(&self) -> u64 {
+        self.to_primitive()
+    }
+}
+
+impl Interrupt {
+    // In the order in which simultaneous interrupts are taken.
+    pub const PRIORITY: [Self; 6] = [
+        Self::MachineExternal,
+        Self::MachineSoftware,
+        Self::MachineTimer,
+        Self::SupervisorExternal,
+        Self::SupervisorSoftware,
+        Self::SupervisorTimer,
+    ];
+
+    // The exception code, which is also the bit of the interrupt in mip and mie.
+    pub fn to_primitive(self) -> u64 {
         match self {
-            Self::Exception(exception) => exception.to_primitive(),
-            Self::ExceptionReturn(_) => panic!(),
+            Self::SupervisorSoftware => 1,
+            Self::MachineSoftware => 3,
+            Self::SupervisorTimer => 5,
+            Self::MachineTimer => 7,
+            Self::SupervisorExternal => 9,
+            Self::MachineExternal => 11,
         }
     }
 }
@@ -37,10 +82,20 @@ impl Cause {
 impl Exception {
     pub fn to_primitive(&self) -> u64 {
         match self {
+            Self::InstructionAddressMisaligned(_) => 0,
+            Self::InstructionAccessFault(_) => 1,
             Self::IllegalInstruction => 2,
+            Self::Breakpoint => 3,
+            Self::LoadAddressMisaligned(_) => 4,
+            Self::LoadAccessFault(_) => 5,
+            Self::StoreAmoAddressMisaligned(_) => 6,
+            Self::StoreAmoAccessFault(_) => 7,
             Self::EnvironmentCallFromUserMode => 8,
             Self::EnvironmentCallFromSupervisorMode => 9,
             Self::EnvironmentCallFromMachineMode => 11,
+            Self::InstructionPageFault(_) => 12,
+            Self::LoadPageFault(_) => 13,
+            Self::StoreAmoPageFault(_) => 15,
         }
     }
 }
@@ -0,0 +1,482 @@
+use std::fs;
+
+use crate::{
+    clint::TimeSource,
+    config::Config,
+    isa::Isa,
+    memory::Region,
+    plic::{DEFAULT_PLIC_SOURCES, MAX_PLIC_SOURCES},
+    riscv_tests::DEFAULT_STEP_LIMIT,
+    simulator::{ImageFormat, DEFAULT_KERNEL_OFFSET},
+    virtio::{DEFAULT_MOUNT_TAG, VIRTIO_MMIO_SLOTS},
+    xlen::Xlen,
+};
+
+// One 32-bit word per line, as RISCOF expects of the signatures.
+const DEFAULT_SIGNATURE_GRANULARITY: usize = 4;
+
+pub const USAGE: &str = "\
+Usage: minimum-five [options] <program> [arguments...]
+       minimum-five [options] --riscv-tests <directory>
+
+Runs a RISC-V program, which exits with the exit code it passes through HTIF, or every test of
+riscv-tests in a directory.
+
+Options:
+  --isa <string>            ISA string of the harts, such as rv64gc or rv64ifd_zicsr
+                            (default: rv64imafdcv_zicsr_zifencei_zicond_zicbom_zicboz_
+                            zihintpause_zfh_zfa_zknd_zkne_zknh_zksed_zksh_zkr_sstc_
+                            smaia_ssaia)
+  --xlen <32|64>            XLEN of the harts, in place of the one in the ISA string
+  --memory-base <address>   base address of the memory (default: 0x80000000)
+  --memory-size <size>      size of the memory, with an optional K, M or G suffix
+                            (default: 1M)
+  --ram <address>:<size>    add RAM of the size at the address
+  --rom <address>:<file>    add ROM holding the contents of the file at the address
+  --no-boot-rom             start the harts at the memory base, or the entry point of
+                            the program, instead of the boot ROM at 0x1000
+  --uart <address>          add an NS16550A UART on the terminal, such as at 0x10000000,
+                            which quits on Ctrl-A x
+  --clint <address>         add a CLINT with a timer and software interrupts, such as at
+                            0x2000000
+  --time-source <instructions|host>
+                            what mtime counts: the instructions run by each hart, or
+                            the host clock at 10 MHz (default: instructions)
+  --plic <address>          add a PLIC that routes the interrupts of the devices to the
+                            harts, such as at 0xc000000
+  --plic-sources <number>   number of interrupt sources of the PLIC (default: 95)
+  --aplic <address>         add an APLIC, in place of a PLIC, with its M-level domain at the
+                            address, such as at 0xc000000, and its S-level domain 16 MiB
+                            above
+  --imsic <address>         add an IMSIC, to which an APLIC can send MSIs, with its
+                            M-level interrupt files at the address, such as at 0x24000000,
+                            and its S-level ones 64 MiB above
+  --virtio-blk <file>[,ro]  add a virtio-blk device on the disk image, which it never
+                            writes: the guest may only read it with ro, or else its
+                            writes are kept in memory until the simulator exits
+  --virtio-net <backend>[,mac=<address>][,pcap=<file>]
+                            add a virtio-net device, which records its frames in the pcap
+                            file, on a backend: loopback, which gives the frames back,
+                            listen:<socket> or connect:<socket>, which connect two
+                            simulators through a Unix socket (default MAC address:
+                            52:54:00:12:34:56, then :57 and so on)
+  --virtio-console <port>[,<port>...]
+                            add a virtio-console device, whose first port is the console
+                            of the guest, where a port is stdio, on the terminal, or
+                            socket:<socket>, which listens on a Unix socket and is named
+                            after it without its extension
+  --virtio-rng <seed>       add a virtio-rng device, which gives the same bytes for the
+                            same seed
+  --virtio-9p <directory>[,tag=<tag>]
+                            add a virtio-9p device, through which the guest mounts the
+                            directory by the tag (default: host)
+  --dtb <file>              pass the device tree in the file to the harts, in place of the
+                            one generated for the platform
+  --dump-dtb <file>         write the device tree that the harts would get to the file
+                            and exit
+  --kernel <file>           load the image of a kernel for the firmware that the program
+                            is, such as OpenSBI, to jump to
+  --kernel-offset <offset>  where the kernel goes from the memory base (default: 0x200000)
+  --initrd <file>           load an initial ramdisk for the kernel, whose place the device
+                            tree gives
+  --bootargs <string>       command line of the kernel, in /chosen/bootargs of the
+                            generated device tree
+  --sbi                     start the harts in S-mode, with the simulator serving their
+                            SBI calls in place of firmware such as OpenSBI
+  --harts <number>          number of harts (default: 1)
+  --vlen <bits>             VLEN of the harts, a power of two from ELEN to 65536
+                            (default: 128)
+  --elen <32|64>            ELEN of the harts (default: 64)
+  --entropy-seed <seed>     seed of the entropy source that the seed CSR reads, the same for
+                            each hart (default: 0x5eed)
+  --cache-block-size <bytes>
+                            size of the cache blocks of the cbo instructions, a power of
+                            two up to 4096 (default: 64)
+  --max-instructions <n>    stop with exit status 124 after each hart has run n
+                            instructions
+  --trace                   print each executed instruction to stderr
+  --format <auto|elf|bin>   format of the program, where bin is a raw image loaded at
+                            the memory base (default: auto)
+  --tohost <address>        address of tohost, in place of the tohost symbol, which raw
+                            binaries lack (default: 0x80001000)
+  --fromhost <address>      address of fromhost, in place of the fromhost symbol
+  --host-files              let the program open the files of the host, relative to the
+                            working directory, through the system calls that HTIF proxies
+  --signature <file>        write the memory from begin_signature to end_signature to
+                            the file once the program has stopped
+  --signature-granularity <bytes>
+                            bytes per line of the signature (default: 4)
+  --riscv-tests <directory> run the tests of riscv-tests in the directory of the suites of
+                            the implemented extensions, with the XLEN given by the name
+                            of each, and print the results of each suite
+  -h, --help                print this help
+";
+
+// A virtio device, which takes the next virtio-mmio transport, from 0x10001000 on line 1.
+pub enum Virtio {
+    Block {
+        image: String,
+        read_only: bool,
+    },
+    Net {
+        backend: NetBackend,
+        mac: Option<[u8; 6]>,
+        pcap: Option<String>,
+    },
+    Console {
+        ports: Vec<ConsolePort>,
+    },
+    Rng {
+        seed: u64,
+    },
+    Filesystem {
+        directory: String,
+        tag: String,
+    },
+}
+
+pub enum NetBackend {
+    Loopback,
+    Listen(String),
+    Connect(String),
+}
+
+pub enum ConsolePort {
+    Stdio,
+    Socket(String),
+}
+
+pub enum Command {
+    Help,
+    Run(Box<Options>),
+    RiscvTests(TestOptions),
+}
+
+pub struct Options {
+    pub config: Config,
+    pub program: String,
+    // the arguments passed to the program, starting with its path
+    pub arguments: Vec<String>,
+    pub max_instructions: Option<u64>,
+    pub trace: bool,
+    pub format: ImageFormat,
+    pub tohost: Option<u64>,
+    pub fromhost: Option<u64>,
+    pub host_files: bool,
+    pub signature: Option<String>,
+    pub signature_granularity: usize,
+    // the base addresses of the devices
+    pub uart: Option<u64>,
+    pub clint: Option<u64>,
+    pub time_source: TimeSource,
+    pub plic: Option<u64>,
+    pub plic_sources: u32,
+    pub aplic: Option<u64>,
+    pub imsic: Option<u64>,
+    pub virtio: Vec<Virtio>,
+    // a device tree to pass in place of the generated one, and where to dump the generated one
+    pub dtb: Option<String>,
+    pub dump_dtb: Option<String>,
+    pub bootargs: Option<String>,
+    // the images that the program, as firmware, boots
+    pub kernel: Option<String>,
+    pub kernel_offset: u64,
+    pub initrd: Option<String>,
+}
+
+pub struct TestOptions {
+    pub config: Config,
+    pub directory: String,
+    pub max_instructions: u64,
+    pub tohost: Option<u64>,
+}
+
+impl Command {
+    // Parses the command line without the name of the simulator. The options end at the
+    // program, and what follows is passed to it.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.into_iter();
+        let mut config = Config::default();
+        let mut xlen = None;
+        let mut max_instructions = None;
+        let mut trace = false;
+        let mut format = ImageFormat::Auto;
+        let mut tohost = None;
+        let mut fromhost = None;
+        let mut host_files = false;
+        let mut signature = None;
+        let mut signature_granularity = DEFAULT_SIGNATURE_GRANULARITY;
+        let mut uart = None;
+        let mut clint = None;
+        let mut time_source = TimeSource::Instructions;
+        let mut plic = None;
+        let mut plic_sources = DEFAULT_PLIC_SOURCES;
+        let mut aplic = None;
+        let mut imsic = None;
+        let mut virtio = Vec::new();
+        let mut dtb = None;
+        let mut dump_dtb = None;
+        let mut bootargs = None;
+        let mut kernel = None;
+        let mut kernel_offset = DEFAULT_KERNEL_OFFSET;
+        let mut initrd = None;
+        let mut riscv_tests = None;
+
+        let program = loop {
+            let Some(arg) = args.next() else {
+                break None;
+            };
+            if !arg.starts_with('-') {
+                break Some(arg);
+            }
+            let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(Self::Help),
+                "--isa" => config.isa = Isa::parse(&value()?)?,
+                "--xlen" => {
+                    xlen = Some(match value()?.as_str() {
+                        "32" => Xlen::Rv32,
+                        "64" => Xlen::Rv64,
+                        other => return Err(format!("invalid XLEN {}", other)),
+                    })
+                }
+                "--memory-base" => config.memory_base = parse_number(&value()?)?,
+                "--memory-size" => config.memory_size = parse_size(&value()?)?,
+                "--ram" => config.regions.push(parse_ram(&value()?)?),
+                "--rom" => config.regions.push(parse_rom(&value()?)?),
+                "--no-boot-rom" => config.boot_rom = false,
+                "--uart" => uart = Some(parse_number(&value()?)?),
+                "--clint" => clint = Some(parse_number(&value()?)?),
+                "--time-source" => {
+                    time_source = match value()?.as_str() {
+                        "instructions" => TimeSource::Instructions,
+                        "host" => TimeSource::Host,
+                        other => return Err(format!("invalid time source {}", other)),
+                    }
+                }
+                "--plic" => plic = Some(parse_number(&value()?)?),
+                "--plic-sources" => {
+                    let sources = parse_number(&value()?)?;
+                    if !(1..=MAX_PLIC_SOURCES as u64).contains(&sources) {
+                        return Err(format!(
+                            "the PLIC has from 1 to {} sources",
+                            MAX_PLIC_SOURCES
+                        ));
+                    }
+                    plic_sources = sources as u32;
+                }
+                "--aplic" => aplic = Some(parse_number(&value()?)?),
+                "--imsic" => imsic = Some(parse_number(&value()?)?),
+                "--virtio-blk" => virtio.push(parse_block(&value()?)),
+                "--virtio-net" => virtio.push(parse_net(&value()?)?),
+                "--virtio-console" => virtio.push(parse_console(&value()?)?),
+                "--virtio-rng" => virtio.push(Virtio::Rng {
+                    seed: parse_number(&value()?)?,
+                }),
+                "--virtio-9p" => virtio.push(parse_filesystem(&value()?)?),
+                "--dtb" => dtb = Some(value()?),
+                "--dump-dtb" => dump_dtb = Some(value()?),
+                "--bootargs" => bootargs = Some(value()?),
+                "--kernel" => kernel = Some(value()?),
+                "--kernel-offset" => kernel_offset = parse_number(&value()?)?,
+                "--initrd" => initrd = Some(value()?),
+                "--sbi" => config.sbi = true,
+                "--harts" => config.harts = parse_number(&value()?)? as usize,
+                "--vlen" => config.vlen = parse_number(&value()?)? as usize,
+                "--elen" => config.elen = parse_number(&value()?)? as usize,
+                "--entropy-seed" => config.entropy_seed = parse_number(&value()?)?,
+                "--cache-block-size" => config.cache_block_size = parse_number(&value()?)?,
+                "--max-instructions" => max_instructions = Some(parse_number(&value()?)?),
+                "--trace" => trace = true,
+                "--format" => {
+                    format = match value()?.as_str() {
+                        "auto" => ImageFormat::Auto,
+                        "elf" => ImageFormat::Elf,
+                        "bin" => ImageFormat::Binary,
+                        other => return Err(format!("invalid format {}", other)),
+                    }
+                }
+                "--tohost" => tohost = Some(parse_number(&value()?)?),
+                "--fromhost" => fromhost = Some(parse_number(&value()?)?),
+                "--host-files" => host_files = true,
+                "--signature" => signature = Some(value()?),
+                "--signature-granularity" => {
+                    signature_granularity = parse_number(&value()?)? as usize;
+                    if signature_granularity == 0 {
+                        return Err("the signature granularity must be positive".to_string());
+                    }
+                }
+                "--riscv-tests" => riscv_tests = Some(value()?),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        };
+        if plic.is_some() && aplic.is_some() {
+            return Err("--plic and --aplic cannot both be given".to_string());
+        }
+        if virtio.len() > VIRTIO_MMIO_SLOTS {
+            return Err(format!("at most {} virtio devices fit", VIRTIO_MMIO_SLOTS));
+        }
+        if let Some(xlen) = xlen {
+            config.isa.xlen = xlen;
+        }
+        config.validate()?;
+
+        if let Some(directory) = riscv_tests {
+            if program.is_some() {
+                return Err("--riscv-tests runs no program".to_string());
+            }
+            return Ok(Self::RiscvTests(TestOptions {
+                config,
+                directory,
+                max_instructions: max_instructions.unwrap_or(DEFAULT_STEP_LIMIT),
+                tohost,
+            }));
+        }
+        let Some(program) = program else {
+            return Err("missing program".to_string());
+        };
+        let arguments = std::iter::once(program.clone()).chain(args).collect();
+        Ok(Self::Run(Box::new(Options {
+            config,
+            program,
+            arguments,
+            max_instructions,
+            trace,
+            format,
+            tohost,
+            fromhost,
+            host_files,
+            signature,
+            signature_granularity,
+            uart,
+            clint,
+            time_source,
+            plic,
+            plic_sources,
+            aplic,
+            imsic,
+            virtio,
+            dtb,
+            dump_dtb,
+            bootargs,
+            kernel,
+            kernel_offset,
+            initrd,
+        })))
+    }
+}
+
+// Parses <address>:<size>.
+fn parse_ram(value: &str) -> Result<Region, String> {
+    let (address, size) = value
+        .split_once(':')
+        .ok_or(format!("invalid RAM {}, expected <address>:<size>", value))?;
+    Ok(Region::ram(parse_number(address)?, parse_size(size)?))
+}
+
+// Parses <address>:<file> and reads the file.
+fn parse_rom(value: &str) -> Result<Region, String> {
+    let (address, path) = value
+        .split_once(':')
+        .ok_or(format!("invalid ROM {}, expected <address>:<file>", value))?;
+    let data = fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(Region::rom(parse_number(address)?, data))
+}
+
+// Parses <file>[,ro].
+fn parse_block(value: &str) -> Virtio {
+    match value.strip_suffix(",ro") {
+        Some(image) => Virtio::Block {
+            image: image.to_string(),
+            read_only: true,
+        },
+        None => Virtio::Block {
+            image: value.to_string(),
+            read_only: false,
+        },
+    }
+}
+
+// Parses <backend>[,mac=<address>][,pcap=<file>].
+fn parse_net(value: &str) -> Result<Virtio, String> {
+    let mut parts = value.split(',');
+    let backend = match parts.next().unwrap_or_default() {
+        "loopback" => NetBackend::Loopback,
+        backend => match backend.split_once(':') {
+            Some(("listen", path)) => NetBackend::Listen(path.to_string()),
+            Some(("connect", path)) => NetBackend::Connect(path.to_string()),
+            _ => return Err(format!("invalid network backend {}", backend)),
+        },
+    };
+    let (mut mac, mut pcap) = (None, None);
+    for part in parts {
+        match part.split_once('=') {
+            Some(("mac", address)) => mac = Some(parse_mac(address)?),
+            Some(("pcap", path)) => pcap = Some(path.to_string()),
+            _ => return Err(format!("invalid network option {}", part)),
+        }
+    }
+    Ok(Virtio::Net { backend, mac, pcap })
+}
+
+// Parses <port>[,<port>...].
+fn parse_console(value: &str) -> Result<Virtio, String> {
+    let ports = value
+        .split(',')
+        .map(|port| match port.split_once(':') {
+            None if port == "stdio" => Ok(ConsolePort::Stdio),
+            Some(("socket", path)) => Ok(ConsolePort::Socket(path.to_string())),
+            _ => Err(format!("invalid console port {}", port)),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Virtio::Console { ports })
+}
+
+// Parses <directory>[,tag=<tag>].
+fn parse_filesystem(value: &str) -> Result<Virtio, String> {
+    let (directory, tag) = match value.split_once(",tag=") {
+        Some((directory, tag)) => (directory, tag),
+        None => (value, DEFAULT_MOUNT_TAG),
+    };
+    // the tag is at most 65535 bytes, as the configuration space has its length in 16 bits
+    if tag.is_empty() || tag.len() > u16::MAX as usize {
+        return Err(format!("invalid mount tag {}", tag));
+    }
+    Ok(Virtio::Filesystem {
+        directory: directory.to_string(),
+        tag: tag.to_string(),
+    })
+}
+
+// Parses six hexadecimal bytes separated by colons.
+fn parse_mac(value: &str) -> Result<[u8; 6], String> {
+    let bytes: Vec<u8> = value
+        .split(':')
+        .map(|byte| u8::from_str_radix(byte, 16))
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid MAC address {}", value))?;
+    bytes
+        .try_into()
+        .map_err(|_| format!("invalid MAC address {}", value))
+}
+
+// Parses a decimal number, or a hexadecimal one prefixed with 0x.
+fn parse_number(value: &str) -> Result<u64, String> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(&hex.replace('_', ""), 16),
+        None => value.replace('_', "").parse(),
+    };
+    parsed.map_err(|_| format!("invalid number {}", value))
+}
+
+fn parse_size(value: &str) -> Result<u64, String> {
+    let (number, shift) = match value.to_uppercase().chars().last() {
+        Some('K') => (&value[..value.len() - 1], 10),
+        Some('M') => (&value[..value.len() - 1], 20),
+        Some('G') => (&value[..value.len() - 1], 30),
+        _ => (value, 0),
+    };
+    parse_number(number)?
+        .checked_mul(1 << shift)
+        .ok_or(format!("invalid size {}", value))
+}
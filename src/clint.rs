@@ -0,0 +1,160 @@
+use std::time::Instant;
+
+use crate::{
+    bus::{BusError, Device, Platform},
+    cause::Interrupt,
+};
+
+// Where QEMU's virt machine and Spike place their CLINT.
+pub const DEFAULT_CLINT_BASE: u64 = 0x0200_0000;
+// The frequency of mtime, as the device tree reports it in timebase-frequency.
+pub const TIMEBASE_FREQUENCY: u64 = 10_000_000;
+
+pub const CLINT_SIZE: u64 = 0x1_0000;
+
+// The layout of SiFive's CLINT, which the ACLINT MSWI and MTIMER devices keep.
+const MSIP_BASE: u64 = 0x0;
+const MTIMECMP_BASE: u64 = 0x4000;
+const MTIME: u64 = 0xbff8;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TimeSource {
+    // mtime counts the steps, in each of which every hart runs an instruction, so that runs are
+    // reproducible
+    Instructions,
+    // mtime follows the clock of the host at TIMEBASE_FREQUENCY
+    Host,
+}
+
+// The core-local interruptor: a machine timer with a mtimecmp for each hart, and a msip for each
+// hart through which harts interrupt one another.
+pub struct Clint {
+    source: TimeSource,
+    // the steps counted, and the host time when the CLINT was created
+    steps: u64,
+    start: Instant,
+    // added to the source, as software sets mtime
+    offset: u64,
+    msip: Vec<u32>,
+    mtimecmp: Vec<u64>,
+}
+
+impl Clint {
+    pub fn new(harts: usize, source: TimeSource) -> Self {
+        Self {
+            source,
+            steps: 0,
+            start: Instant::now(),
+            offset: 0,
+            msip: vec![0; harts],
+            // no timer interrupt is pending until software sets mtimecmp
+            mtimecmp: vec![u64::MAX; harts],
+        }
+    }
+
+    fn source_time(&self) -> u64 {
+        match self.source {
+            TimeSource::Instructions => self.steps,
+            TimeSource::Host => {
+                let elapsed = self.start.elapsed();
+                elapsed.as_secs() * TIMEBASE_FREQUENCY
+                    + elapsed.subsec_nanos() as u64 * TIMEBASE_FREQUENCY / 1_000_000_000
+            }
+        }
+    }
+
+    fn mtime(&self) -> u64 {
+        self.source_time().wrapping_add(self.offset)
+    }
+
+    // The register holding the byte at `offset`, with the offset of that register.
+    fn register(&self, offset: u64) -> Option<(Register, u64)> {
+        let harts = self.msip.len() as u64;
+        match offset {
+            MSIP_BASE.. if offset < MSIP_BASE + 4 * harts => {
+                let hart = (offset - MSIP_BASE) / 4;
+                Some((Register::Msip(hart as usize), MSIP_BASE + 4 * hart))
+            }
+            MTIMECMP_BASE.. if offset < MTIMECMP_BASE + 8 * harts => {
+                let hart = (offset - MTIMECMP_BASE) / 8;
+                Some((Register::Mtimecmp(hart as usize), MTIMECMP_BASE + 8 * hart))
+            }
+            MTIME..=0xbfff => Some((Register::Mtime, MTIME)),
+            _ => None,
+        }
+    }
+}
+
+enum Register {
+    Msip(usize),
+    Mtimecmp(usize),
+    Mtime,
+}
+
+impl Device for Clint {
+    fn size(&self) -> u64 {
+        CLINT_SIZE
+    }
+
+    // The 64-bit registers can be accessed whole or by halves, as RV32 does.
+    fn read(&mut self, offset: u64, size: usize) -> Result<u64, BusError> {
+        let Some((register, base)) = self.register(offset) else {
+            // the reserved addresses read as zero
+            return Ok(0);
+        };
+        let value = match register {
+            Register::Msip(hart) => self.msip[hart] as u64,
+            Register::Mtimecmp(hart) => self.mtimecmp[hart],
+            Register::Mtime => self.mtime(),
+        };
+        let shift = 8 * (offset - base);
+        let mask = if size == 8 {
+            u64::MAX
+        } else {
+            (1 << (8 * size)) - 1
+        };
+        Ok((value >> shift) & mask)
+    }
+
+    fn write(&mut self, offset: u64, size: usize, value: u64) -> Result<(), BusError> {
+        let Some((register, base)) = self.register(offset) else {
+            return Ok(());
+        };
+        let shift = 8 * (offset - base);
+        let mask = if size == 8 {
+            u64::MAX
+        } else {
+            ((1 << (8 * size)) - 1) << shift
+        };
+        let merge = |old: u64| old & !mask | (value << shift) & mask;
+        match register {
+            // only the least significant bit is implemented
+            Register::Msip(hart) => self.msip[hart] = merge(self.msip[hart] as u64) as u32 & 1,
+            Register::Mtimecmp(hart) => self.mtimecmp[hart] = merge(self.mtimecmp[hart]),
+            Register::Mtime => {
+                let mtime = merge(self.mtime());
+                self.offset = mtime.wrapping_sub(self.source_time());
+            }
+        }
+        Ok(())
+    }
+
+    fn tick(&mut self) {
+        self.steps += 1;
+    }
+}
+
+impl Platform for Clint {
+    fn hart_interrupts(&self, hart: usize) -> u64 {
+        let (Some(&msip), Some(&mtimecmp)) = (self.msip.get(hart), self.mtimecmp.get(hart)) else {
+            return 0;
+        };
+        let software = (msip as u64 & 1) << Interrupt::MachineSoftware.to_primitive();
+        let timer = ((self.mtime() >= mtimecmp) as u64) << Interrupt::MachineTimer.to_primitive();
+        software | timer
+    }
+
+    fn time(&self) -> Option<u64> {
+        Some(self.mtime())
+    }
+}
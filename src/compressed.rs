@@ -0,0 +1,202 @@
+use crate::xlen::Xlen;
+
+// Expands a compressed instruction of C into the 32-bit instruction that it stands for, which
+// the decoders then decode as any other. Returns None for the reserved encodings, and for the
+// ones that only exist at the other XLEN or in extensions such as Zcb.
+pub fn expand(instruction: u16, xlen: Xlen) -> Option<u32> {
+    let c = instruction as u32;
+    let bits = |high: u32, low: u32| (c >> low) & ((1 << (high - low + 1)) - 1);
+    // the registers x8 to x15 of the three-bit fields, where rd' of the loads is in the place of
+    // rs2' of the stores and of the arithmetic
+    let rd_prime = 8 + bits(4, 2);
+    let rs1_prime = 8 + bits(9, 7);
+    let rd = bits(11, 7);
+    let rs2 = bits(6, 2);
+    let rv64 = xlen == Xlen::Rv64;
+
+    // the offsets of the loads and stores, scaled by their width
+    let word_offset = bits(12, 10) << 3 | bits(6, 6) << 2 | bits(5, 5) << 6;
+    let doubleword_offset = bits(12, 10) << 3 | bits(6, 5) << 6;
+    let word_sp_offset = bits(12, 12) << 5 | bits(6, 4) << 2 | bits(3, 2) << 6;
+    let doubleword_sp_offset = bits(12, 12) << 5 | bits(6, 5) << 3 | bits(4, 2) << 6;
+    let word_sp_store_offset = bits(12, 9) << 2 | bits(8, 7) << 6;
+    let doubleword_sp_store_offset = bits(12, 10) << 3 | bits(9, 7) << 6;
+    // the six-bit immediates of the arithmetic, and the shift amounts
+    let immediate = sign_extend(bits(12, 12) << 5 | bits(6, 2), 6);
+    let shamt = bits(12, 12) << 5 | bits(6, 2);
+
+    match (c & 0b11, bits(15, 13)) {
+        (0b00, 0b000) => {
+            let immediate =
+                bits(12, 11) << 4 | bits(10, 7) << 6 | bits(6, 6) << 2 | bits(5, 5) << 3;
+            // which includes the instruction of zeros, which is illegal
+            (immediate != 0).then(|| i_type(immediate, 2, 0b000, rd_prime, OP_IMM))
+        }
+        (0b00, 0b001) => Some(i_type(
+            doubleword_offset,
+            rs1_prime,
+            0b011,
+            rd_prime,
+            LOAD_FP,
+        )),
+        (0b00, 0b010) => Some(i_type(word_offset, rs1_prime, 0b010, rd_prime, LOAD)),
+        (0b00, 0b011) if rv64 => Some(i_type(doubleword_offset, rs1_prime, 0b011, rd_prime, LOAD)),
+        (0b00, 0b011) => Some(i_type(word_offset, rs1_prime, 0b010, rd_prime, LOAD_FP)),
+        (0b00, 0b101) => Some(s_type(
+            doubleword_offset,
+            rd_prime,
+            rs1_prime,
+            0b011,
+            STORE_FP,
+        )),
+        (0b00, 0b110) => Some(s_type(word_offset, rd_prime, rs1_prime, 0b010, STORE)),
+        (0b00, 0b111) if rv64 => Some(s_type(doubleword_offset, rd_prime, rs1_prime, 0b011, STORE)),
+        (0b00, 0b111) => Some(s_type(word_offset, rd_prime, rs1_prime, 0b010, STORE_FP)),
+
+        (0b01, 0b000) => Some(i_type(immediate, rd, 0b000, rd, OP_IMM)),
+        (0b01, 0b001) if rv64 => (rd != 0).then(|| i_type(immediate, rd, 0b000, rd, OP_IMM_32)),
+        (0b01, 0b001) => Some(j_type(jump_offset(c), 1)),
+        (0b01, 0b010) => Some(i_type(immediate, 0, 0b000, rd, OP_IMM)),
+        (0b01, 0b011) if rd == 2 => {
+            let immediate = sign_extend(
+                bits(12, 12) << 9
+                    | bits(6, 6) << 4
+                    | bits(5, 5) << 6
+                    | bits(4, 3) << 7
+                    | bits(2, 2) << 5,
+                10,
+            );
+            (immediate != 0).then(|| i_type(immediate, 2, 0b000, 2, OP_IMM))
+        }
+        (0b01, 0b011) => (immediate != 0).then_some((immediate & 0xfffff) << 12 | rd << 7 | LUI),
+        (0b01, 0b100) => match (bits(11, 10), bits(12, 12), bits(6, 5)) {
+            (0b00, _, _) if rv64 || shamt < 32 => {
+                Some(i_type(shamt, rs1_prime, 0b101, rs1_prime, OP_IMM))
+            }
+            (0b01, _, _) if rv64 || shamt < 32 => Some(i_type(
+                0b0100000 << 5 | shamt,
+                rs1_prime,
+                0b101,
+                rs1_prime,
+                OP_IMM,
+            )),
+            (0b10, _, _) => Some(i_type(immediate, rs1_prime, 0b111, rs1_prime, OP_IMM)),
+            (0b11, 0, operation) => {
+                let (funct7, funct3) =
+                    [(0b0100000, 0b000), (0, 0b100), (0, 0b110), (0, 0b111)][operation as usize];
+                Some(r_type(funct7, rd_prime, rs1_prime, funct3, rs1_prime, OP))
+            }
+            (0b11, 1, 0b00) if rv64 => Some(r_type(
+                0b0100000, rd_prime, rs1_prime, 0b000, rs1_prime, OP_32,
+            )),
+            (0b11, 1, 0b01) if rv64 => {
+                Some(r_type(0, rd_prime, rs1_prime, 0b000, rs1_prime, OP_32))
+            }
+            _ => None,
+        },
+        (0b01, 0b101) => Some(j_type(jump_offset(c), 0)),
+        (0b01, 0b110 | 0b111) => {
+            let offset = sign_extend(
+                bits(12, 12) << 8
+                    | bits(11, 10) << 3
+                    | bits(6, 5) << 6
+                    | bits(4, 3) << 1
+                    | bits(2, 2) << 5,
+                9,
+            );
+            let funct3 = bits(13, 13);
+            Some(b_type(offset, 0, rs1_prime, funct3))
+        }
+
+        (0b10, 0b000) if rv64 || shamt < 32 => Some(i_type(shamt, rd, 0b001, rd, OP_IMM)),
+        (0b10, 0b001) => Some(i_type(doubleword_sp_offset, 2, 0b011, rd, LOAD_FP)),
+        (0b10, 0b010) => (rd != 0).then(|| i_type(word_sp_offset, 2, 0b010, rd, LOAD)),
+        (0b10, 0b011) if rv64 => {
+            (rd != 0).then(|| i_type(doubleword_sp_offset, 2, 0b011, rd, LOAD))
+        }
+        (0b10, 0b011) => Some(i_type(word_sp_offset, 2, 0b010, rd, LOAD_FP)),
+        (0b10, 0b100) => match (bits(12, 12), rd, rs2) {
+            // c.jr, c.mv, c.ebreak, c.jalr and c.add
+            (0, 0, 0) => None,
+            (0, _, 0) => Some(i_type(0, rd, 0b000, 0, JALR)),
+            (0, _, _) => Some(r_type(0, rs2, 0, 0b000, rd, OP)),
+            (1, 0, 0) => Some(EBREAK),
+            (1, _, 0) => Some(i_type(0, rd, 0b000, 1, JALR)),
+            _ => Some(r_type(0, rs2, rd, 0b000, rd, OP)),
+        },
+        (0b10, 0b101) => Some(s_type(doubleword_sp_store_offset, rs2, 2, 0b011, STORE_FP)),
+        (0b10, 0b110) => Some(s_type(word_sp_store_offset, rs2, 2, 0b010, STORE)),
+        (0b10, 0b111) if rv64 => Some(s_type(doubleword_sp_store_offset, rs2, 2, 0b011, STORE)),
+        (0b10, 0b111) => Some(s_type(word_sp_store_offset, rs2, 2, 0b010, STORE_FP)),
+        _ => None,
+    }
+}
+
+const LOAD: u32 = 0b0000011;
+const LOAD_FP: u32 = 0b0000111;
+const OP_IMM: u32 = 0b0010011;
+const OP_IMM_32: u32 = 0b0011011;
+const STORE: u32 = 0b0100011;
+const STORE_FP: u32 = 0b0100111;
+const OP: u32 = 0b0110011;
+const LUI: u32 = 0b0110111;
+const OP_32: u32 = 0b0111011;
+const BRANCH: u32 = 0b1100011;
+const JALR: u32 = 0b1100111;
+const JAL: u32 = 0b1101111;
+const EBREAK: u32 = 0x0010_0073;
+
+// The offset of c.j and c.jal.
+fn jump_offset(c: u32) -> u32 {
+    let bit = |position: u32| (c >> position) & 1;
+    let offset = bit(12) << 11
+        | bit(11) << 4
+        | (c >> 9 & 0b11) << 8
+        | bit(8) << 10
+        | bit(7) << 6
+        | bit(6) << 7
+        | (c >> 3 & 0b111) << 1
+        | bit(2) << 5;
+    sign_extend(offset, 12)
+}
+
+fn sign_extend(value: u32, bits: u32) -> u32 {
+    ((value << (32 - bits)) as i32 >> (32 - bits)) as u32
+}
+
+fn r_type(funct7: u32, rs2: u32, rs1: u32, funct3: u32, rd: u32, opcode: u32) -> u32 {
+    funct7 << 25 | rs2 << 20 | rs1 << 15 | funct3 << 12 | rd << 7 | opcode
+}
+
+fn i_type(immediate: u32, rs1: u32, funct3: u32, rd: u32, opcode: u32) -> u32 {
+    (immediate & 0xfff) << 20 | rs1 << 15 | funct3 << 12 | rd << 7 | opcode
+}
+
+fn s_type(immediate: u32, rs2: u32, rs1: u32, funct3: u32, opcode: u32) -> u32 {
+    (immediate >> 5 & 0x7f) << 25
+        | rs2 << 20
+        | rs1 << 15
+        | funct3 << 12
+        | (immediate & 0x1f) << 7
+        | opcode
+}
+
+fn b_type(offset: u32, rs2: u32, rs1: u32, funct3: u32) -> u32 {
+    (offset >> 12 & 1) << 31
+        | (offset >> 5 & 0x3f) << 25
+        | rs2 << 20
+        | rs1 << 15
+        | funct3 << 12
+        | (offset >> 1 & 0xf) << 8
+        | (offset >> 11 & 1) << 7
+        | BRANCH
+}
+
+fn j_type(offset: u32, rd: u32) -> u32 {
+    (offset >> 20 & 1) << 31
+        | (offset >> 1 & 0x3ff) << 21
+        | (offset >> 11 & 1) << 20
+        | (offset >> 12 & 0xff) << 12
+        | rd << 7
+        | JAL
+}
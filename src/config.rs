@@ -0,0 +1,78 @@
+use crate::{
+    boot_rom::{boot_rom, BOOT_ROM_BASE},
+    entropy::DEFAULT_ENTROPY_SEED,
+    isa::{Isa, DEFAULT_ISA},
+    memory::{Memory, Region, DEFAULT_CACHE_BLOCK_SIZE, DEFAULT_MEMORY_BASE, DEFAULT_MEMORY_SIZE},
+    v::{VectorRegister, DEFAULT_ELEN, DEFAULT_VLEN},
+};
+
+// Parameters of the simulated machine that are fixed at reset.
+#[derive(Clone)]
+pub struct Config {
+    pub isa: Isa,
+    pub harts: usize,
+    pub vlen: usize,
+    pub elen: usize,
+    pub entropy_seed: u64,
+    pub cache_block_size: u64,
+    pub memory_base: u64,
+    pub memory_size: u64,
+    // RAM and ROM besides the memory
+    pub regions: Vec<Region>,
+    // whether the harts start in the boot ROM, or else at the base of the memory
+    pub boot_rom: bool,
+    // whether the harts start in S-mode, with the simulator serving their SBI calls in place of
+    // M-mode firmware
+    pub sbi: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            isa: Isa::parse(DEFAULT_ISA).unwrap(),
+            harts: 1,
+            vlen: DEFAULT_VLEN,
+            elen: DEFAULT_ELEN,
+            entropy_seed: DEFAULT_ENTROPY_SEED,
+            cache_block_size: DEFAULT_CACHE_BLOCK_SIZE,
+            memory_base: DEFAULT_MEMORY_BASE,
+            memory_size: DEFAULT_MEMORY_SIZE,
+            regions: Vec::new(),
+            boot_rom: true,
+            sbi: false,
+        }
+    }
+}
+
+impl Config {
+    // Checks the parameters that the harts and the memory can take, and the memory map, before
+    // a simulator is made of them.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.harts == 0 {
+            return Err("at least one hart is needed".to_string());
+        }
+        VectorRegister::validate(self.vlen, self.elen)?;
+        Memory::validate_cache_block_size(self.cache_block_size)?;
+        self.memory_map().map(|_| ())
+    }
+
+    // The memory, the other regions and the boot ROM, which jumps to the base of the memory, with
+    // no device tree, until a program says otherwise. Fails if two of them overlap.
+    pub fn memory_map(&self) -> Result<Vec<Region>, String> {
+        let mut regions = vec![Region::ram(self.memory_base, self.memory_size)];
+        if self.boot_rom {
+            let rom = boot_rom(self.isa.xlen, self.memory_base, 0, 0);
+            regions.push(Region::rom(BOOT_ROM_BASE, rom));
+        }
+        regions.extend(self.regions.iter().cloned());
+        for (i, region) in regions.iter().enumerate() {
+            if let Some(other) = regions[..i].iter().find(|other| region.overlaps(other)) {
+                return Err(format!(
+                    "the region at {:#x} overlaps the one at {:#x}",
+                    region.base, other.base
+                ));
+            }
+        }
+        Ok(regions)
+    }
+}
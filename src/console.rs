@@ -0,0 +1,80 @@
+use std::{
+    io::{self, IsTerminal, Read},
+    process::{self, Command, Stdio},
+    sync::{
+        mpsc::{self, Receiver},
+        Mutex,
+    },
+    thread,
+};
+
+// Ctrl-A, which followed by x quits the simulator while the terminal is in raw mode.
+const ESCAPE: u8 = 0x01;
+
+// The settings of the terminal before raw mode, as printed by stty -g.
+static SAVED_SETTINGS: Mutex<Option<String>> = Mutex::new(None);
+
+// Keeps the terminal on stdin in raw mode, so that the guest gets each key as typed, Ctrl-C
+// included, until it is dropped.
+pub struct RawMode;
+
+impl RawMode {
+    // Returns None when stdin is not a terminal, or stty has failed.
+    pub fn enable() -> Option<Self> {
+        if !io::stdin().is_terminal() {
+            return None;
+        }
+        let saved = stty(&["-g"])?;
+        // keep the output processing, which turns a newline into a carriage return and newline
+        stty(&["raw", "-echo", "opost"])?;
+        *SAVED_SETTINGS.lock().unwrap() = Some(saved.trim().to_string());
+        Some(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+fn restore_terminal() {
+    if let Some(saved) = SAVED_SETTINGS.lock().unwrap().take() {
+        stty(&[&saved]);
+    }
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Reading stdin blocks, so it is left to a thread that forwards each byte. In raw mode, Ctrl-A x
+// quits.
+pub fn spawn_stdin_reader() -> Receiver<u8> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut escaped = false;
+        for byte in io::stdin().lock().bytes().map_while(Result::ok) {
+            let raw = SAVED_SETTINGS.lock().unwrap().is_some();
+            if raw && escaped && byte == b'x' {
+                restore_terminal();
+                eprintln!("minimum-five: terminated");
+                process::exit(0);
+            }
+            escaped = raw && byte == ESCAPE && !escaped;
+            if escaped {
+                continue;
+            }
+            if sender.send(byte).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
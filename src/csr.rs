@@ -1,5 +1,10 @@
 use std::collections::HashMap;
-use std::ops::Range;
+use std::ops::{Range, RangeInclusive};
+
+use crate::{
+    cause::Interrupt, entropy::EntropySource, imsic::Level, isa::Isa, mode::PrivilegeMode,
+    xlen::Xlen,
+};
 
 pub const STATUS_UIE: Range<usize> = 0..0;
 pub const STATUS_SIE: Range<usize> = 1..1;
@@ -9,28 +14,133 @@ pub const STATUS_SPIE: Range<usize> = 5..5;
 pub const STATUS_MPIE: Range<usize> = 7..7;
 pub const STATUS_SPP: Range<usize> = 8..8;
 pub const STATUS_MPP: Range<usize> = 11..12;
+pub const STATUS_VS: Range<usize> = 9..10;
+pub const STATUS_FS: Range<usize> = 13..14;
+pub const STATUS_XS: Range<usize> = 15..16;
+pub const STATUS_MPRV: Range<usize> = 17..17;
+pub const STATUS_SUM: Range<usize> = 18..18;
+pub const STATUS_MXR: Range<usize> = 19..19;
+pub const STATUS_TVM: Range<usize> = 20..20;
+pub const STATUS_TW: Range<usize> = 21..21;
+pub const STATUS_TSR: Range<usize> = 22..22;
+pub const STATUS_UXL: Range<usize> = 32..33;
+pub const STATUS_SXL: Range<usize> = 34..35;
+
+// The fields of mstatus that sstatus shows, apart from SD.
+const SSTATUS_MASK: u64 = 0x3_000d_e762;
+
+// Values of the FS, VS and XS status fields.
+pub const EXTENSION_STATE_OFF: u64 = 0b00;
+pub const EXTENSION_STATE_DIRTY: u64 = 0b11;
+
+pub const ENVCFG_CBIE: Range<usize> = 4..5;
+pub const ENVCFG_CBCFE: Range<usize> = 6..6;
+pub const ENVCFG_CBZE: Range<usize> = 7..7;
+// Sstc: stimecmp drives the supervisor timer interrupt
+pub const MENVCFG_STCE: Range<usize> = 63..63;
+
+// Values of the MODE field of satp, which is bit 31 in RV32 and bits 60 to 63 in RV64.
+pub const SATP_MODE_BARE: u64 = 0;
+pub const SATP_MODE_SV32: u64 = 1;
+pub const SATP_MODE_SV39: u64 = 8;
+
+// Values of the CBIE field.
+pub const ENVCFG_CBIE_ILLEGAL: u64 = 0b00;
+
+// The interrupts in mip and mie, at the bits of their exception codes.
+const SUPERVISOR_INTERRUPTS: u64 = 0x222;
+const MACHINE_INTERRUPTS: u64 = 0x888;
+const SEIP: u64 = 1 << 9;
+// The pending bits that the platform drives. Software cannot write them, except for SEIP, which
+// is pending when either software or the platform asserts it.
+const PLATFORM_INTERRUPTS: u64 = MACHINE_INTERRUPTS | SEIP;
+
+pub const MSECCFG_USEED: Range<usize> = 8..8;
+pub const MSECCFG_SSEED: Range<usize> = 9..9;
+
+// Machine Information Registers (MRO)
+pub const MVENDORID: u64 = 0xf11; // Vendor ID.
+pub const MARCHID: u64 = 0xf12; // Architecture ID.
+pub const MIMPID: u64 = 0xf13; // Implementation ID.
+pub const MHARTID: u64 = 0xf14; // Hardware thread ID.
 
 // Machine Trap Setup (MRW)
 pub const MSTATUS: u64 = 0x300; // Machine status register.
+pub const MISA: u64 = 0x301; // ISA and extensions.
 pub const MEDELEG: u64 = 0x302; // Machine exception delegation register.
 pub const MIDELEG: u64 = 0x303; // Machine interrupt delegation register.
+pub const MIE: u64 = 0x304; // Machine interrupt-enable register.
 pub const MTVEC: u64 = 0x305; // Machine trap-handler base address.
+pub const MCOUNTEREN: u64 = 0x306; // Machine counter enable.
+pub const MSTATUSH: u64 = 0x310; // Additional machine status register, RV32 only.
 
 // Machine Trap Handling (MRW)
+pub const MSCRATCH: u64 = 0x340; // Scratch register for machine trap handlers.
 pub const MEPC: u64 = 0x341; // Machine exception program counter.
 pub const MCAUSE: u64 = 0x342; // Machine trap cause.
 pub const MTVAL: u64 = 0x343; // Machine bad address or instruction.
+pub const MIP: u64 = 0x344; // Machine interrupt pending.
+
+// Machine Configuration (MRW)
+pub const MENVCFG: u64 = 0x30a; // Machine environment configuration register.
+pub const MENVCFGH: u64 = 0x31a; // Additional machine env. conf. register, RV32 only.
+pub const MSECCFG: u64 = 0x747; // Machine security configuration register.
+pub const MSECCFGH: u64 = 0x757; // Additional machine security conf. register, RV32 only.
+
+// Machine-Level Advanced Interrupt Architecture (Smaia)
+pub const MISELECT: u64 = 0x350; // Machine indirect register select.
+pub const MIREG: u64 = 0x351; // Machine indirect register alias.
+pub const MTOPEI: u64 = 0x35c; // Machine top external interrupt.
+pub const MTOPI: u64 = 0xfb0; // Machine top interrupt.
+pub const MVIEN: u64 = 0x308; // Machine virtual interrupt enables.
+pub const MVIP: u64 = 0x309; // Machine virtual interrupt-pending bits.
+pub const MIDELEGH: u64 = 0x313; // Upper 32 bits of mideleg, RV32 only.
+pub const MIEH: u64 = 0x314; // Upper 32 bits of mie, RV32 only.
+pub const MVIENH: u64 = 0x318; // Upper 32 bits of mvien, RV32 only.
+pub const MVIPH: u64 = 0x319; // Upper 32 bits of mvip, RV32 only.
+pub const MIPH: u64 = 0x354; // Upper 32 bits of mip, RV32 only.
+
+// Debug/Trace Registers (shared with Debug Mode)
+pub const TSELECT: u64 = 0x7a0; // Debug/Trace trigger register select.
+pub const TDATA1: u64 = 0x7a1; // First Debug/Trace trigger data register.
+pub const TDATA2: u64 = 0x7a2; // Second Debug/Trace trigger data register.
+
+// Machine Counter/Timers (MRW)
+pub const MCYCLE: u64 = 0xb00; // Machine cycle counter.
+pub const MINSTRET: u64 = 0xb02; // Machine instructions-retired counter.
+pub const MCYCLEH: u64 = 0xb80; // Upper 32 bits of mcycle, RV32 only.
+pub const MINSTRETH: u64 = 0xb82; // Upper 32 bits of minstret, RV32 only.
 
 // Supervisor Trap Setup (SRW)
 pub const SSTATUS: u64 = 0x100; // Supervisor status register.
 pub const SEDELEG: u64 = 0x102; // Supervisor exception delegation register.
 pub const SIDELEG: u64 = 0x103; // Supervisor interrupt delegation register.
+pub const SIE: u64 = 0x104; // Supervisor interrupt-enable register.
 pub const STVEC: u64 = 0x105; // Supervisor trap handler base address.
+pub const SCOUNTEREN: u64 = 0x106; // Supervisor counter enable.
+
+// Supervisor Configuration (SRW)
+pub const SENVCFG: u64 = 0x10a; // Supervisor environment configuration register.
 
 // Supervisor Trap Handling (SRW)
+pub const SSCRATCH: u64 = 0x140; // Scratch register for supervisor trap handlers.
 pub const SEPC: u64 = 0x141; // Supervisor exception program counter.
 pub const SCAUSE: u64 = 0x142; // Supervisor trap cause.
 pub const STVAL: u64 = 0x143; // Supervisor bad address or instruction.
+pub const SIP: u64 = 0x144; // Supervisor interrupt pending.
+pub const STIMECMP: u64 = 0x14d; // Supervisor timer compare, with Sstc.
+pub const STIMECMPH: u64 = 0x15d; // Upper 32 bits of stimecmp, RV32 only.
+
+// Supervisor Protection and Translation (SRW)
+pub const SATP: u64 = 0x180; // Supervisor address translation and protection.
+
+// Supervisor-Level Advanced Interrupt Architecture (Ssaia)
+pub const SISELECT: u64 = 0x150; // Supervisor indirect register select.
+pub const SIREG: u64 = 0x151; // Supervisor indirect register alias.
+pub const STOPEI: u64 = 0x15c; // Supervisor top external interrupt.
+pub const STOPI: u64 = 0xdb0; // Supervisor top interrupt.
+pub const SIEH: u64 = 0x114; // Upper 32 bits of sie, RV32 only.
+pub const SIPH: u64 = 0x154; // Upper 32 bits of sip, RV32 only.
 
 // User Trap Setup (URW)
 pub const USTATUS: u64 = 0x000; // User status register.
@@ -41,58 +151,476 @@ pub const UEPC: u64 = 0x041; // User exception program counter.
 pub const UCAUSE: u64 = 0x042; // User trap cause.
 pub const UTVAL: u64 = 0x043; // User bad address or instruction.
 
+// User Floating-Point CSRs (URW)
+pub const FFLAGS: u64 = 0x001; // Floating-Point Accrued Exceptions.
+pub const FRM: u64 = 0x002; // Floating-Point Dynamic Rounding Mode.
+pub const FCSR: u64 = 0x003; // Floating-Point Control and Status Register (frm + fflags).
+
+// Counters/Timers (URO)
+pub const CYCLE: u64 = 0xc00; // Cycle counter for RDCYCLE instruction.
+pub const TIME: u64 = 0xc01; // Timer for RDTIME instruction.
+pub const INSTRET: u64 = 0xc02; // Instructions-retired counter for RDINSTRET instruction.
+pub const CYCLEH: u64 = 0xc80; // Upper 32 bits of cycle, RV32 only.
+pub const TIMEH: u64 = 0xc81; // Upper 32 bits of time, RV32 only.
+pub const INSTRETH: u64 = 0xc82; // Upper 32 bits of instret, RV32 only.
+
+// Entropy Source (URW)
+pub const SEED: u64 = 0x015; // Seed for cryptographic random bit generators.
+
+// Vector CSRs (URW)
+pub const VSTART: u64 = 0x008; // Vector start position.
+pub const VXSAT: u64 = 0x009; // Fixed-point accrued saturation flag.
+pub const VXRM: u64 = 0x00a; // Fixed-point rounding mode.
+pub const VCSR: u64 = 0x00f; // Vector control and status register (vxrm + vxsat).
+
+// Vector CSRs (URO)
+pub const VL: u64 = 0xc20; // Vector length.
+pub const VTYPE: u64 = 0xc21; // Vector data type register.
+pub const VLENB: u64 = 0xc22; // VLEN/8 (vector register length in bytes).
+
+// vill is the most significant bit of vtype.
+pub fn vtype_vill(xlen: Xlen) -> u64 {
+    1 << (xlen.bits() - 1)
+}
+
+// The registers that miselect and siselect select: the priorities of the major interrupts, which
+// are read-only zero, and those of the interrupt files of an IMSIC.
+const ISELECT_IPRIO: RangeInclusive<u64> = 0x30..=0x3f;
+const ISELECT_IMSIC: RangeInclusive<u64> = 0x70..=0xff;
+
+// The entropy source is always healthy and delivers 16 bits per read (OPST = ES16).
+const SEED_OPST_ES16: u64 = 0b10 << 30;
+
 pub struct ControlAndStatusRegister {
     csr: HashMap<u64, u64>,
+    // the entropy source of Zkr
+    entropy: Option<EntropySource>,
+    xlen: Xlen,
+    // the time of the platform, which changes on every step
+    time: u64,
+    // the supervisor timer compare of Sstc, if implemented
+    stimecmp: Option<u64>,
+    // the pending bits driven by the platform, and SEIP as written by software
+    platform_interrupts: u64,
+    software_seip: u64,
 }
 
 impl ControlAndStatusRegister {
+    pub fn new(isa: &Isa) -> Self {
+        let xlen = isa.xlen;
+        let mut addresses = vec![
+            MVENDORID, MARCHID, MIMPID, MHARTID, MSTATUS, MISA, MEDELEG, MIDELEG, MIE, MTVEC,
+            MCOUNTEREN, MSCRATCH, MEPC, MCAUSE, MTVAL, MIP, MENVCFG, MSECCFG, MCYCLE, MINSTRET,
+            TSELECT, TDATA1, TDATA2, SEDELEG, SIDELEG, STVEC, SCOUNTEREN, SENVCFG, SSCRATCH, SEPC,
+            SCAUSE, STVAL, SATP, USTATUS, UTVEC, UEPC, UCAUSE, UTVAL,
+        ];
+        if isa.f {
+            addresses.push(FCSR);
+        }
+        if isa.v {
+            addresses.extend([VSTART, VCSR, VL, VTYPE, VLENB]);
+        }
+        if isa.smaia {
+            addresses.push(MISELECT);
+        }
+        if isa.smaia || isa.ssaia {
+            addresses.push(SISELECT);
+        }
+        let mut csr = Self {
+            csr: addresses.into_iter().map(|a| (a, 0)).collect(),
+            entropy: isa.zkr.then(EntropySource::default),
+            xlen,
+            time: 0,
+            // as mtimecmp, no timer interrupt is pending until software sets stimecmp
+            stimecmp: isa.sstc.then_some(u64::MAX),
+            platform_interrupts: 0,
+            software_seip: 0,
+        };
+        // MXL sits in the two most significant bits
+        csr.write_raw(
+            MISA,
+            xlen.to_primitive() << (xlen.bits() - 2) | isa.misa_extensions(),
+        );
+        // the status registers of RV64 report the XLEN of S-mode and U-mode
+        if xlen == Xlen::Rv64 {
+            let status =
+                xlen.to_primitive() << STATUS_SXL.start | xlen.to_primitive() << STATUS_UXL.start;
+            csr.write_raw(MSTATUS, status);
+        }
+        csr
+    }
+
     fn contains(&self, address: u64) -> bool {
         self.csr.contains_key(&address)
     }
 
+    // Whether software can access the CSR, including the ones that are views of others.
+    fn implemented(&self, address: u64) -> bool {
+        match address {
+            SSTATUS | SIE | SIP | CYCLE | TIME | INSTRET => true,
+            FFLAGS | FRM => self.contains(FCSR),
+            VXSAT | VXRM => self.contains(VCSR),
+            SEED => self.entropy.is_some(),
+            STIMECMP => self.stimecmp.is_some(),
+            MIREG => self.contains(MISELECT) && self.iprio_selected(self.read(MISELECT)),
+            SIREG => self.contains(SISELECT) && self.iprio_selected(self.read(SISELECT)),
+            MTOPI | MVIEN | MVIP => self.contains(MISELECT),
+            STOPI => self.contains(SISELECT),
+            _ => self.contains(address),
+        }
+    }
+
+    // In RV32, the upper halves of the 64-bit CSRs are accessed through separate addresses.
+    fn upper_half(address: u64) -> Option<u64> {
+        match address {
+            MSTATUSH => Some(MSTATUS),
+            MENVCFGH => Some(MENVCFG),
+            MSECCFGH => Some(MSECCFG),
+            MCYCLEH => Some(MCYCLE),
+            MINSTRETH => Some(MINSTRET),
+            CYCLEH => Some(CYCLE),
+            TIMEH => Some(TIME),
+            STIMECMPH => Some(STIMECMP),
+            MIDELEGH => Some(MIDELEG),
+            MIEH => Some(MIE),
+            MIPH => Some(MIP),
+            MVIENH => Some(MVIEN),
+            MVIPH => Some(MVIP),
+            SIEH => Some(SIE),
+            SIPH => Some(SIP),
+            INSTRETH => Some(INSTRET),
+            _ => None,
+        }
+    }
+
     fn read(&self, address: u64) -> u64 {
-        if self.contains(address) {
-            return self.csr[&address];
+        match address {
+            FFLAGS => self.read(FCSR) & 0b11111,
+            FRM => (self.read(FCSR) >> 5) & 0b111,
+            VXSAT => self.read(VCSR) & 0b1,
+            VXRM => (self.read(VCSR) >> 1) & 0b11,
+            CYCLE => self.read(MCYCLE),
+            INSTRET => self.read(MINSTRET),
+            MSTATUS => {
+                // SD is the most significant bit
+                let sd = self.xlen.bits() - 1;
+                let status = self.csr[&MSTATUS];
+                let dirty = [STATUS_FS, STATUS_VS, STATUS_XS]
+                    .iter()
+                    .any(|field| Self::extract(status, field) == EXTENSION_STATE_DIRTY);
+                status & !(1 << sd) | (dirty as u64) << sd
+            }
+            SSTATUS => self.read(MSTATUS) & (SSTATUS_MASK | 1 << (self.xlen.bits() - 1)),
+            // sie and sip show the interrupts delegated to S-mode
+            SIE => self.read(MIE) & self.read(MIDELEG),
+            SIP => self.read(MIP) & self.read(MIDELEG),
+            TIME => self.time,
+            STIMECMP => self.stimecmp.unwrap_or(0),
+            // mvip shows the supervisor interrupts that mip can have written, SEIP as written
+            MVIP => self.read(MIP) & (SUPERVISOR_INTERRUPTS & !SEIP) | self.software_seip,
+            MTOPI => Self::topi(self.read(MIP) & self.read(MIE) & !self.read(MIDELEG)),
+            STOPI => Self::topi(self.read(MIP) & self.read(MIE) & self.read(MIDELEG)),
+            _ => self.csr.get(&address).copied().unwrap_or(0),
         }
-        0
     }
 
     fn write(&mut self, address: u64, value: u64) {
+        match address {
+            // neither MXL nor the extensions can be changed
+            MISA => {}
+            // no triggers are implemented, which tdata1 reports by reading as zero
+            TSELECT | TDATA1 | TDATA2 => {}
+            // only the direct mode is supported
+            MTVEC | STVEC | UTVEC => self.write_raw(address, value & !0b11),
+            // an exception program counter holds the address of an instruction, aligned to two
+            // bytes with C and to four without it
+            MEPC | SEPC | UEPC => {
+                let compressed = self.read(MISA) >> (b'c' - b'a') & 1 == 1;
+                self.write_raw(address, value & if compressed { !0b1 } else { !0b11 })
+            }
+            MIE => self.write_raw(MIE, value & (MACHINE_INTERRUPTS | SUPERVISOR_INTERRUPTS)),
+            MIP => {
+                let fixed = self.driven_interrupts();
+                self.software_seip = value & SEIP;
+                let mip = self.read(MIP) & fixed & !PLATFORM_INTERRUPTS | value & !fixed;
+                self.write_raw(MIP, mip | self.platform_interrupts | self.software_seip)
+            }
+            // M-mode handles its own interrupts
+            MIDELEG => self.write_raw(MIDELEG, value & SUPERVISOR_INTERRUPTS),
+            SIE => {
+                let delegated = self.read(MIDELEG);
+                self.write(MIE, self.read(MIE) & !delegated | value & delegated)
+            }
+            // of the pending interrupts, S-mode can only write its software interrupt
+            SIP => {
+                let writable =
+                    self.read(MIDELEG) & 1 << Interrupt::SupervisorSoftware.to_primitive();
+                self.write(MIP, self.read(MIP) & !writable | value & writable)
+            }
+            MSTATUS => {
+                let mut status = value;
+                let mut keep = |field: &Range<usize>| {
+                    let mask = Self::extract(u64::MAX, field) << field.start;
+                    status = status & !mask | self.csr[&MSTATUS] & mask;
+                };
+                for field in [STATUS_SXL, STATUS_UXL] {
+                    // SXL and UXL only exist in RV64, where they can be set to 32 or 64 bits
+                    let xl = Self::extract(value, &field);
+                    if self.xlen == Xlen::Rv32 || Xlen::from_primitive(xl).is_none() {
+                        keep(&field);
+                    }
+                }
+                // MPP cannot hold the reserved mode 2
+                if PrivilegeMode::from_primitive(Self::extract(value, &STATUS_MPP)).is_none() {
+                    keep(&STATUS_MPP);
+                }
+                // bit 31 is reserved in RV64, but is where a 32-bit view writes SD
+                if self.xlen == Xlen::Rv64 {
+                    status &= !(1 << 31);
+                }
+                self.write_raw(MSTATUS, status)
+            }
+            SSTATUS => {
+                let status = self.read(MSTATUS) & !SSTATUS_MASK | value & SSTATUS_MASK;
+                self.write(MSTATUS, status)
+            }
+            FFLAGS => self.write(FCSR, self.read(FCSR) & !0b11111 | value & 0b11111),
+            FRM => self.write(FCSR, self.read(FCSR) & 0b11111 | (value & 0b111) << 5),
+            FCSR => self.write_raw(FCSR, value & 0xff),
+            VXSAT => self.write(VCSR, self.read(VCSR) & !0b1 | value & 0b1),
+            VXRM => self.write(VCSR, self.read(VCSR) & 0b1 | (value & 0b11) << 1),
+            VCSR => self.write_raw(VCSR, value & 0b111),
+            // STCE is read-only zero without Sstc
+            MENVCFG => {
+                let stce = (self.stimecmp.is_some() as u64) << MENVCFG_STCE.start;
+                self.write_raw(MENVCFG, value & (!(1 << MENVCFG_STCE.start) | stce));
+                self.update_supervisor_timer();
+            }
+            // a write that selects a scheme of translation other than Bare, Sv32 in RV32 and Sv39
+            // in RV64 has no effect
+            SATP => {
+                let supported = match self.xlen(&PrivilegeMode::Supervisor) {
+                    Xlen::Rv32 => [SATP_MODE_BARE, SATP_MODE_SV32].contains(&(value >> 31)),
+                    Xlen::Rv64 => [SATP_MODE_BARE, SATP_MODE_SV39].contains(&(value >> 60)),
+                };
+                if supported {
+                    self.write_raw(SATP, value)
+                }
+            }
+            MISELECT | SISELECT => self.write_raw(address, value & 0xfff),
+            // the priorities are read-only zero, and no interrupt is virtual
+            MIREG | SIREG | MVIEN => {}
+            MVIP => {
+                let mip = self.read(MIP) & !SUPERVISOR_INTERRUPTS;
+                self.write(MIP, mip | value & SUPERVISOR_INTERRUPTS)
+            }
+            STIMECMP => {
+                if self.stimecmp.is_some() {
+                    self.stimecmp = Some(value);
+                    self.update_supervisor_timer();
+                }
+            }
+            _ => self.write_raw(address, value),
+        }
+    }
+
+    fn write_raw(&mut self, address: u64, value: u64) {
         if self.contains(address) {
             *self.csr.get_mut(&address).unwrap() = value;
         }
     }
 
+    fn extract(value: u64, field: &Range<usize>) -> u64 {
+        let length = field.end - field.start + 1;
+        let mask = if length == 64 {
+            u64::MAX
+        } else {
+            (1 << length) - 1
+        };
+        (value >> field.start) & mask
+    }
+
+    pub fn read_field(&self, address: u64, field: &Range<usize>) -> u64 {
+        Self::extract(self.read(address), field)
+    }
+
+    pub fn write_field(&mut self, address: u64, field: &Range<usize>, value: u64) {
+        let mask = Self::extract(u64::MAX, field) << field.start;
+        let status = self.read(address) & !mask | (value << field.start) & mask;
+        self.write(address, status);
+    }
+
+    // reading the seed CSR consumes entropy, so it is handled apart from the plain reads
+    fn read_and_consume(&mut self, address: u64) -> u64 {
+        match address {
+            SEED => match &mut self.entropy {
+                Some(entropy) => SEED_OPST_ES16 | entropy.next() & 0xffff,
+                None => 0,
+            },
+            _ => self.read(address),
+        }
+    }
+
+    pub fn seed_entropy(&mut self, seed: u64) {
+        if self.entropy.is_some() {
+            self.entropy = Some(EntropySource::new(seed));
+        }
+    }
+
+    // Performs a CSR instruction as seen by software running at `xlen`, replacing the value with
+    // `update` applied to it. In RV32 the 64-bit CSRs are split into halves. Returns None when
+    // the CSR is not implemented.
+    pub fn access(
+        &mut self,
+        address: u64,
+        xlen: Xlen,
+        update: impl FnOnce(u64) -> u64,
+    ) -> Option<u64> {
+        // the upper halves of the interrupt CSRs come with the AIA
+        let aia = match address {
+            MIDELEGH | MIEH | MIPH | MVIENH | MVIPH => self.contains(MISELECT),
+            SIEH | SIPH => self.contains(SISELECT),
+            _ => true,
+        };
+        if !aia {
+            return None;
+        }
+        let (address, field) = match (xlen, Self::upper_half(address)) {
+            (Xlen::Rv32, Some(address)) => (address, 32..63),
+            (Xlen::Rv32, None) => (address, 0..31),
+            (Xlen::Rv64, Some(_)) => return None,
+            (Xlen::Rv64, None) => (address, 0..63),
+        };
+        if !self.implemented(address) {
+            return None;
+        }
+        let mut t = Self::extract(self.read_and_consume(address), &field);
+        // a 32-bit view of the 64-bit status registers finds SD in its own most significant bit
+        if self.xlen == Xlen::Rv64 && field == (0..31) && matches!(address, MSTATUS | SSTATUS) {
+            t |= (self.read(address) >> 63) << 31;
+        }
+        self.write_field(address, &field, update(t));
+        Some(t)
+    }
+
+    // Whether miselect or siselect selects the priority of a major interrupt, where RV64 has
+    // only the even-numbered registers.
+    fn iprio_selected(&self, select: u64) -> bool {
+        ISELECT_IPRIO.contains(&select) && (self.xlen == Xlen::Rv32 || select.is_multiple_of(2))
+    }
+
+    // The interrupt file of an IMSIC that a CSR accesses, with the register that miselect or
+    // siselect selects through mireg or sireg, or None through mtopei or stopei, which report
+    // and claim its top interrupt.
+    pub fn interrupt_file_register(&self, address: u64) -> Option<(Level, Option<u64>)> {
+        let (level, select) = match address {
+            MIREG if self.contains(MISELECT) => (Level::Machine, Some(self.read(MISELECT))),
+            SIREG if self.contains(SISELECT) => (Level::Supervisor, Some(self.read(SISELECT))),
+            MTOPEI if self.contains(MISELECT) => (Level::Machine, None),
+            STOPEI if self.contains(SISELECT) => (Level::Supervisor, None),
+            _ => return None,
+        };
+        match select {
+            Some(select) if !ISELECT_IMSIC.contains(&select) => None,
+            _ => Some((level, select)),
+        }
+    }
+
+    // mtopi and stopi report the pending and enabled interrupt to take first, whose priority is
+    // 1 as all the priorities are zero.
+    fn topi(pending: u64) -> u64 {
+        Interrupt::PRIORITY
+            .into_iter()
+            .find(|interrupt| (pending >> interrupt.to_primitive()) & 1 == 1)
+            .map_or(0, |interrupt| interrupt.to_primitive() << 16 | 1)
+    }
+
+    // The XLEN that a privilege mode runs at, which is MXL for M-mode and is given by SXL and UXL
+    // for the lower modes.
+    pub fn xlen(&self, prv: &PrivilegeMode) -> Xlen {
+        let field = match prv {
+            _ if self.xlen == Xlen::Rv32 => return Xlen::Rv32,
+            PrivilegeMode::Machine => return self.xlen,
+            PrivilegeMode::Supervisor => STATUS_SXL,
+            PrivilegeMode::User => STATUS_UXL,
+        };
+        // writes keep the fields at 32 or 64 bits
+        let xl = self.read_field(MSTATUS, &field);
+        Xlen::from_primitive(xl).unwrap_or_else(|| panic!("mstatus holds the reserved XLEN {}", xl))
+    }
+
+    // Drives the pending bits of the interrupts that come from the platform.
+    pub fn set_platform_interrupts(&mut self, pending: u64) {
+        self.platform_interrupts = pending & PLATFORM_INTERRUPTS;
+        let mip = self.read(MIP) & !PLATFORM_INTERRUPTS | self.platform_interrupts;
+        self.write_raw(MIP, mip | self.software_seip);
+    }
+
+    // Sets or clears a pending bit as M-mode software may, for the SBI of the simulator.
+    pub fn set_pending(&mut self, interrupt: Interrupt, pending: bool) {
+        let bit = 1 << interrupt.to_primitive();
+        let mip = self.read(MIP);
+        self.write_raw(MIP, if pending { mip | bit } else { mip & !bit });
+    }
+
+    pub fn set_time(&mut self, time: u64) {
+        self.time = time;
+        self.update_supervisor_timer();
+    }
+
+    // The pending bits that software cannot write directly: those of the platform, and STIP
+    // while stimecmp drives it.
+    fn driven_interrupts(&self) -> u64 {
+        let timer = match self.stimecmp.is_some() && self.read_field(MENVCFG, &MENVCFG_STCE) == 1 {
+            true => 1 << Interrupt::SupervisorTimer.to_primitive(),
+            false => 0,
+        };
+        PLATFORM_INTERRUPTS | timer | !(MACHINE_INTERRUPTS | SUPERVISOR_INTERRUPTS)
+    }
+
+    // With Sstc enabled by menvcfg.STCE, STIP is pending as long as time has reached stimecmp.
+    fn update_supervisor_timer(&mut self) {
+        let Some(stimecmp) = self.stimecmp else {
+            return;
+        };
+        if self.read_field(MENVCFG, &MENVCFG_STCE) == 0 {
+            return;
+        }
+        let stip = 1 << Interrupt::SupervisorTimer.to_primitive();
+        let mip = self.read(MIP);
+        let pending = match self.time >= stimecmp {
+            true => mip | stip,
+            false => mip & !stip,
+        };
+        if pending != mip {
+            self.write_raw(MIP, pending);
+        }
+    }
+
+    // The interrupts that are both pending and enabled, whatever the privilege mode.
+    pub fn enabled_interrupts(&self) -> u64 {
+        match self.read(MIP) {
+            0 => 0,
+            pending => pending & self.read(MIE),
+        }
+    }
+
+    pub fn increment_counters(&mut self, retired: bool) {
+        self.write_raw(MCYCLE, self.read(MCYCLE).wrapping_add(1));
+        if retired {
+            self.write_raw(MINSTRET, self.read(MINSTRET).wrapping_add(1));
+        }
+    }
+
     pub fn csrrw(&mut self, address: u64, value: u64) -> u64 {
-        let t = self.read(address);
+        let t = self.read_and_consume(address);
         self.write(address, value);
         t
     }
 
     pub fn csrrs(&mut self, address: u64, value: u64) -> u64 {
-        let t = self.read(address);
+        let t = self.read_and_consume(address);
         self.write(address, self.read(address) | value);
         t
     }
-
-    pub fn csrrc(&mut self, address: u64, value: u64) -> u64 {
-        let t = self.read(address);
-        self.write(address, self.read(address) & !value);
-        t
-    }
-}
-
-impl Default for ControlAndStatusRegister {
-    fn default() -> Self {
-        Self {
-            csr: [
-                MSTATUS, MEDELEG, MIDELEG, MTVEC, MEPC, MCAUSE, MTVAL, SSTATUS, SEDELEG, SIDELEG,
-                STVEC, SEPC, SCAUSE, STVAL, USTATUS, UTVEC, UEPC, UCAUSE, UTVAL,
-            ]
-            .iter()
-            .cloned()
-            .map(|a| (a, 0))
-            .collect::<HashMap<_, _>>(),
-        }
-    }
 }
@@ -44,6 +44,12 @@ impl Decoder for PrivilegedDecoder {
                             0b00010 => Some(PrivilegedOpcodeR::Mret),
                             _ => None,
                         },
+                        0b0001000 => match rs2 {
+                            0b00010 => Some(PrivilegedOpcodeR::Sret),
+                            0b00101 => Some(PrivilegedOpcodeR::Wfi),
+                            _ => None,
+                        },
+                        0b0001001 => Some(PrivilegedOpcodeR::SfenceVma),
                         _ => None,
                     },
                     _ => None,
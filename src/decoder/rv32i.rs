@@ -40,17 +40,40 @@ impl Decoder for Rv32iDecoder {
             0b0110111 => Self::decode_u(Some(Rv32iOpcodeU::Lui), instruction),
             0b0010111 => Self::decode_u(Some(Rv32iOpcodeU::Auipc), instruction),
             0b1101111 => Self::decode_j(Some(Rv32iOpcodeJ::Jal), instruction),
+            0b1100111 => Self::decode_i(
+                match funct3 {
+                    0b000 => Some(Rv32iOpcodeI::Jalr),
+                    _ => None,
+                },
+                instruction,
+            ),
             0b1100011 => Self::decode_b(
                 match funct3 {
                     0b000 => Some(Rv32iOpcodeB::Beq),
                     0b001 => Some(Rv32iOpcodeB::Bne),
+                    0b100 => Some(Rv32iOpcodeB::Blt),
                     0b101 => Some(Rv32iOpcodeB::Bge),
+                    0b110 => Some(Rv32iOpcodeB::Bltu),
+                    0b111 => Some(Rv32iOpcodeB::Bgeu),
+                    _ => None,
+                },
+                instruction,
+            ),
+            0b0000011 => Self::decode_i(
+                match funct3 {
+                    0b000 => Some(Rv32iOpcodeI::Lb),
+                    0b001 => Some(Rv32iOpcodeI::Lh),
+                    0b010 => Some(Rv32iOpcodeI::Lw),
+                    0b100 => Some(Rv32iOpcodeI::Lbu),
+                    0b101 => Some(Rv32iOpcodeI::Lhu),
                     _ => None,
                 },
                 instruction,
             ),
             0b0100011 => Self::decode_s(
                 match funct3 {
+                    0b000 => Some(Rv32iOpcodeS::Sb),
+                    0b001 => Some(Rv32iOpcodeS::Sh),
                     0b010 => Some(Rv32iOpcodeS::Sw),
                     _ => None,
                 },
@@ -59,7 +82,21 @@ impl Decoder for Rv32iDecoder {
             0b0010011 => Self::decode_i(
                 match funct3 {
                     0b000 => Some(Rv32iOpcodeI::Addi),
-                    0b001 => Some(Rv32iOpcodeI::Slli),
+                    0b010 => Some(Rv32iOpcodeI::Slti),
+                    0b011 => Some(Rv32iOpcodeI::Sltiu),
+                    0b100 => Some(Rv32iOpcodeI::Xori),
+                    0b110 => Some(Rv32iOpcodeI::Ori),
+                    0b111 => Some(Rv32iOpcodeI::Andi),
+                    // imm[11:6] selects the shift, and shamt[5] is only valid in RV64
+                    0b001 => match funct7 >> 1 {
+                        0b000000 => Some(Rv32iOpcodeI::Slli),
+                        _ => None,
+                    },
+                    0b101 => match funct7 >> 1 {
+                        0b000000 => Some(Rv32iOpcodeI::Srli),
+                        0b010000 => Some(Rv32iOpcodeI::Srai),
+                        _ => None,
+                    },
                     _ => None,
                 },
                 instruction,
@@ -68,6 +105,18 @@ impl Decoder for Rv32iDecoder {
                 match funct7 {
                     0b0000000 => match funct3 {
                         0b000 => Some(Rv32iOpcodeR::Add),
+                        0b001 => Some(Rv32iOpcodeR::Sll),
+                        0b010 => Some(Rv32iOpcodeR::Slt),
+                        0b011 => Some(Rv32iOpcodeR::Sltu),
+                        0b100 => Some(Rv32iOpcodeR::Xor),
+                        0b101 => Some(Rv32iOpcodeR::Srl),
+                        0b110 => Some(Rv32iOpcodeR::Or),
+                        0b111 => Some(Rv32iOpcodeR::And),
+                        _ => None,
+                    },
+                    0b0100000 => match funct3 {
+                        0b000 => Some(Rv32iOpcodeR::Sub),
+                        0b101 => Some(Rv32iOpcodeR::Sra),
                         _ => None,
                     },
                     _ => None,
@@ -84,7 +133,8 @@ impl Decoder for Rv32iDecoder {
             0b1110011 => Self::decode_i(
                 match funct3 {
                     0b000 => match instruction >> 20 {
-                        0b0 => Some(Rv32iOpcodeI::Ecall),
+                        0b000000000000 => Some(Rv32iOpcodeI::Ecall),
+                        0b000000000001 => Some(Rv32iOpcodeI::Ebreak),
                         _ => None,
                     },
                     _ => None,
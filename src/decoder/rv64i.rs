@@ -33,10 +33,52 @@ impl Decoder for Rv64iDecoder {
     > {
         let opcode = instruction & MASK_7BIT;
         let funct3 = (instruction >> 12) & MASK_3BIT;
+        let funct7 = (instruction >> 25) & MASK_7BIT;
         match opcode {
+            0b0000011 => Self::decode_i(
+                match funct3 {
+                    0b110 => Some(Rv64iOpcodeI::Lwu),
+                    0b011 => Some(Rv64iOpcodeI::Ld),
+                    _ => None,
+                },
+                instruction,
+            ),
+            0b0100011 => Self::decode_s(
+                match funct3 {
+                    0b011 => Some(Rv64iOpcodeS::Sd),
+                    _ => None,
+                },
+                instruction,
+            ),
             0b0011011 => Self::decode_i(
                 match funct3 {
                     0b000 => Some(Rv64iOpcodeI::Addiw),
+                    0b001 => match funct7 {
+                        0b0000000 => Some(Rv64iOpcodeI::Slliw),
+                        _ => None,
+                    },
+                    0b101 => match funct7 {
+                        0b0000000 => Some(Rv64iOpcodeI::Srliw),
+                        0b0100000 => Some(Rv64iOpcodeI::Sraiw),
+                        _ => None,
+                    },
+                    _ => None,
+                },
+                instruction,
+            ),
+            0b0111011 => Self::decode_r(
+                match funct7 {
+                    0b0000000 => match funct3 {
+                        0b000 => Some(Rv64iOpcodeR::Addw),
+                        0b001 => Some(Rv64iOpcodeR::Sllw),
+                        0b101 => Some(Rv64iOpcodeR::Srlw),
+                        _ => None,
+                    },
+                    0b0100000 => match funct3 {
+                        0b000 => Some(Rv64iOpcodeR::Subw),
+                        0b101 => Some(Rv64iOpcodeR::Sraw),
+                        _ => None,
+                    },
                     _ => None,
                 },
                 instruction,
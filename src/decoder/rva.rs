@@ -0,0 +1,58 @@
+use crate::{
+    decoder::{Decoder, MASK_3BIT, MASK_5BIT, MASK_7BIT},
+    instruction::{
+        rva::{RvaOpcodeB, RvaOpcodeI, RvaOpcodeJ, RvaOpcodeR, RvaOpcodeS, RvaOpcodeU},
+        Instruction,
+    },
+};
+
+pub struct RvaDecoder;
+
+impl Decoder for RvaDecoder {
+    type OpcodeR = RvaOpcodeR;
+    type OpcodeI = RvaOpcodeI;
+    type OpcodeS = RvaOpcodeS;
+    type OpcodeB = RvaOpcodeB;
+    type OpcodeU = RvaOpcodeU;
+    type OpcodeJ = RvaOpcodeJ;
+
+    #[allow(clippy::type_complexity)]
+    fn decode(
+        instruction: u32,
+    ) -> Option<
+        Instruction<
+            Self::OpcodeR,
+            Self::OpcodeI,
+            Self::OpcodeS,
+            Self::OpcodeB,
+            Self::OpcodeU,
+            Self::OpcodeJ,
+        >,
+    > {
+        let opcode = instruction & MASK_7BIT;
+        let funct3 = (instruction >> 12) & MASK_3BIT;
+        let rs2 = (instruction >> 20) & MASK_5BIT;
+        // the two low bits of funct7 are aq and rl
+        let funct5 = instruction >> 27;
+        match opcode {
+            0b0101111 if funct3 == 0b010 || funct3 == 0b011 => Self::decode_r(
+                match funct5 {
+                    0b00010 if rs2 == 0 => Some(RvaOpcodeR::Lr),
+                    0b00011 => Some(RvaOpcodeR::Sc),
+                    0b00001 => Some(RvaOpcodeR::Amoswap),
+                    0b00000 => Some(RvaOpcodeR::Amoadd),
+                    0b00100 => Some(RvaOpcodeR::Amoxor),
+                    0b01100 => Some(RvaOpcodeR::Amoand),
+                    0b01000 => Some(RvaOpcodeR::Amoor),
+                    0b10000 => Some(RvaOpcodeR::Amomin),
+                    0b10100 => Some(RvaOpcodeR::Amomax),
+                    0b11000 => Some(RvaOpcodeR::Amominu),
+                    0b11100 => Some(RvaOpcodeR::Amomaxu),
+                    _ => None,
+                },
+                instruction,
+            ),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{
+    decoder::{Decoder, MASK_3BIT, MASK_5BIT, MASK_7BIT},
+    float::Format,
+    instruction::{
+        rvf::{RvfOpcodeB, RvfOpcodeI, RvfOpcodeJ, RvfOpcodeR, RvfOpcodeS, RvfOpcodeU},
+        Instruction,
+    },
+};
+
+pub struct RvfDecoder;
+
+impl Decoder for RvfDecoder {
+    type OpcodeR = RvfOpcodeR;
+    type OpcodeI = RvfOpcodeI;
+    type OpcodeS = RvfOpcodeS;
+    type OpcodeB = RvfOpcodeB;
+    type OpcodeU = RvfOpcodeU;
+    type OpcodeJ = RvfOpcodeJ;
+
+    #[allow(clippy::type_complexity)]
+    fn decode(
+        instruction: u32,
+    ) -> Option<
+        Instruction<
+            Self::OpcodeR,
+            Self::OpcodeI,
+            Self::OpcodeS,
+            Self::OpcodeB,
+            Self::OpcodeU,
+            Self::OpcodeJ,
+        >,
+    > {
+        let opcode = instruction & MASK_7BIT;
+        let funct3 = (instruction >> 12) & MASK_3BIT;
+        let rs2 = (instruction >> 20) & MASK_5BIT;
+        let funct7 = (instruction >> 25) & MASK_7BIT;
+        let funct5 = funct7 >> 2;
+        // the fmt field also occupies the low bits of funct7 in the R4-type instructions
+        let fmt = (funct7 & 0b11) as usize;
+        if matches!(
+            opcode,
+            0b1000011 | 0b1000111 | 0b1001011 | 0b1001111 | 0b1010011
+        ) && Format::from_fmt(fmt).is_none()
+        {
+            return None;
+        }
+        match opcode {
+            0b0000111 => Self::decode_i(
+                match funct3 {
+                    0b001 => Some(RvfOpcodeI::Flh),
+                    0b010 => Some(RvfOpcodeI::Flw),
+                    0b011 => Some(RvfOpcodeI::Fld),
+                    _ => None,
+                },
+                instruction,
+            ),
+            0b0100111 => Self::decode_s(
+                match funct3 {
+                    0b001 => Some(RvfOpcodeS::Fsh),
+                    0b010 => Some(RvfOpcodeS::Fsw),
+                    0b011 => Some(RvfOpcodeS::Fsd),
+                    _ => None,
+                },
+                instruction,
+            ),
+            0b1000011 => Self::decode_r(Some(RvfOpcodeR::Fmadd), instruction),
+            0b1000111 => Self::decode_r(Some(RvfOpcodeR::Fmsub), instruction),
+            0b1001011 => Self::decode_r(Some(RvfOpcodeR::Fnmsub), instruction),
+            0b1001111 => Self::decode_r(Some(RvfOpcodeR::Fnmadd), instruction),
+            0b1010011 => Self::decode_r(
+                match funct5 {
+                    0b00000 => Some(RvfOpcodeR::Fadd),
+                    0b00001 => Some(RvfOpcodeR::Fsub),
+                    0b00010 => Some(RvfOpcodeR::Fmul),
+                    0b00011 => Some(RvfOpcodeR::Fdiv),
+                    0b01011 => match rs2 {
+                        0b00000 => Some(RvfOpcodeR::Fsqrt),
+                        _ => None,
+                    },
+                    0b00100 => match funct3 {
+                        0b000 => Some(RvfOpcodeR::Fsgnj),
+                        0b001 => Some(RvfOpcodeR::Fsgnjn),
+                        0b010 => Some(RvfOpcodeR::Fsgnjx),
+                        _ => None,
+                    },
+                    0b00101 => match funct3 {
+                        0b000 => Some(RvfOpcodeR::Fmin),
+                        0b001 => Some(RvfOpcodeR::Fmax),
+                        _ => None,
+                    },
+                    // rs2 holds the fmt of the source
+                    0b01000 => match Format::from_fmt(rs2 as usize) {
+                        Some(_) if rs2 as usize != fmt => Some(RvfOpcodeR::FcvtFF),
+                        _ => None,
+                    },
+                    0b10100 => match funct3 {
+                        0b010 => Some(RvfOpcodeR::Feq),
+                        0b001 => Some(RvfOpcodeR::Flt),
+                        0b000 => Some(RvfOpcodeR::Fle),
+                        _ => None,
+                    },
+                    0b11000 => match rs2 {
+                        0b00000 => Some(RvfOpcodeR::FcvtWF),
+                        0b00001 => Some(RvfOpcodeR::FcvtWuF),
+                        0b00010 => Some(RvfOpcodeR::FcvtLF),
+                        0b00011 => Some(RvfOpcodeR::FcvtLuF),
+                        _ => None,
+                    },
+                    0b11010 => match rs2 {
+                        0b00000 => Some(RvfOpcodeR::FcvtFW),
+                        0b00001 => Some(RvfOpcodeR::FcvtFWu),
+                        0b00010 => Some(RvfOpcodeR::FcvtFL),
+                        0b00011 => Some(RvfOpcodeR::FcvtFLu),
+                        _ => None,
+                    },
+                    0b11100 => match (funct3, rs2) {
+                        (0b000, 0b00000) => Some(RvfOpcodeR::FmvXF),
+                        (0b001, 0b00000) => Some(RvfOpcodeR::Fclass),
+                        _ => None,
+                    },
+                    0b11110 => match (funct3, rs2) {
+                        (0b000, 0b00000) => Some(RvfOpcodeR::FmvFX),
+                        _ => None,
+                    },
+                    _ => None,
+                },
+                instruction,
+            ),
+            _ => None,
+        }
+    }
+}
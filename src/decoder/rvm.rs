@@ -0,0 +1,67 @@
+use crate::{
+    decoder::{Decoder, MASK_3BIT, MASK_7BIT},
+    instruction::{
+        rvm::{RvmOpcodeB, RvmOpcodeI, RvmOpcodeJ, RvmOpcodeR, RvmOpcodeS, RvmOpcodeU},
+        Instruction,
+    },
+};
+
+pub struct RvmDecoder;
+
+impl Decoder for RvmDecoder {
+    type OpcodeR = RvmOpcodeR;
+    type OpcodeI = RvmOpcodeI;
+    type OpcodeS = RvmOpcodeS;
+    type OpcodeB = RvmOpcodeB;
+    type OpcodeU = RvmOpcodeU;
+    type OpcodeJ = RvmOpcodeJ;
+
+    #[allow(clippy::type_complexity)]
+    fn decode(
+        instruction: u32,
+    ) -> Option<
+        Instruction<
+            Self::OpcodeR,
+            Self::OpcodeI,
+            Self::OpcodeS,
+            Self::OpcodeB,
+            Self::OpcodeU,
+            Self::OpcodeJ,
+        >,
+    > {
+        let opcode = instruction & MASK_7BIT;
+        let funct3 = (instruction >> 12) & MASK_3BIT;
+        let funct7 = (instruction >> 25) & MASK_7BIT;
+        if funct7 != 0b0000001 {
+            return None;
+        }
+        match opcode {
+            0b0110011 => Self::decode_r(
+                match funct3 {
+                    0b000 => Some(RvmOpcodeR::Mul),
+                    0b001 => Some(RvmOpcodeR::Mulh),
+                    0b010 => Some(RvmOpcodeR::Mulhsu),
+                    0b011 => Some(RvmOpcodeR::Mulhu),
+                    0b100 => Some(RvmOpcodeR::Div),
+                    0b101 => Some(RvmOpcodeR::Divu),
+                    0b110 => Some(RvmOpcodeR::Rem),
+                    0b111 => Some(RvmOpcodeR::Remu),
+                    _ => None,
+                },
+                instruction,
+            ),
+            0b0111011 => Self::decode_r(
+                match funct3 {
+                    0b000 => Some(RvmOpcodeR::Mulw),
+                    0b100 => Some(RvmOpcodeR::Divw),
+                    0b101 => Some(RvmOpcodeR::Divuw),
+                    0b110 => Some(RvmOpcodeR::Remw),
+                    0b111 => Some(RvmOpcodeR::Remuw),
+                    _ => None,
+                },
+                instruction,
+            ),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use crate::{
+    decoder::{Decoder, MASK_3BIT, MASK_5BIT, MASK_7BIT},
+    instruction::{
+        rvv::{RvvOpcodeB, RvvOpcodeI, RvvOpcodeJ, RvvOpcodeR, RvvOpcodeS, RvvOpcodeU},
+        Instruction,
+    },
+};
+
+pub const OPIVV: u32 = 0b000;
+pub const OPFVV: u32 = 0b001;
+pub const OPMVV: u32 = 0b010;
+pub const OPIVI: u32 = 0b011;
+pub const OPIVX: u32 = 0b100;
+pub const OPFVF: u32 = 0b101;
+pub const OPMVX: u32 = 0b110;
+pub const OPCFG: u32 = 0b111;
+
+const MASK_6BIT: u32 = 0b111111;
+
+pub struct RvvDecoder;
+
+impl RvvDecoder {
+    fn decode_integer(funct3: u32, funct6: u32, vm: u32, vs1: u32) -> Option<RvvOpcodeR> {
+        let vv = funct3 == OPIVV;
+        let vi = funct3 == OPIVI;
+        match funct6 {
+            0b000000 => Some(RvvOpcodeR::Vadd),
+            0b000010 if !vi => Some(RvvOpcodeR::Vsub),
+            0b000011 if !vv => Some(RvvOpcodeR::Vrsub),
+            0b000100 if !vi => Some(RvvOpcodeR::Vminu),
+            0b000101 if !vi => Some(RvvOpcodeR::Vmin),
+            0b000110 if !vi => Some(RvvOpcodeR::Vmaxu),
+            0b000111 if !vi => Some(RvvOpcodeR::Vmax),
+            0b001001 => Some(RvvOpcodeR::Vand),
+            0b001010 => Some(RvvOpcodeR::Vor),
+            0b001011 => Some(RvvOpcodeR::Vxor),
+            0b001100 => Some(RvvOpcodeR::Vrgather),
+            0b001110 if vv => Some(RvvOpcodeR::Vrgatherei16),
+            0b001110 => Some(RvvOpcodeR::Vslideup),
+            0b001111 if !vv => Some(RvvOpcodeR::Vslidedown),
+            0b010000 if vm == 0 => Some(RvvOpcodeR::Vadc),
+            0b010001 => Some(RvvOpcodeR::Vmadc),
+            0b010010 if vm == 0 && !vi => Some(RvvOpcodeR::Vsbc),
+            0b010011 if !vi => Some(RvvOpcodeR::Vmsbc),
+            0b010111 if vm == 0 => Some(RvvOpcodeR::Vmerge),
+            0b010111 => Some(RvvOpcodeR::Vmv),
+            0b011000 => Some(RvvOpcodeR::Vmseq),
+            0b011001 => Some(RvvOpcodeR::Vmsne),
+            0b011010 if !vi => Some(RvvOpcodeR::Vmsltu),
+            0b011011 if !vi => Some(RvvOpcodeR::Vmslt),
+            0b011100 => Some(RvvOpcodeR::Vmsleu),
+            0b011101 => Some(RvvOpcodeR::Vmsle),
+            0b011110 if !vv => Some(RvvOpcodeR::Vmsgtu),
+            0b011111 if !vv => Some(RvvOpcodeR::Vmsgt),
+            0b100000 => Some(RvvOpcodeR::Vsaddu),
+            0b100001 => Some(RvvOpcodeR::Vsadd),
+            0b100010 if !vi => Some(RvvOpcodeR::Vssubu),
+            0b100011 if !vi => Some(RvvOpcodeR::Vssub),
+            0b100101 => Some(RvvOpcodeR::Vsll),
+            0b100111 if vi && vm == 1 && matches!(vs1, 0 | 1 | 3 | 7) => Some(RvvOpcodeR::Vmvnr),
+            0b100111 if !vi => Some(RvvOpcodeR::Vsmul),
+            0b101000 => Some(RvvOpcodeR::Vsrl),
+            0b101001 => Some(RvvOpcodeR::Vsra),
+            0b101010 => Some(RvvOpcodeR::Vssrl),
+            0b101011 => Some(RvvOpcodeR::Vssra),
+            0b101100 => Some(RvvOpcodeR::Vnsrl),
+            0b101101 => Some(RvvOpcodeR::Vnsra),
+            0b101110 => Some(RvvOpcodeR::Vnclipu),
+            0b101111 => Some(RvvOpcodeR::Vnclip),
+            0b110000 if vv => Some(RvvOpcodeR::Vwredsumu),
+            0b110001 if vv => Some(RvvOpcodeR::Vwredsum),
+            _ => None,
+        }
+    }
+
+    fn decode_multiply(funct3: u32, funct6: u32, vm: u32, vs1: u32) -> Option<RvvOpcodeR> {
+        let vv = funct3 == OPMVV;
+        match funct6 {
+            0b000000 if vv => Some(RvvOpcodeR::Vredsum),
+            0b000001 if vv => Some(RvvOpcodeR::Vredand),
+            0b000010 if vv => Some(RvvOpcodeR::Vredor),
+            0b000011 if vv => Some(RvvOpcodeR::Vredxor),
+            0b000100 if vv => Some(RvvOpcodeR::Vredminu),
+            0b000101 if vv => Some(RvvOpcodeR::Vredmin),
+            0b000110 if vv => Some(RvvOpcodeR::Vredmaxu),
+            0b000111 if vv => Some(RvvOpcodeR::Vredmax),
+            0b001000 => Some(RvvOpcodeR::Vaaddu),
+            0b001001 => Some(RvvOpcodeR::Vaadd),
+            0b001010 => Some(RvvOpcodeR::Vasubu),
+            0b001011 => Some(RvvOpcodeR::Vasub),
+            0b001110 if !vv => Some(RvvOpcodeR::Vslide1up),
+            0b001111 if !vv => Some(RvvOpcodeR::Vslide1down),
+            0b010000 if vv => match vs1 {
+                0b00000 if vm == 1 => Some(RvvOpcodeR::Vmvxs),
+                0b10000 => Some(RvvOpcodeR::Vcpop),
+                0b10001 => Some(RvvOpcodeR::Vfirst),
+                _ => None,
+            },
+            0b010000 if vm == 1 => Some(RvvOpcodeR::Vmvsx),
+            0b010010 if vv => match vs1 {
+                0b00010 | 0b00100 | 0b00110 => Some(RvvOpcodeR::Vzext),
+                0b00011 | 0b00101 | 0b00111 => Some(RvvOpcodeR::Vsext),
+                _ => None,
+            },
+            0b010100 if vv => match vs1 {
+                0b00001 => Some(RvvOpcodeR::Vmsbf),
+                0b00010 => Some(RvvOpcodeR::Vmsof),
+                0b00011 => Some(RvvOpcodeR::Vmsif),
+                0b10000 => Some(RvvOpcodeR::Viota),
+                0b10001 => Some(RvvOpcodeR::Vid),
+                _ => None,
+            },
+            0b010111 if vv && vm == 1 => Some(RvvOpcodeR::Vcompress),
+            0b011000 if vv && vm == 1 => Some(RvvOpcodeR::Vmandn),
+            0b011001 if vv && vm == 1 => Some(RvvOpcodeR::Vmand),
+            0b011010 if vv && vm == 1 => Some(RvvOpcodeR::Vmor),
+            0b011011 if vv && vm == 1 => Some(RvvOpcodeR::Vmxor),
+            0b011100 if vv && vm == 1 => Some(RvvOpcodeR::Vmorn),
+            0b011101 if vv && vm == 1 => Some(RvvOpcodeR::Vmnand),
+            0b011110 if vv && vm == 1 => Some(RvvOpcodeR::Vmnor),
+            0b011111 if vv && vm == 1 => Some(RvvOpcodeR::Vmxnor),
+            0b100000 => Some(RvvOpcodeR::Vdivu),
+            0b100001 => Some(RvvOpcodeR::Vdiv),
+            0b100010 => Some(RvvOpcodeR::Vremu),
+            0b100011 => Some(RvvOpcodeR::Vrem),
+            0b100100 => Some(RvvOpcodeR::Vmulhu),
+            0b100101 => Some(RvvOpcodeR::Vmul),
+            0b100110 => Some(RvvOpcodeR::Vmulhsu),
+            0b100111 => Some(RvvOpcodeR::Vmulh),
+            0b101001 => Some(RvvOpcodeR::Vmadd),
+            0b101011 => Some(RvvOpcodeR::Vnmsub),
+            0b101101 => Some(RvvOpcodeR::Vmacc),
+            0b101111 => Some(RvvOpcodeR::Vnmsac),
+            0b110000 => Some(RvvOpcodeR::Vwaddu),
+            0b110001 => Some(RvvOpcodeR::Vwadd),
+            0b110010 => Some(RvvOpcodeR::Vwsubu),
+            0b110011 => Some(RvvOpcodeR::Vwsub),
+            0b110100 => Some(RvvOpcodeR::VwadduW),
+            0b110101 => Some(RvvOpcodeR::VwaddW),
+            0b110110 => Some(RvvOpcodeR::VwsubuW),
+            0b110111 => Some(RvvOpcodeR::VwsubW),
+            0b111000 => Some(RvvOpcodeR::Vwmulu),
+            0b111010 => Some(RvvOpcodeR::Vwmulsu),
+            0b111011 => Some(RvvOpcodeR::Vwmul),
+            0b111100 => Some(RvvOpcodeR::Vwmaccu),
+            0b111101 => Some(RvvOpcodeR::Vwmacc),
+            0b111110 if !vv => Some(RvvOpcodeR::Vwmaccus),
+            0b111111 => Some(RvvOpcodeR::Vwmaccsu),
+            _ => None,
+        }
+    }
+
+    fn decode_floating_point(funct3: u32, funct6: u32, vm: u32, vs1: u32) -> Option<RvvOpcodeR> {
+        let vv = funct3 == OPFVV;
+        match funct6 {
+            0b000000 => Some(RvvOpcodeR::Vfadd),
+            0b000001 if vv => Some(RvvOpcodeR::Vfredusum),
+            0b000010 => Some(RvvOpcodeR::Vfsub),
+            0b000011 if vv => Some(RvvOpcodeR::Vfredosum),
+            0b000100 => Some(RvvOpcodeR::Vfmin),
+            0b000101 if vv => Some(RvvOpcodeR::Vfredmin),
+            0b000110 => Some(RvvOpcodeR::Vfmax),
+            0b000111 if vv => Some(RvvOpcodeR::Vfredmax),
+            0b001000 => Some(RvvOpcodeR::Vfsgnj),
+            0b001001 => Some(RvvOpcodeR::Vfsgnjn),
+            0b001010 => Some(RvvOpcodeR::Vfsgnjx),
+            0b001110 if !vv => Some(RvvOpcodeR::Vfslide1up),
+            0b001111 if !vv => Some(RvvOpcodeR::Vfslide1down),
+            0b010000 if vv && vm == 1 && vs1 == 0 => Some(RvvOpcodeR::Vfmvfs),
+            0b010000 if !vv && vm == 1 => Some(RvvOpcodeR::Vfmvsf),
+            0b010010 if vv => match vs1 {
+                0b00000 => Some(RvvOpcodeR::VfcvtXuF),
+                0b00001 => Some(RvvOpcodeR::VfcvtXF),
+                0b00010 => Some(RvvOpcodeR::VfcvtFXu),
+                0b00011 => Some(RvvOpcodeR::VfcvtFX),
+                0b00110 => Some(RvvOpcodeR::VfcvtRtzXuF),
+                0b00111 => Some(RvvOpcodeR::VfcvtRtzXF),
+                0b01000 => Some(RvvOpcodeR::VfwcvtXuF),
+                0b01001 => Some(RvvOpcodeR::VfwcvtXF),
+                0b01010 => Some(RvvOpcodeR::VfwcvtFXu),
+                0b01011 => Some(RvvOpcodeR::VfwcvtFX),
+                0b01100 => Some(RvvOpcodeR::VfwcvtFF),
+                0b01110 => Some(RvvOpcodeR::VfwcvtRtzXuF),
+                0b01111 => Some(RvvOpcodeR::VfwcvtRtzXF),
+                0b10000 => Some(RvvOpcodeR::VfncvtXuF),
+                0b10001 => Some(RvvOpcodeR::VfncvtXF),
+                0b10010 => Some(RvvOpcodeR::VfncvtFXu),
+                0b10011 => Some(RvvOpcodeR::VfncvtFX),
+                0b10100 => Some(RvvOpcodeR::VfncvtFF),
+                0b10101 => Some(RvvOpcodeR::VfncvtRodFF),
+                0b10110 => Some(RvvOpcodeR::VfncvtRtzXuF),
+                0b10111 => Some(RvvOpcodeR::VfncvtRtzXF),
+                _ => None,
+            },
+            0b010011 if vv => match vs1 {
+                0b00000 => Some(RvvOpcodeR::Vfsqrt),
+                0b00100 => Some(RvvOpcodeR::Vfrsqrt7),
+                0b00101 => Some(RvvOpcodeR::Vfrec7),
+                0b10000 => Some(RvvOpcodeR::Vfclass),
+                _ => None,
+            },
+            0b010111 if !vv && vm == 0 => Some(RvvOpcodeR::Vfmerge),
+            0b010111 if !vv => Some(RvvOpcodeR::Vfmv),
+            0b011000 => Some(RvvOpcodeR::Vmfeq),
+            0b011001 => Some(RvvOpcodeR::Vmfle),
+            0b011011 => Some(RvvOpcodeR::Vmflt),
+            0b011100 => Some(RvvOpcodeR::Vmfne),
+            0b011101 if !vv => Some(RvvOpcodeR::Vmfgt),
+            0b011111 if !vv => Some(RvvOpcodeR::Vmfge),
+            0b100000 => Some(RvvOpcodeR::Vfdiv),
+            0b100001 if !vv => Some(RvvOpcodeR::Vfrdiv),
+            0b100100 => Some(RvvOpcodeR::Vfmul),
+            0b100111 if !vv => Some(RvvOpcodeR::Vfrsub),
+            0b101000 => Some(RvvOpcodeR::Vfmadd),
+            0b101001 => Some(RvvOpcodeR::Vfnmadd),
+            0b101010 => Some(RvvOpcodeR::Vfmsub),
+            0b101011 => Some(RvvOpcodeR::Vfnmsub),
+            0b101100 => Some(RvvOpcodeR::Vfmacc),
+            0b101101 => Some(RvvOpcodeR::Vfnmacc),
+            0b101110 => Some(RvvOpcodeR::Vfmsac),
+            0b101111 => Some(RvvOpcodeR::Vfnmsac),
+            0b110000 => Some(RvvOpcodeR::Vfwadd),
+            0b110001 if vv => Some(RvvOpcodeR::Vfwredusum),
+            0b110010 => Some(RvvOpcodeR::Vfwsub),
+            0b110011 if vv => Some(RvvOpcodeR::Vfwredosum),
+            0b110100 => Some(RvvOpcodeR::VfwaddW),
+            0b110110 => Some(RvvOpcodeR::VfwsubW),
+            0b111000 => Some(RvvOpcodeR::Vfwmul),
+            0b111100 => Some(RvvOpcodeR::Vfwmacc),
+            0b111101 => Some(RvvOpcodeR::Vfwnmacc),
+            0b111110 => Some(RvvOpcodeR::Vfwmsac),
+            0b111111 => Some(RvvOpcodeR::Vfwnmsac),
+            _ => None,
+        }
+    }
+
+    fn decode_load(mop: u32, lumop: u32) -> Option<RvvOpcodeR> {
+        match mop {
+            0b00 => match lumop {
+                0b00000 => Some(RvvOpcodeR::Vle),
+                0b01000 => Some(RvvOpcodeR::Vlr),
+                0b01011 => Some(RvvOpcodeR::Vlm),
+                0b10000 => Some(RvvOpcodeR::Vleff),
+                _ => None,
+            },
+            0b01 => Some(RvvOpcodeR::Vluxei),
+            0b10 => Some(RvvOpcodeR::Vlse),
+            _ => Some(RvvOpcodeR::Vloxei),
+        }
+    }
+
+    fn decode_store(mop: u32, sumop: u32) -> Option<RvvOpcodeR> {
+        match mop {
+            0b00 => match sumop {
+                0b00000 => Some(RvvOpcodeR::Vse),
+                0b01000 => Some(RvvOpcodeR::Vsr),
+                0b01011 => Some(RvvOpcodeR::Vsm),
+                _ => None,
+            },
+            0b01 => Some(RvvOpcodeR::Vsuxei),
+            0b10 => Some(RvvOpcodeR::Vsse),
+            _ => Some(RvvOpcodeR::Vsoxei),
+        }
+    }
+}
+
+impl Decoder for RvvDecoder {
+    type OpcodeR = RvvOpcodeR;
+    type OpcodeI = RvvOpcodeI;
+    type OpcodeS = RvvOpcodeS;
+    type OpcodeB = RvvOpcodeB;
+    type OpcodeU = RvvOpcodeU;
+    type OpcodeJ = RvvOpcodeJ;
+
+    #[allow(clippy::type_complexity)]
+    fn decode(
+        instruction: u32,
+    ) -> Option<
+        Instruction<
+            Self::OpcodeR,
+            Self::OpcodeI,
+            Self::OpcodeS,
+            Self::OpcodeB,
+            Self::OpcodeU,
+            Self::OpcodeJ,
+        >,
+    > {
+        let opcode = instruction & MASK_7BIT;
+        let funct3 = (instruction >> 12) & MASK_3BIT;
+        let vs1 = (instruction >> 15) & MASK_5BIT;
+        let vs2 = (instruction >> 20) & MASK_5BIT;
+        let vm = (instruction >> 25) & 0b1;
+        let funct6 = (instruction >> 26) & MASK_6BIT;
+        let mop = (instruction >> 26) & 0b11;
+        let mew = (instruction >> 28) & 0b1;
+        match opcode {
+            0b1010111 => match funct3 {
+                OPCFG => match instruction >> 30 {
+                    0b00 | 0b01 => Self::decode_i(Some(RvvOpcodeI::Vsetvli), instruction),
+                    0b11 => Self::decode_i(Some(RvvOpcodeI::Vsetivli), instruction),
+                    _ => Self::decode_r(
+                        match (instruction >> 25) & MASK_7BIT {
+                            0b1000000 => Some(RvvOpcodeR::Vsetvl),
+                            _ => None,
+                        },
+                        instruction,
+                    ),
+                },
+                OPIVV | OPIVX | OPIVI => {
+                    Self::decode_r(Self::decode_integer(funct3, funct6, vm, vs1), instruction)
+                }
+                OPMVV | OPMVX => {
+                    Self::decode_r(Self::decode_multiply(funct3, funct6, vm, vs1), instruction)
+                }
+                _ => Self::decode_r(
+                    Self::decode_floating_point(funct3, funct6, vm, vs1),
+                    instruction,
+                ),
+            },
+            // only the vector widths of LOAD-FP and STORE-FP
+            0b0000111 => match funct3 {
+                0b000 | 0b101 | 0b110 | 0b111 if mew == 0 => {
+                    Self::decode_r(Self::decode_load(mop, vs2), instruction)
+                }
+                _ => None,
+            },
+            0b0100111 => match funct3 {
+                0b000 | 0b101 | 0b110 | 0b111 if mew == 0 => {
+                    Self::decode_r(Self::decode_store(mop, vs2), instruction)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{
+    decoder::{Decoder, MASK_3BIT, MASK_5BIT, MASK_7BIT},
+    float::Format,
+    instruction::{
+        zfa::{ZfaOpcodeB, ZfaOpcodeI, ZfaOpcodeJ, ZfaOpcodeR, ZfaOpcodeS, ZfaOpcodeU},
+        Instruction,
+    },
+};
+
+pub struct ZfaDecoder;
+
+impl Decoder for ZfaDecoder {
+    type OpcodeR = ZfaOpcodeR;
+    type OpcodeI = ZfaOpcodeI;
+    type OpcodeS = ZfaOpcodeS;
+    type OpcodeB = ZfaOpcodeB;
+    type OpcodeU = ZfaOpcodeU;
+    type OpcodeJ = ZfaOpcodeJ;
+
+    #[allow(clippy::type_complexity)]
+    fn decode(
+        instruction: u32,
+    ) -> Option<
+        Instruction<
+            Self::OpcodeR,
+            Self::OpcodeI,
+            Self::OpcodeS,
+            Self::OpcodeB,
+            Self::OpcodeU,
+            Self::OpcodeJ,
+        >,
+    > {
+        let opcode = instruction & MASK_7BIT;
+        let funct3 = (instruction >> 12) & MASK_3BIT;
+        let rs2 = (instruction >> 20) & MASK_5BIT;
+        let funct7 = (instruction >> 25) & MASK_7BIT;
+        Format::from_fmt((funct7 & 0b11) as usize)?;
+        match opcode {
+            0b1010011 => Self::decode_r(
+                match (funct7 >> 2, funct3, rs2) {
+                    (0b11110, 0b000, 0b00001) => Some(ZfaOpcodeR::Fli),
+                    (0b00101, 0b010, _) => Some(ZfaOpcodeR::Fminm),
+                    (0b00101, 0b011, _) => Some(ZfaOpcodeR::Fmaxm),
+                    (0b01000, _, 0b00100) => Some(ZfaOpcodeR::Fround),
+                    (0b01000, _, 0b00101) => Some(ZfaOpcodeR::Froundnx),
+                    // fcvtmod.w.d is only defined for double precision with rtz
+                    (0b11000, 0b001, 0b01000) if funct7 == 0b1100001 => Some(ZfaOpcodeR::FcvtmodWD),
+                    (0b10100, 0b100, _) => Some(ZfaOpcodeR::Fleq),
+                    (0b10100, 0b101, _) => Some(ZfaOpcodeR::Fltq),
+                    (0b11100, 0b000, 0b00001) if funct7 == 0b1110001 => Some(ZfaOpcodeR::FmvhXD),
+                    (0b10110, 0b000, _) if funct7 == 0b1011001 => Some(ZfaOpcodeR::FmvpDX),
+                    _ => None,
+                },
+                instruction,
+            ),
+            _ => None,
+        }
+    }
+}
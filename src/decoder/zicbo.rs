@@ -0,0 +1,54 @@
+use crate::{
+    decoder::{Decoder, MASK_3BIT, MASK_5BIT, MASK_7BIT},
+    instruction::{
+        zicbo::{
+            ZicboOpcodeB, ZicboOpcodeI, ZicboOpcodeJ, ZicboOpcodeR, ZicboOpcodeS, ZicboOpcodeU,
+        },
+        Instruction,
+    },
+};
+
+pub struct ZicboDecoder;
+
+impl Decoder for ZicboDecoder {
+    type OpcodeR = ZicboOpcodeR;
+    type OpcodeI = ZicboOpcodeI;
+    type OpcodeS = ZicboOpcodeS;
+    type OpcodeB = ZicboOpcodeB;
+    type OpcodeU = ZicboOpcodeU;
+    type OpcodeJ = ZicboOpcodeJ;
+
+    #[allow(clippy::type_complexity)]
+    fn decode(
+        instruction: u32,
+    ) -> Option<
+        Instruction<
+            Self::OpcodeR,
+            Self::OpcodeI,
+            Self::OpcodeS,
+            Self::OpcodeB,
+            Self::OpcodeU,
+            Self::OpcodeJ,
+        >,
+    > {
+        let opcode = instruction & MASK_7BIT;
+        let funct3 = (instruction >> 12) & MASK_3BIT;
+        let rd = (instruction >> 7) & MASK_5BIT;
+        match opcode {
+            0b0001111 => Self::decode_i(
+                match (funct3, rd) {
+                    (0b010, 0b00000) => match instruction >> 20 {
+                        0b000000000000 => Some(ZicboOpcodeI::Inval),
+                        0b000000000001 => Some(ZicboOpcodeI::Clean),
+                        0b000000000010 => Some(ZicboOpcodeI::Flush),
+                        0b000000000100 => Some(ZicboOpcodeI::Zero),
+                        _ => None,
+                    },
+                    _ => None,
+                },
+                instruction,
+            ),
+            _ => None,
+        }
+    }
+}
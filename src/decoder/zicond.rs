@@ -0,0 +1,53 @@
+use crate::{
+    decoder::{Decoder, MASK_3BIT, MASK_7BIT},
+    instruction::{
+        zicond::{
+            ZicondOpcodeB, ZicondOpcodeI, ZicondOpcodeJ, ZicondOpcodeR, ZicondOpcodeS,
+            ZicondOpcodeU,
+        },
+        Instruction,
+    },
+};
+
+pub struct ZicondDecoder;
+
+impl Decoder for ZicondDecoder {
+    type OpcodeR = ZicondOpcodeR;
+    type OpcodeI = ZicondOpcodeI;
+    type OpcodeS = ZicondOpcodeS;
+    type OpcodeB = ZicondOpcodeB;
+    type OpcodeU = ZicondOpcodeU;
+    type OpcodeJ = ZicondOpcodeJ;
+
+    #[allow(clippy::type_complexity)]
+    fn decode(
+        instruction: u32,
+    ) -> Option<
+        Instruction<
+            Self::OpcodeR,
+            Self::OpcodeI,
+            Self::OpcodeS,
+            Self::OpcodeB,
+            Self::OpcodeU,
+            Self::OpcodeJ,
+        >,
+    > {
+        let opcode = instruction & MASK_7BIT;
+        let funct3 = (instruction >> 12) & MASK_3BIT;
+        let funct7 = (instruction >> 25) & MASK_7BIT;
+        match opcode {
+            0b0110011 => Self::decode_r(
+                match funct7 {
+                    0b0000111 => match funct3 {
+                        0b101 => Some(ZicondOpcodeR::CzeroEqz),
+                        0b111 => Some(ZicondOpcodeR::CzeroNez),
+                        _ => None,
+                    },
+                    _ => None,
+                },
+                instruction,
+            ),
+            _ => None,
+        }
+    }
+}
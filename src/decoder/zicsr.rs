@@ -38,7 +38,10 @@ impl Decoder for ZicsrDecoder {
                 match funct3 {
                     0b001 => Some(ZicsrOpcodeI::Csrrw),
                     0b010 => Some(ZicsrOpcodeI::Csrrs),
+                    0b011 => Some(ZicsrOpcodeI::Csrrc),
                     0b101 => Some(ZicsrOpcodeI::Csrrwi),
+                    0b110 => Some(ZicsrOpcodeI::Csrrsi),
+                    0b111 => Some(ZicsrOpcodeI::Csrrci),
                     _ => None,
                 },
                 instruction,
@@ -0,0 +1,48 @@
+use crate::{
+    decoder::{Decoder, MASK_7BIT},
+    instruction::{
+        zihintpause::{
+            ZihintpauseOpcodeB, ZihintpauseOpcodeI, ZihintpauseOpcodeJ, ZihintpauseOpcodeR,
+            ZihintpauseOpcodeS, ZihintpauseOpcodeU,
+        },
+        Instruction,
+    },
+};
+
+pub struct ZihintpauseDecoder;
+
+impl Decoder for ZihintpauseDecoder {
+    type OpcodeR = ZihintpauseOpcodeR;
+    type OpcodeI = ZihintpauseOpcodeI;
+    type OpcodeS = ZihintpauseOpcodeS;
+    type OpcodeB = ZihintpauseOpcodeB;
+    type OpcodeU = ZihintpauseOpcodeU;
+    type OpcodeJ = ZihintpauseOpcodeJ;
+
+    #[allow(clippy::type_complexity)]
+    fn decode(
+        instruction: u32,
+    ) -> Option<
+        Instruction<
+            Self::OpcodeR,
+            Self::OpcodeI,
+            Self::OpcodeS,
+            Self::OpcodeB,
+            Self::OpcodeU,
+            Self::OpcodeJ,
+        >,
+    > {
+        let opcode = instruction & MASK_7BIT;
+        match opcode {
+            // pause is encoded as a fence with pred = W, succ = 0, rs1 = rd = x0
+            0b0001111 => Self::decode_i(
+                match instruction {
+                    0x0100000f => Some(ZihintpauseOpcodeI::Pause),
+                    _ => None,
+                },
+                instruction,
+            ),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    decoder::{Decoder, MASK_3BIT, MASK_5BIT, MASK_7BIT},
+    instruction::{
+        zkn::{ZknOpcodeB, ZknOpcodeI, ZknOpcodeJ, ZknOpcodeR, ZknOpcodeS, ZknOpcodeU},
+        Instruction,
+    },
+};
+
+pub struct ZknDecoder;
+
+impl Decoder for ZknDecoder {
+    type OpcodeR = ZknOpcodeR;
+    type OpcodeI = ZknOpcodeI;
+    type OpcodeS = ZknOpcodeS;
+    type OpcodeB = ZknOpcodeB;
+    type OpcodeU = ZknOpcodeU;
+    type OpcodeJ = ZknOpcodeJ;
+
+    #[allow(clippy::type_complexity)]
+    fn decode(
+        instruction: u32,
+    ) -> Option<
+        Instruction<
+            Self::OpcodeR,
+            Self::OpcodeI,
+            Self::OpcodeS,
+            Self::OpcodeB,
+            Self::OpcodeU,
+            Self::OpcodeJ,
+        >,
+    > {
+        let opcode = instruction & MASK_7BIT;
+        let funct3 = (instruction >> 12) & MASK_3BIT;
+        let funct7 = (instruction >> 25) & MASK_7BIT;
+        match opcode {
+            0b0110011 => Self::decode_r(
+                match funct3 {
+                    0b000 => match funct7 {
+                        0b0011001 => Some(ZknOpcodeR::Aes64es),
+                        0b0011011 => Some(ZknOpcodeR::Aes64esm),
+                        0b0011101 => Some(ZknOpcodeR::Aes64ds),
+                        0b0011111 => Some(ZknOpcodeR::Aes64dsm),
+                        0b0111111 => Some(ZknOpcodeR::Aes64ks2),
+                        0b0101000 => Some(ZknOpcodeR::Sha512sum0r),
+                        0b0101001 => Some(ZknOpcodeR::Sha512sum1r),
+                        0b0101010 => Some(ZknOpcodeR::Sha512sig0l),
+                        0b0101011 => Some(ZknOpcodeR::Sha512sig1l),
+                        0b0101110 => Some(ZknOpcodeR::Sha512sig0h),
+                        0b0101111 => Some(ZknOpcodeR::Sha512sig1h),
+                        // the byte select bs occupies the top two bits
+                        _ => match funct7 & MASK_5BIT {
+                            0b10001 => Some(ZknOpcodeR::Aes32esi),
+                            0b10011 => Some(ZknOpcodeR::Aes32esmi),
+                            0b10101 => Some(ZknOpcodeR::Aes32dsi),
+                            0b10111 => Some(ZknOpcodeR::Aes32dsmi),
+                            _ => None,
+                        },
+                    },
+                    _ => None,
+                },
+                instruction,
+            ),
+            0b0010011 => Self::decode_i(
+                match funct3 {
+                    0b001 => match instruction >> 20 {
+                        0x300 => Some(ZknOpcodeI::Aes64im),
+                        // rnum values above 0xa are reserved
+                        0x310..=0x31a => Some(ZknOpcodeI::Aes64ks1i),
+                        0x100 => Some(ZknOpcodeI::Sha256sum0),
+                        0x101 => Some(ZknOpcodeI::Sha256sum1),
+                        0x102 => Some(ZknOpcodeI::Sha256sig0),
+                        0x103 => Some(ZknOpcodeI::Sha256sig1),
+                        0x104 => Some(ZknOpcodeI::Sha512sum0),
+                        0x105 => Some(ZknOpcodeI::Sha512sum1),
+                        0x106 => Some(ZknOpcodeI::Sha512sig0),
+                        0x107 => Some(ZknOpcodeI::Sha512sig1),
+                        _ => None,
+                    },
+                    _ => None,
+                },
+                instruction,
+            ),
+            _ => None,
+        }
+    }
+}
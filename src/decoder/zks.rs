@@ -0,0 +1,62 @@
+use crate::{
+    decoder::{Decoder, MASK_3BIT, MASK_5BIT, MASK_7BIT},
+    instruction::{
+        zks::{ZksOpcodeB, ZksOpcodeI, ZksOpcodeJ, ZksOpcodeR, ZksOpcodeS, ZksOpcodeU},
+        Instruction,
+    },
+};
+
+pub struct ZksDecoder;
+
+impl Decoder for ZksDecoder {
+    type OpcodeR = ZksOpcodeR;
+    type OpcodeI = ZksOpcodeI;
+    type OpcodeS = ZksOpcodeS;
+    type OpcodeB = ZksOpcodeB;
+    type OpcodeU = ZksOpcodeU;
+    type OpcodeJ = ZksOpcodeJ;
+
+    #[allow(clippy::type_complexity)]
+    fn decode(
+        instruction: u32,
+    ) -> Option<
+        Instruction<
+            Self::OpcodeR,
+            Self::OpcodeI,
+            Self::OpcodeS,
+            Self::OpcodeB,
+            Self::OpcodeU,
+            Self::OpcodeJ,
+        >,
+    > {
+        let opcode = instruction & MASK_7BIT;
+        let funct3 = (instruction >> 12) & MASK_3BIT;
+        // the top two bits of funct7 hold the byte select
+        let funct5 = (instruction >> 25) & MASK_5BIT;
+        match opcode {
+            0b0110011 => Self::decode_r(
+                match funct3 {
+                    0b000 => match funct5 {
+                        0b11000 => Some(ZksOpcodeR::Sm4ed),
+                        0b11010 => Some(ZksOpcodeR::Sm4ks),
+                        _ => None,
+                    },
+                    _ => None,
+                },
+                instruction,
+            ),
+            0b0010011 => Self::decode_i(
+                match funct3 {
+                    0b001 => match instruction >> 20 {
+                        0x108 => Some(ZksOpcodeI::Sm3p0),
+                        0x109 => Some(ZksOpcodeI::Sm3p1),
+                        _ => None,
+                    },
+                    _ => None,
+                },
+                instruction,
+            ),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind, Result},
+};
+
+pub const ELF_MAGIC: &[u8] = b"\x7fELF";
+
+const ELFCLASS32: u8 = 1;
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const EM_RISCV: u64 = 243;
+const PT_LOAD: u64 = 1;
+const SHT_SYMTAB: u64 = 2;
+
+// A segment to be placed in memory. The bytes past the end of `data` up to `size` are the BSS.
+pub struct Segment {
+    pub address: u64,
+    pub data: Vec<u8>,
+    pub size: u64,
+}
+
+pub struct Elf {
+    pub entry: u64,
+    pub segments: Vec<Segment>,
+    pub symbols: HashMap<String, u64>,
+}
+
+// Reads the little-endian fields of ELF32 and ELF64 files, whose layouts differ in the offsets
+// and in the width of the address-sized fields.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    is_64: bool,
+}
+
+impl Reader<'_> {
+    fn read(&self, offset: u64, size: u64) -> Result<u64> {
+        let bytes = self.slice(offset, size)?;
+        Ok(bytes.iter().rev().fold(0, |acc, &b| acc << 8 | b as u64))
+    }
+
+    fn half(&self, offset: u64) -> Result<u64> {
+        self.read(offset, 2)
+    }
+
+    fn word(&self, offset: u64) -> Result<u64> {
+        self.read(offset, 4)
+    }
+
+    // Reads an address-sized field (Elf32_Addr/Elf32_Off or Elf64_Addr/Elf64_Off/Elf64_Xword).
+    fn addr(&self, offset: u64) -> Result<u64> {
+        self.read(offset, if self.is_64 { 8 } else { 4 })
+    }
+
+    // Picks the offset of a field in the ELF32 or ELF64 layout.
+    fn select(&self, offset32: u64, offset64: u64) -> u64 {
+        if self.is_64 {
+            offset64
+        } else {
+            offset32
+        }
+    }
+
+    fn slice(&self, offset: u64, size: u64) -> Result<&[u8]> {
+        offset
+            .checked_add(size)
+            .and_then(|end| self.bytes.get(offset as usize..end as usize))
+            .ok_or_else(|| invalid("truncated ELF file"))
+    }
+
+    // The offset of entry `index` of a table of `size`-byte entries at `offset`, which must start
+    // within the file so that the offsets of its fields cannot overflow.
+    fn entry(&self, offset: u64, index: u64, size: u64) -> Result<u64> {
+        index
+            .checked_mul(size)
+            .and_then(|start| start.checked_add(offset))
+            .filter(|&start| start < self.bytes.len() as u64)
+            .ok_or_else(|| invalid("ELF table entry outside the file"))
+    }
+
+    fn string(&self, offset: u64) -> Result<String> {
+        let bytes = self
+            .bytes
+            .get(offset as usize..)
+            .ok_or_else(|| invalid("truncated ELF file"))?;
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+impl Elf {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        if !bytes.starts_with(ELF_MAGIC) || bytes.len() < 16 {
+            return Err(invalid("not an ELF file"));
+        }
+        let is_64 = match bytes[4] {
+            ELFCLASS32 => false,
+            ELFCLASS64 => true,
+            _ => return Err(invalid("unsupported ELF class")),
+        };
+        if bytes[5] != ELFDATA2LSB {
+            return Err(invalid("unsupported ELF byte order"));
+        }
+        let r = Reader { bytes, is_64 };
+        if r.half(18)? != EM_RISCV {
+            return Err(invalid("not a RISC-V ELF file"));
+        }
+
+        let entry = r.addr(24)?;
+        let phoff = r.addr(r.select(28, 32))?;
+        let shoff = r.addr(r.select(32, 40))?;
+        let phentsize = r.half(r.select(42, 54))?;
+        let phnum = r.half(r.select(44, 56))?;
+        let shentsize = r.half(r.select(46, 58))?;
+        let shnum = r.half(r.select(48, 60))?;
+
+        // program headers
+        let mut segments = Vec::new();
+        for i in 0..phnum {
+            let ph = r.entry(phoff, i, phentsize)?;
+            if r.word(ph)? != PT_LOAD {
+                continue;
+            }
+            let offset = r.addr(ph + r.select(4, 8))?;
+            let paddr = r.addr(ph + r.select(12, 24))?;
+            let filesz = r.addr(ph + r.select(16, 32))?;
+            let memsz = r.addr(ph + r.select(20, 40))?;
+            if filesz > memsz {
+                return Err(invalid("ELF segment is larger in the file than in memory"));
+            }
+            if paddr.checked_add(memsz).is_none() {
+                return Err(invalid("ELF segment past the end of the address space"));
+            }
+            segments.push(Segment {
+                address: paddr,
+                data: r.slice(offset, filesz)?.to_vec(),
+                size: memsz,
+            });
+        }
+
+        // section headers, of which only the symbol tables are used
+        let mut symbols = HashMap::new();
+        for i in 0..shnum {
+            let sh = r.entry(shoff, i, shentsize)?;
+            if r.word(sh + 4)? != SHT_SYMTAB {
+                continue;
+            }
+            let offset = r.addr(sh + r.select(16, 24))?;
+            let size = r.addr(sh + r.select(20, 32))?;
+            let link = r.word(sh + r.select(24, 40))?;
+            let entsize = r.addr(sh + r.select(36, 56))?;
+            let strtab = r.addr(r.entry(shoff, link, shentsize)? + r.select(16, 24))?;
+            if entsize == 0 {
+                continue;
+            }
+            for j in 0..size / entsize {
+                let sym = r.entry(offset, j, entsize)?;
+                let name = strtab
+                    .checked_add(r.word(sym)?)
+                    .ok_or_else(|| invalid("truncated ELF file"))?;
+                let name = r.string(name)?;
+                let value = r.addr(sym + r.select(4, 8))?;
+                if !name.is_empty() {
+                    symbols.insert(name, value);
+                }
+            }
+        }
+
+        Ok(Self {
+            entry,
+            segments,
+            symbols,
+        })
+    }
+}
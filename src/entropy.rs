@@ -0,0 +1,27 @@
+pub const DEFAULT_ENTROPY_SEED: u64 = 0x5eed;
+
+// Deterministic stand-in for a physical entropy source, so that runs are reproducible.
+// The generator is SplitMix64.
+pub struct EntropySource {
+    state: u64,
+}
+
+impl Default for EntropySource {
+    fn default() -> Self {
+        Self::new(DEFAULT_ENTROPY_SEED)
+    }
+}
+
+impl EntropySource {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let z = self.state;
+        let z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        let z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
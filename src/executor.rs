@@ -1,11 +1,23 @@
 pub mod privileged;
 pub mod rv32i;
 pub mod rv64i;
+pub mod rva;
+pub mod rvf;
+pub mod rvm;
+pub mod rvv;
+pub mod zfa;
+pub mod zicbo;
+pub mod zicond;
 pub mod zicsr;
+pub mod zifencei;
+pub mod zihintpause;
+pub mod zkn;
+pub mod zks;
 
 use crate::{
-    cause::Cause, csr::ControlAndStatusRegister, instruction::Instruction, memory::Memory,
-    mode::PrivilegeMode, pc::ProgramCounter, x::IntegerRegister,
+    bus::Bus, cause::Cause, csr::ControlAndStatusRegister, f::FloatingPointRegister,
+    instruction::Instruction, mode::PrivilegeMode, pc::ProgramCounter, v::VectorRegister,
+    x::IntegerRegister,
 };
 
 pub trait Executor {
@@ -16,7 +28,7 @@ pub trait Executor {
     type OpcodeU;
     type OpcodeJ;
 
-    #[allow(clippy::type_complexity)]
+    #[allow(clippy::type_complexity, clippy::too_many_arguments)]
     fn execute(
         instruction: Instruction<
             Self::OpcodeR,
@@ -29,7 +41,9 @@ pub trait Executor {
         prv: &PrivilegeMode,
         pc: &mut ProgramCounter,
         x: &mut IntegerRegister,
+        v: &mut VectorRegister,
+        f: &mut FloatingPointRegister,
         csr: &mut ControlAndStatusRegister,
-        memory: &mut Memory,
+        bus: &mut Bus,
     ) -> Result<(), Cause>;
 }
@@ -1,7 +1,9 @@
 use crate::{
+    bus::Bus,
     cause::{Cause, Exception, ExceptionReturn},
-    csr::ControlAndStatusRegister,
+    csr::{ControlAndStatusRegister, MSTATUS, STATUS_TSR, STATUS_TVM, STATUS_TW},
     executor::Executor,
+    f::FloatingPointRegister,
     instruction::{
         privileged::{
             PrivilegedOpcodeB, PrivilegedOpcodeI, PrivilegedOpcodeJ, PrivilegedOpcodeR,
@@ -9,9 +11,9 @@ use crate::{
         },
         Instruction,
     },
-    memory::Memory,
     mode::PrivilegeMode,
     pc::ProgramCounter,
+    v::VectorRegister,
     x::IntegerRegister,
 };
 
@@ -37,29 +39,53 @@ impl Executor for PrivilegedExecutor {
         prv: &PrivilegeMode,
         _: &mut ProgramCounter,
         _: &mut IntegerRegister,
-        _: &mut ControlAndStatusRegister,
-        _: &mut Memory,
+        _: &mut VectorRegister,
+        _: &mut FloatingPointRegister,
+        csr: &mut ControlAndStatusRegister,
+        _: &mut Bus,
     ) -> Result<(), Cause> {
-        if let Instruction::TypeR {
+        let Instruction::TypeR {
             opcode,
             rd: _,
             funct3: _,
             rs1: _,
             rs2: _,
             funct7: _,
-        } = instruction
-        {
-            match opcode {
-                PrivilegedOpcodeR::Mret => {
-                    if prv == &PrivilegeMode::Machine {
-                        Err(Cause::ExceptionReturn(ExceptionReturn::Machine))
-                    } else {
-                        Err(Cause::Exception(Exception::IllegalInstruction))
-                    }
+        } = instruction;
+        match opcode {
+            PrivilegedOpcodeR::Mret => {
+                if prv == &PrivilegeMode::Machine {
+                    Err(Cause::ExceptionReturn(ExceptionReturn::Machine))
+                } else {
+                    Err(Cause::Exception(Exception::IllegalInstruction))
                 }
             }
-        } else {
-            Ok(())
+            // mstatus.TSR keeps S-mode from returning, so that M-mode can emulate sret
+            PrivilegedOpcodeR::Sret => match prv {
+                PrivilegeMode::Machine => Err(Cause::ExceptionReturn(ExceptionReturn::Supervisor)),
+                PrivilegeMode::Supervisor if csr.read_field(MSTATUS, &STATUS_TSR) == 0 => {
+                    Err(Cause::ExceptionReturn(ExceptionReturn::Supervisor))
+                }
+                _ => Err(Cause::Exception(Exception::IllegalInstruction)),
+            },
+            // the interrupts are checked before each instruction, so waiting for one is the same
+            // as going on, unless mstatus.TW forbids it below M-mode
+            PrivilegedOpcodeR::Wfi => {
+                if prv != &PrivilegeMode::Machine && csr.read_field(MSTATUS, &STATUS_TW) == 1 {
+                    Err(Cause::Exception(Exception::IllegalInstruction))
+                } else {
+                    Ok(())
+                }
+            }
+            // the page tables are walked on every access, without a TLB to flush, but
+            // mstatus.TVM keeps S-mode from fencing as from accessing satp
+            PrivilegedOpcodeR::SfenceVma => match prv {
+                PrivilegeMode::User => Err(Cause::Exception(Exception::IllegalInstruction)),
+                PrivilegeMode::Supervisor if csr.read_field(MSTATUS, &STATUS_TVM) == 1 => {
+                    Err(Cause::Exception(Exception::IllegalInstruction))
+                }
+                _ => Ok(()),
+            },
         }
     }
 }
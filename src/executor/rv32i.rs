@@ -1,20 +1,33 @@
 use crate::{
     bitops::{extend_sign, MASK_6BIT},
+    bus::Bus,
     cause::{Cause, Exception},
     csr::ControlAndStatusRegister,
     executor::Executor,
+    f::FloatingPointRegister,
     instruction::{
         rv32i::{
             Rv32iOpcodeB, Rv32iOpcodeI, Rv32iOpcodeJ, Rv32iOpcodeR, Rv32iOpcodeS, Rv32iOpcodeU,
         },
         Instruction,
     },
-    memory::Memory,
     mode::PrivilegeMode,
     pc::ProgramCounter,
+    v::VectorRegister,
     x::IntegerRegister,
 };
 
+// Jumps to `target`, which must be aligned to the instructions: to four bytes, or two with C.
+fn jump(pc: &mut ProgramCounter, target: u64) -> Result<(), Cause> {
+    if !pc.aligned(target) {
+        return Err(Cause::Exception(Exception::InstructionAddressMisaligned(
+            target,
+        )));
+    }
+    pc.jump(target);
+    Ok(())
+}
+
 pub struct Rv32iExecutor;
 
 impl Executor for Rv32iExecutor {
@@ -37,9 +50,12 @@ impl Executor for Rv32iExecutor {
         prv: &PrivilegeMode,
         pc: &mut ProgramCounter,
         x: &mut IntegerRegister,
+        _: &mut VectorRegister,
+        _: &mut FloatingPointRegister,
         _: &mut ControlAndStatusRegister,
-        memory: &mut Memory,
+        bus: &mut Bus,
     ) -> Result<(), Cause> {
+        let xlen = x.xlen();
         match instruction {
             Instruction::TypeR {
                 opcode,
@@ -48,81 +64,134 @@ impl Executor for Rv32iExecutor {
                 rs1,
                 rs2,
                 funct7: _,
-            } => match opcode {
-                Rv32iOpcodeR::Add => {
-                    x.write(rd, x.read(rs1).wrapping_add(x.read(rs2)));
-                    Ok(())
-                }
-            },
+            } => {
+                let (rs1, rs2) = (x.read(rs1), x.read(rs2));
+                let shamt = rs2 & xlen.shamt_mask();
+                let result = match opcode {
+                    Rv32iOpcodeR::Add => rs1.wrapping_add(rs2),
+                    Rv32iOpcodeR::Sub => rs1.wrapping_sub(rs2),
+                    Rv32iOpcodeR::Sll => rs1 << shamt,
+                    Rv32iOpcodeR::Slt => ((rs1 as i64) < (rs2 as i64)) as u64,
+                    Rv32iOpcodeR::Sltu => (rs1 < rs2) as u64,
+                    Rv32iOpcodeR::Xor => rs1 ^ rs2,
+                    Rv32iOpcodeR::Srl => xlen.zero_extend(rs1) >> shamt,
+                    Rv32iOpcodeR::Sra => ((rs1 as i64) >> shamt) as u64,
+                    Rv32iOpcodeR::Or => rs1 | rs2,
+                    Rv32iOpcodeR::And => rs1 & rs2,
+                };
+                x.write(rd, result);
+                Ok(())
+            }
             Instruction::TypeI {
                 opcode,
                 rd,
                 funct3: _,
                 rs1,
                 imm,
-            } => match opcode {
-                Rv32iOpcodeI::Slli => {
-                    x.write(rd, x.read(rs1) << (imm & MASK_6BIT));
-                    Ok(())
+            } => {
+                let offset = extend_sign(imm, 12);
+                let shamt = imm & MASK_6BIT;
+                // shamt[5] is reserved in RV32
+                if matches!(
+                    opcode,
+                    Rv32iOpcodeI::Slli | Rv32iOpcodeI::Srli | Rv32iOpcodeI::Srai
+                ) && shamt > xlen.shamt_mask()
+                {
+                    return Err(Cause::Exception(Exception::IllegalInstruction));
                 }
-                Rv32iOpcodeI::Addi => {
-                    x.write(rd, x.read(rs1).wrapping_add(extend_sign(imm, 12)));
-                    Ok(())
-                }
-                Rv32iOpcodeI::Fence => Ok(()), // not yet supported
-                Rv32iOpcodeI::Ecall => match prv {
-                    PrivilegeMode::User => {
-                        Err(Cause::Exception(Exception::EnvironmentCallFromUserMode))
+                match opcode {
+                    Rv32iOpcodeI::Jalr => {
+                        let link = pc.next_address();
+                        jump(pc, x.address(rs1, offset) & !1)?;
+                        x.write(rd, link);
                     }
-                    PrivilegeMode::Supervisor => Err(Cause::Exception(
-                        Exception::EnvironmentCallFromSupervisorMode,
-                    )),
-                    PrivilegeMode::Machine => {
-                        Err(Cause::Exception(Exception::EnvironmentCallFromMachineMode))
+                    Rv32iOpcodeI::Lb => {
+                        let value = bus.load_bytes(x.address(rs1, offset), 1)?;
+                        x.write(rd, extend_sign(value, 8));
                     }
-                },
-            },
+                    Rv32iOpcodeI::Lh => {
+                        let value = bus.load_bytes(x.address(rs1, offset), 2)?;
+                        x.write(rd, extend_sign(value, 16));
+                    }
+                    Rv32iOpcodeI::Lw => {
+                        let value = bus.load_bytes(x.address(rs1, offset), 4)?;
+                        x.write(rd, extend_sign(value, 32));
+                    }
+                    Rv32iOpcodeI::Lbu => {
+                        let value = bus.load_bytes(x.address(rs1, offset), 1)?;
+                        x.write(rd, value);
+                    }
+                    Rv32iOpcodeI::Lhu => {
+                        let value = bus.load_bytes(x.address(rs1, offset), 2)?;
+                        x.write(rd, value);
+                    }
+                    Rv32iOpcodeI::Addi => x.write(rd, x.read(rs1).wrapping_add(offset)),
+                    Rv32iOpcodeI::Slti => {
+                        x.write(rd, ((x.read(rs1) as i64) < (offset as i64)) as u64)
+                    }
+                    Rv32iOpcodeI::Sltiu => x.write(rd, (x.read(rs1) < offset) as u64),
+                    Rv32iOpcodeI::Xori => x.write(rd, x.read(rs1) ^ offset),
+                    Rv32iOpcodeI::Ori => x.write(rd, x.read(rs1) | offset),
+                    Rv32iOpcodeI::Andi => x.write(rd, x.read(rs1) & offset),
+                    Rv32iOpcodeI::Slli => x.write(rd, x.read(rs1) << shamt),
+                    Rv32iOpcodeI::Srli => x.write(rd, xlen.zero_extend(x.read(rs1)) >> shamt),
+                    Rv32iOpcodeI::Srai => x.write(rd, ((x.read(rs1) as i64) >> shamt) as u64),
+                    // a single hart observes its own memory accesses in program order
+                    Rv32iOpcodeI::Fence => {}
+                    Rv32iOpcodeI::Ecall => {
+                        return match prv {
+                            PrivilegeMode::User => {
+                                Err(Cause::Exception(Exception::EnvironmentCallFromUserMode))
+                            }
+                            PrivilegeMode::Supervisor => Err(Cause::Exception(
+                                Exception::EnvironmentCallFromSupervisorMode,
+                            )),
+                            PrivilegeMode::Machine => {
+                                Err(Cause::Exception(Exception::EnvironmentCallFromMachineMode))
+                            }
+                        }
+                    }
+                    Rv32iOpcodeI::Ebreak => return Err(Cause::Exception(Exception::Breakpoint)),
+                }
+                Ok(())
+            }
             Instruction::TypeS {
                 opcode,
                 funct3: _,
                 rs1,
                 rs2,
                 imm,
-            } => match opcode {
-                Rv32iOpcodeS::Sw => {
-                    memory.store(
-                        x.read(rs1).wrapping_add(extend_sign(imm, 12)) as u64,
-                        x.read(rs2) as u32,
-                    );
-                    Ok(())
-                }
-            },
+            } => {
+                let address = x.address(rs1, extend_sign(imm, 12));
+                let size = match opcode {
+                    Rv32iOpcodeS::Sb => 1,
+                    Rv32iOpcodeS::Sh => 2,
+                    Rv32iOpcodeS::Sw => 4,
+                };
+                bus.store_bytes(address, size, x.read(rs2))
+            }
             Instruction::TypeB {
                 opcode,
                 funct3: _,
                 rs1,
                 rs2,
                 imm,
-            } => match opcode {
-                Rv32iOpcodeB::Beq => {
-                    if x.read(rs1) == x.read(rs2) {
-                        pc.jumpr(imm as i64);
-                    }
+            } => {
+                let (rs1, rs2) = (x.read(rs1), x.read(rs2));
+                let taken = match opcode {
+                    Rv32iOpcodeB::Beq => rs1 == rs2,
+                    Rv32iOpcodeB::Bne => rs1 != rs2,
+                    Rv32iOpcodeB::Blt => (rs1 as i64) < (rs2 as i64),
+                    Rv32iOpcodeB::Bge => (rs1 as i64) >= (rs2 as i64),
+                    Rv32iOpcodeB::Bltu => rs1 < rs2,
+                    Rv32iOpcodeB::Bgeu => rs1 >= rs2,
+                };
+                if taken {
+                    jump(pc, xlen.zero_extend(pc.read().wrapping_add(imm)))
+                } else {
                     Ok(())
                 }
-                Rv32iOpcodeB::Bne => {
-                    if x.read(rs1) != x.read(rs2) {
-                        pc.jumpr(imm as i64);
-                    }
-                    Ok(())
-                }
-                Rv32iOpcodeB::Bge => {
-                    if x.read(rs1) >= x.read(rs2) {
-                        pc.jumpr(imm as i64);
-                    }
-                    Ok(())
-                }
-            },
+            }
             Instruction::TypeU { opcode, rd, imm } => match opcode {
                 Rv32iOpcodeU::Lui => {
                     x.write(rd, (extend_sign(imm, 20) << 12) & 0xfffffffffffff000);
@@ -138,8 +207,9 @@ impl Executor for Rv32iExecutor {
             },
             Instruction::TypeJ { opcode, rd, imm } => match opcode {
                 Rv32iOpcodeJ::Jal => {
-                    x.write(rd, pc.read().wrapping_add(4));
-                    pc.jumpr(imm as i64);
+                    let link = pc.next_address();
+                    jump(pc, xlen.zero_extend(pc.read().wrapping_add(imm)))?;
+                    x.write(rd, link);
                     Ok(())
                 }
             },
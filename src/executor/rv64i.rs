@@ -1,18 +1,21 @@
 use crate::{
-    bitops::extend_sign,
-    cause::Cause,
+    bitops::{extend_sign, MASK_5BIT},
+    bus::Bus,
+    cause::{Cause, Exception},
     csr::ControlAndStatusRegister,
     executor::Executor,
+    f::FloatingPointRegister,
     instruction::{
         rv64i::{
             Rv64iOpcodeB, Rv64iOpcodeI, Rv64iOpcodeJ, Rv64iOpcodeR, Rv64iOpcodeS, Rv64iOpcodeU,
         },
         Instruction,
     },
-    memory::Memory,
     mode::PrivilegeMode,
     pc::ProgramCounter,
+    v::VectorRegister,
     x::IntegerRegister,
+    xlen::Xlen,
 };
 
 pub struct Rv64iExecutor;
@@ -37,27 +40,80 @@ impl Executor for Rv64iExecutor {
         _: &PrivilegeMode,
         _: &mut ProgramCounter,
         x: &mut IntegerRegister,
+        _: &mut VectorRegister,
+        _: &mut FloatingPointRegister,
         _: &mut ControlAndStatusRegister,
-        _: &mut Memory,
+        bus: &mut Bus,
     ) -> Result<(), Cause> {
+        if x.xlen() == Xlen::Rv32 {
+            return Err(Cause::Exception(Exception::IllegalInstruction));
+        }
         match instruction {
+            Instruction::TypeR {
+                opcode,
+                rd,
+                funct3: _,
+                rs1,
+                rs2,
+                funct7: _,
+            } => {
+                let (rs1, rs2) = (x.read(rs1) as u32, x.read(rs2) as u32);
+                let shamt = rs2 & MASK_5BIT as u32;
+                let result = match opcode {
+                    Rv64iOpcodeR::Addw => rs1.wrapping_add(rs2),
+                    Rv64iOpcodeR::Subw => rs1.wrapping_sub(rs2),
+                    Rv64iOpcodeR::Sllw => rs1 << shamt,
+                    Rv64iOpcodeR::Srlw => rs1 >> shamt,
+                    Rv64iOpcodeR::Sraw => ((rs1 as i32) >> shamt) as u32,
+                };
+                x.write(rd, extend_sign(result as u64, 32));
+                Ok(())
+            }
             Instruction::TypeI {
                 opcode,
                 rd,
                 funct3: _,
                 rs1,
                 imm,
-            } => match opcode {
-                Rv64iOpcodeI::Addiw => x.write(
-                    rd,
-                    extend_sign(
-                        (x.read(rs1) as i64).wrapping_add(extend_sign(imm, 12) as i64) as u64,
-                        32,
+            } => {
+                let offset = extend_sign(imm, 12);
+                let shamt = (imm & MASK_5BIT) as u32;
+                match opcode {
+                    Rv64iOpcodeI::Lwu => {
+                        let value = bus.load_bytes(x.address(rs1, offset), 4)?;
+                        x.write(rd, value);
+                    }
+                    Rv64iOpcodeI::Ld => {
+                        let value = bus.load_bytes(x.address(rs1, offset), 8)?;
+                        x.write(rd, value);
+                    }
+                    Rv64iOpcodeI::Addiw => x.write(
+                        rd,
+                        extend_sign((x.read(rs1) as i64).wrapping_add(offset as i64) as u64, 32),
                     ),
-                ),
+                    Rv64iOpcodeI::Slliw => {
+                        x.write(rd, extend_sign(((x.read(rs1) as u32) << shamt) as u64, 32))
+                    }
+                    Rv64iOpcodeI::Srliw => {
+                        x.write(rd, extend_sign(((x.read(rs1) as u32) >> shamt) as u64, 32))
+                    }
+                    Rv64iOpcodeI::Sraiw => {
+                        x.write(rd, ((x.read(rs1) as i32) >> shamt) as i64 as u64)
+                    }
+                }
+                Ok(())
+            }
+            Instruction::TypeS {
+                opcode,
+                funct3: _,
+                rs1,
+                rs2,
+                imm,
+            } => match opcode {
+                Rv64iOpcodeS::Sd => {
+                    bus.store_bytes(x.address(rs1, extend_sign(imm, 12)), 8, x.read(rs2))
+                }
             },
-            _ => (),
         }
-        Ok(())
     }
 }
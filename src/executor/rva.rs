@@ -0,0 +1,110 @@
+use crate::{
+    bitops::extend_sign,
+    bus::Bus,
+    cause::{Cause, Exception},
+    csr::{ControlAndStatusRegister, MHARTID},
+    executor::Executor,
+    f::FloatingPointRegister,
+    instruction::{
+        rva::{RvaOpcodeB, RvaOpcodeI, RvaOpcodeJ, RvaOpcodeR, RvaOpcodeS, RvaOpcodeU},
+        Instruction,
+    },
+    mode::PrivilegeMode,
+    pc::ProgramCounter,
+    v::VectorRegister,
+    x::IntegerRegister,
+    xlen::Xlen,
+};
+
+pub struct RvaExecutor;
+
+impl Executor for RvaExecutor {
+    type OpcodeR = RvaOpcodeR;
+    type OpcodeI = RvaOpcodeI;
+    type OpcodeS = RvaOpcodeS;
+    type OpcodeB = RvaOpcodeB;
+    type OpcodeU = RvaOpcodeU;
+    type OpcodeJ = RvaOpcodeJ;
+
+    fn execute(
+        instruction: Instruction<
+            RvaOpcodeR,
+            RvaOpcodeI,
+            RvaOpcodeS,
+            RvaOpcodeB,
+            RvaOpcodeU,
+            RvaOpcodeJ,
+        >,
+        _: &PrivilegeMode,
+        _: &mut ProgramCounter,
+        x: &mut IntegerRegister,
+        _: &mut VectorRegister,
+        _: &mut FloatingPointRegister,
+        csr: &mut ControlAndStatusRegister,
+        bus: &mut Bus,
+    ) -> Result<(), Cause> {
+        let Instruction::TypeR {
+            opcode,
+            rd,
+            funct3,
+            rs1,
+            rs2,
+            funct7: _,
+        } = instruction;
+        let size = match funct3 {
+            0b010 => 4,
+            _ if x.xlen() == Xlen::Rv32 => {
+                return Err(Cause::Exception(Exception::IllegalInstruction))
+            }
+            _ => 8,
+        };
+        let bits = size as u32 * 8;
+        let address = x.address(rs1, 0);
+        // the accesses are atomic as the harts run an instruction at a time, but must be aligned
+        if !address.is_multiple_of(size as u64) {
+            return Err(Cause::Exception(match opcode {
+                RvaOpcodeR::Lr => Exception::LoadAddressMisaligned(address),
+                _ => Exception::StoreAmoAddressMisaligned(address),
+            }));
+        }
+        let hart = csr.read_field(MHARTID, &(0..63));
+        match opcode {
+            RvaOpcodeR::Lr => {
+                let value = bus.load_reserved(hart, address, size)?;
+                x.write(rd, extend_sign(value, bits));
+            }
+            RvaOpcodeR::Sc => {
+                let stored = bus.store_conditional(hart, address, size, x.read(rs2))?;
+                x.write(rd, !stored as u64);
+            }
+            _ => {
+                let source = extend_sign(x.read(rs2), bits);
+                // the unsigned comparisons look at the bits of the width only
+                let unsigned = |value: u64| value & (u64::MAX >> (64 - bits));
+                let value = bus.amo(address, size, |value| {
+                    let value = extend_sign(value, bits);
+                    match opcode {
+                        RvaOpcodeR::Amoswap => source,
+                        RvaOpcodeR::Amoadd => value.wrapping_add(source),
+                        RvaOpcodeR::Amoxor => value ^ source,
+                        RvaOpcodeR::Amoand => value & source,
+                        RvaOpcodeR::Amoor => value | source,
+                        RvaOpcodeR::Amomin => (value as i64).min(source as i64) as u64,
+                        RvaOpcodeR::Amomax => (value as i64).max(source as i64) as u64,
+                        RvaOpcodeR::Amominu => match unsigned(value) < unsigned(source) {
+                            true => value,
+                            false => source,
+                        },
+                        // amomaxu
+                        _ => match unsigned(value) > unsigned(source) {
+                            true => value,
+                            false => source,
+                        },
+                    }
+                })?;
+                x.write(rd, extend_sign(value, bits));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{
+    bitops::extend_sign,
+    bus::Bus,
+    cause::{Cause, Exception},
+    csr::{
+        ControlAndStatusRegister, EXTENSION_STATE_DIRTY, EXTENSION_STATE_OFF, FFLAGS, FRM, MSTATUS,
+        STATUS_FS,
+    },
+    executor::Executor,
+    f::FloatingPointRegister,
+    float::{Format, RoundingMode, SoftFloat, DOUBLE, HALF, SINGLE},
+    instruction::{
+        rvf::{RvfOpcodeB, RvfOpcodeI, RvfOpcodeJ, RvfOpcodeR, RvfOpcodeS, RvfOpcodeU},
+        Instruction,
+    },
+    mode::PrivilegeMode,
+    pc::ProgramCounter,
+    v::VectorRegister,
+    x::IntegerRegister,
+    xlen::Xlen,
+};
+
+const ROUNDING_MODE_DYNAMIC: usize = 0b111;
+
+fn illegal_instruction() -> Cause {
+    Cause::Exception(Exception::IllegalInstruction)
+}
+
+// Resolves the rm field of an instruction, which selects frm when dynamic.
+pub fn rounding_mode(rm: usize, csr: &mut ControlAndStatusRegister) -> Result<RoundingMode, Cause> {
+    let rm = if rm == ROUNDING_MODE_DYNAMIC {
+        csr.csrrs(FRM, 0)
+    } else {
+        rm as u64
+    };
+    RoundingMode::from_primitive(rm).ok_or_else(illegal_instruction)
+}
+
+pub fn check_enabled(csr: &ControlAndStatusRegister) -> Result<(), Cause> {
+    if csr.read_field(MSTATUS, &STATUS_FS) == EXTENSION_STATE_OFF {
+        Err(illegal_instruction())
+    } else {
+        Ok(())
+    }
+}
+
+// Accrues the exception flags and marks the floating-point state dirty.
+pub fn retire(softfloat: &SoftFloat, csr: &mut ControlAndStatusRegister) {
+    if softfloat.flags != 0 {
+        csr.csrrs(FFLAGS, softfloat.flags);
+    }
+    csr.write_field(MSTATUS, &STATUS_FS, EXTENSION_STATE_DIRTY);
+}
+
+pub struct RvfExecutor;
+
+impl Executor for RvfExecutor {
+    type OpcodeR = RvfOpcodeR;
+    type OpcodeI = RvfOpcodeI;
+    type OpcodeS = RvfOpcodeS;
+    type OpcodeB = RvfOpcodeB;
+    type OpcodeU = RvfOpcodeU;
+    type OpcodeJ = RvfOpcodeJ;
+
+    fn execute(
+        instruction: Instruction<
+            RvfOpcodeR,
+            RvfOpcodeI,
+            RvfOpcodeS,
+            RvfOpcodeB,
+            RvfOpcodeU,
+            RvfOpcodeJ,
+        >,
+        _: &PrivilegeMode,
+        _: &mut ProgramCounter,
+        x: &mut IntegerRegister,
+        _: &mut VectorRegister,
+        f: &mut FloatingPointRegister,
+        csr: &mut ControlAndStatusRegister,
+        bus: &mut Bus,
+    ) -> Result<(), Cause> {
+        check_enabled(csr)?;
+        match instruction {
+            Instruction::TypeI {
+                opcode,
+                rd,
+                funct3: _,
+                rs1,
+                imm,
+            } => {
+                let format = match opcode {
+                    RvfOpcodeI::Flh => HALF,
+                    RvfOpcodeI::Flw => SINGLE,
+                    RvfOpcodeI::Fld => DOUBLE,
+                };
+                let address = x.address(rs1, extend_sign(imm, 12));
+                let value = bus.load_bytes(address, format.width() as usize / 8)?;
+                f.write_boxed(rd, format, value);
+                csr.write_field(MSTATUS, &STATUS_FS, EXTENSION_STATE_DIRTY);
+                Ok(())
+            }
+            Instruction::TypeS {
+                opcode,
+                funct3: _,
+                rs1,
+                rs2,
+                imm,
+            } => {
+                let size = match opcode {
+                    RvfOpcodeS::Fsh => 2,
+                    RvfOpcodeS::Fsw => 4,
+                    RvfOpcodeS::Fsd => 8,
+                };
+                // the stored bits are not unboxed
+                let address = x.address(rs1, extend_sign(imm, 12));
+                bus.store_bytes(address, size, f.read(rs2))
+            }
+            Instruction::TypeR {
+                opcode,
+                rd,
+                funct3,
+                rs1,
+                rs2,
+                funct7,
+            } => {
+                let format = Format::from_fmt(funct7 & 0b11).ok_or_else(illegal_instruction)?;
+                let sign = format.sign_mask();
+                // the conversions with 64-bit integers and the moves of doubles are RV64 only
+                let rv64_only = matches!(
+                    opcode,
+                    RvfOpcodeR::FcvtLF
+                        | RvfOpcodeR::FcvtLuF
+                        | RvfOpcodeR::FcvtFL
+                        | RvfOpcodeR::FcvtFLu
+                ) || matches!(opcode, RvfOpcodeR::FmvXF | RvfOpcodeR::FmvFX)
+                    && format.width() == 64;
+                if rv64_only && x.xlen() == Xlen::Rv32 {
+                    return Err(illegal_instruction());
+                }
+                let mut softfloat = SoftFloat::new(RoundingMode::NearestEven);
+                match opcode {
+                    RvfOpcodeR::Fmadd
+                    | RvfOpcodeR::Fmsub
+                    | RvfOpcodeR::Fnmsub
+                    | RvfOpcodeR::Fnmadd => {
+                        softfloat.rounding_mode = rounding_mode(funct3, csr)?;
+                        let a = f.read_boxed(rs1, format);
+                        let b = f.read_boxed(rs2, format);
+                        let c = f.read_boxed(funct7 >> 2, format);
+                        let result = match opcode {
+                            RvfOpcodeR::Fmadd => softfloat.fma(format, a, b, c),
+                            RvfOpcodeR::Fmsub => softfloat.fma(format, a, b, c ^ sign),
+                            RvfOpcodeR::Fnmsub => softfloat.fma(format, a ^ sign, b, c),
+                            _ => softfloat.fma(format, a ^ sign, b, c ^ sign),
+                        };
+                        f.write_boxed(rd, format, result);
+                    }
+                    RvfOpcodeR::Fadd
+                    | RvfOpcodeR::Fsub
+                    | RvfOpcodeR::Fmul
+                    | RvfOpcodeR::Fdiv
+                    | RvfOpcodeR::Fsqrt => {
+                        softfloat.rounding_mode = rounding_mode(funct3, csr)?;
+                        let a = f.read_boxed(rs1, format);
+                        let b = f.read_boxed(rs2, format);
+                        let result = match opcode {
+                            RvfOpcodeR::Fadd => softfloat.add(format, a, b),
+                            RvfOpcodeR::Fsub => softfloat.sub(format, a, b),
+                            RvfOpcodeR::Fmul => softfloat.mul(format, a, b),
+                            RvfOpcodeR::Fdiv => softfloat.div(format, a, b),
+                            _ => softfloat.sqrt(format, a),
+                        };
+                        f.write_boxed(rd, format, result);
+                    }
+                    RvfOpcodeR::Fsgnj
+                    | RvfOpcodeR::Fsgnjn
+                    | RvfOpcodeR::Fsgnjx
+                    | RvfOpcodeR::Fmin
+                    | RvfOpcodeR::Fmax => {
+                        let a = f.read_boxed(rs1, format);
+                        let b = f.read_boxed(rs2, format);
+                        let result = match opcode {
+                            RvfOpcodeR::Fsgnj => a & !sign | b & sign,
+                            RvfOpcodeR::Fsgnjn => a & !sign | !b & sign,
+                            RvfOpcodeR::Fsgnjx => a ^ b & sign,
+                            RvfOpcodeR::Fmin => softfloat.min(format, a, b),
+                            _ => softfloat.max(format, a, b),
+                        };
+                        f.write_boxed(rd, format, result);
+                    }
+                    RvfOpcodeR::FcvtFF => {
+                        softfloat.rounding_mode = rounding_mode(funct3, csr)?;
+                        let from = Format::from_fmt(rs2).ok_or_else(illegal_instruction)?;
+                        let result = softfloat.convert(from, format, f.read_boxed(rs1, from));
+                        f.write_boxed(rd, format, result);
+                    }
+                    RvfOpcodeR::Feq | RvfOpcodeR::Flt | RvfOpcodeR::Fle => {
+                        let a = f.read_boxed(rs1, format);
+                        let b = f.read_boxed(rs2, format);
+                        let result = match opcode {
+                            RvfOpcodeR::Feq => softfloat.eq(format, a, b),
+                            RvfOpcodeR::Flt => softfloat.lt(format, a, b),
+                            _ => softfloat.le(format, a, b),
+                        };
+                        x.write(rd, result as u64);
+                    }
+                    RvfOpcodeR::FcvtWF
+                    | RvfOpcodeR::FcvtWuF
+                    | RvfOpcodeR::FcvtLF
+                    | RvfOpcodeR::FcvtLuF => {
+                        softfloat.rounding_mode = rounding_mode(funct3, csr)?;
+                        let (width, signed) = match opcode {
+                            RvfOpcodeR::FcvtWF => (32, true),
+                            RvfOpcodeR::FcvtWuF => (32, false),
+                            RvfOpcodeR::FcvtLF => (64, true),
+                            _ => (64, false),
+                        };
+                        let a = f.read_boxed(rs1, format);
+                        x.write(rd, softfloat.float_to_integer(format, a, width, signed));
+                    }
+                    RvfOpcodeR::FcvtFW
+                    | RvfOpcodeR::FcvtFWu
+                    | RvfOpcodeR::FcvtFL
+                    | RvfOpcodeR::FcvtFLu => {
+                        softfloat.rounding_mode = rounding_mode(funct3, csr)?;
+                        let (width, signed) = match opcode {
+                            RvfOpcodeR::FcvtFW => (32, true),
+                            RvfOpcodeR::FcvtFWu => (32, false),
+                            RvfOpcodeR::FcvtFL => (64, true),
+                            _ => (64, false),
+                        };
+                        let result = softfloat.integer_to_float(format, x.read(rs1), width, signed);
+                        f.write_boxed(rd, format, result);
+                    }
+                    // the moves transfer the raw bits, ignoring the NaN-boxing
+                    RvfOpcodeR::FmvXF => {
+                        x.write(rd, extend_sign(f.read(rs1) & format.mask(), format.width()));
+                        return Ok(());
+                    }
+                    RvfOpcodeR::Fclass => {
+                        x.write(rd, format.classify(f.read_boxed(rs1, format)));
+                        return Ok(());
+                    }
+                    RvfOpcodeR::FmvFX => {
+                        f.write_boxed(rd, format, x.read(rs1) & format.mask());
+                    }
+                }
+                retire(&softfloat, csr);
+                Ok(())
+            }
+            _ => Err(illegal_instruction()),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    bitops::extend_sign,
+    bus::Bus,
+    cause::{Cause, Exception},
+    csr::ControlAndStatusRegister,
+    executor::Executor,
+    f::FloatingPointRegister,
+    instruction::{
+        rvm::{RvmOpcodeB, RvmOpcodeI, RvmOpcodeJ, RvmOpcodeR, RvmOpcodeS, RvmOpcodeU},
+        Instruction,
+    },
+    mode::PrivilegeMode,
+    pc::ProgramCounter,
+    v::VectorRegister,
+    x::IntegerRegister,
+    xlen::Xlen,
+};
+
+pub struct RvmExecutor;
+
+impl Executor for RvmExecutor {
+    type OpcodeR = RvmOpcodeR;
+    type OpcodeI = RvmOpcodeI;
+    type OpcodeS = RvmOpcodeS;
+    type OpcodeB = RvmOpcodeB;
+    type OpcodeU = RvmOpcodeU;
+    type OpcodeJ = RvmOpcodeJ;
+
+    fn execute(
+        instruction: Instruction<
+            RvmOpcodeR,
+            RvmOpcodeI,
+            RvmOpcodeS,
+            RvmOpcodeB,
+            RvmOpcodeU,
+            RvmOpcodeJ,
+        >,
+        _: &PrivilegeMode,
+        _: &mut ProgramCounter,
+        x: &mut IntegerRegister,
+        _: &mut VectorRegister,
+        _: &mut FloatingPointRegister,
+        _: &mut ControlAndStatusRegister,
+        _: &mut Bus,
+    ) -> Result<(), Cause> {
+        let Instruction::TypeR {
+            opcode,
+            rd,
+            funct3: _,
+            rs1,
+            rs2,
+            funct7: _,
+        } = instruction;
+        let xlen = x.xlen();
+        // the registers hold their values sign-extended, which are the signed operands, and the
+        // unsigned ones are zero-extended from XLEN
+        let (a, b) = (x.read(rs1), x.read(rs2));
+        let (signed_a, signed_b) = (a as i64, b as i64);
+        let (unsigned_a, unsigned_b) = (xlen.zero_extend(a), xlen.zero_extend(b));
+        let bits = xlen.bits();
+        let result = match opcode {
+            RvmOpcodeR::Mul => a.wrapping_mul(b),
+            RvmOpcodeR::Mulh => ((signed_a as i128 * signed_b as i128) >> bits) as u64,
+            RvmOpcodeR::Mulhsu => ((signed_a as i128 * unsigned_b as i128) >> bits) as u64,
+            RvmOpcodeR::Mulhu => ((unsigned_a as u128 * unsigned_b as u128) >> bits) as u64,
+            // a division by zero gives all ones and leaves the dividend as the remainder, and the
+            // overflow of the most negative number divided by -1 gives it back with no remainder
+            RvmOpcodeR::Div => match signed_b {
+                0 => u64::MAX,
+                _ => signed_a.wrapping_div(signed_b) as u64,
+            },
+            RvmOpcodeR::Divu => match unsigned_b {
+                0 => u64::MAX,
+                _ => unsigned_a / unsigned_b,
+            },
+            RvmOpcodeR::Rem => match signed_b {
+                0 => a,
+                _ => signed_a.wrapping_rem(signed_b) as u64,
+            },
+            RvmOpcodeR::Remu => match unsigned_b {
+                0 => a,
+                _ => unsigned_a % unsigned_b,
+            },
+            // the RV64 instructions on words, whose results are sign-extended
+            _ if xlen == Xlen::Rv32 => {
+                return Err(Cause::Exception(Exception::IllegalInstruction));
+            }
+            _ => {
+                let (a, b) = (a as i32, b as i32);
+                let word = match opcode {
+                    RvmOpcodeR::Mulw => a.wrapping_mul(b) as u32,
+                    RvmOpcodeR::Divw => match b {
+                        0 => u32::MAX,
+                        _ => a.wrapping_div(b) as u32,
+                    },
+                    RvmOpcodeR::Divuw => match b {
+                        0 => u32::MAX,
+                        _ => a as u32 / b as u32,
+                    },
+                    RvmOpcodeR::Remw => match b {
+                        0 => a as u32,
+                        _ => a.wrapping_rem(b) as u32,
+                    },
+                    // remuw
+                    _ => match b {
+                        0 => a as u32,
+                        _ => a as u32 % b as u32,
+                    },
+                };
+                extend_sign(word as u64, 32)
+            }
+        };
+        x.write(rd, result);
+        Ok(())
+    }
+}
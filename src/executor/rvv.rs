@@ -0,0 +1,1483 @@
+use crate::{
+    bitops::extend_sign,
+    bus::Bus,
+    cause::{Cause, Exception},
+    csr::{
+        vtype_vill, ControlAndStatusRegister, EXTENSION_STATE_DIRTY, EXTENSION_STATE_OFF, FFLAGS,
+        FRM, MSTATUS, STATUS_FS, STATUS_VS, VL, VSTART, VTYPE, VXRM, VXSAT,
+    },
+    decoder::rvv::{OPFVF, OPFVV, OPIVI, OPIVV, OPMVV},
+    executor::Executor,
+    f::FloatingPointRegister,
+    float::{Format, RoundingMode, SoftFloat, DOUBLE, FLAG_NX, SINGLE},
+    instruction::{
+        rvv::{RvvOpcodeB, RvvOpcodeI, RvvOpcodeJ, RvvOpcodeR, RvvOpcodeS, RvvOpcodeU},
+        Instruction,
+    },
+    mode::PrivilegeMode,
+    pc::ProgramCounter,
+    v::VectorRegister,
+    x::IntegerRegister,
+};
+
+fn illegal_instruction() -> Cause {
+    Cause::Exception(Exception::IllegalInstruction)
+}
+
+fn mask(eew: usize) -> u64 {
+    if eew == 64 {
+        u64::MAX
+    } else {
+        (1 << eew) - 1
+    }
+}
+
+fn signed(value: u64, eew: usize) -> i64 {
+    extend_sign(value, eew as u32) as i64
+}
+
+fn log2(value: usize) -> i32 {
+    value.trailing_zeros() as i32
+}
+
+// Rounds off the `shift` least significant bits according to vxrm.
+fn round_off(value: i128, shift: u32, vxrm: u64) -> i128 {
+    if shift == 0 {
+        return value;
+    }
+    let bit = |n: u32| (value >> n) & 1 == 1;
+    let below = |n: u32| value & ((1 << n) - 1) != 0;
+    let increment = match vxrm {
+        0b00 => bit(shift - 1),
+        0b01 => bit(shift - 1) && (below(shift - 1) || bit(shift)),
+        0b10 => false,
+        _ => !bit(shift) && below(shift),
+    };
+    (value >> shift) + increment as i128
+}
+
+fn clamp_unsigned(value: i128, eew: usize, saturated: &mut bool) -> u64 {
+    let max = mask(eew) as i128;
+    if value > max {
+        *saturated = true;
+        max as u64
+    } else if value < 0 {
+        *saturated = true;
+        0
+    } else {
+        value as u64
+    }
+}
+
+fn clamp_signed(value: i128, eew: usize, saturated: &mut bool) -> u64 {
+    let max = (1i128 << (eew - 1)) - 1;
+    let min = -(1i128 << (eew - 1));
+    let clamped = if value > max {
+        *saturated = true;
+        max
+    } else if value < min {
+        *saturated = true;
+        min
+    } else {
+        value
+    };
+    clamped as u64 & mask(eew)
+}
+
+fn vector_format(eew: usize) -> Result<Format, Cause> {
+    match eew {
+        32 => Ok(SINGLE),
+        64 => Ok(DOUBLE),
+        _ => Err(illegal_instruction()),
+    }
+}
+
+struct VectorType {
+    sew: usize,
+    lmul: i32,
+}
+
+impl VectorType {
+    fn from_primitive(vtype: u64, elen: usize) -> Option<Self> {
+        // vill and the reserved bits must be zero
+        if vtype >> 8 != 0 {
+            return None;
+        }
+        let lmul = match vtype & 0b111 {
+            0b100 => return None,
+            vlmul if vlmul < 0b100 => vlmul as i32,
+            vlmul => vlmul as i32 - 8,
+        };
+        let sew = 8 << ((vtype >> 3) & 0b111);
+        if sew > elen || (lmul < 0 && sew > elen >> -lmul) {
+            return None;
+        }
+        Some(Self { sew, lmul })
+    }
+
+    fn vlmax(&self, vlen: usize) -> usize {
+        if self.lmul >= 0 {
+            (vlen << self.lmul) / self.sew
+        } else {
+            (vlen >> -self.lmul) / self.sew
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Operand {
+    Vector(usize),
+    Scalar(u64),
+}
+
+impl Operand {
+    fn read(&self, v: &VectorRegister, index: usize, eew: usize) -> u64 {
+        match self {
+            Self::Vector(register) => v.read(*register, index, eew),
+            Self::Scalar(value) => value & mask(eew),
+        }
+    }
+}
+
+// The state shared by all the instructions that operate on vector elements.
+struct Context {
+    vl: usize,
+    vstart: usize,
+    sew: usize,
+    lmul: i32,
+    vlmax: usize,
+    vm: bool,
+}
+
+impl Context {
+    fn new(
+        v: &VectorRegister,
+        csr: &mut ControlAndStatusRegister,
+        vm: bool,
+    ) -> Result<Self, Cause> {
+        let vtype = VectorType::from_primitive(csr.csrrs(VTYPE, 0), v.elen())
+            .ok_or_else(illegal_instruction)?;
+        Ok(Self {
+            vl: csr.csrrs(VL, 0) as usize,
+            vstart: csr.csrrs(VSTART, 0) as usize,
+            sew: vtype.sew,
+            lmul: vtype.lmul,
+            vlmax: vtype.vlmax(v.vlen()),
+            vm,
+        })
+    }
+
+    fn active(&self, v: &VectorRegister, index: usize) -> bool {
+        self.vm || v.read_mask(0, index)
+    }
+
+    // Checks that a register group of the given EMUL is aligned and fits in the register file.
+    fn check_group(register: usize, emul: i32) -> Result<(), Cause> {
+        if !(-3..=3).contains(&emul) {
+            return Err(illegal_instruction());
+        }
+        let count = 1 << emul.max(0);
+        if !register.is_multiple_of(count) {
+            Err(illegal_instruction())
+        } else {
+            Ok(())
+        }
+    }
+
+    // Checks a destination group that is written under the mask in v0.
+    fn check_destination(&self, vd: usize, emul: i32) -> Result<(), Cause> {
+        if !self.vm && vd == 0 {
+            return Err(illegal_instruction());
+        }
+        Self::check_group(vd, emul)
+    }
+
+    fn for_each_active(
+        &self,
+        v: &mut VectorRegister,
+        mut f: impl FnMut(&mut VectorRegister, usize),
+    ) {
+        for i in self.vstart..self.vl {
+            if self.active(v, i) {
+                f(v, i);
+            }
+        }
+    }
+}
+
+fn integer_operation(
+    opcode: &RvvOpcodeR,
+    sew: usize,
+    vxrm: u64,
+    a: u64,
+    b: u64,
+    d: u64,
+    saturated: &mut bool,
+) -> u64 {
+    let shamt = (b & (sew as u64 - 1)) as u32;
+    let result = match opcode {
+        RvvOpcodeR::Vadd => a.wrapping_add(b),
+        RvvOpcodeR::Vsub => a.wrapping_sub(b),
+        RvvOpcodeR::Vrsub => b.wrapping_sub(a),
+        RvvOpcodeR::Vminu => a.min(b),
+        RvvOpcodeR::Vmin => signed(a, sew).min(signed(b, sew)) as u64,
+        RvvOpcodeR::Vmaxu => a.max(b),
+        RvvOpcodeR::Vmax => signed(a, sew).max(signed(b, sew)) as u64,
+        RvvOpcodeR::Vand => a & b,
+        RvvOpcodeR::Vor => a | b,
+        RvvOpcodeR::Vxor => a ^ b,
+        RvvOpcodeR::Vsll => a << shamt,
+        RvvOpcodeR::Vsrl => a >> shamt,
+        RvvOpcodeR::Vsra => (signed(a, sew) >> shamt) as u64,
+        RvvOpcodeR::Vmul => a.wrapping_mul(b),
+        RvvOpcodeR::Vmulh => ((signed(a, sew) as i128 * signed(b, sew) as i128) >> sew) as u64,
+        RvvOpcodeR::Vmulhu => ((a as u128 * b as u128) >> sew) as u64,
+        RvvOpcodeR::Vmulhsu => ((signed(a, sew) as i128 * b as i128) >> sew) as u64,
+        RvvOpcodeR::Vdivu => a.checked_div(b).unwrap_or(u64::MAX),
+        RvvOpcodeR::Vdiv => match signed(b, sew) {
+            0 => u64::MAX,
+            divisor => (signed(a, sew) as i128 / divisor as i128) as u64,
+        },
+        RvvOpcodeR::Vremu => a.checked_rem(b).unwrap_or(a),
+        RvvOpcodeR::Vrem => match signed(b, sew) {
+            0 => a,
+            divisor => (signed(a, sew) as i128 % divisor as i128) as u64,
+        },
+        RvvOpcodeR::Vmacc => b.wrapping_mul(a).wrapping_add(d),
+        RvvOpcodeR::Vnmsac => d.wrapping_sub(b.wrapping_mul(a)),
+        RvvOpcodeR::Vmadd => b.wrapping_mul(d).wrapping_add(a),
+        RvvOpcodeR::Vnmsub => a.wrapping_sub(b.wrapping_mul(d)),
+        RvvOpcodeR::Vsaddu => clamp_unsigned(a as i128 + b as i128, sew, saturated),
+        RvvOpcodeR::Vsadd => clamp_signed(
+            signed(a, sew) as i128 + signed(b, sew) as i128,
+            sew,
+            saturated,
+        ),
+        RvvOpcodeR::Vssubu => clamp_unsigned(a as i128 - b as i128, sew, saturated),
+        RvvOpcodeR::Vssub => clamp_signed(
+            signed(a, sew) as i128 - signed(b, sew) as i128,
+            sew,
+            saturated,
+        ),
+        RvvOpcodeR::Vaaddu => round_off(a as i128 + b as i128, 1, vxrm) as u64,
+        RvvOpcodeR::Vaadd => {
+            round_off(signed(a, sew) as i128 + signed(b, sew) as i128, 1, vxrm) as u64
+        }
+        RvvOpcodeR::Vasubu => round_off(a as i128 - b as i128, 1, vxrm) as u64,
+        RvvOpcodeR::Vasub => {
+            round_off(signed(a, sew) as i128 - signed(b, sew) as i128, 1, vxrm) as u64
+        }
+        RvvOpcodeR::Vsmul => {
+            let product = signed(a, sew) as i128 * signed(b, sew) as i128;
+            clamp_signed(round_off(product, sew as u32 - 1, vxrm), sew, saturated)
+        }
+        RvvOpcodeR::Vssrl => round_off(a as i128, shamt, vxrm) as u64,
+        RvvOpcodeR::Vssra => round_off(signed(a, sew) as i128, shamt, vxrm) as u64,
+        _ => unreachable!(),
+    };
+    result & mask(sew)
+}
+
+fn integer_comparison(opcode: &RvvOpcodeR, sew: usize, a: u64, b: u64) -> bool {
+    match opcode {
+        RvvOpcodeR::Vmseq => a == b,
+        RvvOpcodeR::Vmsne => a != b,
+        RvvOpcodeR::Vmsltu => a < b,
+        RvvOpcodeR::Vmslt => signed(a, sew) < signed(b, sew),
+        RvvOpcodeR::Vmsleu => a <= b,
+        RvvOpcodeR::Vmsle => signed(a, sew) <= signed(b, sew),
+        RvvOpcodeR::Vmsgtu => a > b,
+        RvvOpcodeR::Vmsgt => signed(a, sew) > signed(b, sew),
+        _ => unreachable!(),
+    }
+}
+
+// `a` is read with 2*SEW bits for the .wv/.wx forms, `d` is the 2*SEW accumulator.
+fn widening_operation(opcode: &RvvOpcodeR, sew: usize, a: u64, b: u64, d: u64) -> u64 {
+    let wide = 2 * sew;
+    let sa = signed(a, sew) as u64;
+    let sb = signed(b, sew) as u64;
+    let result = match opcode {
+        RvvOpcodeR::Vwaddu => a.wrapping_add(b),
+        RvvOpcodeR::Vwadd => sa.wrapping_add(sb),
+        RvvOpcodeR::Vwsubu => a.wrapping_sub(b),
+        RvvOpcodeR::Vwsub => sa.wrapping_sub(sb),
+        RvvOpcodeR::VwadduW => a.wrapping_add(b),
+        RvvOpcodeR::VwaddW => a.wrapping_add(sb),
+        RvvOpcodeR::VwsubuW => a.wrapping_sub(b),
+        RvvOpcodeR::VwsubW => a.wrapping_sub(sb),
+        RvvOpcodeR::Vwmulu => a.wrapping_mul(b),
+        RvvOpcodeR::Vwmulsu => sa.wrapping_mul(b),
+        RvvOpcodeR::Vwmul => sa.wrapping_mul(sb),
+        RvvOpcodeR::Vwmaccu => b.wrapping_mul(a).wrapping_add(d),
+        RvvOpcodeR::Vwmacc => sb.wrapping_mul(sa).wrapping_add(d),
+        RvvOpcodeR::Vwmaccsu => sb.wrapping_mul(a).wrapping_add(d),
+        RvvOpcodeR::Vwmaccus => b.wrapping_mul(sa).wrapping_add(d),
+        _ => unreachable!(),
+    };
+    result & mask(wide)
+}
+
+fn narrowing_operation(
+    opcode: &RvvOpcodeR,
+    sew: usize,
+    vxrm: u64,
+    a: u64,
+    b: u64,
+    saturated: &mut bool,
+) -> u64 {
+    let wide = 2 * sew;
+    let shamt = (b & (wide as u64 - 1)) as u32;
+    match opcode {
+        RvvOpcodeR::Vnsrl => (a >> shamt) & mask(sew),
+        RvvOpcodeR::Vnsra => (signed(a, wide) >> shamt) as u64 & mask(sew),
+        RvvOpcodeR::Vnclipu => clamp_unsigned(round_off(a as i128, shamt, vxrm), sew, saturated),
+        RvvOpcodeR::Vnclip => clamp_signed(
+            round_off(signed(a, wide) as i128, shamt, vxrm),
+            sew,
+            saturated,
+        ),
+        _ => unreachable!(),
+    }
+}
+
+fn reduction_operation(opcode: &RvvOpcodeR, sew: usize, accumulator: u64, element: u64) -> u64 {
+    let result = match opcode {
+        RvvOpcodeR::Vredsum => accumulator.wrapping_add(element),
+        RvvOpcodeR::Vredand => accumulator & element,
+        RvvOpcodeR::Vredor => accumulator | element,
+        RvvOpcodeR::Vredxor => accumulator ^ element,
+        RvvOpcodeR::Vredminu => accumulator.min(element),
+        RvvOpcodeR::Vredmin => signed(accumulator, sew).min(signed(element, sew)) as u64,
+        RvvOpcodeR::Vredmaxu => accumulator.max(element),
+        RvvOpcodeR::Vredmax => signed(accumulator, sew).max(signed(element, sew)) as u64,
+        _ => unreachable!(),
+    };
+    result & mask(sew)
+}
+
+fn float_operation(
+    opcode: &RvvOpcodeR,
+    format: Format,
+    softfloat: &mut SoftFloat,
+    a: u64,
+    b: u64,
+    d: u64,
+) -> u64 {
+    let sign = format.sign_mask();
+    match opcode {
+        RvvOpcodeR::Vfadd => softfloat.add(format, a, b),
+        RvvOpcodeR::Vfsub => softfloat.sub(format, a, b),
+        RvvOpcodeR::Vfrsub => softfloat.sub(format, b, a),
+        RvvOpcodeR::Vfmul => softfloat.mul(format, a, b),
+        RvvOpcodeR::Vfdiv => softfloat.div(format, a, b),
+        RvvOpcodeR::Vfrdiv => softfloat.div(format, b, a),
+        RvvOpcodeR::Vfmin => softfloat.min(format, a, b),
+        RvvOpcodeR::Vfmax => softfloat.max(format, a, b),
+        RvvOpcodeR::Vfsgnj => a & !sign | b & sign,
+        RvvOpcodeR::Vfsgnjn => a & !sign | !b & sign,
+        RvvOpcodeR::Vfsgnjx => a ^ b & sign,
+        RvvOpcodeR::Vfmacc | RvvOpcodeR::Vfwmacc => softfloat.fma(format, b, a, d),
+        RvvOpcodeR::Vfnmacc | RvvOpcodeR::Vfwnmacc => softfloat.fma(format, b ^ sign, a, d ^ sign),
+        RvvOpcodeR::Vfmsac | RvvOpcodeR::Vfwmsac => softfloat.fma(format, b, a, d ^ sign),
+        RvvOpcodeR::Vfnmsac | RvvOpcodeR::Vfwnmsac => softfloat.fma(format, b ^ sign, a, d),
+        RvvOpcodeR::Vfmadd => softfloat.fma(format, b, d, a),
+        RvvOpcodeR::Vfnmadd => softfloat.fma(format, b ^ sign, d, a ^ sign),
+        RvvOpcodeR::Vfmsub => softfloat.fma(format, b, d, a ^ sign),
+        RvvOpcodeR::Vfnmsub => softfloat.fma(format, b ^ sign, d, a),
+        RvvOpcodeR::Vfwadd | RvvOpcodeR::VfwaddW => softfloat.add(format, a, b),
+        RvvOpcodeR::Vfwsub | RvvOpcodeR::VfwsubW => softfloat.sub(format, a, b),
+        RvvOpcodeR::Vfwmul => softfloat.mul(format, a, b),
+        _ => unreachable!(),
+    }
+}
+
+fn float_comparison(
+    opcode: &RvvOpcodeR,
+    format: Format,
+    softfloat: &mut SoftFloat,
+    a: u64,
+    b: u64,
+) -> bool {
+    match opcode {
+        RvvOpcodeR::Vmfeq => softfloat.eq(format, a, b),
+        RvvOpcodeR::Vmfne => !softfloat.eq(format, a, b),
+        RvvOpcodeR::Vmflt => softfloat.lt(format, a, b),
+        RvvOpcodeR::Vmfle => softfloat.le(format, a, b),
+        RvvOpcodeR::Vmfgt => softfloat.lt(format, b, a),
+        RvvOpcodeR::Vmfge => softfloat.le(format, b, a),
+        _ => unreachable!(),
+    }
+}
+
+pub struct RvvExecutor;
+
+impl RvvExecutor {
+    fn set_configuration(
+        rd: usize,
+        rs1: usize,
+        immediate_avl: Option<u64>,
+        vtype: u64,
+        x: &mut IntegerRegister,
+        v: &VectorRegister,
+        csr: &mut ControlAndStatusRegister,
+    ) {
+        let vl = match VectorType::from_primitive(vtype, v.elen()) {
+            Some(t) => {
+                let vlmax = t.vlmax(v.vlen()) as u64;
+                let avl = match immediate_avl {
+                    Some(uimm) => uimm,
+                    None if rs1 != 0 => x.read(rs1),
+                    None if rd != 0 => u64::MAX,
+                    // keep the existing vl
+                    None => csr.csrrs(VL, 0),
+                };
+                csr.csrrw(VTYPE, vtype);
+                avl.min(vlmax)
+            }
+            None => {
+                csr.csrrw(VTYPE, vtype_vill(x.xlen()));
+                0
+            }
+        };
+        csr.csrrw(VL, vl);
+        csr.csrrw(VSTART, 0);
+        x.write(rd, vl);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn load_store(
+        opcode: &RvvOpcodeR,
+        vd: usize,
+        width: usize,
+        rs1: usize,
+        rs2: usize,
+        funct7: usize,
+        x: &IntegerRegister,
+        v: &mut VectorRegister,
+        csr: &mut ControlAndStatusRegister,
+        bus: &mut Bus,
+    ) -> Result<(), Cause> {
+        let eew = match width {
+            0b000 => 8,
+            0b101 => 16,
+            0b110 => 32,
+            _ => 64,
+        };
+        if eew > v.elen() {
+            return Err(illegal_instruction());
+        }
+        let nf = (funct7 >> 4) + 1;
+        let vm = funct7 & 1 == 1;
+        let base = x.read(rs1);
+        let xlen = x.xlen();
+        let vstart = csr.csrrs(VSTART, 0) as usize;
+        let is_load = matches!(
+            opcode,
+            RvvOpcodeR::Vle
+                | RvvOpcodeR::Vleff
+                | RvvOpcodeR::Vlm
+                | RvvOpcodeR::Vlr
+                | RvvOpcodeR::Vlse
+                | RvvOpcodeR::Vluxei
+                | RvvOpcodeR::Vloxei
+        );
+        let mut transfer = |v: &mut VectorRegister,
+                            register: usize,
+                            index: usize,
+                            eew: usize,
+                            address: u64|
+         -> Result<(), Cause> {
+            // addresses wrap around at XLEN
+            let address = xlen.zero_extend(address);
+            if is_load {
+                let value = bus.load_bytes(address, eew / 8)?;
+                v.write(register, index, eew, value);
+                Ok(())
+            } else {
+                bus.store_bytes(address, eew / 8, v.read(register, index, eew))
+            }
+        };
+
+        // whole register and mask transfers ignore vtype
+        match opcode {
+            RvvOpcodeR::Vlr | RvvOpcodeR::Vsr => {
+                if !nf.is_power_of_two() || !vd.is_multiple_of(nf) {
+                    return Err(illegal_instruction());
+                }
+                let evl = nf * v.vlen() / eew;
+                for i in vstart..evl {
+                    let address = base.wrapping_add((i * eew / 8) as u64);
+                    if let Err(cause) = transfer(v, vd, i, eew, address) {
+                        csr.csrrw(VSTART, i as u64);
+                        return Err(cause);
+                    }
+                }
+                csr.csrrw(VSTART, 0);
+                return Ok(());
+            }
+            RvvOpcodeR::Vlm | RvvOpcodeR::Vsm => {
+                let context = Context::new(v, csr, true)?;
+                let evl = context.vl.div_ceil(8);
+                for i in vstart..evl {
+                    if let Err(cause) = transfer(v, vd, i, 8, base.wrapping_add(i as u64)) {
+                        csr.csrrw(VSTART, i as u64);
+                        return Err(cause);
+                    }
+                }
+                csr.csrrw(VSTART, 0);
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        let context = Context::new(v, csr, vm)?;
+        let indexed = matches!(
+            opcode,
+            RvvOpcodeR::Vluxei | RvvOpcodeR::Vloxei | RvvOpcodeR::Vsuxei | RvvOpcodeR::Vsoxei
+        );
+        // indexed accesses use SEW for data and the encoded width for the indices
+        let (data_eew, data_emul) = if indexed {
+            let index_emul = context.lmul + log2(eew) - log2(context.sew);
+            Context::check_group(rs2, index_emul)?;
+            (context.sew, context.lmul)
+        } else {
+            (eew, context.lmul + log2(eew) - log2(context.sew))
+        };
+        Context::check_group(vd, data_emul)?;
+        let registers = 1 << data_emul.max(0);
+        if nf * registers > 8 || vd + nf * registers > 32 || (is_load && !vm && vd == 0) {
+            return Err(illegal_instruction());
+        }
+        let stride = match opcode {
+            RvvOpcodeR::Vlse | RvvOpcodeR::Vsse => x.read(rs2),
+            _ => (nf * data_eew / 8) as u64,
+        };
+        for i in context.vstart..context.vl {
+            if !context.active(v, i) {
+                continue;
+            }
+            let element_address = if indexed {
+                base.wrapping_add(v.read(rs2, i, eew))
+            } else {
+                base.wrapping_add(stride.wrapping_mul(i as u64))
+            };
+            for field in 0..nf {
+                let address = element_address.wrapping_add((field * data_eew / 8) as u64);
+                if let Err(cause) = transfer(v, vd + field * registers, i, data_eew, address) {
+                    // fault-only-first loads trim vl instead of trapping after the first element
+                    if *opcode == RvvOpcodeR::Vleff && i > 0 {
+                        csr.csrrw(VL, i as u64);
+                        csr.csrrw(VSTART, 0);
+                        return Ok(());
+                    }
+                    csr.csrrw(VSTART, i as u64);
+                    return Err(cause);
+                }
+            }
+        }
+        csr.csrrw(VSTART, 0);
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn integer(
+        opcode: &RvvOpcodeR,
+        context: &Context,
+        vd: usize,
+        funct3: usize,
+        rs1: usize,
+        vs2: usize,
+        x: &mut IntegerRegister,
+        v: &mut VectorRegister,
+        csr: &mut ControlAndStatusRegister,
+    ) -> Result<(), Cause> {
+        let sew = context.sew;
+        let lmul = context.lmul;
+        let vxrm = csr.csrrs(VXRM, 0);
+        let mut saturated = false;
+        let unsigned_immediate = matches!(
+            opcode,
+            RvvOpcodeR::Vsll
+                | RvvOpcodeR::Vsrl
+                | RvvOpcodeR::Vsra
+                | RvvOpcodeR::Vssrl
+                | RvvOpcodeR::Vssra
+                | RvvOpcodeR::Vnsrl
+                | RvvOpcodeR::Vnsra
+                | RvvOpcodeR::Vnclipu
+                | RvvOpcodeR::Vnclip
+                | RvvOpcodeR::Vslideup
+                | RvvOpcodeR::Vslidedown
+                | RvvOpcodeR::Vrgather
+        );
+        let op1 = match funct3 as u32 {
+            OPIVV | OPMVV => Operand::Vector(rs1),
+            OPIVI if unsigned_immediate => Operand::Scalar(rs1 as u64),
+            OPIVI => Operand::Scalar(extend_sign(rs1 as u64, 5)),
+            _ => Operand::Scalar(x.read(rs1)),
+        };
+        // vs1 is a group of LMUL registers but for the reductions and the operations on masks,
+        // which read a single register, and the unary operations, whose vs1 field selects one
+        if let Operand::Vector(vs1) = op1 {
+            if !matches!(
+                opcode,
+                RvvOpcodeR::Vredsum
+                    | RvvOpcodeR::Vredand
+                    | RvvOpcodeR::Vredor
+                    | RvvOpcodeR::Vredxor
+                    | RvvOpcodeR::Vredminu
+                    | RvvOpcodeR::Vredmin
+                    | RvvOpcodeR::Vredmaxu
+                    | RvvOpcodeR::Vredmax
+                    | RvvOpcodeR::Vwredsumu
+                    | RvvOpcodeR::Vwredsum
+                    | RvvOpcodeR::Vrgatherei16
+                    | RvvOpcodeR::Vcompress
+                    | RvvOpcodeR::Vmandn
+                    | RvvOpcodeR::Vmand
+                    | RvvOpcodeR::Vmor
+                    | RvvOpcodeR::Vmxor
+                    | RvvOpcodeR::Vmorn
+                    | RvvOpcodeR::Vmnand
+                    | RvvOpcodeR::Vmnor
+                    | RvvOpcodeR::Vmxnor
+                    | RvvOpcodeR::Vzext
+                    | RvvOpcodeR::Vsext
+                    | RvvOpcodeR::Vcpop
+                    | RvvOpcodeR::Vfirst
+                    | RvvOpcodeR::Vmsbf
+                    | RvvOpcodeR::Vmsif
+                    | RvvOpcodeR::Vmsof
+                    | RvvOpcodeR::Viota
+                    | RvvOpcodeR::Vid
+                    | RvvOpcodeR::Vmvxs
+            ) {
+                Context::check_group(vs1, lmul)?;
+            }
+        }
+
+        match opcode {
+            RvvOpcodeR::Vadd
+            | RvvOpcodeR::Vsub
+            | RvvOpcodeR::Vrsub
+            | RvvOpcodeR::Vminu
+            | RvvOpcodeR::Vmin
+            | RvvOpcodeR::Vmaxu
+            | RvvOpcodeR::Vmax
+            | RvvOpcodeR::Vand
+            | RvvOpcodeR::Vor
+            | RvvOpcodeR::Vxor
+            | RvvOpcodeR::Vsll
+            | RvvOpcodeR::Vsrl
+            | RvvOpcodeR::Vsra
+            | RvvOpcodeR::Vmul
+            | RvvOpcodeR::Vmulh
+            | RvvOpcodeR::Vmulhu
+            | RvvOpcodeR::Vmulhsu
+            | RvvOpcodeR::Vdivu
+            | RvvOpcodeR::Vdiv
+            | RvvOpcodeR::Vremu
+            | RvvOpcodeR::Vrem
+            | RvvOpcodeR::Vmacc
+            | RvvOpcodeR::Vnmsac
+            | RvvOpcodeR::Vmadd
+            | RvvOpcodeR::Vnmsub
+            | RvvOpcodeR::Vsaddu
+            | RvvOpcodeR::Vsadd
+            | RvvOpcodeR::Vssubu
+            | RvvOpcodeR::Vssub
+            | RvvOpcodeR::Vaaddu
+            | RvvOpcodeR::Vaadd
+            | RvvOpcodeR::Vasubu
+            | RvvOpcodeR::Vasub
+            | RvvOpcodeR::Vsmul
+            | RvvOpcodeR::Vssrl
+            | RvvOpcodeR::Vssra => {
+                context.check_destination(vd, lmul)?;
+                Context::check_group(vs2, lmul)?;
+                context.for_each_active(v, |v, i| {
+                    let a = v.read(vs2, i, sew);
+                    let b = op1.read(v, i, sew);
+                    let d = v.read(vd, i, sew);
+                    let result = integer_operation(opcode, sew, vxrm, a, b, d, &mut saturated);
+                    v.write(vd, i, sew, result);
+                });
+            }
+            RvvOpcodeR::Vmerge | RvvOpcodeR::Vmv => {
+                Context::check_group(vd, lmul)?;
+                Context::check_group(vs2, lmul)?;
+                for i in context.vstart..context.vl {
+                    let value = if *opcode == RvvOpcodeR::Vmv || v.read_mask(0, i) {
+                        op1.read(v, i, sew)
+                    } else {
+                        v.read(vs2, i, sew)
+                    };
+                    v.write(vd, i, sew, value);
+                }
+            }
+            RvvOpcodeR::Vadc | RvvOpcodeR::Vsbc => {
+                context.check_destination(vd, lmul)?;
+                Context::check_group(vs2, lmul)?;
+                for i in context.vstart..context.vl {
+                    let a = v.read(vs2, i, sew);
+                    let b = op1.read(v, i, sew);
+                    let carry = v.read_mask(0, i) as u64;
+                    let result = if *opcode == RvvOpcodeR::Vadc {
+                        a.wrapping_add(b).wrapping_add(carry)
+                    } else {
+                        a.wrapping_sub(b).wrapping_sub(carry)
+                    };
+                    v.write(vd, i, sew, result & mask(sew));
+                }
+            }
+            RvvOpcodeR::Vmadc | RvvOpcodeR::Vmsbc => {
+                Context::check_group(vs2, lmul)?;
+                for i in context.vstart..context.vl {
+                    let a = v.read(vs2, i, sew) as u128;
+                    let b = op1.read(v, i, sew) as u128;
+                    let carry = (!context.vm && v.read_mask(0, i)) as u128;
+                    let out = if *opcode == RvvOpcodeR::Vmadc {
+                        (a + b + carry) >> sew != 0
+                    } else {
+                        a < b + carry
+                    };
+                    v.write_mask(vd, i, out);
+                }
+            }
+            RvvOpcodeR::Vmseq
+            | RvvOpcodeR::Vmsne
+            | RvvOpcodeR::Vmsltu
+            | RvvOpcodeR::Vmslt
+            | RvvOpcodeR::Vmsleu
+            | RvvOpcodeR::Vmsle
+            | RvvOpcodeR::Vmsgtu
+            | RvvOpcodeR::Vmsgt => {
+                Context::check_group(vs2, lmul)?;
+                for i in context.vstart..context.vl {
+                    if context.active(v, i) {
+                        let a = v.read(vs2, i, sew);
+                        let b = op1.read(v, i, sew);
+                        v.write_mask(vd, i, integer_comparison(opcode, sew, a, b));
+                    }
+                }
+            }
+            RvvOpcodeR::Vwaddu
+            | RvvOpcodeR::Vwadd
+            | RvvOpcodeR::Vwsubu
+            | RvvOpcodeR::Vwsub
+            | RvvOpcodeR::VwadduW
+            | RvvOpcodeR::VwaddW
+            | RvvOpcodeR::VwsubuW
+            | RvvOpcodeR::VwsubW
+            | RvvOpcodeR::Vwmulu
+            | RvvOpcodeR::Vwmulsu
+            | RvvOpcodeR::Vwmul
+            | RvvOpcodeR::Vwmaccu
+            | RvvOpcodeR::Vwmacc
+            | RvvOpcodeR::Vwmaccsu
+            | RvvOpcodeR::Vwmaccus => {
+                if sew * 2 > v.elen() {
+                    return Err(illegal_instruction());
+                }
+                let wide_source = matches!(
+                    opcode,
+                    RvvOpcodeR::VwadduW
+                        | RvvOpcodeR::VwaddW
+                        | RvvOpcodeR::VwsubuW
+                        | RvvOpcodeR::VwsubW
+                );
+                let a_eew = if wide_source { 2 * sew } else { sew };
+                context.check_destination(vd, lmul + 1)?;
+                Context::check_group(vs2, if wide_source { lmul + 1 } else { lmul })?;
+                context.for_each_active(v, |v, i| {
+                    let a = v.read(vs2, i, a_eew);
+                    let b = op1.read(v, i, sew);
+                    let d = v.read(vd, i, 2 * sew);
+                    v.write(vd, i, 2 * sew, widening_operation(opcode, sew, a, b, d));
+                });
+            }
+            RvvOpcodeR::Vnsrl | RvvOpcodeR::Vnsra | RvvOpcodeR::Vnclipu | RvvOpcodeR::Vnclip => {
+                if sew * 2 > v.elen() {
+                    return Err(illegal_instruction());
+                }
+                context.check_destination(vd, lmul)?;
+                Context::check_group(vs2, lmul + 1)?;
+                context.for_each_active(v, |v, i| {
+                    let a = v.read(vs2, i, 2 * sew);
+                    let b = op1.read(v, i, sew);
+                    let result = narrowing_operation(opcode, sew, vxrm, a, b, &mut saturated);
+                    v.write(vd, i, sew, result);
+                });
+            }
+            RvvOpcodeR::Vzext | RvvOpcodeR::Vsext => {
+                let factor = 16 >> (rs1 >> 1);
+                let source_eew = sew / factor;
+                if source_eew < 8 {
+                    return Err(illegal_instruction());
+                }
+                context.check_destination(vd, lmul)?;
+                Context::check_group(vs2, lmul - log2(factor))?;
+                context.for_each_active(v, |v, i| {
+                    let value = v.read(vs2, i, source_eew);
+                    let extended = if *opcode == RvvOpcodeR::Vsext {
+                        signed(value, source_eew) as u64 & mask(sew)
+                    } else {
+                        value
+                    };
+                    v.write(vd, i, sew, extended);
+                });
+            }
+            RvvOpcodeR::Vredsum
+            | RvvOpcodeR::Vredand
+            | RvvOpcodeR::Vredor
+            | RvvOpcodeR::Vredxor
+            | RvvOpcodeR::Vredminu
+            | RvvOpcodeR::Vredmin
+            | RvvOpcodeR::Vredmaxu
+            | RvvOpcodeR::Vredmax
+            | RvvOpcodeR::Vwredsumu
+            | RvvOpcodeR::Vwredsum => {
+                if context.vstart != 0 {
+                    return Err(illegal_instruction());
+                }
+                Context::check_group(vs2, lmul)?;
+                let widening = matches!(opcode, RvvOpcodeR::Vwredsumu | RvvOpcodeR::Vwredsum);
+                if widening && sew * 2 > v.elen() {
+                    return Err(illegal_instruction());
+                }
+                if context.vl == 0 {
+                    return Ok(());
+                }
+                let accumulator_eew = if widening { 2 * sew } else { sew };
+                let mut accumulator = op1.read(v, 0, accumulator_eew);
+                context.for_each_active(v, |v, i| {
+                    let element = v.read(vs2, i, sew);
+                    accumulator = match opcode {
+                        RvvOpcodeR::Vwredsumu => accumulator.wrapping_add(element),
+                        RvvOpcodeR::Vwredsum => {
+                            accumulator.wrapping_add(signed(element, sew) as u64)
+                        }
+                        _ => reduction_operation(opcode, sew, accumulator, element),
+                    } & mask(accumulator_eew);
+                });
+                v.write(vd, 0, accumulator_eew, accumulator);
+            }
+            RvvOpcodeR::Vmandn
+            | RvvOpcodeR::Vmand
+            | RvvOpcodeR::Vmor
+            | RvvOpcodeR::Vmxor
+            | RvvOpcodeR::Vmorn
+            | RvvOpcodeR::Vmnand
+            | RvvOpcodeR::Vmnor
+            | RvvOpcodeR::Vmxnor => {
+                for i in context.vstart..context.vl {
+                    let a = v.read_mask(vs2, i);
+                    let b = v.read_mask(rs1, i);
+                    let result = match opcode {
+                        RvvOpcodeR::Vmandn => a && !b,
+                        RvvOpcodeR::Vmand => a && b,
+                        RvvOpcodeR::Vmor => a || b,
+                        RvvOpcodeR::Vmxor => a != b,
+                        RvvOpcodeR::Vmorn => a || !b,
+                        RvvOpcodeR::Vmnand => !(a && b),
+                        RvvOpcodeR::Vmnor => !(a || b),
+                        _ => a == b,
+                    };
+                    v.write_mask(vd, i, result);
+                }
+            }
+            RvvOpcodeR::Vcpop | RvvOpcodeR::Vfirst => {
+                if context.vstart != 0 {
+                    return Err(illegal_instruction());
+                }
+                let mut set =
+                    (0..context.vl).filter(|&i| context.active(v, i) && v.read_mask(vs2, i));
+                let result = if *opcode == RvvOpcodeR::Vcpop {
+                    set.count() as u64
+                } else {
+                    set.next().map_or(u64::MAX, |i| i as u64)
+                };
+                x.write(vd, result);
+            }
+            RvvOpcodeR::Vmsbf | RvvOpcodeR::Vmsif | RvvOpcodeR::Vmsof => {
+                if context.vstart != 0 || vd == vs2 || (!context.vm && vd == 0) {
+                    return Err(illegal_instruction());
+                }
+                let first = (0..context.vl).find(|&i| context.active(v, i) && v.read_mask(vs2, i));
+                context.for_each_active(v, |v, i| {
+                    let result = match (opcode, first) {
+                        (RvvOpcodeR::Vmsbf, Some(first)) => i < first,
+                        (RvvOpcodeR::Vmsif, Some(first)) => i <= first,
+                        (RvvOpcodeR::Vmsof, Some(first)) => i == first,
+                        (RvvOpcodeR::Vmsof, None) => false,
+                        _ => true,
+                    };
+                    v.write_mask(vd, i, result);
+                });
+            }
+            RvvOpcodeR::Viota => {
+                if context.vstart != 0 {
+                    return Err(illegal_instruction());
+                }
+                context.check_destination(vd, lmul)?;
+                let mut count = 0;
+                context.for_each_active(v, |v, i| {
+                    let set = v.read_mask(vs2, i);
+                    v.write(vd, i, sew, count & mask(sew));
+                    count += set as u64;
+                });
+            }
+            RvvOpcodeR::Vid => {
+                context.check_destination(vd, lmul)?;
+                context.for_each_active(v, |v, i| v.write(vd, i, sew, i as u64 & mask(sew)));
+            }
+            RvvOpcodeR::Vmvxs => {
+                x.write(vd, signed(v.read(vs2, 0, sew), sew) as u64);
+            }
+            RvvOpcodeR::Vmvsx => {
+                if context.vstart < context.vl {
+                    v.write(vd, 0, sew, x.read(rs1) & mask(sew));
+                }
+            }
+            RvvOpcodeR::Vslideup | RvvOpcodeR::Vslidedown => {
+                context.check_destination(vd, lmul)?;
+                Context::check_group(vs2, lmul)?;
+                let offset = op1.read(v, 0, 64);
+                if *opcode == RvvOpcodeR::Vslideup {
+                    for i in context.vstart.max(offset.min(usize::MAX as u64) as usize)..context.vl
+                    {
+                        if context.active(v, i) {
+                            let value = v.read(vs2, i - offset as usize, sew);
+                            v.write(vd, i, sew, value);
+                        }
+                    }
+                } else {
+                    context.for_each_active(v, |v, i| {
+                        let source = (i as u64).checked_add(offset);
+                        let value = match source {
+                            Some(s) if s < context.vlmax as u64 => v.read(vs2, s as usize, sew),
+                            _ => 0,
+                        };
+                        v.write(vd, i, sew, value);
+                    });
+                }
+            }
+            RvvOpcodeR::Vslide1up | RvvOpcodeR::Vslide1down => {
+                context.check_destination(vd, lmul)?;
+                Context::check_group(vs2, lmul)?;
+                let scalar = op1.read(v, 0, sew);
+                context.for_each_active(v, |v, i| {
+                    let value = if *opcode == RvvOpcodeR::Vslide1up {
+                        if i == 0 {
+                            scalar
+                        } else {
+                            v.read(vs2, i - 1, sew)
+                        }
+                    } else if i + 1 == context.vl {
+                        scalar
+                    } else {
+                        v.read(vs2, i + 1, sew)
+                    };
+                    v.write(vd, i, sew, value);
+                });
+            }
+            RvvOpcodeR::Vrgather | RvvOpcodeR::Vrgatherei16 => {
+                context.check_destination(vd, lmul)?;
+                Context::check_group(vs2, lmul)?;
+                let index_eew = if *opcode == RvvOpcodeR::Vrgatherei16 {
+                    Context::check_group(rs1, lmul + log2(16) - log2(sew))?;
+                    16
+                } else {
+                    sew
+                };
+                let mut results = Vec::new();
+                context.for_each_active(v, |v, i| {
+                    let index = match op1 {
+                        Operand::Vector(vs1) => v.read(vs1, i, index_eew),
+                        Operand::Scalar(value) => value,
+                    };
+                    let value = if index < context.vlmax as u64 {
+                        v.read(vs2, index as usize, sew)
+                    } else {
+                        0
+                    };
+                    results.push((i, value));
+                });
+                for (i, value) in results {
+                    v.write(vd, i, sew, value);
+                }
+            }
+            RvvOpcodeR::Vcompress => {
+                if context.vstart != 0 {
+                    return Err(illegal_instruction());
+                }
+                Context::check_group(vd, lmul)?;
+                Context::check_group(vs2, lmul)?;
+                let selected = (0..context.vl)
+                    .filter(|&i| v.read_mask(rs1, i))
+                    .map(|i| v.read(vs2, i, sew))
+                    .collect::<Vec<_>>();
+                for (i, value) in selected.into_iter().enumerate() {
+                    v.write(vd, i, sew, value);
+                }
+            }
+            RvvOpcodeR::Vmvnr => {
+                let registers = rs1 + 1;
+                if !vd.is_multiple_of(registers) || !vs2.is_multiple_of(registers) {
+                    return Err(illegal_instruction());
+                }
+                let evl = registers * v.vlen() / sew;
+                for i in context.vstart..evl {
+                    let value = v.read(vs2, i, sew);
+                    v.write(vd, i, sew, value);
+                }
+            }
+            _ => unreachable!(),
+        }
+        if saturated {
+            csr.csrrw(VXSAT, 1);
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn floating_point(
+        opcode: &RvvOpcodeR,
+        context: &Context,
+        vd: usize,
+        funct3: usize,
+        rs1: usize,
+        vs2: usize,
+        v: &mut VectorRegister,
+        f: &mut FloatingPointRegister,
+        csr: &mut ControlAndStatusRegister,
+    ) -> Result<(), Cause> {
+        if csr.read_field(MSTATUS, &STATUS_FS) == EXTENSION_STATE_OFF {
+            return Err(illegal_instruction());
+        }
+        let sew = context.sew;
+        let lmul = context.lmul;
+        let rounding_mode =
+            RoundingMode::from_primitive(csr.csrrs(FRM, 0)).ok_or_else(illegal_instruction)?;
+        let mut softfloat = SoftFloat::new(rounding_mode);
+        let op1 = match funct3 as u32 {
+            OPFVV => Operand::Vector(rs1),
+            OPFVF => Operand::Scalar(f.read_boxed(rs1, vector_format(sew)?)),
+            _ => unreachable!(),
+        };
+
+        match opcode {
+            RvvOpcodeR::Vfadd
+            | RvvOpcodeR::Vfsub
+            | RvvOpcodeR::Vfrsub
+            | RvvOpcodeR::Vfmul
+            | RvvOpcodeR::Vfdiv
+            | RvvOpcodeR::Vfrdiv
+            | RvvOpcodeR::Vfmin
+            | RvvOpcodeR::Vfmax
+            | RvvOpcodeR::Vfsgnj
+            | RvvOpcodeR::Vfsgnjn
+            | RvvOpcodeR::Vfsgnjx
+            | RvvOpcodeR::Vfmacc
+            | RvvOpcodeR::Vfnmacc
+            | RvvOpcodeR::Vfmsac
+            | RvvOpcodeR::Vfnmsac
+            | RvvOpcodeR::Vfmadd
+            | RvvOpcodeR::Vfnmadd
+            | RvvOpcodeR::Vfmsub
+            | RvvOpcodeR::Vfnmsub => {
+                let format = vector_format(sew)?;
+                context.check_destination(vd, lmul)?;
+                Context::check_group(vs2, lmul)?;
+                if let Operand::Vector(vs1) = op1 {
+                    Context::check_group(vs1, lmul)?;
+                }
+                context.for_each_active(v, |v, i| {
+                    let a = v.read(vs2, i, sew);
+                    let b = op1.read(v, i, sew);
+                    let d = v.read(vd, i, sew);
+                    let result = float_operation(opcode, format, &mut softfloat, a, b, d);
+                    v.write(vd, i, sew, result);
+                });
+            }
+            RvvOpcodeR::Vfwadd
+            | RvvOpcodeR::Vfwsub
+            | RvvOpcodeR::VfwaddW
+            | RvvOpcodeR::VfwsubW
+            | RvvOpcodeR::Vfwmul
+            | RvvOpcodeR::Vfwmacc
+            | RvvOpcodeR::Vfwnmacc
+            | RvvOpcodeR::Vfwmsac
+            | RvvOpcodeR::Vfwnmsac => {
+                let narrow = vector_format(sew)?;
+                let wide = vector_format(2 * sew)?;
+                let wide_source = matches!(opcode, RvvOpcodeR::VfwaddW | RvvOpcodeR::VfwsubW);
+                context.check_destination(vd, lmul + 1)?;
+                Context::check_group(vs2, if wide_source { lmul + 1 } else { lmul })?;
+                if let Operand::Vector(vs1) = op1 {
+                    Context::check_group(vs1, lmul)?;
+                }
+                context.for_each_active(v, |v, i| {
+                    let a = if wide_source {
+                        v.read(vs2, i, 2 * sew)
+                    } else {
+                        softfloat.convert(narrow, wide, v.read(vs2, i, sew))
+                    };
+                    let b = softfloat.convert(narrow, wide, op1.read(v, i, sew));
+                    let d = v.read(vd, i, 2 * sew);
+                    let result = float_operation(opcode, wide, &mut softfloat, a, b, d);
+                    v.write(vd, i, 2 * sew, result);
+                });
+            }
+            RvvOpcodeR::Vmfeq
+            | RvvOpcodeR::Vmfne
+            | RvvOpcodeR::Vmflt
+            | RvvOpcodeR::Vmfle
+            | RvvOpcodeR::Vmfgt
+            | RvvOpcodeR::Vmfge => {
+                let format = vector_format(sew)?;
+                Context::check_group(vs2, lmul)?;
+                if let Operand::Vector(vs1) = op1 {
+                    Context::check_group(vs1, lmul)?;
+                }
+                for i in context.vstart..context.vl {
+                    if context.active(v, i) {
+                        let a = v.read(vs2, i, sew);
+                        let b = op1.read(v, i, sew);
+                        let result = float_comparison(opcode, format, &mut softfloat, a, b);
+                        v.write_mask(vd, i, result);
+                    }
+                }
+            }
+            RvvOpcodeR::Vfsqrt
+            | RvvOpcodeR::Vfclass
+            | RvvOpcodeR::VfcvtXuF
+            | RvvOpcodeR::VfcvtXF
+            | RvvOpcodeR::VfcvtFXu
+            | RvvOpcodeR::VfcvtFX
+            | RvvOpcodeR::VfcvtRtzXuF
+            | RvvOpcodeR::VfcvtRtzXF => {
+                let format = vector_format(sew)?;
+                context.check_destination(vd, lmul)?;
+                Context::check_group(vs2, lmul)?;
+                let width = sew as u32;
+                context.for_each_active(v, |v, i| {
+                    let a = v.read(vs2, i, sew);
+                    let result = match opcode {
+                        RvvOpcodeR::Vfsqrt => softfloat.sqrt(format, a),
+                        RvvOpcodeR::Vfclass => format.classify(a),
+                        RvvOpcodeR::VfcvtXuF => softfloat.float_to_integer(format, a, width, false),
+                        RvvOpcodeR::VfcvtXF => softfloat.float_to_integer(format, a, width, true),
+                        RvvOpcodeR::VfcvtFXu => softfloat.integer_to_float(format, a, width, false),
+                        RvvOpcodeR::VfcvtFX => softfloat.integer_to_float(format, a, width, true),
+                        _ => {
+                            softfloat.rounding_mode = RoundingMode::TowardZero;
+                            let signed = *opcode == RvvOpcodeR::VfcvtRtzXF;
+                            let result = softfloat.float_to_integer(format, a, width, signed);
+                            softfloat.rounding_mode = rounding_mode;
+                            result
+                        }
+                    };
+                    v.write(vd, i, sew, result & mask(sew));
+                });
+            }
+            RvvOpcodeR::VfwcvtXuF
+            | RvvOpcodeR::VfwcvtXF
+            | RvvOpcodeR::VfwcvtFXu
+            | RvvOpcodeR::VfwcvtFX
+            | RvvOpcodeR::VfwcvtFF
+            | RvvOpcodeR::VfwcvtRtzXuF
+            | RvvOpcodeR::VfwcvtRtzXF => {
+                let wide = 2 * sew;
+                if wide > v.elen() {
+                    return Err(illegal_instruction());
+                }
+                let (from_float, to_float) = match opcode {
+                    RvvOpcodeR::VfwcvtFXu | RvvOpcodeR::VfwcvtFX => {
+                        (None, Some(vector_format(wide)?))
+                    }
+                    RvvOpcodeR::VfwcvtFF => (Some(vector_format(sew)?), Some(vector_format(wide)?)),
+                    _ => (Some(vector_format(sew)?), None),
+                };
+                context.check_destination(vd, lmul + 1)?;
+                Context::check_group(vs2, lmul)?;
+                context.for_each_active(v, |v, i| {
+                    let a = v.read(vs2, i, sew);
+                    let result = match (from_float, to_float) {
+                        (Some(from), Some(to)) => softfloat.convert(from, to, a),
+                        (None, Some(to)) => softfloat.integer_to_float(
+                            to,
+                            a,
+                            sew as u32,
+                            *opcode == RvvOpcodeR::VfwcvtFX,
+                        ),
+                        (Some(from), None) => {
+                            if matches!(opcode, RvvOpcodeR::VfwcvtRtzXuF | RvvOpcodeR::VfwcvtRtzXF)
+                            {
+                                softfloat.rounding_mode = RoundingMode::TowardZero;
+                            }
+                            let signed =
+                                matches!(opcode, RvvOpcodeR::VfwcvtXF | RvvOpcodeR::VfwcvtRtzXF);
+                            let result = softfloat.float_to_integer(from, a, wide as u32, signed);
+                            softfloat.rounding_mode = rounding_mode;
+                            result
+                        }
+                        (None, None) => unreachable!(),
+                    };
+                    v.write(vd, i, wide, result & mask(wide));
+                });
+            }
+            RvvOpcodeR::VfncvtXuF
+            | RvvOpcodeR::VfncvtXF
+            | RvvOpcodeR::VfncvtFXu
+            | RvvOpcodeR::VfncvtFX
+            | RvvOpcodeR::VfncvtFF
+            | RvvOpcodeR::VfncvtRodFF
+            | RvvOpcodeR::VfncvtRtzXuF
+            | RvvOpcodeR::VfncvtRtzXF => {
+                let wide = 2 * sew;
+                if wide > v.elen() {
+                    return Err(illegal_instruction());
+                }
+                let (from_float, to_float) = match opcode {
+                    RvvOpcodeR::VfncvtFXu | RvvOpcodeR::VfncvtFX => {
+                        (None, Some(vector_format(sew)?))
+                    }
+                    RvvOpcodeR::VfncvtFF | RvvOpcodeR::VfncvtRodFF => {
+                        (Some(vector_format(wide)?), Some(vector_format(sew)?))
+                    }
+                    _ => (Some(vector_format(wide)?), None),
+                };
+                context.check_destination(vd, lmul)?;
+                Context::check_group(vs2, lmul + 1)?;
+                context.for_each_active(v, |v, i| {
+                    let a = v.read(vs2, i, wide);
+                    let result = match (from_float, to_float) {
+                        (Some(from), Some(to)) if *opcode == RvvOpcodeR::VfncvtRodFF => {
+                            // round towards zero, then jam the inexactness into the lsb
+                            let mut rounding = SoftFloat::new(RoundingMode::TowardZero);
+                            let result = rounding.convert(from, to, a);
+                            softfloat.flags |= rounding.flags;
+                            if rounding.flags & FLAG_NX != 0
+                                && !to.is_nan(result)
+                                && !to.is_infinite(result)
+                            {
+                                result | 1
+                            } else {
+                                result
+                            }
+                        }
+                        (Some(from), Some(to)) => softfloat.convert(from, to, a),
+                        (None, Some(to)) => softfloat.integer_to_float(
+                            to,
+                            a,
+                            wide as u32,
+                            *opcode == RvvOpcodeR::VfncvtFX,
+                        ),
+                        (Some(from), None) => {
+                            if matches!(opcode, RvvOpcodeR::VfncvtRtzXuF | RvvOpcodeR::VfncvtRtzXF)
+                            {
+                                softfloat.rounding_mode = RoundingMode::TowardZero;
+                            }
+                            let signed =
+                                matches!(opcode, RvvOpcodeR::VfncvtXF | RvvOpcodeR::VfncvtRtzXF);
+                            let result = softfloat.float_to_integer(from, a, sew as u32, signed);
+                            softfloat.rounding_mode = rounding_mode;
+                            result
+                        }
+                        (None, None) => unreachable!(),
+                    };
+                    v.write(vd, i, sew, result & mask(sew));
+                });
+            }
+            RvvOpcodeR::Vfredusum
+            | RvvOpcodeR::Vfredosum
+            | RvvOpcodeR::Vfredmin
+            | RvvOpcodeR::Vfredmax
+            | RvvOpcodeR::Vfwredusum
+            | RvvOpcodeR::Vfwredosum => {
+                if context.vstart != 0 {
+                    return Err(illegal_instruction());
+                }
+                Context::check_group(vs2, lmul)?;
+                let narrow = vector_format(sew)?;
+                let widening = matches!(opcode, RvvOpcodeR::Vfwredusum | RvvOpcodeR::Vfwredosum);
+                let (accumulator_eew, format) = if widening {
+                    (2 * sew, vector_format(2 * sew)?)
+                } else {
+                    (sew, narrow)
+                };
+                if context.vl == 0 {
+                    return Ok(());
+                }
+                // the unordered sums are computed in element order as well
+                let mut accumulator = op1.read(v, 0, accumulator_eew);
+                context.for_each_active(v, |v, i| {
+                    let element = v.read(vs2, i, sew);
+                    accumulator = match opcode {
+                        RvvOpcodeR::Vfredmin => softfloat.min(format, accumulator, element),
+                        RvvOpcodeR::Vfredmax => softfloat.max(format, accumulator, element),
+                        RvvOpcodeR::Vfwredusum | RvvOpcodeR::Vfwredosum => {
+                            let element = softfloat.convert(narrow, format, element);
+                            softfloat.add(format, accumulator, element)
+                        }
+                        _ => softfloat.add(format, accumulator, element),
+                    };
+                });
+                v.write(vd, 0, accumulator_eew, accumulator);
+            }
+            RvvOpcodeR::Vfmerge | RvvOpcodeR::Vfmv => {
+                vector_format(sew)?;
+                Context::check_group(vd, lmul)?;
+                Context::check_group(vs2, lmul)?;
+                for i in context.vstart..context.vl {
+                    let value = if *opcode == RvvOpcodeR::Vfmv || v.read_mask(0, i) {
+                        op1.read(v, i, sew)
+                    } else {
+                        v.read(vs2, i, sew)
+                    };
+                    v.write(vd, i, sew, value);
+                }
+            }
+            RvvOpcodeR::Vfslide1up | RvvOpcodeR::Vfslide1down => {
+                vector_format(sew)?;
+                context.check_destination(vd, lmul)?;
+                Context::check_group(vs2, lmul)?;
+                let scalar = op1.read(v, 0, sew);
+                context.for_each_active(v, |v, i| {
+                    let value = if *opcode == RvvOpcodeR::Vfslide1up {
+                        if i == 0 {
+                            scalar
+                        } else {
+                            v.read(vs2, i - 1, sew)
+                        }
+                    } else if i + 1 == context.vl {
+                        scalar
+                    } else {
+                        v.read(vs2, i + 1, sew)
+                    };
+                    v.write(vd, i, sew, value);
+                });
+            }
+            RvvOpcodeR::Vfmvfs => {
+                let format = vector_format(sew)?;
+                f.write_boxed(vd, format, v.read(vs2, 0, sew));
+            }
+            RvvOpcodeR::Vfmvsf => {
+                vector_format(sew)?;
+                if context.vstart < context.vl {
+                    v.write(vd, 0, sew, op1.read(v, 0, sew));
+                }
+            }
+            // the estimate instructions are not implemented
+            _ => return Err(illegal_instruction()),
+        }
+        if softfloat.flags != 0 {
+            csr.csrrs(FFLAGS, softfloat.flags);
+        }
+        csr.write_field(MSTATUS, &STATUS_FS, EXTENSION_STATE_DIRTY);
+        Ok(())
+    }
+}
+
+impl Executor for RvvExecutor {
+    type OpcodeR = RvvOpcodeR;
+    type OpcodeI = RvvOpcodeI;
+    type OpcodeS = RvvOpcodeS;
+    type OpcodeB = RvvOpcodeB;
+    type OpcodeU = RvvOpcodeU;
+    type OpcodeJ = RvvOpcodeJ;
+
+    fn execute(
+        instruction: Instruction<
+            RvvOpcodeR,
+            RvvOpcodeI,
+            RvvOpcodeS,
+            RvvOpcodeB,
+            RvvOpcodeU,
+            RvvOpcodeJ,
+        >,
+        _: &PrivilegeMode,
+        _: &mut ProgramCounter,
+        x: &mut IntegerRegister,
+        v: &mut VectorRegister,
+        f: &mut FloatingPointRegister,
+        csr: &mut ControlAndStatusRegister,
+        bus: &mut Bus,
+    ) -> Result<(), Cause> {
+        if csr.read_field(MSTATUS, &STATUS_VS) == EXTENSION_STATE_OFF {
+            return Err(illegal_instruction());
+        }
+        let result = match instruction {
+            Instruction::TypeI {
+                opcode,
+                rd,
+                funct3: _,
+                rs1,
+                imm,
+            } => {
+                match opcode {
+                    RvvOpcodeI::Vsetvli => {
+                        Self::set_configuration(rd, rs1, None, imm & 0x7ff, x, v, csr)
+                    }
+                    RvvOpcodeI::Vsetivli => {
+                        Self::set_configuration(rd, rs1, Some(rs1 as u64), imm & 0x3ff, x, v, csr)
+                    }
+                }
+                Ok(())
+            }
+            Instruction::TypeR {
+                opcode: RvvOpcodeR::Vsetvl,
+                rd,
+                funct3: _,
+                rs1,
+                rs2,
+                funct7: _,
+            } => {
+                Self::set_configuration(rd, rs1, None, x.read(rs2), x, v, csr);
+                Ok(())
+            }
+            Instruction::TypeR {
+                opcode,
+                rd,
+                funct3,
+                rs1,
+                rs2,
+                funct7,
+            } => match opcode {
+                RvvOpcodeR::Vle
+                | RvvOpcodeR::Vleff
+                | RvvOpcodeR::Vlm
+                | RvvOpcodeR::Vlr
+                | RvvOpcodeR::Vlse
+                | RvvOpcodeR::Vluxei
+                | RvvOpcodeR::Vloxei
+                | RvvOpcodeR::Vse
+                | RvvOpcodeR::Vsm
+                | RvvOpcodeR::Vsr
+                | RvvOpcodeR::Vsse
+                | RvvOpcodeR::Vsuxei
+                | RvvOpcodeR::Vsoxei => {
+                    Self::load_store(&opcode, rd, funct3, rs1, rs2, funct7, x, v, csr, bus)
+                }
+                _ => {
+                    let context = Context::new(v, csr, funct7 & 1 == 1)?;
+                    let result = match funct3 as u32 {
+                        OPFVV | OPFVF => {
+                            Self::floating_point(&opcode, &context, rd, funct3, rs1, rs2, v, f, csr)
+                        }
+                        _ => Self::integer(&opcode, &context, rd, funct3, rs1, rs2, x, v, csr),
+                    };
+                    if result.is_ok() {
+                        csr.csrrw(VSTART, 0);
+                    }
+                    result
+                }
+            },
+            _ => Ok(()),
+        };
+        csr.write_field(MSTATUS, &STATUS_VS, EXTENSION_STATE_DIRTY);
+        result
+    }
+}
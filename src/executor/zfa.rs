@@ -0,0 +1,162 @@
+use crate::{
+    bus::Bus,
+    cause::{Cause, Exception},
+    csr::ControlAndStatusRegister,
+    executor::{
+        rvf::{check_enabled, retire, rounding_mode},
+        Executor,
+    },
+    f::FloatingPointRegister,
+    float::{Format, RoundingMode, SoftFloat, DOUBLE},
+    instruction::{
+        zfa::{ZfaOpcodeB, ZfaOpcodeI, ZfaOpcodeJ, ZfaOpcodeR, ZfaOpcodeS, ZfaOpcodeU},
+        Instruction,
+    },
+    mode::PrivilegeMode,
+    pc::ProgramCounter,
+    v::VectorRegister,
+    x::IntegerRegister,
+    xlen::Xlen,
+};
+
+// The values loaded by fli as significand * 2^exponent, indexed by rs1.
+// Entry 1 is the minimum positive normal value and entries 30 and 31 are +inf and NaN.
+const FLI_CONSTANTS: [(u64, i32); 32] = [
+    (1, 0),
+    (0, 0),
+    (1, -16),
+    (1, -15),
+    (1, -8),
+    (1, -7),
+    (1, -4),
+    (1, -3),
+    (1, -2),
+    (5, -4),
+    (3, -3),
+    (7, -4),
+    (1, -1),
+    (5, -3),
+    (3, -2),
+    (7, -3),
+    (1, 0),
+    (5, -2),
+    (3, -1),
+    (7, -2),
+    (1, 1),
+    (5, -1),
+    (3, 0),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (1, 7),
+    (1, 8),
+    (1, 15),
+    (1, 16),
+    (0, 0),
+    (0, 0),
+];
+
+fn load_constant(softfloat: &mut SoftFloat, format: Format, index: usize) -> u64 {
+    match index {
+        1 => format.min_normal(),
+        30 => format.infinity(false),
+        31 => format.canonical_nan(),
+        _ => {
+            let (significand, exponent) = FLI_CONSTANTS[index];
+            softfloat.compose(format, index == 0, exponent, significand)
+        }
+    }
+}
+
+pub struct ZfaExecutor;
+
+impl Executor for ZfaExecutor {
+    type OpcodeR = ZfaOpcodeR;
+    type OpcodeI = ZfaOpcodeI;
+    type OpcodeS = ZfaOpcodeS;
+    type OpcodeB = ZfaOpcodeB;
+    type OpcodeU = ZfaOpcodeU;
+    type OpcodeJ = ZfaOpcodeJ;
+
+    fn execute(
+        instruction: Instruction<
+            ZfaOpcodeR,
+            ZfaOpcodeI,
+            ZfaOpcodeS,
+            ZfaOpcodeB,
+            ZfaOpcodeU,
+            ZfaOpcodeJ,
+        >,
+        _: &PrivilegeMode,
+        _: &mut ProgramCounter,
+        x: &mut IntegerRegister,
+        _: &mut VectorRegister,
+        f: &mut FloatingPointRegister,
+        csr: &mut ControlAndStatusRegister,
+        _: &mut Bus,
+    ) -> Result<(), Cause> {
+        check_enabled(csr)?;
+        let Instruction::TypeR {
+            opcode,
+            rd,
+            funct3,
+            rs1,
+            rs2,
+            funct7,
+        } = instruction;
+        let format = Format::from_fmt(funct7 & 0b11)
+            .ok_or(Cause::Exception(Exception::IllegalInstruction))?;
+        let mut softfloat = SoftFloat::new(RoundingMode::NearestEven);
+        match opcode {
+            ZfaOpcodeR::Fli => {
+                let result = load_constant(&mut softfloat, format, rs1);
+                f.write_boxed(rd, format, result);
+            }
+            ZfaOpcodeR::Fminm | ZfaOpcodeR::Fmaxm => {
+                let a = f.read_boxed(rs1, format);
+                let b = f.read_boxed(rs2, format);
+                let result = if opcode == ZfaOpcodeR::Fminm {
+                    softfloat.minimum(format, a, b)
+                } else {
+                    softfloat.maximum(format, a, b)
+                };
+                f.write_boxed(rd, format, result);
+            }
+            ZfaOpcodeR::Fround | ZfaOpcodeR::Froundnx => {
+                softfloat.rounding_mode = rounding_mode(funct3, csr)?;
+                let a = f.read_boxed(rs1, format);
+                let result = softfloat.round_to_integral(format, a, opcode == ZfaOpcodeR::Froundnx);
+                f.write_boxed(rd, format, result);
+            }
+            ZfaOpcodeR::FcvtmodWD => {
+                let a = f.read_boxed(rs1, DOUBLE);
+                x.write(rd, softfloat.float_to_int32_modular(DOUBLE, a));
+            }
+            ZfaOpcodeR::Fleq | ZfaOpcodeR::Fltq => {
+                let a = f.read_boxed(rs1, format);
+                let b = f.read_boxed(rs2, format);
+                let result = if opcode == ZfaOpcodeR::Fleq {
+                    softfloat.le_quiet(format, a, b)
+                } else {
+                    softfloat.lt_quiet(format, a, b)
+                };
+                x.write(rd, result as u64);
+            }
+            // the moves of the upper and both halves of a double need two registers in RV32
+            ZfaOpcodeR::FmvhXD | ZfaOpcodeR::FmvpDX => {
+                if x.xlen() == Xlen::Rv64 {
+                    return Err(Cause::Exception(Exception::IllegalInstruction));
+                }
+                if opcode == ZfaOpcodeR::FmvhXD {
+                    x.write(rd, f.read(rs1) >> 32);
+                    return Ok(());
+                } else {
+                    let value = x.read(rs2) << 32 | x.read(rs1) & 0xffffffff;
+                    f.write_boxed(rd, DOUBLE, value);
+                }
+            }
+        }
+        retire(&softfloat, csr);
+        Ok(())
+    }
+}
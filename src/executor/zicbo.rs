@@ -0,0 +1,81 @@
+use crate::{
+    bus::Bus,
+    cause::{Cause, Exception},
+    csr::{
+        ControlAndStatusRegister, ENVCFG_CBCFE, ENVCFG_CBIE, ENVCFG_CBIE_ILLEGAL, ENVCFG_CBZE,
+        MENVCFG, SENVCFG,
+    },
+    executor::Executor,
+    f::FloatingPointRegister,
+    instruction::{
+        zicbo::{
+            ZicboOpcodeB, ZicboOpcodeI, ZicboOpcodeJ, ZicboOpcodeR, ZicboOpcodeS, ZicboOpcodeU,
+        },
+        Instruction,
+    },
+    mode::PrivilegeMode,
+    pc::ProgramCounter,
+    v::VectorRegister,
+    x::IntegerRegister,
+};
+
+pub struct ZicboExecutor;
+
+impl Executor for ZicboExecutor {
+    type OpcodeR = ZicboOpcodeR;
+    type OpcodeI = ZicboOpcodeI;
+    type OpcodeS = ZicboOpcodeS;
+    type OpcodeB = ZicboOpcodeB;
+    type OpcodeU = ZicboOpcodeU;
+    type OpcodeJ = ZicboOpcodeJ;
+
+    fn execute(
+        instruction: Instruction<
+            ZicboOpcodeR,
+            ZicboOpcodeI,
+            ZicboOpcodeS,
+            ZicboOpcodeB,
+            ZicboOpcodeU,
+            ZicboOpcodeJ,
+        >,
+        prv: &PrivilegeMode,
+        _: &mut ProgramCounter,
+        x: &mut IntegerRegister,
+        _: &mut VectorRegister,
+        _: &mut FloatingPointRegister,
+        csr: &mut ControlAndStatusRegister,
+        bus: &mut Bus,
+    ) -> Result<(), Cause> {
+        let Instruction::TypeI {
+            opcode,
+            rd: _,
+            funct3: _,
+            rs1,
+            imm: _,
+        } = instruction;
+        // menvcfg gates the instructions below M-mode and senvcfg additionally gates them in U-mode
+        let enabled = |field, disabled| {
+            let registers: &[u64] = match prv {
+                PrivilegeMode::Machine => &[],
+                PrivilegeMode::Supervisor => &[MENVCFG],
+                PrivilegeMode::User => &[MENVCFG, SENVCFG],
+            };
+            registers
+                .iter()
+                .all(|&register| csr.read_field(register, field) != disabled)
+        };
+        let enabled = match opcode {
+            ZicboOpcodeI::Inval => enabled(&ENVCFG_CBIE, ENVCFG_CBIE_ILLEGAL),
+            ZicboOpcodeI::Clean | ZicboOpcodeI::Flush => enabled(&ENVCFG_CBCFE, 0),
+            ZicboOpcodeI::Zero => enabled(&ENVCFG_CBZE, 0),
+        };
+        if !enabled {
+            return Err(Cause::Exception(Exception::IllegalInstruction));
+        }
+        let address = x.address(rs1, 0);
+        match opcode {
+            ZicboOpcodeI::Zero => bus.zero_block(address),
+            _ => bus.check_block(address),
+        }
+    }
+}
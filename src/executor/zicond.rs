@@ -0,0 +1,63 @@
+use crate::{
+    bus::Bus,
+    cause::Cause,
+    csr::ControlAndStatusRegister,
+    executor::Executor,
+    f::FloatingPointRegister,
+    instruction::{
+        zicond::{
+            ZicondOpcodeB, ZicondOpcodeI, ZicondOpcodeJ, ZicondOpcodeR, ZicondOpcodeS,
+            ZicondOpcodeU,
+        },
+        Instruction,
+    },
+    mode::PrivilegeMode,
+    pc::ProgramCounter,
+    v::VectorRegister,
+    x::IntegerRegister,
+};
+
+pub struct ZicondExecutor;
+
+impl Executor for ZicondExecutor {
+    type OpcodeR = ZicondOpcodeR;
+    type OpcodeI = ZicondOpcodeI;
+    type OpcodeS = ZicondOpcodeS;
+    type OpcodeB = ZicondOpcodeB;
+    type OpcodeU = ZicondOpcodeU;
+    type OpcodeJ = ZicondOpcodeJ;
+
+    fn execute(
+        instruction: Instruction<
+            ZicondOpcodeR,
+            ZicondOpcodeI,
+            ZicondOpcodeS,
+            ZicondOpcodeB,
+            ZicondOpcodeU,
+            ZicondOpcodeJ,
+        >,
+        _: &PrivilegeMode,
+        _: &mut ProgramCounter,
+        x: &mut IntegerRegister,
+        _: &mut VectorRegister,
+        _: &mut FloatingPointRegister,
+        _: &mut ControlAndStatusRegister,
+        _: &mut Bus,
+    ) -> Result<(), Cause> {
+        let Instruction::TypeR {
+            opcode,
+            rd,
+            funct3: _,
+            rs1,
+            rs2,
+            funct7: _,
+        } = instruction;
+        let condition = x.read(rs2) == 0;
+        let zero = match opcode {
+            ZicondOpcodeR::CzeroEqz => condition,
+            ZicondOpcodeR::CzeroNez => !condition,
+        };
+        x.write(rd, if zero { 0 } else { x.read(rs1) });
+        Ok(())
+    }
+}
@@ -1,17 +1,24 @@
 use crate::{
     bitops::MASK_12BIT,
-    cause::Cause,
-    csr::ControlAndStatusRegister,
+    bus::Bus,
+    cause::{Cause, Exception},
+    csr::{
+        ControlAndStatusRegister, EXTENSION_STATE_DIRTY, EXTENSION_STATE_OFF, FCSR, FFLAGS, FRM,
+        MCOUNTEREN, MENVCFG, MENVCFG_STCE, MHARTID, MSECCFG, MSECCFG_SSEED, MSECCFG_USEED, MSTATUS,
+        SATP, SCOUNTEREN, SEED, STATUS_FS, STATUS_TVM, STATUS_VS, STIMECMP, STIMECMPH, TIME, VCSR,
+        VL, VLENB, VSTART, VTYPE, VXRM, VXSAT,
+    },
     executor::Executor,
+    f::FloatingPointRegister,
     instruction::{
         zicsr::{
             ZicsrOpcodeB, ZicsrOpcodeI, ZicsrOpcodeJ, ZicsrOpcodeR, ZicsrOpcodeS, ZicsrOpcodeU,
         },
         Instruction,
     },
-    memory::Memory,
     mode::PrivilegeMode,
     pc::ProgramCounter,
+    v::VectorRegister,
     x::IntegerRegister,
 };
 
@@ -34,26 +41,121 @@ impl Executor for ZicsrExecutor {
             ZicsrOpcodeU,
             ZicsrOpcodeJ,
         >,
-        _: &PrivilegeMode,
+        prv: &PrivilegeMode,
         _: &mut ProgramCounter,
         x: &mut IntegerRegister,
+        _: &mut VectorRegister,
+        _: &mut FloatingPointRegister,
         csr: &mut ControlAndStatusRegister,
-        _: &mut Memory,
+        bus: &mut Bus,
     ) -> Result<(), Cause> {
-        if let Instruction::TypeI {
+        let Instruction::TypeI {
             opcode,
             rd,
             funct3: _,
             rs1,
             imm,
-        } = instruction
+        } = instruction;
+        let address = imm & MASK_12BIT;
+        // the top two bits of the address indicate whether the CSR is read-only
+        let writes = matches!(opcode, ZicsrOpcodeI::Csrrw | ZicsrOpcodeI::Csrrwi) || rs1 != 0;
+        if address >> 10 == 0b11 && writes {
+            return Err(Cause::Exception(Exception::IllegalInstruction));
+        }
+        // the next two bits indicate the lowest privilege mode that can access the CSR
+        if (address >> 8) & 0b11 > *prv as u64 {
+            return Err(Cause::Exception(Exception::IllegalInstruction));
+        }
+        // the counters are only accessible below M-mode when mcounteren, and in U-mode also
+        // scounteren, allow it
+        if let 0xc00..=0xc1f | 0xc80..=0xc9f = address {
+            let bit = (address & 0x1f) as usize;
+            let enabled = match prv {
+                PrivilegeMode::Machine => true,
+                PrivilegeMode::Supervisor => csr.read_field(MCOUNTEREN, &(bit..bit)) == 1,
+                PrivilegeMode::User => {
+                    csr.read_field(MCOUNTEREN, &(bit..bit)) == 1
+                        && csr.read_field(SCOUNTEREN, &(bit..bit)) == 1
+                }
+            };
+            if !enabled {
+                return Err(Cause::Exception(Exception::IllegalInstruction));
+            }
+        }
+        // below M-mode, stimecmp requires both menvcfg.STCE and the time counter of mcounteren
+        if matches!(address, STIMECMP | STIMECMPH) && *prv != PrivilegeMode::Machine {
+            let time = (TIME & 0x1f) as usize;
+            if csr.read_field(MENVCFG, &MENVCFG_STCE) == 0
+                || csr.read_field(MCOUNTEREN, &(time..time)) == 0
+            {
+                return Err(Cause::Exception(Exception::IllegalInstruction));
+            }
+        }
+        // mstatus.TVM keeps S-mode from accessing satp, so that M-mode can manage the translation
+        if address == SATP
+            && *prv == PrivilegeMode::Supervisor
+            && csr.read_field(MSTATUS, &STATUS_TVM) == 1
         {
-            match opcode {
-                ZicsrOpcodeI::Csrrw => x.write(rd, csr.csrrw(imm & MASK_12BIT, x.read(rs1))),
-                ZicsrOpcodeI::Csrrs => x.write(rd, csr.csrrs(imm & MASK_12BIT, x.read(rs1))),
-                ZicsrOpcodeI::Csrrwi => x.write(rd, csr.csrrw(imm & MASK_12BIT, rs1 as u64)),
+            return Err(Cause::Exception(Exception::IllegalInstruction));
+        }
+        // the seed CSR only supports read-write access, and mseccfg grants it to lower modes
+        if address == SEED {
+            let granted = match prv {
+                PrivilegeMode::Machine => true,
+                PrivilegeMode::Supervisor => csr.read_field(MSECCFG, &MSECCFG_SSEED) == 1,
+                PrivilegeMode::User => csr.read_field(MSECCFG, &MSECCFG_USEED) == 1,
+            };
+            if !writes || !granted {
+                return Err(Cause::Exception(Exception::IllegalInstruction));
+            }
+        }
+        // the CSRs of the floating-point and vector units are inaccessible while mstatus.FS or VS
+        // is off, and a write to them makes the state of their unit dirty
+        let state = match address {
+            FFLAGS | FRM | FCSR => Some(STATUS_FS),
+            VSTART | VXSAT | VXRM | VCSR | VL | VTYPE | VLENB => Some(STATUS_VS),
+            _ => None,
+        };
+        if let Some(field) = &state {
+            if csr.read_field(MSTATUS, field) == EXTENSION_STATE_OFF {
+                return Err(Cause::Exception(Exception::IllegalInstruction));
             }
         }
+        let value = match opcode {
+            ZicsrOpcodeI::Csrrw | ZicsrOpcodeI::Csrrs | ZicsrOpcodeI::Csrrc => x.read(rs1),
+            ZicsrOpcodeI::Csrrwi | ZicsrOpcodeI::Csrrsi | ZicsrOpcodeI::Csrrci => rs1 as u64,
+        };
+        let update = |t| match opcode {
+            ZicsrOpcodeI::Csrrw | ZicsrOpcodeI::Csrrwi => value,
+            ZicsrOpcodeI::Csrrs | ZicsrOpcodeI::Csrrsi => t | value,
+            ZicsrOpcodeI::Csrrc | ZicsrOpcodeI::Csrrci => t & !value,
+        };
+        // the interrupt files of an IMSIC are reached through the bus, and a write to mtopei or
+        // stopei claims the interrupt that they report
+        let t = match csr.interrupt_file_register(address) {
+            Some((level, select)) => {
+                let hart = csr.read_field(MHARTID, &(0..63)) as usize;
+                let file = bus
+                    .interrupt_file(hart, level)
+                    .ok_or(Cause::Exception(Exception::IllegalInstruction))?;
+                match select {
+                    Some(select) => file.access(select, x.xlen(), update),
+                    None => {
+                        let t = file.topei();
+                        if writes {
+                            file.claim();
+                        }
+                        Some(t)
+                    }
+                }
+            }
+            None => csr.access(address, x.xlen(), update),
+        }
+        .ok_or(Cause::Exception(Exception::IllegalInstruction))?;
+        if let (Some(field), true) = (&state, writes) {
+            csr.write_field(MSTATUS, field, EXTENSION_STATE_DIRTY);
+        }
+        x.write(rd, t);
         Ok(())
     }
 }
@@ -0,0 +1,54 @@
+use crate::{
+    bus::Bus,
+    cause::Cause,
+    csr::ControlAndStatusRegister,
+    executor::Executor,
+    f::FloatingPointRegister,
+    instruction::{
+        zifencei::{
+            ZifenceiOpcodeB, ZifenceiOpcodeI, ZifenceiOpcodeJ, ZifenceiOpcodeR, ZifenceiOpcodeS,
+            ZifenceiOpcodeU,
+        },
+        Instruction,
+    },
+    mode::PrivilegeMode,
+    pc::ProgramCounter,
+    v::VectorRegister,
+    x::IntegerRegister,
+};
+
+pub struct ZifenceiExecutor;
+
+impl Executor for ZifenceiExecutor {
+    type OpcodeR = ZifenceiOpcodeR;
+    type OpcodeI = ZifenceiOpcodeI;
+    type OpcodeS = ZifenceiOpcodeS;
+    type OpcodeB = ZifenceiOpcodeB;
+    type OpcodeU = ZifenceiOpcodeU;
+    type OpcodeJ = ZifenceiOpcodeJ;
+
+    fn execute(
+        instruction: Instruction<
+            ZifenceiOpcodeR,
+            ZifenceiOpcodeI,
+            ZifenceiOpcodeS,
+            ZifenceiOpcodeB,
+            ZifenceiOpcodeU,
+            ZifenceiOpcodeJ,
+        >,
+        _: &PrivilegeMode,
+        _: &mut ProgramCounter,
+        _: &mut IntegerRegister,
+        _: &mut VectorRegister,
+        _: &mut FloatingPointRegister,
+        _: &mut ControlAndStatusRegister,
+        _: &mut Bus,
+    ) -> Result<(), Cause> {
+        let Instruction::TypeI { opcode, .. } = instruction;
+        match opcode {
+            // every instruction is fetched from memory and decoded afresh on each step, so stores
+            // are already visible to instruction fetch and there is no cache to invalidate
+            ZifenceiOpcodeI::FenceI => Ok(()),
+        }
+    }
+}
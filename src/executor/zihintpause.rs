@@ -0,0 +1,53 @@
+use crate::{
+    bus::Bus,
+    cause::Cause,
+    csr::ControlAndStatusRegister,
+    executor::Executor,
+    f::FloatingPointRegister,
+    instruction::{
+        zihintpause::{
+            ZihintpauseOpcodeB, ZihintpauseOpcodeI, ZihintpauseOpcodeJ, ZihintpauseOpcodeR,
+            ZihintpauseOpcodeS, ZihintpauseOpcodeU,
+        },
+        Instruction,
+    },
+    mode::PrivilegeMode,
+    pc::ProgramCounter,
+    v::VectorRegister,
+    x::IntegerRegister,
+};
+
+pub struct ZihintpauseExecutor;
+
+impl Executor for ZihintpauseExecutor {
+    type OpcodeR = ZihintpauseOpcodeR;
+    type OpcodeI = ZihintpauseOpcodeI;
+    type OpcodeS = ZihintpauseOpcodeS;
+    type OpcodeB = ZihintpauseOpcodeB;
+    type OpcodeU = ZihintpauseOpcodeU;
+    type OpcodeJ = ZihintpauseOpcodeJ;
+
+    fn execute(
+        instruction: Instruction<
+            ZihintpauseOpcodeR,
+            ZihintpauseOpcodeI,
+            ZihintpauseOpcodeS,
+            ZihintpauseOpcodeB,
+            ZihintpauseOpcodeU,
+            ZihintpauseOpcodeJ,
+        >,
+        _: &PrivilegeMode,
+        _: &mut ProgramCounter,
+        _: &mut IntegerRegister,
+        _: &mut VectorRegister,
+        _: &mut FloatingPointRegister,
+        _: &mut ControlAndStatusRegister,
+        _: &mut Bus,
+    ) -> Result<(), Cause> {
+        let Instruction::TypeI { opcode, .. } = instruction;
+        match opcode {
+            // a single hart has nothing to yield to, so the hint has no effect
+            ZihintpauseOpcodeI::Pause => Ok(()),
+        }
+    }
+}
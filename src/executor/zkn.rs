@@ -0,0 +1,303 @@
+use crate::{
+    bitops::extend_sign,
+    bus::Bus,
+    cause::{Cause, Exception},
+    csr::ControlAndStatusRegister,
+    executor::Executor,
+    f::FloatingPointRegister,
+    instruction::{
+        zkn::{ZknOpcodeB, ZknOpcodeI, ZknOpcodeJ, ZknOpcodeR, ZknOpcodeS, ZknOpcodeU},
+        Instruction,
+    },
+    mode::PrivilegeMode,
+    pc::ProgramCounter,
+    v::VectorRegister,
+    x::IntegerRegister,
+    xlen::Xlen,
+};
+
+const AES_SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+const AES_INVERSE_SBOX: [u8; 256] = [
+    0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
+    0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
+    0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
+    0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
+    0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
+    0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
+    0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a, 0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
+    0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02, 0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
+    0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
+    0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
+    0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
+    0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
+    0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
+    0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
+    0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+    0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
+];
+
+// round constants indexed by rnum; rnum = 0xa has none
+const AES_ROUND_CONSTANT: [u64; 11] = [
+    0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36, 0x00,
+];
+
+// multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1
+fn gf_multiply(a: u8, b: u8) -> u8 {
+    let mut product = 0;
+    let mut a = a;
+    for i in 0..8 {
+        if (b >> i) & 1 == 1 {
+            product ^= a;
+        }
+        a = if a & 0x80 != 0 {
+            (a << 1) ^ 0x1b
+        } else {
+            a << 1
+        };
+    }
+    product
+}
+
+fn substitute(value: u64, sbox: &[u8; 256]) -> u64 {
+    (0..8).fold(0, |acc, i| {
+        acc | (sbox[((value >> (8 * i)) & 0xff) as usize] as u64) << (8 * i)
+    })
+}
+
+// Returns the lower half of ShiftRows applied to the 128-bit state rs2:rs1,
+// where column c is bits 32c+31..32c and row r is byte r of a column.
+fn shift_rows(rs1: u64, rs2: u64, inverse: bool) -> u64 {
+    let state = (rs2 as u128) << 64 | rs1 as u128;
+    let mut result = 0;
+    for column in 0..2 {
+        for row in 0..4 {
+            let source = if inverse {
+                (column + 4 - row) % 4
+            } else {
+                (column + row) % 4
+            };
+            let byte = (state >> (32 * source + 8 * row)) as u64 & 0xff;
+            result |= byte << (32 * column + 8 * row);
+        }
+    }
+    result
+}
+
+fn mix_column(word: u64, inverse: bool) -> u64 {
+    let coefficients = if inverse {
+        [0x0e, 0x0b, 0x0d, 0x09]
+    } else {
+        [0x02, 0x03, 0x01, 0x01]
+    };
+    let s = (word as u32).to_le_bytes();
+    (0..4).fold(0, |acc, i| {
+        let b = (0..4).fold(0, |b, j| b ^ gf_multiply(s[(i + j) % 4], coefficients[j]));
+        acc | (b as u64) << (8 * i)
+    })
+}
+
+fn mix_columns(value: u64, inverse: bool) -> u64 {
+    mix_column(value >> 32, inverse) << 32 | mix_column(value, inverse)
+}
+
+pub struct ZknExecutor;
+
+impl Executor for ZknExecutor {
+    type OpcodeR = ZknOpcodeR;
+    type OpcodeI = ZknOpcodeI;
+    type OpcodeS = ZknOpcodeS;
+    type OpcodeB = ZknOpcodeB;
+    type OpcodeU = ZknOpcodeU;
+    type OpcodeJ = ZknOpcodeJ;
+
+    fn execute(
+        instruction: Instruction<
+            ZknOpcodeR,
+            ZknOpcodeI,
+            ZknOpcodeS,
+            ZknOpcodeB,
+            ZknOpcodeU,
+            ZknOpcodeJ,
+        >,
+        _: &PrivilegeMode,
+        _: &mut ProgramCounter,
+        x: &mut IntegerRegister,
+        _: &mut VectorRegister,
+        _: &mut FloatingPointRegister,
+        _: &mut ControlAndStatusRegister,
+        _: &mut Bus,
+    ) -> Result<(), Cause> {
+        match instruction {
+            Instruction::TypeR {
+                opcode,
+                rd,
+                funct3: _,
+                rs1,
+                rs2,
+                funct7,
+            } => {
+                let rv32_only = matches!(
+                    opcode,
+                    ZknOpcodeR::Aes32esi
+                        | ZknOpcodeR::Aes32esmi
+                        | ZknOpcodeR::Aes32dsi
+                        | ZknOpcodeR::Aes32dsmi
+                        | ZknOpcodeR::Sha512sum0r
+                        | ZknOpcodeR::Sha512sum1r
+                        | ZknOpcodeR::Sha512sig0l
+                        | ZknOpcodeR::Sha512sig0h
+                        | ZknOpcodeR::Sha512sig1l
+                        | ZknOpcodeR::Sha512sig1h
+                );
+                // the other instructions are RV64 only
+                if rv32_only != (x.xlen() == Xlen::Rv32) {
+                    return Err(Cause::Exception(Exception::IllegalInstruction));
+                }
+                let (rs1, rs2) = (x.read(rs1), x.read(rs2));
+                let (a, b) = (rs1 as u32, rs2 as u32);
+                let result = match opcode {
+                    ZknOpcodeR::Aes64es => substitute(shift_rows(rs1, rs2, false), &AES_SBOX),
+                    ZknOpcodeR::Aes64esm => {
+                        mix_columns(substitute(shift_rows(rs1, rs2, false), &AES_SBOX), false)
+                    }
+                    ZknOpcodeR::Aes64ds => {
+                        substitute(shift_rows(rs1, rs2, true), &AES_INVERSE_SBOX)
+                    }
+                    ZknOpcodeR::Aes64dsm => mix_columns(
+                        substitute(shift_rows(rs1, rs2, true), &AES_INVERSE_SBOX),
+                        true,
+                    ),
+                    ZknOpcodeR::Aes64ks2 => {
+                        let w0 = (rs1 >> 32) ^ (rs2 & 0xffffffff);
+                        let w1 = w0 ^ (rs2 >> 32);
+                        w1 << 32 | w0
+                    }
+                    // a single byte of the state selected by bs goes through one round
+                    ZknOpcodeR::Aes32esi
+                    | ZknOpcodeR::Aes32esmi
+                    | ZknOpcodeR::Aes32dsi
+                    | ZknOpcodeR::Aes32dsmi => {
+                        let shamt = 8 * (funct7 >> 5) as u32;
+                        let inverse =
+                            matches!(opcode, ZknOpcodeR::Aes32dsi | ZknOpcodeR::Aes32dsmi);
+                        let sbox = if inverse {
+                            &AES_INVERSE_SBOX
+                        } else {
+                            &AES_SBOX
+                        };
+                        let byte = sbox[((b >> shamt) & 0xff) as usize] as u64;
+                        let mixed =
+                            if matches!(opcode, ZknOpcodeR::Aes32esmi | ZknOpcodeR::Aes32dsmi) {
+                                mix_column(byte, inverse)
+                            } else {
+                                byte
+                            };
+                        (a ^ (mixed as u32).rotate_left(shamt)) as u64
+                    }
+                    // the SHA-512 functions on one half of a 64-bit word held in rs1 and rs2
+                    ZknOpcodeR::Sha512sum0r => {
+                        (a << 25 ^ a << 30 ^ a >> 28 ^ b >> 7 ^ b >> 2 ^ b << 4) as u64
+                    }
+                    ZknOpcodeR::Sha512sum1r => {
+                        (a << 23 ^ a >> 14 ^ a >> 18 ^ b >> 9 ^ b << 18 ^ b << 14) as u64
+                    }
+                    ZknOpcodeR::Sha512sig0l => {
+                        (a >> 1 ^ a >> 7 ^ a >> 8 ^ b << 31 ^ b << 25 ^ b << 24) as u64
+                    }
+                    ZknOpcodeR::Sha512sig0h => {
+                        (a >> 1 ^ a >> 7 ^ a >> 8 ^ b << 31 ^ b << 24) as u64
+                    }
+                    ZknOpcodeR::Sha512sig1l => {
+                        (a << 3 ^ a >> 6 ^ a >> 19 ^ b >> 29 ^ b << 26 ^ b << 13) as u64
+                    }
+                    ZknOpcodeR::Sha512sig1h => {
+                        (a << 3 ^ a >> 6 ^ a >> 19 ^ b >> 29 ^ b << 13) as u64
+                    }
+                };
+                x.write(rd, result);
+            }
+            Instruction::TypeI {
+                opcode,
+                rd,
+                funct3: _,
+                rs1,
+                imm,
+            } => {
+                let rv64_only = !matches!(
+                    opcode,
+                    ZknOpcodeI::Sha256sig0
+                        | ZknOpcodeI::Sha256sig1
+                        | ZknOpcodeI::Sha256sum0
+                        | ZknOpcodeI::Sha256sum1
+                );
+                if rv64_only && x.xlen() == Xlen::Rv32 {
+                    return Err(Cause::Exception(Exception::IllegalInstruction));
+                }
+                let rs1 = x.read(rs1);
+                let w = rs1 as u32;
+                let result = match opcode {
+                    ZknOpcodeI::Aes64ks1i => {
+                        let rnum = (imm & 0xf) as usize;
+                        let word = (rs1 >> 32) as u32;
+                        // the rotation is skipped for the AES-256 odd rounds (rnum = 0xa)
+                        let word = if rnum == 0xa {
+                            word
+                        } else {
+                            word.rotate_right(8)
+                        };
+                        let word = (substitute(word as u64, &AES_SBOX) & 0xffffffff)
+                            ^ AES_ROUND_CONSTANT[rnum];
+                        word << 32 | word
+                    }
+                    ZknOpcodeI::Aes64im => mix_columns(rs1, true),
+                    // the 32-bit SHA-256 results are sign-extended
+                    ZknOpcodeI::Sha256sig0 => {
+                        extend_sign((w.rotate_right(7) ^ w.rotate_right(18) ^ w >> 3) as u64, 32)
+                    }
+                    ZknOpcodeI::Sha256sig1 => extend_sign(
+                        (w.rotate_right(17) ^ w.rotate_right(19) ^ w >> 10) as u64,
+                        32,
+                    ),
+                    ZknOpcodeI::Sha256sum0 => extend_sign(
+                        (w.rotate_right(2) ^ w.rotate_right(13) ^ w.rotate_right(22)) as u64,
+                        32,
+                    ),
+                    ZknOpcodeI::Sha256sum1 => extend_sign(
+                        (w.rotate_right(6) ^ w.rotate_right(11) ^ w.rotate_right(25)) as u64,
+                        32,
+                    ),
+                    ZknOpcodeI::Sha512sig0 => rs1.rotate_right(1) ^ rs1.rotate_right(8) ^ rs1 >> 7,
+                    ZknOpcodeI::Sha512sig1 => {
+                        rs1.rotate_right(19) ^ rs1.rotate_right(61) ^ rs1 >> 6
+                    }
+                    ZknOpcodeI::Sha512sum0 => {
+                        rs1.rotate_right(28) ^ rs1.rotate_right(34) ^ rs1.rotate_right(39)
+                    }
+                    ZknOpcodeI::Sha512sum1 => {
+                        rs1.rotate_right(14) ^ rs1.rotate_right(18) ^ rs1.rotate_right(41)
+                    }
+                };
+                x.write(rd, result);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use crate::float::Format;
+
+#[derive(Default)]
+pub struct FloatingPointRegister {
+    f: [u64; 32],
+}
+
+impl FloatingPointRegister {
+    pub fn read(&self, register: usize) -> u64 {
+        self.f[register]
+    }
+
+    // Reads a value of the given format. Narrower values must be NaN-boxed,
+    // i.e. all the upper bits set, or they are read as the canonical NaN.
+    pub fn read_boxed(&self, register: usize, format: Format) -> u64 {
+        let value = self.f[register];
+        if value | format.mask() == u64::MAX {
+            value & format.mask()
+        } else {
+            format.canonical_nan()
+        }
+    }
+
+    pub fn write_boxed(&mut self, register: usize, format: Format, value: u64) {
+        self.f[register] = value | !format.mask();
+    }
+}
@@ -0,0 +1,372 @@
+use crate::{
+    aplic::{DEFAULT_APLIC_SOURCES, DOMAIN_SIZE, SUPERVISOR_DOMAIN},
+    cause::Interrupt,
+    cli::Options,
+    clint::{CLINT_SIZE, TIMEBASE_FREQUENCY},
+    imsic::{DEFAULT_IMSIC_IDS, FILE_SIZE, SUPERVISOR_FILES},
+    plic::PLIC_SIZE,
+    uart::{UART_IRQ, UART_SIZE},
+    virtio::{VIRTIO_MMIO_BASE, VIRTIO_MMIO_IRQ, VIRTIO_MMIO_STRIDE},
+    xlen::Xlen,
+};
+
+// The header of a flattened device tree, version 17, which is compatible with version 16.
+const FDT_MAGIC: u32 = 0xd00d_feed;
+const FDT_VERSION: u32 = 17;
+const FDT_LAST_COMPATIBLE_VERSION: u32 = 16;
+const HEADER_SIZE: usize = 40;
+// the memory reservation block, which holds only the entry that ends it
+const RESERVATION_SIZE: usize = 16;
+
+// The tokens of the structure block.
+const FDT_BEGIN_NODE: u32 = 1;
+const FDT_END_NODE: u32 = 2;
+const FDT_PROP: u32 = 3;
+const FDT_END: u32 = 9;
+
+// The frequency of the clock of the UART, as QEMU reports it for its NS16550A.
+const UART_CLOCK_FREQUENCY: u32 = 3_686_400;
+
+// The type of the interrupts of the devices for the APLIC, IRQ_TYPE_LEVEL_HIGH.
+const LEVEL_HIGH: u32 = 4;
+
+// Builds the structure and strings blocks of a device tree, node by node.
+#[derive(Default)]
+struct Writer {
+    structure: Vec<u8>,
+    strings: Vec<u8>,
+}
+
+impl Writer {
+    fn token(&mut self, token: u32) {
+        self.structure.extend_from_slice(&token.to_be_bytes());
+    }
+
+    // Pads the structure block to a multiple of 4 bytes, where every token starts.
+    fn align(&mut self) {
+        let padding = self.structure.len().next_multiple_of(4) - self.structure.len();
+        self.structure.extend(std::iter::repeat_n(0, padding));
+    }
+
+    fn begin_node(&mut self, name: &str) {
+        self.token(FDT_BEGIN_NODE);
+        self.structure.extend_from_slice(name.as_bytes());
+        self.structure.push(0);
+        self.align();
+    }
+
+    fn end_node(&mut self) {
+        self.token(FDT_END_NODE);
+    }
+
+    // The offset of a property name in the strings block, which holds each name once.
+    fn name_offset(&mut self, name: &str) -> u32 {
+        let mut offset = 0;
+        for string in self.strings.split(|&b| b == 0) {
+            if string == name.as_bytes() {
+                return offset as u32;
+            }
+            offset += string.len() + 1;
+        }
+        let offset = self.strings.len();
+        self.strings.extend_from_slice(name.as_bytes());
+        self.strings.push(0);
+        offset as u32
+    }
+
+    fn property(&mut self, name: &str, value: &[u8]) {
+        let offset = self.name_offset(name);
+        self.token(FDT_PROP);
+        self.token(value.len() as u32);
+        self.token(offset);
+        self.structure.extend_from_slice(value);
+        self.align();
+    }
+
+    fn empty(&mut self, name: &str) {
+        self.property(name, &[]);
+    }
+
+    fn cells(&mut self, name: &str, cells: &[u32]) {
+        let value: Vec<u8> = cells.iter().flat_map(|cell| cell.to_be_bytes()).collect();
+        self.property(name, &value);
+    }
+
+    fn string(&mut self, name: &str, string: &str) {
+        self.strings_list(name, &[string]);
+    }
+
+    fn strings_list(&mut self, name: &str, strings: &[&str]) {
+        let value: Vec<u8> = strings
+            .iter()
+            .flat_map(|string| string.bytes().chain([0]))
+            .collect();
+        self.property(name, &value);
+    }
+
+    // A reg property of address and size pairs, each of two cells.
+    fn reg(&mut self, ranges: &[(u64, u64)]) {
+        let cells: Vec<u32> = ranges
+            .iter()
+            .flat_map(|&(base, size)| [base >> 32, base, size >> 32, size])
+            .map(|cell| cell as u32)
+            .collect();
+        self.cells("reg", &cells);
+    }
+
+    // The device tree blob, with an empty memory reservation block.
+    fn finish(mut self) -> Vec<u8> {
+        self.token(FDT_END);
+        let structure_offset = HEADER_SIZE + RESERVATION_SIZE;
+        let strings_offset = structure_offset + self.structure.len();
+        let total_size = strings_offset + self.strings.len();
+        let header = [
+            FDT_MAGIC,
+            total_size as u32,
+            structure_offset as u32,
+            strings_offset as u32,
+            HEADER_SIZE as u32,
+            FDT_VERSION,
+            FDT_LAST_COMPATIBLE_VERSION,
+            // the boot hart
+            0,
+            self.strings.len() as u32,
+            self.structure.len() as u32,
+        ];
+        let mut blob: Vec<u8> = header.iter().flat_map(|word| word.to_be_bytes()).collect();
+        blob.extend_from_slice(&[0; RESERVATION_SIZE]);
+        blob.extend_from_slice(&self.structure);
+        blob.extend_from_slice(&self.strings);
+        blob
+    }
+}
+
+// The device tree of the platform that the options configure, laid out as QEMU's virt machine
+// lays out its own: the harts, the memory, and the devices under /soc, with the start and end of
+// the initrd, if any, in /chosen. The harts report the MMU of their XLEN, Sv32 or Sv39.
+pub fn device_tree(options: &Options, initrd: Option<(u64, u64)>) -> Vec<u8> {
+    let config = &options.config;
+    let harts = config.harts as u32;
+    // the interrupt controller of hart i is phandle i + 1, and the others follow
+    let intc = |hart: u32| hart + 1;
+    let mut next_phandle = harts + 1;
+    let mut phandle = || {
+        next_phandle += 1;
+        next_phandle - 1
+    };
+    // each hart's interrupt controller with a local interrupt, as interrupts-extended lists them
+    let local = |interrupt: Interrupt| -> Vec<u32> {
+        (0..harts)
+            .flat_map(|hart| [intc(hart), interrupt.to_primitive() as u32])
+            .collect()
+    };
+
+    let mut fdt = Writer::default();
+    fdt.begin_node("");
+    fdt.cells("#address-cells", &[2]);
+    fdt.cells("#size-cells", &[2]);
+    fdt.string("compatible", "minimum-five");
+    fdt.string("model", "minimum-five");
+
+    fdt.begin_node("chosen");
+    if let Some(bootargs) = &options.bootargs {
+        fdt.string("bootargs", bootargs);
+    }
+    if let Some((start, end)) = initrd {
+        let cells = |address: u64| [(address >> 32) as u32, address as u32];
+        fdt.cells("linux,initrd-start", &cells(start));
+        fdt.cells("linux,initrd-end", &cells(end));
+    }
+    if let Some(base) = options.uart {
+        fdt.string("stdout-path", &format!("/soc/serial@{:x}", base));
+    }
+    fdt.end_node();
+
+    fdt.begin_node("cpus");
+    fdt.cells("#address-cells", &[1]);
+    fdt.cells("#size-cells", &[0]);
+    fdt.cells("timebase-frequency", &[TIMEBASE_FREQUENCY as u32]);
+    let isa = config.isa.to_string();
+    let base = format!("rv{}i", config.isa.xlen.bits());
+    for hart in 0..harts {
+        fdt.begin_node(&format!("cpu@{:x}", hart));
+        fdt.string("device_type", "cpu");
+        fdt.cells("reg", &[hart]);
+        fdt.string("status", "okay");
+        fdt.string("compatible", "riscv");
+        fdt.string("riscv,isa", &isa);
+        fdt.string("riscv,isa-base", &base);
+        fdt.strings_list("riscv,isa-extensions", &config.isa.extensions());
+        let mmu = match config.isa.xlen {
+            Xlen::Rv32 => "riscv,sv32",
+            Xlen::Rv64 => "riscv,sv39",
+        };
+        fdt.string("mmu-type", mmu);
+        if config.isa.zicbom {
+            fdt.cells("riscv,cbom-block-size", &[config.cache_block_size as u32]);
+        }
+        if config.isa.zicboz {
+            fdt.cells("riscv,cboz-block-size", &[config.cache_block_size as u32]);
+        }
+        fdt.begin_node("interrupt-controller");
+        fdt.cells("#interrupt-cells", &[1]);
+        fdt.empty("interrupt-controller");
+        fdt.string("compatible", "riscv,cpu-intc");
+        fdt.cells("phandle", &[intc(hart)]);
+        fdt.end_node();
+        fdt.end_node();
+    }
+    fdt.end_node();
+
+    let memory = [(config.memory_base, config.memory_size)]
+        .into_iter()
+        .chain(
+            config
+                .regions
+                .iter()
+                .filter(|region| region.writable)
+                .map(|region| (region.base, region.data.len() as u64)),
+        );
+    for (base, size) in memory {
+        fdt.begin_node(&format!("memory@{:x}", base));
+        fdt.string("device_type", "memory");
+        fdt.reg(&[(base, size)]);
+        fdt.end_node();
+    }
+
+    fdt.begin_node("soc");
+    fdt.cells("#address-cells", &[2]);
+    fdt.cells("#size-cells", &[2]);
+    fdt.string("compatible", "simple-bus");
+    fdt.empty("ranges");
+
+    if let Some(base) = options.clint {
+        fdt.begin_node(&format!("clint@{:x}", base));
+        fdt.strings_list("compatible", &["sifive,clint0", "riscv,clint0"]);
+        fdt.reg(&[(base, CLINT_SIZE)]);
+        let interrupts: Vec<u32> = local(Interrupt::MachineSoftware)
+            .chunks(2)
+            .zip(local(Interrupt::MachineTimer).chunks(2))
+            .flat_map(|(software, timer)| [software, timer].concat())
+            .collect();
+        fdt.cells("interrupts-extended", &interrupts);
+        fdt.end_node();
+    }
+
+    // the interrupt controller that the lines of the devices go to, and the cells of a line
+    let mut parent: Option<(u32, bool)> = None;
+    if let Some(base) = options.plic {
+        let plic = phandle();
+        fdt.begin_node(&format!("plic@{:x}", base));
+        fdt.strings_list("compatible", &["sifive,plic-1.0.0", "riscv,plic0"]);
+        fdt.reg(&[(base, PLIC_SIZE)]);
+        fdt.cells("#address-cells", &[0]);
+        fdt.cells("#interrupt-cells", &[1]);
+        fdt.empty("interrupt-controller");
+        fdt.cells("riscv,ndev", &[options.plic_sources]);
+        // the M-mode and S-mode contexts of each hart, in turn
+        let interrupts: Vec<u32> = local(Interrupt::MachineExternal)
+            .chunks(2)
+            .zip(local(Interrupt::SupervisorExternal).chunks(2))
+            .flat_map(|(machine, supervisor)| [machine, supervisor].concat())
+            .collect();
+        fdt.cells("interrupts-extended", &interrupts);
+        fdt.cells("phandle", &[plic]);
+        fdt.end_node();
+        parent = Some((plic, false));
+    }
+
+    // the M-level interrupt files, then the S-level ones
+    let mut imsics = None;
+    if let Some(base) = options.imsic {
+        let files = [
+            (base, Interrupt::MachineExternal, phandle()),
+            (
+                base + SUPERVISOR_FILES,
+                Interrupt::SupervisorExternal,
+                phandle(),
+            ),
+        ];
+        for (base, interrupt, imsic) in files {
+            fdt.begin_node(&format!("imsics@{:x}", base));
+            fdt.string("compatible", "riscv,imsics");
+            fdt.reg(&[(base, FILE_SIZE * harts as u64)]);
+            fdt.cells("#interrupt-cells", &[0]);
+            fdt.empty("interrupt-controller");
+            fdt.empty("msi-controller");
+            fdt.cells("riscv,num-ids", &[DEFAULT_IMSIC_IDS]);
+            fdt.cells("interrupts-extended", &local(interrupt));
+            fdt.cells("phandle", &[imsic]);
+            fdt.end_node();
+        }
+        imsics = Some((files[0].2, files[1].2));
+    }
+
+    // the S-level domain, which the M-level one delegates every source to
+    if let Some(base) = options.aplic {
+        let (machine, supervisor) = (phandle(), phandle());
+        let domains = [
+            (
+                base + SUPERVISOR_DOMAIN,
+                Interrupt::SupervisorExternal,
+                supervisor,
+                imsics.map(|(_, imsic)| imsic),
+            ),
+            (
+                base,
+                Interrupt::MachineExternal,
+                machine,
+                imsics.map(|(imsic, _)| imsic),
+            ),
+        ];
+        for (base, interrupt, aplic, imsic) in domains {
+            fdt.begin_node(&format!("aplic@{:x}", base));
+            fdt.string("compatible", "riscv,aplic");
+            fdt.reg(&[(base, DOMAIN_SIZE)]);
+            fdt.cells("#interrupt-cells", &[2]);
+            fdt.empty("interrupt-controller");
+            fdt.cells("riscv,num-sources", &[DEFAULT_APLIC_SOURCES]);
+            match imsic {
+                Some(imsic) => fdt.cells("msi-parent", &[imsic]),
+                None => fdt.cells("interrupts-extended", &local(interrupt)),
+            }
+            if aplic == machine {
+                fdt.cells("riscv,children", &[supervisor]);
+                fdt.cells("riscv,delegation", &[supervisor, 1, DEFAULT_APLIC_SOURCES]);
+            }
+            fdt.cells("phandle", &[aplic]);
+            fdt.end_node();
+        }
+        parent = parent.or(Some((supervisor, true)));
+    }
+
+    // a device with its interrupt line wired to the parent, if there is one
+    let device = |fdt: &mut Writer, name: &str, base: u64, size: u64, irq: u32| {
+        fdt.begin_node(&format!("{}@{:x}", name, base));
+        fdt.reg(&[(base, size)]);
+        if let Some((parent, typed)) = parent {
+            fdt.cells("interrupt-parent", &[parent]);
+            match typed {
+                true => fdt.cells("interrupts", &[irq, LEVEL_HIGH]),
+                false => fdt.cells("interrupts", &[irq]),
+            }
+        }
+    };
+    if let Some(base) = options.uart {
+        device(&mut fdt, "serial", base, UART_SIZE, UART_IRQ);
+        fdt.string("compatible", "ns16550a");
+        fdt.cells("clock-frequency", &[UART_CLOCK_FREQUENCY]);
+        fdt.end_node();
+    }
+    for slot in 0..options.virtio.len() {
+        let base = VIRTIO_MMIO_BASE + VIRTIO_MMIO_STRIDE * slot as u64;
+        let irq = VIRTIO_MMIO_IRQ + slot as u32;
+        device(&mut fdt, "virtio_mmio", base, VIRTIO_MMIO_STRIDE, irq);
+        fdt.string("compatible", "virtio,mmio");
+        fdt.end_node();
+    }
+    fdt.end_node();
+
+    fdt.end_node();
+    fdt.finish()
+}
@@ -0,0 +1,838 @@
+use std::cmp::Ordering;
+
+// Accrued exception flags, laid out as in fflags.
+pub const FLAG_NX: u64 = 0b00001; // Inexact.
+pub const FLAG_UF: u64 = 0b00010; // Underflow.
+pub const FLAG_OF: u64 = 0b00100; // Overflow.
+pub const FLAG_DZ: u64 = 0b01000; // Divide by zero.
+pub const FLAG_NV: u64 = 0b10000; // Invalid operation.
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RoundingMode {
+    NearestEven = 0b000,
+    TowardZero = 0b001,
+    Down = 0b010,
+    Up = 0b011,
+    NearestMaxMagnitude = 0b100,
+}
+
+impl RoundingMode {
+    pub fn from_primitive(rm: u64) -> Option<Self> {
+        match rm {
+            0b000 => Some(Self::NearestEven),
+            0b001 => Some(Self::TowardZero),
+            0b010 => Some(Self::Down),
+            0b011 => Some(Self::Up),
+            0b100 => Some(Self::NearestMaxMagnitude),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Format {
+    exponent: u32,
+    mantissa: u32,
+}
+
+pub const HALF: Format = Format {
+    exponent: 5,
+    mantissa: 10,
+};
+pub const SINGLE: Format = Format {
+    exponent: 8,
+    mantissa: 23,
+};
+pub const DOUBLE: Format = Format {
+    exponent: 11,
+    mantissa: 52,
+};
+
+impl Format {
+    // Maps the fmt field of the floating-point instructions; quad precision is not supported.
+    pub fn from_fmt(fmt: usize) -> Option<Self> {
+        match fmt {
+            0b00 => Some(SINGLE),
+            0b01 => Some(DOUBLE),
+            0b10 => Some(HALF),
+            _ => None,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        1 + self.exponent + self.mantissa
+    }
+
+    fn bias(&self) -> i32 {
+        (1 << (self.exponent - 1)) - 1
+    }
+
+    fn max_exponent_field(&self) -> u64 {
+        (1 << self.exponent) - 1
+    }
+
+    fn mantissa_mask(&self) -> u64 {
+        (1 << self.mantissa) - 1
+    }
+
+    pub fn sign_mask(&self) -> u64 {
+        1 << (self.width() - 1)
+    }
+
+    pub fn mask(&self) -> u64 {
+        if self.width() == 64 {
+            u64::MAX
+        } else {
+            (1 << self.width()) - 1
+        }
+    }
+
+    fn exponent_field(&self, bits: u64) -> u64 {
+        (bits >> self.mantissa) & self.max_exponent_field()
+    }
+
+    fn pack(&self, sign: bool, exponent: u64, mantissa: u64) -> u64 {
+        (sign as u64) << (self.width() - 1) | exponent << self.mantissa | mantissa
+    }
+
+    pub fn canonical_nan(&self) -> u64 {
+        self.pack(false, self.max_exponent_field(), 1 << (self.mantissa - 1))
+    }
+
+    pub fn infinity(&self, sign: bool) -> u64 {
+        self.pack(sign, self.max_exponent_field(), 0)
+    }
+
+    pub fn zero(&self, sign: bool) -> u64 {
+        self.pack(sign, 0, 0)
+    }
+
+    pub fn min_normal(&self) -> u64 {
+        self.pack(false, 1, 0)
+    }
+
+    fn max_finite(&self, sign: bool) -> u64 {
+        self.pack(sign, self.max_exponent_field() - 1, self.mantissa_mask())
+    }
+
+    pub fn is_negative(&self, bits: u64) -> bool {
+        bits & self.sign_mask() != 0
+    }
+
+    pub fn is_nan(&self, bits: u64) -> bool {
+        self.exponent_field(bits) == self.max_exponent_field() && bits & self.mantissa_mask() != 0
+    }
+
+    pub fn is_signaling_nan(&self, bits: u64) -> bool {
+        self.is_nan(bits) && bits & (1 << (self.mantissa - 1)) == 0
+    }
+
+    pub fn is_infinite(&self, bits: u64) -> bool {
+        self.exponent_field(bits) == self.max_exponent_field() && bits & self.mantissa_mask() == 0
+    }
+
+    pub fn is_zero(&self, bits: u64) -> bool {
+        bits & self.mask() & !self.sign_mask() == 0
+    }
+
+    pub fn is_subnormal(&self, bits: u64) -> bool {
+        self.exponent_field(bits) == 0 && bits & self.mantissa_mask() != 0
+    }
+
+    // 10-bit mask as produced by fclass.
+    pub fn classify(&self, bits: u64) -> u64 {
+        let negative = self.is_negative(bits);
+        let class = if self.is_infinite(bits) {
+            if negative {
+                0
+            } else {
+                7
+            }
+        } else if self.is_nan(bits) {
+            if self.is_signaling_nan(bits) {
+                8
+            } else {
+                9
+            }
+        } else if self.is_zero(bits) {
+            if negative {
+                3
+            } else {
+                4
+            }
+        } else if self.is_subnormal(bits) {
+            if negative {
+                2
+            } else {
+                5
+            }
+        } else if negative {
+            1
+        } else {
+            6
+        };
+        1 << class
+    }
+
+    fn unpack(&self, bits: u64) -> Unpacked {
+        let sign = self.is_negative(bits);
+        let exponent = self.exponent_field(bits);
+        let mantissa = bits & self.mantissa_mask();
+        let value = if exponent == self.max_exponent_field() {
+            if mantissa == 0 {
+                Value::Infinity
+            } else {
+                Value::Nan
+            }
+        } else if exponent == 0 {
+            if mantissa == 0 {
+                Value::Zero
+            } else {
+                Value::Finite {
+                    exponent: 1 - self.bias() - self.mantissa as i32,
+                    significand: mantissa as u128,
+                }
+            }
+        } else {
+            Value::Finite {
+                exponent: exponent as i32 - self.bias() - self.mantissa as i32,
+                significand: (mantissa | 1 << self.mantissa) as u128,
+            }
+        };
+        Unpacked { sign, value }
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Value {
+    Zero,
+    // significand * 2^exponent
+    Finite { exponent: i32, significand: u128 },
+    Infinity,
+    Nan,
+}
+
+#[derive(Copy, Clone)]
+struct Unpacked {
+    sign: bool,
+    value: Value,
+}
+
+fn bit_length(value: u128) -> i32 {
+    128 - value.leading_zeros() as i32
+}
+
+// Left-justifies a significand so that its most significant bit is bit 125.
+fn justify(exponent: i32, significand: u128) -> (i32, u128) {
+    let shift = 126 - bit_length(significand);
+    (exponent - shift, significand << shift)
+}
+
+fn isqrt(value: u128) -> u128 {
+    let mut remainder = value;
+    let mut root = 0u128;
+    let mut bit = 1u128 << 126;
+    while bit > value {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if remainder >= root + bit {
+            remainder -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root
+}
+
+// Software IEEE 754 arithmetic with RISC-V NaN and flag semantics.
+// Flags raised by every operation accumulate in `flags`.
+pub struct SoftFloat {
+    pub rounding_mode: RoundingMode,
+    pub flags: u64,
+}
+
+impl SoftFloat {
+    pub fn new(rounding_mode: RoundingMode) -> Self {
+        Self {
+            rounding_mode,
+            flags: 0,
+        }
+    }
+
+    // Shifts `significand` right by `shift` bits and rounds it to an integer.
+    // Returns the rounded value and whether any nonzero bits were discarded.
+    fn shift_round(&self, sign: bool, significand: u128, sticky: bool, shift: i32) -> (u128, bool) {
+        if shift <= 0 {
+            return (significand << -shift, sticky);
+        }
+        let (quotient, remainder, half) = if shift > 128 {
+            (0, significand, None)
+        } else if shift == 128 {
+            (0, significand, Some(1u128 << 127))
+        } else {
+            let quotient = significand >> shift;
+            (
+                quotient,
+                significand - (quotient << shift),
+                Some(1u128 << (shift - 1)),
+            )
+        };
+        let inexact = remainder != 0 || sticky;
+        let round_up = match self.rounding_mode {
+            RoundingMode::NearestEven => match half.map(|h| remainder.cmp(&h)) {
+                Some(Ordering::Greater) => true,
+                Some(Ordering::Equal) => sticky || quotient & 1 == 1,
+                _ => false,
+            },
+            RoundingMode::NearestMaxMagnitude => half.is_some_and(|h| remainder >= h),
+            RoundingMode::TowardZero => false,
+            RoundingMode::Down => sign && inexact,
+            RoundingMode::Up => !sign && inexact,
+        };
+        (quotient + round_up as u128, inexact)
+    }
+
+    // Rounds significand * 2^exponent (plus a sticky fraction below the
+    // least significant bit) to the given format.
+    fn round_pack(
+        &mut self,
+        format: Format,
+        sign: bool,
+        exponent: i32,
+        significand: u128,
+        sticky: bool,
+    ) -> u64 {
+        if significand == 0 && !sticky {
+            return format.zero(sign);
+        }
+        let mantissa = format.mantissa as i32;
+        let emin = 1 - format.bias();
+        let top = exponent + bit_length(significand) - 1;
+        let unbounded_quantum = top - mantissa;
+        let quantum = unbounded_quantum.max(emin - mantissa);
+        let (mut rounded, inexact) =
+            self.shift_round(sign, significand, sticky, quantum - exponent);
+        let mut quantum = quantum;
+        if rounded == 1 << (mantissa + 1) {
+            rounded >>= 1;
+            quantum += 1;
+        }
+        if inexact {
+            self.flags |= FLAG_NX;
+            // tininess is detected after rounding
+            let tiny = top < emin && {
+                let (unbounded, _) =
+                    self.shift_round(sign, significand, sticky, unbounded_quantum - exponent);
+                !(top == emin - 1 && unbounded == 1 << (mantissa + 1))
+            };
+            if tiny {
+                self.flags |= FLAG_UF;
+            }
+        }
+        if rounded >> mantissa == 0 {
+            return format.pack(sign, 0, rounded as u64);
+        }
+        let biased = quantum + mantissa + format.bias();
+        if biased as u64 >= format.max_exponent_field() {
+            self.flags |= FLAG_OF | FLAG_NX;
+            let to_infinity = match self.rounding_mode {
+                RoundingMode::NearestEven | RoundingMode::NearestMaxMagnitude => true,
+                RoundingMode::TowardZero => false,
+                RoundingMode::Down => sign,
+                RoundingMode::Up => !sign,
+            };
+            return if to_infinity {
+                format.infinity(sign)
+            } else {
+                format.max_finite(sign)
+            };
+        }
+        format.pack(sign, biased as u64, rounded as u64 & format.mantissa_mask())
+    }
+
+    fn propagate_nan(&mut self, format: Format, operands: &[u64]) -> Option<u64> {
+        if operands.iter().any(|&o| format.is_signaling_nan(o)) {
+            self.flags |= FLAG_NV;
+        }
+        if operands.iter().any(|&o| format.is_nan(o)) {
+            Some(format.canonical_nan())
+        } else {
+            None
+        }
+    }
+
+    fn add_unpacked(&mut self, format: Format, a: Unpacked, b: Unpacked) -> u64 {
+        match (a.value, b.value) {
+            (Value::Infinity, Value::Infinity) if a.sign != b.sign => {
+                self.flags |= FLAG_NV;
+                format.canonical_nan()
+            }
+            (Value::Infinity, _) => format.infinity(a.sign),
+            (_, Value::Infinity) => format.infinity(b.sign),
+            (Value::Zero, Value::Zero) => format.zero(if a.sign == b.sign {
+                a.sign
+            } else {
+                self.rounding_mode == RoundingMode::Down
+            }),
+            (
+                Value::Zero,
+                Value::Finite {
+                    exponent,
+                    significand,
+                },
+            )
+            | (
+                Value::Finite {
+                    exponent,
+                    significand,
+                },
+                Value::Zero,
+            ) => {
+                let sign = if let Value::Zero = a.value {
+                    b.sign
+                } else {
+                    a.sign
+                };
+                self.round_pack(format, sign, exponent, significand, false)
+            }
+            (
+                Value::Finite {
+                    exponent: ea,
+                    significand: sa,
+                },
+                Value::Finite {
+                    exponent: eb,
+                    significand: sb,
+                },
+            ) => {
+                let (ea, sa) = justify(ea, sa);
+                let (eb, sb) = justify(eb, sb);
+                let ((ea, sa, sign_a), (eb, sb, sign_b)) = if (ea, sa) >= (eb, sb) {
+                    ((ea, sa, a.sign), (eb, sb, b.sign))
+                } else {
+                    ((eb, sb, b.sign), (ea, sa, a.sign))
+                };
+                let distance = (ea - eb) as u32;
+                let (sb, sticky) = if distance >= 128 {
+                    (0, sb != 0)
+                } else {
+                    (sb >> distance, sb & ((1 << distance) - 1) != 0)
+                };
+                if sign_a == sign_b {
+                    self.round_pack(format, sign_a, ea, sa + sb, sticky)
+                } else {
+                    let difference = sa - sb - sticky as u128;
+                    if difference == 0 && !sticky {
+                        format.zero(self.rounding_mode == RoundingMode::Down)
+                    } else {
+                        self.round_pack(format, sign_a, ea, difference, sticky)
+                    }
+                }
+            }
+            _ => format.canonical_nan(),
+        }
+    }
+
+    pub fn add(&mut self, format: Format, a: u64, b: u64) -> u64 {
+        if let Some(nan) = self.propagate_nan(format, &[a, b]) {
+            return nan;
+        }
+        self.add_unpacked(format, format.unpack(a), format.unpack(b))
+    }
+
+    pub fn sub(&mut self, format: Format, a: u64, b: u64) -> u64 {
+        self.add(format, a, b ^ format.sign_mask())
+    }
+
+    fn multiply_unpacked(&mut self, a: Unpacked, b: Unpacked) -> Option<Unpacked> {
+        let sign = a.sign != b.sign;
+        let value = match (a.value, b.value) {
+            (Value::Infinity, Value::Zero) | (Value::Zero, Value::Infinity) => {
+                self.flags |= FLAG_NV;
+                return None;
+            }
+            (Value::Infinity, _) | (_, Value::Infinity) => Value::Infinity,
+            (Value::Zero, _) | (_, Value::Zero) => Value::Zero,
+            (
+                Value::Finite {
+                    exponent: ea,
+                    significand: sa,
+                },
+                Value::Finite {
+                    exponent: eb,
+                    significand: sb,
+                },
+            ) => Value::Finite {
+                exponent: ea + eb,
+                significand: sa * sb,
+            },
+            _ => Value::Nan,
+        };
+        Some(Unpacked { sign, value })
+    }
+
+    pub fn mul(&mut self, format: Format, a: u64, b: u64) -> u64 {
+        if let Some(nan) = self.propagate_nan(format, &[a, b]) {
+            return nan;
+        }
+        match self.multiply_unpacked(format.unpack(a), format.unpack(b)) {
+            Some(Unpacked {
+                sign,
+                value:
+                    Value::Finite {
+                        exponent,
+                        significand,
+                    },
+            }) => self.round_pack(format, sign, exponent, significand, false),
+            Some(Unpacked {
+                sign,
+                value: Value::Infinity,
+            }) => format.infinity(sign),
+            Some(Unpacked { sign, .. }) => format.zero(sign),
+            None => format.canonical_nan(),
+        }
+    }
+
+    // Computes a * b + c with a single rounding.
+    pub fn fma(&mut self, format: Format, a: u64, b: u64, c: u64) -> u64 {
+        let product = if format.is_nan(a) || format.is_nan(b) {
+            None
+        } else {
+            self.multiply_unpacked(format.unpack(a), format.unpack(b))
+        };
+        if let Some(nan) = self.propagate_nan(format, &[a, b, c]) {
+            return nan;
+        }
+        match product {
+            Some(product) => self.add_unpacked(format, product, format.unpack(c)),
+            None => format.canonical_nan(),
+        }
+    }
+
+    pub fn div(&mut self, format: Format, a: u64, b: u64) -> u64 {
+        if let Some(nan) = self.propagate_nan(format, &[a, b]) {
+            return nan;
+        }
+        let (a, b) = (format.unpack(a), format.unpack(b));
+        let sign = a.sign != b.sign;
+        match (a.value, b.value) {
+            (Value::Infinity, Value::Infinity) | (Value::Zero, Value::Zero) => {
+                self.flags |= FLAG_NV;
+                format.canonical_nan()
+            }
+            (Value::Infinity, _) => format.infinity(sign),
+            (_, Value::Zero) => {
+                self.flags |= FLAG_DZ;
+                format.infinity(sign)
+            }
+            (Value::Zero, _) | (_, Value::Infinity) => format.zero(sign),
+            (
+                Value::Finite {
+                    exponent: ea,
+                    significand: sa,
+                },
+                Value::Finite {
+                    exponent: eb,
+                    significand: sb,
+                },
+            ) => {
+                let shift = 127 - bit_length(sa);
+                let dividend = sa << shift;
+                let quotient = dividend / sb;
+                let sticky = !dividend.is_multiple_of(sb);
+                self.round_pack(format, sign, ea - shift - eb, quotient, sticky)
+            }
+            _ => format.canonical_nan(),
+        }
+    }
+
+    pub fn sqrt(&mut self, format: Format, a: u64) -> u64 {
+        if let Some(nan) = self.propagate_nan(format, &[a]) {
+            return nan;
+        }
+        let a = format.unpack(a);
+        match a.value {
+            Value::Zero => format.zero(a.sign),
+            _ if a.sign => {
+                self.flags |= FLAG_NV;
+                format.canonical_nan()
+            }
+            Value::Infinity => format.infinity(false),
+            Value::Finite {
+                exponent,
+                significand,
+            } => {
+                let mut shift = 126 - bit_length(significand);
+                if (exponent - shift) % 2 != 0 {
+                    shift -= 1;
+                }
+                let radicand = significand << shift;
+                let root = isqrt(radicand);
+                let sticky = root * root != radicand;
+                self.round_pack(format, false, (exponent - shift) / 2, root, sticky)
+            }
+            Value::Nan => format.canonical_nan(),
+        }
+    }
+
+    // Orders two non-NaN values, treating both zeros as equal.
+    fn order(format: Format, a: u64, b: u64) -> Ordering {
+        let key = |bits: u64| -> i128 {
+            let magnitude = (bits & format.mask() & !format.sign_mask()) as i128;
+            if format.is_negative(bits) {
+                -magnitude
+            } else {
+                magnitude
+            }
+        };
+        key(a).cmp(&key(b))
+    }
+
+    pub fn eq(&mut self, format: Format, a: u64, b: u64) -> bool {
+        if format.is_nan(a) || format.is_nan(b) {
+            self.propagate_nan(format, &[a, b]);
+            return false;
+        }
+        Self::order(format, a, b) == Ordering::Equal
+    }
+
+    pub fn lt(&mut self, format: Format, a: u64, b: u64) -> bool {
+        if format.is_nan(a) || format.is_nan(b) {
+            self.flags |= FLAG_NV;
+            return false;
+        }
+        Self::order(format, a, b) == Ordering::Less
+    }
+
+    pub fn le(&mut self, format: Format, a: u64, b: u64) -> bool {
+        if format.is_nan(a) || format.is_nan(b) {
+            self.flags |= FLAG_NV;
+            return false;
+        }
+        Self::order(format, a, b) != Ordering::Greater
+    }
+
+    pub fn lt_quiet(&mut self, format: Format, a: u64, b: u64) -> bool {
+        if format.is_nan(a) || format.is_nan(b) {
+            self.propagate_nan(format, &[a, b]);
+            return false;
+        }
+        Self::order(format, a, b) == Ordering::Less
+    }
+
+    pub fn le_quiet(&mut self, format: Format, a: u64, b: u64) -> bool {
+        if format.is_nan(a) || format.is_nan(b) {
+            self.propagate_nan(format, &[a, b]);
+            return false;
+        }
+        Self::order(format, a, b) != Ordering::Greater
+    }
+
+    fn min_max(&mut self, format: Format, a: u64, b: u64, max: bool) -> u64 {
+        self.propagate_nan(format, &[a, b]);
+        match (format.is_nan(a), format.is_nan(b)) {
+            (true, true) => format.canonical_nan(),
+            (true, false) => b,
+            (false, true) => a,
+            (false, false) => {
+                // -0.0 is considered to be less than +0.0
+                let a_less = match Self::order(format, a, b) {
+                    Ordering::Equal => format.is_negative(a),
+                    ordering => ordering == Ordering::Less,
+                };
+                if a_less != max {
+                    a
+                } else {
+                    b
+                }
+            }
+        }
+    }
+
+    pub fn min(&mut self, format: Format, a: u64, b: u64) -> u64 {
+        self.min_max(format, a, b, false)
+    }
+
+    pub fn max(&mut self, format: Format, a: u64, b: u64) -> u64 {
+        self.min_max(format, a, b, true)
+    }
+
+    // IEEE 754-2019 minimum and maximum, which return NaN if either operand is NaN.
+    pub fn minimum(&mut self, format: Format, a: u64, b: u64) -> u64 {
+        match self.propagate_nan(format, &[a, b]) {
+            Some(nan) => nan,
+            None => self.min_max(format, a, b, false),
+        }
+    }
+
+    pub fn maximum(&mut self, format: Format, a: u64, b: u64) -> u64 {
+        match self.propagate_nan(format, &[a, b]) {
+            Some(nan) => nan,
+            None => self.min_max(format, a, b, true),
+        }
+    }
+
+    // Builds (-1)^sign * significand * 2^exponent rounded to the given format.
+    pub fn compose(&mut self, format: Format, sign: bool, exponent: i32, significand: u64) -> u64 {
+        self.round_pack(format, sign, exponent, significand as u128, false)
+    }
+
+    // Rounds to an integral value in the same format. Inexact is only raised when `exact` is set.
+    pub fn round_to_integral(&mut self, format: Format, a: u64, exact: bool) -> u64 {
+        if let Some(nan) = self.propagate_nan(format, &[a]) {
+            return nan;
+        }
+        let unpacked = format.unpack(a);
+        match unpacked.value {
+            Value::Finite {
+                exponent,
+                significand,
+            } if exponent < 0 => {
+                let (rounded, inexact) =
+                    self.shift_round(unpacked.sign, significand, false, -exponent);
+                if inexact && exact {
+                    self.flags |= FLAG_NX;
+                }
+                self.round_pack(format, unpacked.sign, 0, rounded, false)
+            }
+            _ => a,
+        }
+    }
+
+    pub fn convert(&mut self, from: Format, to: Format, a: u64) -> u64 {
+        if self.propagate_nan(from, &[a]).is_some() {
+            return to.canonical_nan();
+        }
+        let a = from.unpack(a);
+        match a.value {
+            Value::Zero => to.zero(a.sign),
+            Value::Infinity => to.infinity(a.sign),
+            Value::Finite {
+                exponent,
+                significand,
+            } => self.round_pack(to, a.sign, exponent, significand, false),
+            Value::Nan => to.canonical_nan(),
+        }
+    }
+
+    // Converts an integer of the given width to floating point.
+    pub fn integer_to_float(&mut self, to: Format, value: u64, width: u32, signed: bool) -> u64 {
+        let value = if width == 64 {
+            value
+        } else {
+            value & ((1 << width) - 1)
+        };
+        let negative = signed && (value >> (width - 1)) & 1 == 1;
+        let magnitude = if negative {
+            (value as i64)
+                .wrapping_shl(64 - width)
+                .wrapping_shr(64 - width)
+                .unsigned_abs()
+        } else {
+            value
+        };
+        self.round_pack(to, negative, 0, magnitude as u128, false)
+    }
+
+    // Converts to an integer of the given width, saturating on overflow.
+    // The result is sign-extended to 64 bits.
+    pub fn float_to_integer(&mut self, from: Format, a: u64, width: u32, signed: bool) -> u64 {
+        let (min, max): (i128, i128) = if signed {
+            (-(1 << (width - 1)), (1 << (width - 1)) - 1)
+        } else {
+            (0, (1 << width) - 1)
+        };
+        let saturate = |value: i128| -> u64 {
+            if width == 64 {
+                value as u64
+            } else {
+                ((value as i64) << (64 - width) >> (64 - width)) as u64
+            }
+        };
+        let unpacked = from.unpack(a);
+        let (magnitude, inexact) = match unpacked.value {
+            Value::Nan => {
+                self.flags |= FLAG_NV;
+                return saturate(max);
+            }
+            Value::Infinity => {
+                self.flags |= FLAG_NV;
+                return saturate(if unpacked.sign { min } else { max });
+            }
+            Value::Zero => (0, false),
+            Value::Finite {
+                exponent,
+                significand,
+            } => {
+                if exponent + bit_length(significand) > 65 {
+                    (u128::MAX, false)
+                } else {
+                    self.shift_round(unpacked.sign, significand, false, -exponent)
+                }
+            }
+        };
+        let value = if unpacked.sign {
+            -(magnitude.min(1 << 65) as i128)
+        } else {
+            magnitude.min(1 << 65) as i128
+        };
+        if value < min || value > max {
+            self.flags |= FLAG_NV;
+            return saturate(if unpacked.sign { min } else { max });
+        }
+        if inexact {
+            self.flags |= FLAG_NX;
+        }
+        saturate(value)
+    }
+
+    // Converts towards zero to a 32-bit integer, wrapping modulo 2^32 instead of saturating.
+    // The result is sign-extended to 64 bits.
+    pub fn float_to_int32_modular(&mut self, from: Format, a: u64) -> u64 {
+        let unpacked = from.unpack(a);
+        let (exponent, significand) = match unpacked.value {
+            Value::Nan | Value::Infinity => {
+                self.flags |= FLAG_NV;
+                return 0;
+            }
+            Value::Zero => return 0,
+            Value::Finite {
+                exponent,
+                significand,
+            } => (exponent, significand),
+        };
+        let (magnitude, inexact) = if exponent >= 0 {
+            // the bits above 2^64 do not affect the result, only whether it overflows
+            let magnitude = if exponent + bit_length(significand) > 127 {
+                None
+            } else {
+                Some(significand << exponent)
+            };
+            (magnitude, false)
+        } else if -exponent >= 128 {
+            (Some(0), true)
+        } else {
+            let truncated = significand >> -exponent;
+            (Some(truncated), truncated << -exponent != significand)
+        };
+        let value = match magnitude {
+            Some(m) if unpacked.sign => (m as u32).wrapping_neg(),
+            Some(m) => m as u32,
+            None => 0,
+        };
+        let in_range = match magnitude {
+            Some(m) if unpacked.sign => m <= 1 << 31,
+            Some(m) => m < 1 << 31,
+            None => false,
+        };
+        if !in_range {
+            self.flags |= FLAG_NV;
+        } else if inexact {
+            self.flags |= FLAG_NX;
+        }
+        value as i32 as i64 as u64
+    }
+}
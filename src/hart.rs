@@ -0,0 +1,389 @@
+use std::mem;
+
+use crate::{
+    boot_rom::BOOT_ROM_BASE,
+    bus::Bus,
+    cause::{Cause, Exception},
+    compressed,
+    config::Config,
+    csr::{vtype_vill, ControlAndStatusRegister, MHARTID, VLENB, VTYPE},
+    decoder::{
+        privileged::PrivilegedDecoder, rv32i::Rv32iDecoder, rv64i::Rv64iDecoder, rva::RvaDecoder,
+        rvf::RvfDecoder, rvm::RvmDecoder, rvv::RvvDecoder, zfa::ZfaDecoder, zicbo::ZicboDecoder,
+        zicond::ZicondDecoder, zicsr::ZicsrDecoder, zifencei::ZifenceiDecoder,
+        zihintpause::ZihintpauseDecoder, zkn::ZknDecoder, zks::ZksDecoder, Decoder,
+    },
+    executor::{
+        privileged::PrivilegedExecutor, rv32i::Rv32iExecutor, rv64i::Rv64iExecutor,
+        rva::RvaExecutor, rvf::RvfExecutor, rvm::RvmExecutor, rvv::RvvExecutor, zfa::ZfaExecutor,
+        zicbo::ZicboExecutor, zicond::ZicondExecutor, zicsr::ZicsrExecutor,
+        zifencei::ZifenceiExecutor, zihintpause::ZihintpauseExecutor, zkn::ZknExecutor,
+        zks::ZksExecutor, Executor,
+    },
+    f::FloatingPointRegister,
+    isa::Isa,
+    mmu::Translation,
+    mode::PrivilegeMode,
+    pc::ProgramCounter,
+    trap_handler::{handle_cause, pending_interrupt},
+    v::VectorRegister,
+    x::IntegerRegister,
+    xlen::Xlen,
+};
+
+pub struct Hart {
+    prv: PrivilegeMode,
+    pc: ProgramCounter,
+    x: IntegerRegister,
+    f: FloatingPointRegister,
+    v: VectorRegister,
+    csr: ControlAndStatusRegister,
+    isa: Isa,
+    // the interrupts last raised by the platform
+    platform_interrupts: u64,
+    // whether the environment calls from S-mode go to the SBI of the simulator, and whether one
+    // has been made at the last step
+    sbi: bool,
+    environment_call: bool,
+}
+
+impl Hart {
+    pub fn new(id: u64, config: &Config) -> Result<Self, String> {
+        let isa = config.isa.clone();
+        let v = VectorRegister::new(config.vlen, config.elen)?;
+        let mut csr = ControlAndStatusRegister::new(&isa);
+        csr.seed_entropy(config.entropy_seed);
+        csr.csrrw(MHARTID, id);
+        csr.csrrw(VLENB, v.vlenb() as u64);
+        csr.csrrw(VTYPE, vtype_vill(isa.xlen));
+        Ok(Self {
+            prv: PrivilegeMode::default(),
+            pc: ProgramCounter::new(
+                match config.boot_rom {
+                    true => BOOT_ROM_BASE,
+                    false => config.memory_base,
+                },
+                isa.c,
+            ),
+            x: IntegerRegister::new(isa.xlen),
+            f: FloatingPointRegister::default(),
+            v,
+            csr,
+            isa,
+            platform_interrupts: 0,
+            sbi: config.sbi,
+            environment_call: false,
+        })
+    }
+
+    pub fn pc(&self) -> u64 {
+        self.pc.read()
+    }
+
+    pub fn reset_pc(&mut self, address: u64) {
+        self.pc.reset(address);
+    }
+
+    // Sets a0 and a1 as the boot ROM does: the hart ID and the address of the device tree.
+    pub fn set_boot_arguments(&mut self, id: u64, fdt: u64) {
+        self.x.write(10, id);
+        self.x.write(11, fdt);
+    }
+
+    pub fn xlen(&self) -> Xlen {
+        self.x.xlen()
+    }
+
+    pub fn register(&self, register: usize) -> u64 {
+        self.x.read(register)
+    }
+
+    pub fn set_register(&mut self, register: usize, value: u64) {
+        self.x.write(register, value);
+    }
+
+    pub fn csr(&mut self) -> &mut ControlAndStatusRegister {
+        &mut self.csr
+    }
+
+    // Continues in a privilege mode at an address, as after a return from a trap.
+    pub fn resume(&mut self, prv: PrivilegeMode, address: u64) {
+        let xlen = self.csr.xlen(&prv);
+        self.prv = prv;
+        self.x.set_xlen(xlen);
+        self.pc.reset(xlen.zero_extend(address));
+    }
+
+    // Whether an environment call has been made to the SBI at the last step, which has returned
+    // to the next instruction for the SBI to serve.
+    pub fn take_environment_call(&mut self) -> bool {
+        mem::take(&mut self.environment_call)
+    }
+
+    // Sets the interrupts that the platform raises at the hart, as bits of mip.
+    pub fn set_interrupts(&mut self, pending: u64) {
+        if pending != self.platform_interrupts {
+            self.platform_interrupts = pending;
+            self.csr.set_platform_interrupts(pending);
+        }
+    }
+
+    pub fn set_time(&mut self, time: u64) {
+        self.csr.set_time(time);
+    }
+
+    // Takes a pending interrupt, or else fetches and executes an instruction, taking the trap it
+    // raises. Returns the instruction, or None when none has run.
+    pub fn step(&mut self, bus: &mut Bus) -> Option<u32> {
+        if let Some(interrupt) = pending_interrupt(self.prv, &self.csr) {
+            self.trap(&interrupt, 0);
+            self.pc.advance(self.x.xlen());
+            return None;
+        }
+        bus.set_translation(Translation::new(self.prv, &self.csr));
+        let instruction = match self.fetch(bus) {
+            Ok(instruction) => instruction,
+            Err(cause) => {
+                self.trap(&cause, 0);
+                self.pc.advance(self.x.xlen());
+                return None;
+            }
+        };
+        // a compressed instruction is executed as the one it expands to
+        let (expanded, length) = match instruction & 0b11 {
+            0b11 => (Some(instruction), 4),
+            _ if self.isa.c => (compressed::expand(instruction as u16, self.x.xlen()), 2),
+            _ => (None, 4),
+        };
+        self.pc.set_length(length);
+
+        let result = match expanded {
+            Some(expanded) => self.execute(expanded, bus),
+            None => Err(Cause::Exception(Exception::IllegalInstruction)),
+        };
+        self.csr.increment_counters(result.is_ok());
+
+        match result {
+            Err(Cause::Exception(Exception::EnvironmentCallFromSupervisorMode)) if self.sbi => {
+                self.environment_call = true
+            }
+            Err(cause) => self.trap(&cause, instruction),
+            Ok(()) => {}
+        }
+        // move to the next instruction unless the pc has been updated
+        self.pc.advance(self.x.xlen());
+        Some(instruction)
+    }
+
+    // Fetches the instruction at the pc. With C, it is fetched a half at a time, as a compressed
+    // instruction may end the memory.
+    fn fetch(&self, bus: &mut Bus) -> Result<u32, Cause> {
+        let pc = self.pc.read();
+        if !self.isa.c {
+            return bus.fetch(pc, 4);
+        }
+        let low = bus.fetch(pc, 2)?;
+        if low & 0b11 != 0b11 {
+            return Ok(low);
+        }
+        let high = bus.fetch(self.x.xlen().zero_extend(pc.wrapping_add(2)), 2)?;
+        Ok(high << 16 | low)
+    }
+
+    // Decodes and executes a 32-bit instruction with the decoder of its extension.
+    fn execute(&mut self, instruction: u32, bus: &mut Bus) -> Result<(), Cause> {
+        if let Some(decoded) = PrivilegedDecoder::decode(instruction) {
+            PrivilegedExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else if let Some(decoded) =
+            ZihintpauseDecoder::decode(instruction).filter(|_| self.isa.zihintpause)
+        {
+            // matched before rv32i, which would decode pause as a fence
+            ZihintpauseExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else if let Some(decoded) = Rv32iDecoder::decode(instruction) {
+            Rv32iExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else if let Some(decoded) = Rv64iDecoder::decode(instruction) {
+            Rv64iExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else if let Some(decoded) = RvmDecoder::decode(instruction).filter(|_| self.isa.m) {
+            RvmExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else if let Some(decoded) = RvaDecoder::decode(instruction).filter(|_| self.isa.a) {
+            RvaExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else if let Some(decoded) =
+            RvfDecoder::decode(instruction).filter(|_| self.isa.allows_float(instruction))
+        {
+            RvfExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else if let Some(decoded) = ZfaDecoder::decode(instruction)
+            .filter(|_| self.isa.zfa && self.isa.allows_float(instruction))
+        {
+            ZfaExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else if let Some(decoded) =
+            ZicboDecoder::decode(instruction).filter(|_| self.isa.allows_cache_block(instruction))
+        {
+            ZicboExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else if let Some(decoded) = ZicondDecoder::decode(instruction).filter(|_| self.isa.zicond)
+        {
+            ZicondExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else if let Some(decoded) =
+            ZifenceiDecoder::decode(instruction).filter(|_| self.isa.zifencei)
+        {
+            ZifenceiExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else if let Some(decoded) = ZicsrDecoder::decode(instruction).filter(|_| self.isa.zicsr) {
+            ZicsrExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else if let Some(decoded) = RvvDecoder::decode(instruction).filter(|_| self.isa.v) {
+            RvvExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else if let Some(decoded) =
+            ZknDecoder::decode(instruction).filter(|_| self.isa.allows_zkn(instruction))
+        {
+            ZknExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else if let Some(decoded) =
+            ZksDecoder::decode(instruction).filter(|_| self.isa.allows_zks(instruction))
+        {
+            ZksExecutor::execute(
+                decoded,
+                &self.prv,
+                &mut self.pc,
+                &mut self.x,
+                &mut self.v,
+                &mut self.f,
+                &mut self.csr,
+                bus,
+            )
+        } else {
+            Err(Cause::Exception(Exception::IllegalInstruction))
+        }
+    }
+
+    fn trap(&mut self, cause: &Cause, instruction: u32) {
+        let (prv, pc) = handle_cause(cause, self.pc.read(), instruction, self.prv, &mut self.csr);
+        // the new privilege mode may run at a different XLEN
+        let xlen = self.csr.xlen(&prv);
+        self.prv = prv;
+        self.x.set_xlen(xlen);
+        self.pc.jump(xlen.zero_extend(pc));
+    }
+}
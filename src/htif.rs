@@ -0,0 +1,305 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    sync::mpsc::Receiver,
+};
+
+use crate::{console::spawn_stdin_reader, memory::Memory};
+
+// Where the riscv-tests linker script places tohost, for raw binaries without symbols.
+pub const DEFAULT_TOHOST_ADDRESS: u64 = 0x8000_1000;
+
+// Devices
+const DEVICE_SYSCALL: u64 = 0;
+const DEVICE_CONSOLE: u64 = 1;
+
+// Console commands
+const CONSOLE_GETCHAR: u64 = 0;
+const CONSOLE_PUTCHAR: u64 = 1;
+
+// System calls proxied for riscv-pk, numbered as in Linux.
+const SYS_LSEEK: u64 = 62;
+const SYS_READ: u64 = 63;
+const SYS_WRITE: u64 = 64;
+const SYS_PREAD: u64 = 67;
+const SYS_PWRITE: u64 = 68;
+const SYS_OPENAT: u64 = 56;
+const SYS_CLOSE: u64 = 57;
+const SYS_EXIT: u64 = 93;
+const SYS_EXIT_GROUP: u64 = 94;
+const SYS_GETMAINVARS: u64 = 2011;
+
+// Linux errno values returned by the proxied system calls.
+const EBADF: i64 = 9;
+const EACCES: i64 = 13;
+const EFAULT: i64 = 14;
+const ENOMEM: i64 = 12;
+const ESPIPE: i64 = 29;
+const ENOSYS: i64 = 38;
+const EIO: i64 = 5;
+
+// Linux open flags.
+const O_ACCMODE: u64 = 0b11;
+const O_WRONLY: u64 = 1;
+const O_RDWR: u64 = 2;
+const O_CREAT: u64 = 0o100;
+const O_EXCL: u64 = 0o200;
+const O_TRUNC: u64 = 0o1000;
+const O_APPEND: u64 = 0o2000;
+
+enum HostFile {
+    Stdin,
+    Stdout,
+    Stderr,
+    File(File),
+}
+
+// The host-target interface of Spike and riscv-pk. The target writes a command to tohost as
+// device (63:56), command (55:48) and payload (47:0), and the host answers through fromhost.
+pub struct Htif {
+    tohost: u64,
+    fromhost: Option<u64>,
+    arguments: Vec<String>,
+    files: HashMap<u64, HostFile>,
+    next_fd: u64,
+    input: Option<Receiver<u8>>,
+    pending_reads: usize,
+    // whether the target may open the files of the host
+    host_files: bool,
+}
+
+impl Htif {
+    pub fn new(tohost: u64, fromhost: Option<u64>, arguments: Vec<String>) -> Self {
+        Self {
+            tohost,
+            fromhost,
+            arguments,
+            files: HashMap::from([
+                (0, HostFile::Stdin),
+                (1, HostFile::Stdout),
+                (2, HostFile::Stderr),
+            ]),
+            next_fd: 3,
+            input: None,
+            pending_reads: 0,
+            host_files: false,
+        }
+    }
+
+    pub fn tohost(&self) -> u64 {
+        self.tohost
+    }
+
+    pub fn set_host_files(&mut self, allowed: bool) {
+        self.host_files = allowed;
+    }
+
+    // Serves a command written to tohost, if any, once the target has `written` all of it. Returns
+    // the exit code once the target has exited, which riscv-tests sets to the number of the
+    // failing test.
+    pub fn tick(&mut self, memory: &mut Memory, written: bool) -> Option<u64> {
+        self.answer_reads(memory);
+        if !written {
+            return None;
+        }
+        let command = memory.load_bytes(self.tohost, 8).ok()?;
+        if command == 0 {
+            return None;
+        }
+        memory.store_bytes(self.tohost, 8, 0).ok()?;
+
+        let device = command >> 56;
+        let cmd = (command >> 48) & 0xff;
+        let payload = command & 0xffff_ffff_ffff;
+        match (device, cmd) {
+            (DEVICE_SYSCALL, _) if payload & 1 == 1 => return Some(payload >> 1),
+            (DEVICE_SYSCALL, _) => {
+                let exit = self.syscall(payload, memory);
+                if exit.is_some() {
+                    return exit;
+                }
+                self.respond(memory, device, cmd, 1);
+            }
+            (DEVICE_CONSOLE, CONSOLE_GETCHAR) => {
+                self.input.get_or_insert_with(spawn_stdin_reader);
+                self.pending_reads += 1;
+            }
+            (DEVICE_CONSOLE, CONSOLE_PUTCHAR) => {
+                let mut stdout = io::stdout();
+                let _ = stdout.write_all(&[payload as u8]);
+                let _ = stdout.flush();
+                self.respond(memory, device, cmd, 0x100 | payload & 0xff);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn respond(&self, memory: &mut Memory, device: u64, cmd: u64, payload: u64) {
+        if let Some(fromhost) = self.fromhost {
+            let _ = memory.store_bytes(fromhost, 8, device << 56 | cmd << 48 | payload);
+        }
+    }
+
+    // A getchar is answered once a character has been typed and fromhost is free.
+    fn answer_reads(&mut self, memory: &mut Memory) {
+        let (Some(input), Some(fromhost)) = (&self.input, self.fromhost) else {
+            return;
+        };
+        if self.pending_reads == 0 || memory.load_bytes(fromhost, 8).ok() != Some(0) {
+            return;
+        }
+        if let Ok(c) = input.try_recv() {
+            self.pending_reads -= 1;
+            self.respond(memory, DEVICE_CONSOLE, CONSOLE_GETCHAR, 0x100 | c as u64);
+        }
+    }
+
+    // Performs the system call described at `address`: its number followed by its arguments,
+    // all 64 bits wide. The result replaces the number. Returns the exit code of exit.
+    fn syscall(&mut self, address: u64, memory: &mut Memory) -> Option<u64> {
+        let Ok(call) = memory.load_slice(address, 8 * 8) else {
+            return None;
+        };
+        let word = |i: usize| u64::from_le_bytes(call[8 * i..8 * i + 8].try_into().unwrap());
+        let (number, a) = (word(0), [word(1), word(2), word(3), word(4), word(5)]);
+
+        let result = match number {
+            SYS_EXIT | SYS_EXIT_GROUP => return Some(a[0]),
+            SYS_READ => self.read(memory, a[0], a[1], a[2], None),
+            SYS_PREAD => self.read(memory, a[0], a[1], a[2], Some(a[3])),
+            SYS_WRITE => self.write(memory, a[0], a[1], a[2], None),
+            SYS_PWRITE => self.write(memory, a[0], a[1], a[2], Some(a[3])),
+            SYS_LSEEK => self.lseek(a[0], a[1], a[2]),
+            SYS_OPENAT if self.host_files => self.openat(memory, a[1], a[2], a[3]),
+            SYS_OPENAT => Err(EACCES),
+            SYS_CLOSE => match self.files.remove(&a[0]) {
+                Some(_) => Ok(0),
+                None => Err(EBADF),
+            },
+            SYS_GETMAINVARS => self.getmainvars(memory, a[0], a[1]),
+            _ => Err(ENOSYS),
+        };
+        let result = result.unwrap_or_else(|errno| -errno) as u64;
+        let _ = memory.store_bytes(address, 8, result);
+        None
+    }
+
+    fn file(&mut self, fd: u64, offset: Option<u64>) -> Result<&mut HostFile, i64> {
+        let file = self.files.get_mut(&fd).ok_or(EBADF)?;
+        match (file, offset) {
+            (HostFile::File(f), Some(offset)) => {
+                f.seek(SeekFrom::Start(offset)).map_err(errno)?;
+            }
+            (_, Some(_)) => return Err(ESPIPE),
+            _ => {}
+        }
+        Ok(self.files.get_mut(&fd).unwrap())
+    }
+
+    fn read(
+        &mut self,
+        memory: &mut Memory,
+        fd: u64,
+        buffer: u64,
+        length: u64,
+        offset: Option<u64>,
+    ) -> Result<i64, i64> {
+        memory
+            .load_slice(buffer, length as usize)
+            .map_err(|_| EFAULT)?;
+        let mut data = vec![0; length as usize];
+        let n = match self.file(fd, offset)? {
+            HostFile::Stdin => io::stdin().read(&mut data),
+            HostFile::File(f) => f.read(&mut data),
+            _ => return Err(EBADF),
+        }
+        .map_err(errno)?;
+        memory.store_slice(buffer, &data[..n]).map_err(|_| EFAULT)?;
+        Ok(n as i64)
+    }
+
+    fn write(
+        &mut self,
+        memory: &mut Memory,
+        fd: u64,
+        buffer: u64,
+        length: u64,
+        offset: Option<u64>,
+    ) -> Result<i64, i64> {
+        let data = memory
+            .load_slice(buffer, length as usize)
+            .map_err(|_| EFAULT)?
+            .to_vec();
+        match self.file(fd, offset)? {
+            HostFile::Stdout => io::stdout().write_all(&data).and(io::stdout().flush()),
+            HostFile::Stderr => io::stderr().write_all(&data),
+            HostFile::File(f) => f.write_all(&data),
+            HostFile::Stdin => return Err(EBADF),
+        }
+        .map_err(errno)?;
+        Ok(data.len() as i64)
+    }
+
+    fn lseek(&mut self, fd: u64, offset: u64, whence: u64) -> Result<i64, i64> {
+        let position = match whence {
+            0 => SeekFrom::Start(offset),
+            1 => SeekFrom::Current(offset as i64),
+            2 => SeekFrom::End(offset as i64),
+            _ => return Err(ESPIPE),
+        };
+        match self.file(fd, None)? {
+            HostFile::File(f) => f.seek(position).map(|p| p as i64).map_err(errno),
+            _ => Err(ESPIPE),
+        }
+    }
+
+    // Paths are relative to the working directory of the simulator, whatever the directory fd.
+    fn openat(&mut self, memory: &Memory, path: u64, length: u64, flags: u64) -> Result<i64, i64> {
+        let path = memory
+            .load_slice(path, length as usize)
+            .map_err(|_| EFAULT)?;
+        let path = String::from_utf8_lossy(path.split(|&b| b == 0).next().unwrap_or_default());
+        let file = OpenOptions::new()
+            .read(flags & O_ACCMODE != O_WRONLY)
+            .write(flags & O_ACCMODE == O_WRONLY || flags & O_ACCMODE == O_RDWR)
+            .append(flags & O_APPEND != 0)
+            .truncate(flags & O_TRUNC != 0)
+            .create(flags & O_CREAT != 0 && flags & O_EXCL == 0)
+            .create_new(flags & O_CREAT != 0 && flags & O_EXCL != 0)
+            .open(path.as_ref())
+            .map_err(errno)?;
+        let fd = self.next_fd;
+        self.next_fd += 1;
+        self.files.insert(fd, HostFile::File(file));
+        Ok(fd as i64)
+    }
+
+    // Passes the arguments for the target's main: argc, the argv pointers, a null argv and a null
+    // envp, followed by the strings.
+    fn getmainvars(&self, memory: &mut Memory, buffer: u64, limit: u64) -> Result<i64, i64> {
+        let count = self.arguments.len();
+        let mut words = vec![count as u64];
+        let mut strings = Vec::new();
+        let start = buffer + 8 * (count as u64 + 3);
+        for argument in &self.arguments {
+            words.push(start + strings.len() as u64);
+            strings.extend_from_slice(argument.as_bytes());
+            strings.push(0);
+        }
+        words.extend([0, 0]);
+
+        let mut bytes: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        bytes.extend(strings);
+        if bytes.len() as u64 > limit {
+            return Err(ENOMEM);
+        }
+        memory.store_slice(buffer, &bytes).map_err(|_| EFAULT)?;
+        Ok(0)
+    }
+}
+
+fn errno(error: io::Error) -> i64 {
+    error.raw_os_error().map_or(EIO, |e| e as i64)
+}
@@ -0,0 +1,242 @@
+use crate::{
+    bus::{BusError, Device, Platform},
+    cause::Interrupt,
+    xlen::Xlen,
+};
+
+// Where QEMU's virt machine places the M-level interrupt files of its IMSIC. The S-level ones
+// follow at SUPERVISOR_FILES.
+pub const DEFAULT_IMSIC_BASE: u64 = 0x2400_0000;
+// The identities of the external interrupts that each file implements, from 1, as QEMU does.
+pub const DEFAULT_IMSIC_IDS: u32 = 255;
+
+pub const SUPERVISOR_FILES: u64 = 0x400_0000;
+// Each interrupt file has a page, with a register at its start to which MSIs are written.
+pub const FILE_SIZE: u64 = 0x1000;
+const SETEIPNUM_LE: u64 = 0x0;
+const SETEIPNUM_BE: u64 = 0x4;
+
+// The registers of an interrupt file, as selected by miselect and siselect.
+const EIDELIVERY: u64 = 0x70;
+const EITHRESHOLD: u64 = 0x72;
+const EIP0: u64 = 0x80;
+const EIE0: u64 = 0xc0;
+const EIE63: u64 = 0xff;
+
+// The privilege levels that have interrupt files, and the domains of an APLIC.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Level {
+    Machine,
+    Supervisor,
+}
+
+// An interrupt file, which records the MSIs received for a hart at a privilege level as pending
+// interrupt identities.
+pub struct InterruptFile {
+    // a multiple of 64 minus 1, so that the words cover the identities and 0 exactly
+    ids: u32,
+    delivery: bool,
+    threshold: u32,
+    // bit sets of identities, 32 to a word, as RV32 reads them through eip and eie
+    pending: Vec<u32>,
+    enabled: Vec<u32>,
+    // the identity of the enabled pending interrupt to take, or 0, kept up to date
+    top: u32,
+}
+
+impl InterruptFile {
+    fn new(ids: u32) -> Self {
+        let words = ids as usize / 32 + 1;
+        Self {
+            ids,
+            delivery: false,
+            threshold: 0,
+            pending: vec![0; words],
+            enabled: vec![0; words],
+            top: 0,
+        }
+    }
+
+    // An MSI sets the pending bit of its identity. Identity 0, and those not implemented, are
+    // ignored.
+    pub fn set_pending(&mut self, id: u32) {
+        if (1..=self.ids).contains(&id) {
+            self.pending[id as usize / 32] |= 1 << (id % 32);
+            self.update();
+        }
+    }
+
+    // The lowest identity that is both pending and enabled, and under the threshold if one is
+    // set.
+    fn update(&mut self) {
+        let limit = match self.threshold {
+            0 => self.ids,
+            threshold => (threshold - 1).min(self.ids),
+        };
+        self.top = (1..=limit)
+            .find(|&id| {
+                (self.pending[id as usize / 32] & self.enabled[id as usize / 32]) >> (id % 32) & 1
+                    == 1
+            })
+            .unwrap_or(0);
+    }
+
+    // Whether the file interrupts its hart.
+    pub fn interrupt(&self) -> bool {
+        self.delivery && self.top != 0
+    }
+
+    // The value of mtopei or stopei: the identity of the top interrupt, also as its priority.
+    pub fn topei(&self) -> u64 {
+        (self.top as u64) << 16 | self.top as u64
+    }
+
+    // Claims the top interrupt, clearing its pending bit.
+    pub fn claim(&mut self) {
+        if self.top != 0 {
+            self.pending[self.top as usize / 32] &= !(1 << (self.top % 32));
+            self.update();
+        }
+    }
+
+    // The words of eip or eie that a register holds: one in RV32, and two in RV64, where the
+    // odd-numbered registers do not exist.
+    fn words(&self, select: u64, xlen: Xlen) -> Option<(bool, usize, usize)> {
+        let (enabled, index) = match select {
+            EIP0..EIE0 => (false, select - EIP0),
+            EIE0..=EIE63 => (true, select - EIE0),
+            _ => return None,
+        };
+        match xlen {
+            Xlen::Rv32 => Some((enabled, index as usize, 1)),
+            Xlen::Rv64 if index.is_multiple_of(2) => Some((enabled, index as usize, 2)),
+            Xlen::Rv64 => None,
+        }
+    }
+
+    fn read(&self, select: u64, xlen: Xlen) -> Option<u64> {
+        match select {
+            EIDELIVERY => return Some(self.delivery as u64),
+            EITHRESHOLD => return Some(self.threshold as u64),
+            _ => {}
+        }
+        let (enabled, first, count) = self.words(select, xlen)?;
+        let set = if enabled {
+            &self.enabled
+        } else {
+            &self.pending
+        };
+        Some((0..count).fold(0, |value, i| {
+            let word = set.get(first + i).copied().unwrap_or(0);
+            value | (word as u64) << (32 * i)
+        }))
+    }
+
+    fn write(&mut self, select: u64, xlen: Xlen, value: u64) {
+        match select {
+            // only the delivery of interrupts from the file itself is supported
+            EIDELIVERY => self.delivery = value & 1 == 1,
+            EITHRESHOLD => self.threshold = (value as u32).min(self.ids),
+            _ => {
+                let Some((enabled, first, count)) = self.words(select, xlen) else {
+                    return;
+                };
+                let set = if enabled {
+                    &mut self.enabled
+                } else {
+                    &mut self.pending
+                };
+                for i in 0..count {
+                    if let Some(word) = set.get_mut(first + i) {
+                        *word = (value >> (32 * i)) as u32;
+                    }
+                }
+                // there is no interrupt 0
+                set[0] &= !1;
+            }
+        }
+        self.update();
+    }
+
+    // Performs a CSR instruction on the register that miselect or siselect selects, through
+    // mireg or sireg, as seen by software running at `xlen`. Returns None when there is no such
+    // register.
+    pub fn access(
+        &mut self,
+        select: u64,
+        xlen: Xlen,
+        update: impl FnOnce(u64) -> u64,
+    ) -> Option<u64> {
+        let t = self.read(select, xlen)?;
+        self.write(select, xlen, update(t));
+        Some(t)
+    }
+}
+
+// An incoming MSI controller with an M-level and an S-level interrupt file for each hart, which
+// interrupt the hart through MEIP and SEIP.
+pub struct Imsic {
+    machine: Vec<InterruptFile>,
+    supervisor: Vec<InterruptFile>,
+}
+
+impl Imsic {
+    pub fn new(harts: usize, ids: u32) -> Self {
+        // the identities are limited to 2047, and come in multiples of 64 minus 1
+        let ids = (ids | 63).min(2047);
+        Self {
+            machine: (0..harts).map(|_| InterruptFile::new(ids)).collect(),
+            supervisor: (0..harts).map(|_| InterruptFile::new(ids)).collect(),
+        }
+    }
+
+    // The interrupt file whose page holds `offset`.
+    fn file(&mut self, offset: u64) -> Option<&mut InterruptFile> {
+        let (files, offset) = match offset.checked_sub(SUPERVISOR_FILES) {
+            Some(offset) => (&mut self.supervisor, offset),
+            None => (&mut self.machine, offset),
+        };
+        files.get_mut((offset / FILE_SIZE) as usize)
+    }
+}
+
+impl Device for Imsic {
+    fn size(&self) -> u64 {
+        SUPERVISOR_FILES + FILE_SIZE * self.supervisor.len() as u64
+    }
+
+    // The registers are write-only.
+    fn read(&mut self, _: u64, _: usize) -> Result<u64, BusError> {
+        Ok(0)
+    }
+
+    fn write(&mut self, offset: u64, size: usize, value: u64) -> Result<(), BusError> {
+        let id = match (offset % FILE_SIZE, size) {
+            (SETEIPNUM_LE, 4) => value as u32,
+            (SETEIPNUM_BE, 4) => (value as u32).swap_bytes(),
+            _ => return Ok(()),
+        };
+        if let Some(file) = self.file(offset) {
+            file.set_pending(id);
+        }
+        Ok(())
+    }
+}
+
+impl Platform for Imsic {
+    fn hart_interrupts(&self, hart: usize) -> u64 {
+        let (Some(machine), Some(supervisor)) = (self.machine.get(hart), self.supervisor.get(hart))
+        else {
+            return 0;
+        };
+        (machine.interrupt() as u64) << Interrupt::MachineExternal.to_primitive()
+            | (supervisor.interrupt() as u64) << Interrupt::SupervisorExternal.to_primitive()
+    }
+
+    fn interrupt_file(&mut self, hart: usize, level: Level) -> Option<&mut InterruptFile> {
+        match level {
+            Level::Machine => self.machine.get_mut(hart),
+            Level::Supervisor => self.supervisor.get_mut(hart),
+        }
+    }
+}
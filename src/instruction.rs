@@ -1,7 +1,18 @@
 pub mod privileged;
 pub mod rv32i;
 pub mod rv64i;
+pub mod rva;
+pub mod rvf;
+pub mod rvm;
+pub mod rvv;
+pub mod zfa;
+pub mod zicbo;
+pub mod zicond;
 pub mod zicsr;
+pub mod zifencei;
+pub mod zihintpause;
+pub mod zkn;
+pub mod zks;
 
 #[derive(Debug, PartialEq)]
 pub enum Instruction<OpcodeR, OpcodeI, OpcodeS, OpcodeB, OpcodeU, OpcodeJ> {
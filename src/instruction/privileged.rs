@@ -1,6 +1,9 @@
 #[derive(Debug, PartialEq)]
 pub enum PrivilegedOpcodeR {
     Mret,
+    Sret,
+    Wfi,
+    SfenceVma,
 }
 
 #[derive(Debug, PartialEq)]
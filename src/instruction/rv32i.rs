@@ -1,18 +1,43 @@
 #[derive(Debug, PartialEq)]
 pub enum Rv32iOpcodeR {
     Add,
+    Sub,
+    Sll,
+    Slt,
+    Sltu,
+    Xor,
+    Srl,
+    Sra,
+    Or,
+    And,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Rv32iOpcodeI {
-    Slli,
+    Jalr,
+    Lb,
+    Lh,
+    Lw,
+    Lbu,
+    Lhu,
     Addi,
+    Slti,
+    Sltiu,
+    Xori,
+    Ori,
+    Andi,
+    Slli,
+    Srli,
+    Srai,
     Fence,
     Ecall,
+    Ebreak,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Rv32iOpcodeS {
+    Sb,
+    Sh,
     Sw,
 }
 
@@ -20,7 +45,10 @@ pub enum Rv32iOpcodeS {
 pub enum Rv32iOpcodeB {
     Beq,
     Bne,
+    Blt,
     Bge,
+    Bltu,
+    Bgeu,
 }
 
 #[derive(Debug, PartialEq)]
@@ -1,13 +1,26 @@
 #[derive(Debug, PartialEq)]
-pub enum Rv64iOpcodeR {}
+pub enum Rv64iOpcodeR {
+    Addw,
+    Subw,
+    Sllw,
+    Srlw,
+    Sraw,
+}
 
 #[derive(Debug, PartialEq)]
 pub enum Rv64iOpcodeI {
+    Lwu,
+    Ld,
     Addiw,
+    Slliw,
+    Srliw,
+    Sraiw,
 }
 
 #[derive(Debug, PartialEq)]
-pub enum Rv64iOpcodeS {}
+pub enum Rv64iOpcodeS {
+    Sd,
+}
 
 #[derive(Debug, PartialEq)]
 pub enum Rv64iOpcodeB {}
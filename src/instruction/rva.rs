@@ -0,0 +1,30 @@
+// The width of an instruction, a word or a doubleword, is in its funct3.
+#[derive(Debug, PartialEq)]
+pub enum RvaOpcodeR {
+    Lr,
+    Sc,
+    Amoswap,
+    Amoadd,
+    Amoxor,
+    Amoand,
+    Amoor,
+    Amomin,
+    Amomax,
+    Amominu,
+    Amomaxu,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RvaOpcodeI {}
+
+#[derive(Debug, PartialEq)]
+pub enum RvaOpcodeS {}
+
+#[derive(Debug, PartialEq)]
+pub enum RvaOpcodeB {}
+
+#[derive(Debug, PartialEq)]
+pub enum RvaOpcodeU {}
+
+#[derive(Debug, PartialEq)]
+pub enum RvaOpcodeJ {}
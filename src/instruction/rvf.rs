@@ -0,0 +1,57 @@
+// F, D and Zfh share their encodings, which differ only in the fmt field
+// (or the width of the loads and stores).
+#[derive(Debug, PartialEq)]
+pub enum RvfOpcodeR {
+    Fmadd,
+    Fmsub,
+    Fnmsub,
+    Fnmadd,
+    Fadd,
+    Fsub,
+    Fmul,
+    Fdiv,
+    Fsqrt,
+    Fsgnj,
+    Fsgnjn,
+    Fsgnjx,
+    Fmin,
+    Fmax,
+    FcvtFF,
+    Feq,
+    Flt,
+    Fle,
+    FcvtWF,
+    FcvtWuF,
+    FcvtLF,
+    FcvtLuF,
+    FcvtFW,
+    FcvtFWu,
+    FcvtFL,
+    FcvtFLu,
+    FmvXF,
+    Fclass,
+    FmvFX,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RvfOpcodeI {
+    Flh,
+    Flw,
+    Fld,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RvfOpcodeS {
+    Fsh,
+    Fsw,
+    Fsd,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RvfOpcodeB {}
+
+#[derive(Debug, PartialEq)]
+pub enum RvfOpcodeU {}
+
+#[derive(Debug, PartialEq)]
+pub enum RvfOpcodeJ {}
@@ -0,0 +1,31 @@
+#[derive(Debug, PartialEq)]
+pub enum RvmOpcodeR {
+    Mul,
+    Mulh,
+    Mulhsu,
+    Mulhu,
+    Div,
+    Divu,
+    Rem,
+    Remu,
+    Mulw,
+    Divw,
+    Divuw,
+    Remw,
+    Remuw,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RvmOpcodeI {}
+
+#[derive(Debug, PartialEq)]
+pub enum RvmOpcodeS {}
+
+#[derive(Debug, PartialEq)]
+pub enum RvmOpcodeB {}
+
+#[derive(Debug, PartialEq)]
+pub enum RvmOpcodeU {}
+
+#[derive(Debug, PartialEq)]
+pub enum RvmOpcodeJ {}
@@ -0,0 +1,220 @@
+#[derive(Debug, PartialEq)]
+pub enum RvvOpcodeR {
+    // Configuration-Setting Instructions
+    Vsetvl,
+    // Vector Loads and Stores
+    Vle,
+    Vleff,
+    Vlm,
+    Vlr,
+    Vlse,
+    Vluxei,
+    Vloxei,
+    Vse,
+    Vsm,
+    Vsr,
+    Vsse,
+    Vsuxei,
+    Vsoxei,
+    // Vector Integer Arithmetic Instructions
+    Vadd,
+    Vsub,
+    Vrsub,
+    Vminu,
+    Vmin,
+    Vmaxu,
+    Vmax,
+    Vand,
+    Vor,
+    Vxor,
+    Vadc,
+    Vmadc,
+    Vsbc,
+    Vmsbc,
+    Vmerge,
+    Vmv,
+    Vmseq,
+    Vmsne,
+    Vmsltu,
+    Vmslt,
+    Vmsleu,
+    Vmsle,
+    Vmsgtu,
+    Vmsgt,
+    Vsll,
+    Vsrl,
+    Vsra,
+    Vnsrl,
+    Vnsra,
+    Vzext,
+    Vsext,
+    Vdivu,
+    Vdiv,
+    Vremu,
+    Vrem,
+    Vmulhu,
+    Vmul,
+    Vmulhsu,
+    Vmulh,
+    Vmadd,
+    Vnmsub,
+    Vmacc,
+    Vnmsac,
+    Vwaddu,
+    Vwadd,
+    Vwsubu,
+    Vwsub,
+    VwadduW,
+    VwaddW,
+    VwsubuW,
+    VwsubW,
+    Vwmulu,
+    Vwmulsu,
+    Vwmul,
+    Vwmaccu,
+    Vwmacc,
+    Vwmaccus,
+    Vwmaccsu,
+    // Vector Fixed-Point Arithmetic Instructions
+    Vsaddu,
+    Vsadd,
+    Vssubu,
+    Vssub,
+    Vaaddu,
+    Vaadd,
+    Vasubu,
+    Vasub,
+    Vsmul,
+    Vssrl,
+    Vssra,
+    Vnclipu,
+    Vnclip,
+    // Vector Floating-Point Instructions
+    Vfadd,
+    Vfsub,
+    Vfrsub,
+    Vfmul,
+    Vfdiv,
+    Vfrdiv,
+    Vfmin,
+    Vfmax,
+    Vfsgnj,
+    Vfsgnjn,
+    Vfsgnjx,
+    Vfmacc,
+    Vfnmacc,
+    Vfmsac,
+    Vfnmsac,
+    Vfmadd,
+    Vfnmadd,
+    Vfmsub,
+    Vfnmsub,
+    Vfwadd,
+    Vfwsub,
+    VfwaddW,
+    VfwsubW,
+    Vfwmul,
+    Vfwmacc,
+    Vfwnmacc,
+    Vfwmsac,
+    Vfwnmsac,
+    Vfsqrt,
+    Vfrsqrt7,
+    Vfrec7,
+    Vfclass,
+    Vmfeq,
+    Vmfne,
+    Vmflt,
+    Vmfle,
+    Vmfgt,
+    Vmfge,
+    Vfmerge,
+    Vfmv,
+    VfcvtXuF,
+    VfcvtXF,
+    VfcvtFXu,
+    VfcvtFX,
+    VfcvtRtzXuF,
+    VfcvtRtzXF,
+    VfwcvtXuF,
+    VfwcvtXF,
+    VfwcvtFXu,
+    VfwcvtFX,
+    VfwcvtFF,
+    VfwcvtRtzXuF,
+    VfwcvtRtzXF,
+    VfncvtXuF,
+    VfncvtXF,
+    VfncvtFXu,
+    VfncvtFX,
+    VfncvtFF,
+    VfncvtRodFF,
+    VfncvtRtzXuF,
+    VfncvtRtzXF,
+    // Vector Reduction Operations
+    Vredsum,
+    Vredand,
+    Vredor,
+    Vredxor,
+    Vredminu,
+    Vredmin,
+    Vredmaxu,
+    Vredmax,
+    Vwredsumu,
+    Vwredsum,
+    Vfredusum,
+    Vfredosum,
+    Vfredmin,
+    Vfredmax,
+    Vfwredusum,
+    Vfwredosum,
+    // Vector Mask Instructions
+    Vmandn,
+    Vmand,
+    Vmor,
+    Vmxor,
+    Vmorn,
+    Vmnand,
+    Vmnor,
+    Vmxnor,
+    Vcpop,
+    Vfirst,
+    Vmsbf,
+    Vmsif,
+    Vmsof,
+    Viota,
+    Vid,
+    // Vector Permutation Instructions
+    Vmvxs,
+    Vmvsx,
+    Vfmvfs,
+    Vfmvsf,
+    Vslideup,
+    Vslidedown,
+    Vslide1up,
+    Vslide1down,
+    Vfslide1up,
+    Vfslide1down,
+    Vrgather,
+    Vrgatherei16,
+    Vcompress,
+    Vmvnr,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RvvOpcodeI {
+    Vsetvli,
+    Vsetivli,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RvvOpcodeS {}
+
+#[derive(Debug, PartialEq)]
+pub enum RvvOpcodeB {}
+
+#[derive(Debug, PartialEq)]
+pub enum RvvOpcodeU {}
+
+#[derive(Debug, PartialEq)]
+pub enum RvvOpcodeJ {}
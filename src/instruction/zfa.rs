@@ -0,0 +1,29 @@
+#[derive(Debug, PartialEq)]
+pub enum ZfaOpcodeR {
+    Fli,
+    Fminm,
+    Fmaxm,
+    Fround,
+    Froundnx,
+    FcvtmodWD,
+    Fleq,
+    Fltq,
+    // RV32 only
+    FmvhXD,
+    FmvpDX,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ZfaOpcodeI {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZfaOpcodeS {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZfaOpcodeB {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZfaOpcodeU {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZfaOpcodeJ {}
@@ -0,0 +1,24 @@
+#[derive(Debug, PartialEq)]
+pub enum ZicboOpcodeR {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZicboOpcodeI {
+    // Zicbom
+    Inval,
+    Clean,
+    Flush,
+    // Zicboz
+    Zero,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ZicboOpcodeS {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZicboOpcodeB {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZicboOpcodeU {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZicboOpcodeJ {}
@@ -0,0 +1,20 @@
+#[derive(Debug, PartialEq)]
+pub enum ZicondOpcodeR {
+    CzeroEqz,
+    CzeroNez,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ZicondOpcodeI {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZicondOpcodeS {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZicondOpcodeB {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZicondOpcodeU {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZicondOpcodeJ {}
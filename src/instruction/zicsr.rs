@@ -5,7 +5,10 @@ pub enum ZicsrOpcodeR {}
 pub enum ZicsrOpcodeI {
     Csrrw,
     Csrrs,
+    Csrrc,
     Csrrwi,
+    Csrrsi,
+    Csrrci,
 }
 
 #[derive(Debug, PartialEq)]
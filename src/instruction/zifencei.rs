@@ -0,0 +1,19 @@
+#[derive(Debug, PartialEq)]
+pub enum ZifenceiOpcodeR {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZifenceiOpcodeI {
+    FenceI,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ZifenceiOpcodeS {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZifenceiOpcodeB {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZifenceiOpcodeU {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZifenceiOpcodeJ {}
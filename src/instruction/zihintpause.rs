@@ -0,0 +1,19 @@
+#[derive(Debug, PartialEq)]
+pub enum ZihintpauseOpcodeR {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZihintpauseOpcodeI {
+    Pause,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ZihintpauseOpcodeS {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZihintpauseOpcodeB {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZihintpauseOpcodeU {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZihintpauseOpcodeJ {}
@@ -0,0 +1,50 @@
+#[derive(Debug, PartialEq)]
+pub enum ZknOpcodeR {
+    // Zkne
+    Aes64es,
+    Aes64esm,
+    Aes64ks2,
+    // Zknd
+    Aes64ds,
+    Aes64dsm,
+    // Zkne/Zknd, RV32 only
+    Aes32esi,
+    Aes32esmi,
+    Aes32dsi,
+    Aes32dsmi,
+    // Zknh, RV32 only
+    Sha512sum0r,
+    Sha512sum1r,
+    Sha512sig0l,
+    Sha512sig0h,
+    Sha512sig1l,
+    Sha512sig1h,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ZknOpcodeI {
+    // Zkne/Zknd
+    Aes64ks1i,
+    Aes64im,
+    // Zknh
+    Sha256sig0,
+    Sha256sig1,
+    Sha256sum0,
+    Sha256sum1,
+    Sha512sig0,
+    Sha512sig1,
+    Sha512sum0,
+    Sha512sum1,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ZknOpcodeS {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZknOpcodeB {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZknOpcodeU {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZknOpcodeJ {}
@@ -0,0 +1,25 @@
+#[derive(Debug, PartialEq)]
+pub enum ZksOpcodeR {
+    // Zksed
+    Sm4ed,
+    Sm4ks,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ZksOpcodeI {
+    // Zksh
+    Sm3p0,
+    Sm3p1,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ZksOpcodeS {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZksOpcodeB {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZksOpcodeU {}
+
+#[derive(Debug, PartialEq)]
+pub enum ZksOpcodeJ {}
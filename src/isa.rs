@@ -0,0 +1,269 @@
+use std::fmt;
+
+use crate::xlen::Xlen;
+
+pub const DEFAULT_ISA: &str =
+    "rv64imafdcv_zicsr_zifencei_zicond_zicbom_zicboz_zihintpause_zfh_zfa_\
+    zknd_zkne_zknh_zksed_zksh_zkr_sstc_smaia_ssaia";
+
+// The extensions implemented by the harts, as given by an ISA string such as rv64ifd_zicsr.
+// The base ISA is always I, with the S and U privilege modes.
+#[derive(Clone, Default)]
+pub struct Isa {
+    pub xlen: Xlen,
+    pub m: bool,
+    pub a: bool,
+    pub f: bool,
+    pub d: bool,
+    pub c: bool,
+    pub v: bool,
+    pub zicsr: bool,
+    pub zifencei: bool,
+    pub zicond: bool,
+    pub zicbom: bool,
+    pub zicboz: bool,
+    pub zihintpause: bool,
+    pub zfh: bool,
+    pub zfhmin: bool,
+    pub zfa: bool,
+    pub zknd: bool,
+    pub zkne: bool,
+    pub zknh: bool,
+    pub zksed: bool,
+    pub zksh: bool,
+    pub zkr: bool,
+    pub sstc: bool,
+    pub smaia: bool,
+    pub ssaia: bool,
+}
+
+impl Isa {
+    pub fn parse(isa: &str) -> Result<Self, String> {
+        let lowercase = isa.to_lowercase();
+        let (xlen, extensions) = if let Some(extensions) = lowercase.strip_prefix("rv32") {
+            (Xlen::Rv32, extensions)
+        } else if let Some(extensions) = lowercase.strip_prefix("rv64") {
+            (Xlen::Rv64, extensions)
+        } else {
+            return Err(format!(
+                "{}: the ISA string must start with rv32 or rv64",
+                isa
+            ));
+        };
+
+        let mut result = Self {
+            xlen,
+            ..Default::default()
+        };
+        // G is IMAFD with Zicsr and Zifencei
+        let extensions = match extensions.strip_prefix('g') {
+            Some(extensions) => {
+                result.m = true;
+                result.a = true;
+                result.f = true;
+                result.d = true;
+                result.zifencei = true;
+                extensions
+            }
+            None => extensions
+                .strip_prefix('i')
+                .ok_or_else(|| format!("{}: the base ISA must be i or g", isa))?,
+        };
+        // the single letters run up to the first multi-letter extension, which may follow them
+        // without an underscore, and the others are separated by underscores
+        for part in extensions.split('_').filter(|part| !part.is_empty()) {
+            let start = part.find(['z', 's', 'x']).unwrap_or(part.len());
+            let (letters, name) = part.split_at(start);
+            for letter in letters.chars() {
+                match letter {
+                    'm' => result.m = true,
+                    'a' => result.a = true,
+                    'f' => result.f = true,
+                    'd' => result.d = true,
+                    'c' => result.c = true,
+                    'v' => result.v = true,
+                    _ => return Err(format!("{}: unsupported extension {}", isa, letter)),
+                }
+            }
+            if name.is_empty() {
+                continue;
+            }
+            let extension = match name {
+                "zicsr" => &mut result.zicsr,
+                "zifencei" => &mut result.zifencei,
+                "zicond" => &mut result.zicond,
+                "zicbom" => &mut result.zicbom,
+                "zicboz" => &mut result.zicboz,
+                "zihintpause" => &mut result.zihintpause,
+                "zfh" => &mut result.zfh,
+                "zfhmin" => &mut result.zfhmin,
+                "zfa" => &mut result.zfa,
+                "zknd" => &mut result.zknd,
+                "zkne" => &mut result.zkne,
+                "zknh" => &mut result.zknh,
+                "zksed" => &mut result.zksed,
+                "zksh" => &mut result.zksh,
+                "zkr" => &mut result.zkr,
+                "sstc" => &mut result.sstc,
+                "smaia" => &mut result.smaia,
+                "ssaia" => &mut result.ssaia,
+                _ => return Err(format!("{}: unsupported extension {}", isa, name)),
+            };
+            *extension = true;
+        }
+
+        // the extensions that others imply, in the order of their dependencies
+        result.f |= result.d || result.zfh || result.zfhmin || result.zfa;
+        result.zicsr |= result.f || result.v;
+        Ok(result)
+    }
+
+    // The extensions in the order of an ISA string: the base, the single letters, then the
+    // others, as the riscv,isa-extensions property of the device tree lists them.
+    pub fn extensions(&self) -> Vec<&'static str> {
+        [
+            ("i", true),
+            ("m", self.m),
+            ("a", self.a),
+            ("f", self.f),
+            ("d", self.d),
+            ("c", self.c),
+            ("v", self.v),
+            ("zicsr", self.zicsr),
+            ("zifencei", self.zifencei),
+            ("zicond", self.zicond),
+            ("zicbom", self.zicbom),
+            ("zicboz", self.zicboz),
+            ("zihintpause", self.zihintpause),
+            ("zfh", self.zfh),
+            ("zfhmin", self.zfhmin),
+            ("zfa", self.zfa),
+            ("zknd", self.zknd),
+            ("zkne", self.zkne),
+            ("zknh", self.zknh),
+            ("zksed", self.zksed),
+            ("zksh", self.zksh),
+            ("zkr", self.zkr),
+            ("sstc", self.sstc),
+            ("smaia", self.smaia),
+            ("ssaia", self.ssaia),
+        ]
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| name)
+        .collect()
+    }
+
+    // The Extensions field of misa.
+    pub fn misa_extensions(&self) -> u64 {
+        let letters = [
+            ('a', self.a),
+            ('c', self.c),
+            ('d', self.d),
+            ('f', self.f),
+            ('i', true),
+            ('m', self.m),
+            ('s', true),
+            ('u', true),
+            ('v', self.v),
+        ];
+        letters
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .fold(0, |acc, (letter, _)| acc | 1 << (*letter as u8 - b'a'))
+    }
+
+    // Whether the floating-point formats used by an F, D, Zfh or Zfa instruction are enabled.
+    // Loads and stores give the format as their width, and the others in the fmt field, and
+    // conversions between formats in rs2 for the source. Zfhmin only has the loads, stores, moves
+    // and conversions of half precision.
+    pub fn allows_float(&self, instruction: u32) -> bool {
+        let opcode = instruction & 0b1111111;
+        let funct3 = (instruction >> 12) & 0b111;
+        let rs2 = (instruction >> 20) & 0b11111;
+        let fmt = (instruction >> 25) & 0b11;
+        let funct5 = instruction >> 27;
+        let formats = match opcode {
+            0b0000111 | 0b0100111 => match funct3 {
+                0b001 => [0b10, 0b10],
+                0b010 => [0b00, 0b00],
+                0b011 => [0b01, 0b01],
+                // the vector loads and stores
+                _ => return true,
+            },
+            0b1010011 if funct5 == 0b01000 => [fmt, rs2 & 0b11],
+            0b1000011 | 0b1000111 | 0b1001011 | 0b1001111 | 0b1010011 => [fmt, fmt],
+            _ => return true,
+        };
+        let minimal = match opcode {
+            0b1010011 => match funct5 {
+                // fround and froundnx share the funct5 of the conversions
+                0b01000 => rs2 < 0b100,
+                0b11100 | 0b11110 => funct3 == 0 && rs2 == 0,
+                _ => false,
+            },
+            _ => true,
+        };
+        formats.iter().all(|fmt| match fmt {
+            0b00 => self.f,
+            0b01 => self.d,
+            0b10 => self.zfh || self.zfhmin && minimal,
+            _ => false,
+        })
+    }
+
+    // Of the instructions of Zkn without Zbkb, Zbkc and Zbkx, Zkne has the AES encryption, Zknd
+    // the decryption, both the key schedule, and Zknh the SHA-2 instructions.
+    pub fn allows_zkn(&self, instruction: u32) -> bool {
+        if instruction & 0b1111111 == 0b0010011 {
+            return match instruction >> 20 {
+                0x300 => self.zknd,
+                0x310..=0x31a => self.zknd || self.zkne,
+                _ => self.zknh,
+            };
+        }
+        let funct7 = instruction >> 25;
+        match funct7 {
+            0b0011001 | 0b0011011 => self.zkne,
+            0b0011101 | 0b0011111 => self.zknd,
+            0b0111111 => self.zknd || self.zkne,
+            0b0101000..=0b0101111 => self.zknh,
+            // the AES instructions of RV32, with the byte select in the upper bits
+            _ => match funct7 & 0b11111 {
+                0b10001 | 0b10011 => self.zkne,
+                _ => self.zknd,
+            },
+        }
+    }
+
+    // Of the instructions of Zks without Zbkb, Zbkc and Zbkx, Zksed has the SM4 and Zksh the SM3
+    // instructions.
+    pub fn allows_zks(&self, instruction: u32) -> bool {
+        if instruction & 0b1111111 == 0b0010011 {
+            self.zksh
+        } else {
+            self.zksed
+        }
+    }
+
+    // cbo.zero belongs to Zicboz, and the other cache-block operations to Zicbom.
+    pub fn allows_cache_block(&self, instruction: u32) -> bool {
+        if instruction >> 20 == 0b100 {
+            self.zicboz
+        } else {
+            self.zicbom
+        }
+    }
+}
+
+// The ISA string, such as rv64ifd_zicsr.
+impl fmt::Display for Isa {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "rv{}", self.xlen.bits())?;
+        let extensions = self.extensions();
+        let (letters, others): (Vec<&str>, Vec<&str>) =
+            extensions.iter().partition(|name| name.len() == 1);
+        write!(f, "{}", letters.concat())?;
+        others.iter().try_for_each(|name| write!(f, "_{}", name))
+    }
+}
@@ -0,0 +1,37 @@
+pub mod aplic;
+mod bitops;
+mod boot_rom;
+pub mod bus;
+mod cause;
+pub mod cli;
+pub mod clint;
+mod compressed;
+pub mod config;
+mod console;
+mod csr;
+mod decoder;
+mod elf;
+mod entropy;
+mod executor;
+mod f;
+pub mod fdt;
+mod float;
+mod hart;
+pub mod htif;
+pub mod imsic;
+mod instruction;
+pub mod isa;
+pub mod memory;
+mod mmu;
+mod mode;
+mod pc;
+pub mod plic;
+pub mod riscv_tests;
+mod sbi;
+pub mod simulator;
+mod trap_handler;
+pub mod uart;
+mod v;
+pub mod virtio;
+mod x;
+pub mod xlen;
@@ -1,135 +1,202 @@
-mod bitops;
-mod cause;
-mod csr;
-mod decoder;
-mod executor;
-mod instruction;
-mod memory;
-mod mode;
-mod pc;
-mod trap_handler;
-mod x;
-
 use std::{
-    fs::File,
-    io::{BufReader, Read, Result},
+    env,
+    fs::{self, File},
+    io::{Error, ErrorKind, Result},
+    path::Path,
+    process,
 };
 
-use crate::{
-    csr::ControlAndStatusRegister,
-    decoder::{
-        privileged::PrivilegedDecoder, rv32i::Rv32iDecoder, rv64i::Rv64iDecoder,
-        zicsr::ZicsrDecoder, Decoder,
-    },
-    executor::{
-        privileged::PrivilegedExecutor, rv32i::Rv32iExecutor, rv64i::Rv64iExecutor,
-        zicsr::ZicsrExecutor, Executor,
+use minimum_five::{
+    aplic::{Aplic, DEFAULT_APLIC_SOURCES},
+    cli::{Command, ConsolePort, NetBackend, Options, TestOptions, Virtio, USAGE},
+    clint::Clint,
+    fdt::device_tree,
+    htif::{Htif, DEFAULT_TOHOST_ADDRESS},
+    imsic::{Imsic, DEFAULT_IMSIC_IDS},
+    plic::Plic,
+    riscv_tests::{report, run_directory, Outcome},
+    simulator::{Exit, Simulator},
+    uart::{Uart, UART_IRQ},
+    virtio::{
+        Backend, Block, Console, Filesystem, Loopback, Net, Port, Rng, Socket, VirtioDevice,
+        VirtioMmio, DEFAULT_MAC, VIRTIO_MMIO_BASE, VIRTIO_MMIO_IRQ, VIRTIO_MMIO_STRIDE,
     },
-    memory::{Memory, MEMORY_BASE_ADDRESS},
-    mode::PrivilegeMode,
-    pc::ProgramCounter,
-    trap_handler::handle_cause,
-    x::IntegerRegister,
 };
 
-fn main() -> Result<()> {
-    let file = File::open("./tests/isa/rv64ui-p-add.bin")?;
-    let mut simulator = Simulator::default();
-    simulator.load(file)?;
-    let terminator = |simulator: &Simulator| {
-        let value = simulator.memory.load(0x80001000);
-        if value == 1 {
-            Some(1)
-        } else {
-            None
+// The exit status when the instruction limit is reached, as timeout(1) does.
+const LIMIT_EXIT_STATUS: i32 = 124;
+
+fn main() {
+    let command = match Command::parse(env::args().skip(1)) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("minimum-five: {}", message);
+            eprintln!("Try 'minimum-five --help' for more information.");
+            process::exit(2);
         }
     };
-    let result = simulator.run(terminator);
-    println!("{}", if result == 1 { "PASS" } else { "FAIL" });
-    Ok(())
-}
-
-#[derive(Default)]
-pub struct Simulator {
-    prv: PrivilegeMode,
-    pc: ProgramCounter,
-    x: IntegerRegister,
-    csr: ControlAndStatusRegister,
-    memory: Memory,
+    let status = match command {
+        Command::Help => {
+            print!("{}", USAGE);
+            Ok(0)
+        }
+        Command::Run(options) => run(*options),
+        Command::RiscvTests(options) => run_riscv_tests(options),
+    };
+    match status {
+        Ok(status) => process::exit(status),
+        Err(error) => {
+            eprintln!("minimum-five: {}", error);
+            process::exit(2);
+        }
+    }
 }
 
-impl Simulator {
-    pub fn load(&mut self, file: File) -> Result<()> {
-        let buffer = BufReader::new(file);
-        for (address, byte) in buffer.bytes().enumerate() {
-            self.memory
-                .store(address as u64 + MEMORY_BASE_ADDRESS, byte? as u32);
+// Runs the program and returns the exit status of the simulator.
+fn run(options: Options) -> Result<i32> {
+    let mut simulator =
+        Simulator::new(&options.config).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+    let initrd = match &options.initrd {
+        Some(path) => {
+            let image = fs::read(path).map_err(|e| in_file(path, e))?;
+            Some(
+                simulator
+                    .load_initrd(&image)
+                    .map_err(|e| in_file(path, e))?,
+            )
         }
-        Ok(())
+        None => None,
+    };
+    // the program and the kernel are loaded over the device tree, should they reach the end of
+    // the memory
+    let dtb = match &options.dtb {
+        Some(path) => fs::read(path).map_err(|e| in_file(path, e))?,
+        None => device_tree(&options, initrd),
+    };
+    if let Some(path) = &options.dump_dtb {
+        fs::write(path, &dtb).map_err(|e| in_file(path, e))?;
+        return Ok(0);
     }
-
-    fn run(&mut self, terminator: impl Fn(&Simulator) -> Option<u64>) -> u64 {
-        while self.pc.read() < self.memory.size() {
-            let address = self.pc.read();
-            let instruction = self.memory.load(address);
-
-            let result = if let Some(decoded) = PrivilegedDecoder::decode(instruction) {
-                PrivilegedExecutor::execute(
-                    decoded,
-                    &self.prv,
-                    &mut self.pc,
-                    &mut self.x,
-                    &mut self.csr,
-                    &mut self.memory,
-                )
-            } else if let Some(decoded) = Rv32iDecoder::decode(instruction) {
-                Rv32iExecutor::execute(
-                    decoded,
-                    &self.prv,
-                    &mut self.pc,
-                    &mut self.x,
-                    &mut self.csr,
-                    &mut self.memory,
-                )
-            } else if let Some(decoded) = Rv64iDecoder::decode(instruction) {
-                Rv64iExecutor::execute(
-                    decoded,
-                    &self.prv,
-                    &mut self.pc,
-                    &mut self.x,
-                    &mut self.csr,
-                    &mut self.memory,
-                )
-            } else if let Some(decoded) = ZicsrDecoder::decode(instruction) {
-                ZicsrExecutor::execute(
-                    decoded,
-                    &self.prv,
-                    &mut self.pc,
-                    &mut self.x,
-                    &mut self.csr,
-                    &mut self.memory,
-                )
-            } else {
-                // end the loop when unable to decode the instruction
-                break;
-            };
-
-            if let Some(result) = terminator(self) {
-                return result;
+    simulator.set_device_tree(&dtb)?;
+    simulator.load(File::open(&options.program)?, options.format)?;
+    if let Some(path) = &options.kernel {
+        let image = fs::read(path).map_err(|e| in_file(path, e))?;
+        simulator
+            .load_kernel(&image, options.kernel_offset)
+            .map_err(|e| in_file(path, e))?;
+    }
+    let tohost = options
+        .tohost
+        .or(simulator.symbol("tohost"))
+        .unwrap_or(DEFAULT_TOHOST_ADDRESS);
+    let fromhost = options.fromhost.or(simulator.symbol("fromhost"));
+    let mut htif = Htif::new(tohost, fromhost, options.arguments);
+    htif.set_host_files(options.host_files);
+    simulator.set_htif(htif);
+    simulator.set_trace(options.trace);
+    let overlapping = |message| Error::new(ErrorKind::InvalidInput, message);
+    if let Some(base) = options.uart {
+        simulator
+            .attach(base, Box::new(Uart::stdio()), Some(UART_IRQ))
+            .map_err(overlapping)?;
+    }
+    if let Some(base) = options.clint {
+        let clint = Clint::new(options.config.harts, options.time_source);
+        simulator.attach_clint(base, clint).map_err(overlapping)?;
+    }
+    if let Some(base) = options.plic {
+        let plic = Plic::new(options.config.harts, options.plic_sources);
+        simulator.attach_plic(base, plic).map_err(overlapping)?;
+    }
+    if let Some(base) = options.aplic {
+        let aplic = Aplic::new(options.config.harts, DEFAULT_APLIC_SOURCES);
+        simulator.attach_aplic(base, aplic).map_err(overlapping)?;
+    }
+    if let Some(base) = options.imsic {
+        let imsic = Imsic::new(options.config.harts, DEFAULT_IMSIC_IDS);
+        simulator.attach_imsic(base, imsic).map_err(overlapping)?;
+    }
+    // the network devices without a MAC address take QEMU's, one after the other
+    let mut next_mac = DEFAULT_MAC;
+    for (slot, virtio) in options.virtio.iter().enumerate() {
+        let device: Box<dyn VirtioDevice> = match virtio {
+            Virtio::Block { image, read_only } => {
+                Box::new(Block::open(image, *read_only).map_err(|e| in_file(image, e))?)
             }
-
-            // handle the trap
-            if let Err(cause) = result {
-                let (prv, pc) =
-                    handle_cause(&cause, self.pc.read(), instruction, self.prv, &mut self.csr);
-                self.prv = prv;
-                self.pc.jump(pc);
+            Virtio::Net { backend, mac, pcap } => {
+                let backend: Box<dyn Backend> = match backend {
+                    NetBackend::Loopback => Box::<Loopback>::default(),
+                    NetBackend::Listen(path) => {
+                        Box::new(Socket::listen(path).map_err(|e| in_file(path, e))?)
+                    }
+                    NetBackend::Connect(path) => {
+                        Box::new(Socket::connect(path).map_err(|e| in_file(path, e))?)
+                    }
+                };
+                let mac = mac.unwrap_or_else(|| {
+                    let mac = next_mac;
+                    next_mac[5] = next_mac[5].wrapping_add(1);
+                    mac
+                });
+                let mut net = Net::new(backend, mac);
+                if let Some(path) = pcap {
+                    net.capture(path).map_err(|e| in_file(path, e))?;
+                }
+                Box::new(net)
             }
-            // increment the pc when the pc has not been updated
-            else if self.pc.read() == address {
-                self.pc.increment();
+            Virtio::Console { ports } => {
+                let ports = ports
+                    .iter()
+                    .map(|port| match port {
+                        ConsolePort::Stdio => Ok(Port::stdio("")),
+                        ConsolePort::Socket(path) => {
+                            let name = Path::new(path).file_stem().unwrap_or_default();
+                            Port::socket(&name.to_string_lossy(), path)
+                                .map_err(|e| in_file(path, e))
+                        }
+                    })
+                    .collect::<Result<_>>()?;
+                Box::new(Console::new(ports))
             }
-        }
-        0
+            Virtio::Rng { seed } => Box::new(Rng::new(*seed)),
+            Virtio::Filesystem { directory, tag } => {
+                Box::new(Filesystem::new(directory, tag).map_err(|e| in_file(directory, e))?)
+            }
+        };
+        let base = VIRTIO_MMIO_BASE + VIRTIO_MMIO_STRIDE * slot as u64;
+        let irq = VIRTIO_MMIO_IRQ + slot as u32;
+        simulator
+            .attach_virtio(base, VirtioMmio::new(device), irq)
+            .map_err(overlapping)?;
+    }
+    let exit = simulator.run(options.max_instructions);
+    if let Some(path) = &options.signature {
+        fs::write(path, simulator.signature(options.signature_granularity)?)?;
     }
+    Ok(match exit {
+        // the status is 8 bits wide, which could turn a failure into a success
+        Exit::Code(code) => code.min(255) as i32,
+        Exit::Limit => {
+            eprintln!("minimum-five: reached the instruction limit");
+            LIMIT_EXIT_STATUS
+        }
+    })
+}
+
+// Names the file that an error is about.
+fn in_file(path: &str, error: Error) -> Error {
+    Error::new(error.kind(), format!("{}: {}", path, error))
+}
+
+// Runs riscv-tests and returns 0 when every test passes.
+fn run_riscv_tests(options: TestOptions) -> Result<i32> {
+    let results = run_directory(
+        Path::new(&options.directory),
+        &options.config,
+        options.max_instructions,
+        options.tohost,
+    )?;
+    println!("{}", report(&results));
+    let passed = results.iter().all(|r| r.outcome == Outcome::Pass);
+    Ok(if passed { 0 } else { 1 })
 }
@@ -1,32 +1,186 @@
-pub const MEMORY_SIZE: u64 = 1024 * 1024;
-pub const MEMORY_BASE_ADDRESS: u64 = 0x8000_0000;
+use crate::{
+    cause::{Cause, Exception},
+    mmu::PAGE_SIZE,
+};
 
+pub const DEFAULT_MEMORY_SIZE: u64 = 1024 * 1024;
+pub const DEFAULT_MEMORY_BASE: u64 = 0x8000_0000;
+pub const DEFAULT_CACHE_BLOCK_SIZE: u64 = 64;
+
+// A range of physical addresses backed by RAM or ROM.
+#[derive(Clone)]
+pub struct Region {
+    pub base: u64,
+    pub data: Vec<u8>,
+    // stores to ROM raise access faults, though loaders may write it
+    pub writable: bool,
+}
+
+impl Region {
+    pub fn ram(base: u64, size: u64) -> Self {
+        Self {
+            base,
+            data: vec![0; size as usize],
+            writable: true,
+        }
+    }
+
+    pub fn rom(base: u64, data: Vec<u8>) -> Self {
+        Self {
+            base,
+            data,
+            writable: false,
+        }
+    }
+
+    // The address just past the end of the region.
+    pub fn end(&self) -> u64 {
+        self.base + self.data.len() as u64
+    }
+
+    fn contains(&self, address: u64, size: usize) -> bool {
+        address >= self.base
+            && address
+                .checked_add(size as u64)
+                .is_some_and(|end| end <= self.end())
+    }
+
+    pub fn overlaps(&self, other: &Region) -> bool {
+        self.base < other.end() && other.base < self.end()
+    }
+}
+
+// The physical memory map: regions of RAM and ROM, which must not overlap, and nothing in
+// between.
 pub struct Memory {
-    pub memory: Vec<u8>,
+    regions: Vec<Region>,
+    cache_block_size: u64,
 }
 
 impl Default for Memory {
     fn default() -> Self {
-        Self {
-            memory: vec![0; MEMORY_SIZE as usize],
-        }
+        Self::new(
+            vec![Region::ram(DEFAULT_MEMORY_BASE, DEFAULT_MEMORY_SIZE)],
+            DEFAULT_CACHE_BLOCK_SIZE,
+        )
+        .unwrap()
     }
 }
 
 impl Memory {
-    pub fn size(&self) -> u64 {
-        MEMORY_BASE_ADDRESS + MEMORY_SIZE
+    pub fn new(regions: Vec<Region>, cache_block_size: u64) -> Result<Self, String> {
+        Self::validate_cache_block_size(cache_block_size)?;
+        Ok(Self {
+            regions,
+            cache_block_size,
+        })
     }
 
-    pub fn load(&self, address: u64) -> u32 {
-        (0..4 as usize).fold(0, |acc, i| {
-            acc | (self.memory[(address - MEMORY_BASE_ADDRESS) as usize + i] as u32) << (8 * i)
-        })
+    // A cache block is a power of two no larger than a page.
+    pub fn validate_cache_block_size(size: u64) -> Result<(), String> {
+        if !size.is_power_of_two() || size > PAGE_SIZE {
+            return Err(format!(
+                "unsupported cache block size {}, which must be a power of two up to {}",
+                size, PAGE_SIZE
+            ));
+        }
+        Ok(())
     }
 
-    pub fn store(&mut self, address: u64, value: u32) {
-        for i in 0..4 as usize {
-            self.memory[(address - MEMORY_BASE_ADDRESS) as usize + i] = (value >> (i * 8)) as u8;
+    // Whether any region overlaps the addresses from `base` up to `end`.
+    pub fn overlaps(&self, base: u64, end: u64) -> bool {
+        self.regions.iter().any(|r| r.base < end && base < r.end())
+    }
+
+    // The region holding all of the `size` bytes at `address`. An access may not straddle two
+    // regions, even adjacent ones.
+    fn region(&self, address: u64, size: usize) -> Option<&Region> {
+        self.regions.iter().find(|r| r.contains(address, size))
+    }
+
+    fn writable_region(&mut self, address: u64, size: usize) -> Option<&mut Region> {
+        self.regions
+            .iter_mut()
+            .find(|r| r.writable && r.contains(address, size))
+    }
+
+    // Loads `size` bytes (1, 2, 4 or 8) in little-endian order.
+    pub fn load_bytes(&self, address: u64, size: usize) -> Result<u64, Cause> {
+        let bytes = self.load_slice(address, size)?;
+        Ok(bytes.iter().rev().fold(0, |acc, &b| acc << 8 | b as u64))
+    }
+
+    // Stores the low `size` bytes (1, 2, 4 or 8) of `value` in little-endian order.
+    pub fn store_bytes(&mut self, address: u64, size: usize, value: u64) -> Result<(), Cause> {
+        let bytes = value.to_le_bytes();
+        self.store_slice(address, &bytes[..size])
+    }
+
+    // Fetches the instruction at `address`, or the half of one.
+    pub fn fetch(&self, address: u64, size: usize) -> Result<u32, Cause> {
+        self.load_bytes(address, size)
+            .map(|instruction| instruction as u32)
+            .map_err(|_| Cause::Exception(Exception::InstructionAccessFault(address)))
+    }
+
+    pub fn load_slice(&self, address: u64, size: usize) -> Result<&[u8], Cause> {
+        let region = self
+            .region(address, size)
+            .ok_or(Cause::Exception(Exception::LoadAccessFault(address)))?;
+        let offset = (address - region.base) as usize;
+        Ok(&region.data[offset..offset + size])
+    }
+
+    pub fn store_slice(&mut self, address: u64, data: &[u8]) -> Result<(), Cause> {
+        let region = self
+            .writable_region(address, data.len())
+            .ok_or(Cause::Exception(Exception::StoreAmoAccessFault(address)))?;
+        let offset = (address - region.base) as usize;
+        region.data[offset..offset + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    // The `size` bytes at `address` as a loader sees them, which may be ROM.
+    fn image_slice(&mut self, address: u64, size: u64) -> Result<&mut [u8], Cause> {
+        // a size beyond usize is contained in no region
+        let size = usize::try_from(size).unwrap_or(usize::MAX);
+        let region = self
+            .regions
+            .iter_mut()
+            .find(|r| r.contains(address, size))
+            .ok_or(Cause::Exception(Exception::StoreAmoAccessFault(address)))?;
+        let offset = (address - region.base) as usize;
+        Ok(&mut region.data[offset..offset + size])
+    }
+
+    // Copies `data` to memory as a loader does, which may write ROM.
+    pub fn load_image(&mut self, address: u64, data: &[u8]) -> Result<(), Cause> {
+        self.image_slice(address, data.len() as u64)?
+            .copy_from_slice(data);
+        Ok(())
+    }
+
+    // Zeroes `size` bytes as a loader does for a BSS, in place rather than from a buffer of that
+    // size.
+    pub fn zero_image(&mut self, address: u64, size: u64) -> Result<(), Cause> {
+        self.image_slice(address, size)?.fill(0);
+        Ok(())
+    }
+
+    // Cache blocks are not modelled, so managing one only checks that it is accessible.
+    pub fn check_block(&self, address: u64) -> Result<(), Cause> {
+        let base = address & !(self.cache_block_size - 1);
+        match self.region(base, self.cache_block_size as usize) {
+            Some(_) => Ok(()),
+            None => Err(Cause::Exception(Exception::StoreAmoAccessFault(address))),
         }
     }
+
+    // Zeroes the cache block containing `address`.
+    pub fn zero_block(&mut self, address: u64) -> Result<(), Cause> {
+        let base = address & !(self.cache_block_size - 1);
+        let zeros = vec![0; self.cache_block_size as usize];
+        self.store_slice(base, &zeros)
+            .map_err(|_| Cause::Exception(Exception::StoreAmoAccessFault(address)))
+    }
 }
@@ -0,0 +1,196 @@
+use crate::{
+    cause::{Cause, Exception},
+    csr::{
+        ControlAndStatusRegister, MSTATUS, SATP, SATP_MODE_SV32, SATP_MODE_SV39, STATUS_MPP,
+        STATUS_MPRV, STATUS_MXR, STATUS_SUM,
+    },
+    memory::Memory,
+    mode::PrivilegeMode,
+    xlen::Xlen,
+};
+
+pub const PAGE_SIZE: u64 = 4096;
+
+// The bits of a page-table entry.
+const PTE_V: u64 = 1 << 0;
+const PTE_R: u64 = 1 << 1;
+const PTE_W: u64 = 1 << 2;
+const PTE_X: u64 = 1 << 3;
+const PTE_U: u64 = 1 << 4;
+const PTE_A: u64 = 1 << 6;
+const PTE_D: u64 = 1 << 7;
+// N, PBMT and the reserved bits of Sv39, as neither Svnapot nor Svpbmt is implemented
+const PTE_SV39_UPPER: u64 = 0x3ff << 54;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Access {
+    Fetch,
+    Load,
+    Store,
+}
+
+impl Access {
+    pub fn page_fault(self, address: u64) -> Cause {
+        Cause::Exception(match self {
+            Self::Fetch => Exception::InstructionPageFault(address),
+            Self::Load => Exception::LoadPageFault(address),
+            Self::Store => Exception::StoreAmoPageFault(address),
+        })
+    }
+
+    pub fn access_fault(self, address: u64) -> Cause {
+        Cause::Exception(match self {
+            Self::Fetch => Exception::InstructionAccessFault(address),
+            Self::Load => Exception::LoadAccessFault(address),
+            Self::Store => Exception::StoreAmoAccessFault(address),
+        })
+    }
+}
+
+// The schemes of virtual memory that satp can select besides Bare.
+#[derive(Clone, Copy, PartialEq)]
+enum Scheme {
+    Sv32,
+    Sv39,
+}
+
+impl Scheme {
+    fn levels(self) -> u32 {
+        match self {
+            Self::Sv32 => 2,
+            Self::Sv39 => 3,
+        }
+    }
+
+    // The bits of the virtual page number that each level indexes.
+    fn vpn_bits(self) -> u32 {
+        match self {
+            Self::Sv32 => 10,
+            Self::Sv39 => 9,
+        }
+    }
+
+    fn pte_size(self) -> usize {
+        match self {
+            Self::Sv32 => 4,
+            Self::Sv39 => 8,
+        }
+    }
+
+    fn ppn(self, pte: u64) -> u64 {
+        match self {
+            Self::Sv32 => pte >> 10 & 0x3f_ffff,
+            Self::Sv39 => pte >> 10 & 0xfff_ffff_ffff,
+        }
+    }
+}
+
+// The translation of the virtual addresses of a hart, as its satp, mstatus and privilege mode
+// set it at the start of a step.
+#[derive(Clone, Copy)]
+pub struct Translation {
+    scheme: Scheme,
+    // the physical address of the root page table
+    root: u64,
+    // the privilege mode of the fetches, and that of the loads and stores, which MPRV lowers
+    fetch_mode: PrivilegeMode,
+    data_mode: PrivilegeMode,
+    // whether S-mode may access the pages of U-mode, and load from the executable ones
+    sum: bool,
+    mxr: bool,
+}
+
+impl Translation {
+    // The translation of the hart, or None when satp selects Bare.
+    pub fn new(prv: PrivilegeMode, csr: &ControlAndStatusRegister) -> Option<Self> {
+        let satp = csr.read_field(SATP, &(0..63));
+        let (scheme, ppn) = match csr.xlen(&PrivilegeMode::Supervisor) {
+            Xlen::Rv32 if satp >> 31 & 1 == SATP_MODE_SV32 => (Scheme::Sv32, satp & 0x3f_ffff),
+            Xlen::Rv64 if satp >> 60 == SATP_MODE_SV39 => (Scheme::Sv39, satp & 0xfff_ffff_ffff),
+            _ => return None,
+        };
+        let data_mode = match (prv, csr.read_field(MSTATUS, &STATUS_MPRV)) {
+            (PrivilegeMode::Machine, 1) => {
+                PrivilegeMode::from_primitive(csr.read_field(MSTATUS, &STATUS_MPP))
+                    .unwrap_or(PrivilegeMode::User)
+            }
+            _ => prv,
+        };
+        Some(Self {
+            scheme,
+            root: ppn * PAGE_SIZE,
+            fetch_mode: prv,
+            data_mode,
+            sum: csr.read_field(MSTATUS, &STATUS_SUM) == 1,
+            mxr: csr.read_field(MSTATUS, &STATUS_MXR) == 1,
+        })
+    }
+
+    // Translates a virtual address by walking the page tables in the memory, and sets the
+    // accessed and dirty bits of the leaf as the hardware may.
+    pub fn translate(
+        &self,
+        memory: &mut Memory,
+        address: u64,
+        access: Access,
+    ) -> Result<u64, Cause> {
+        let mode = match access {
+            Access::Fetch => self.fetch_mode,
+            Access::Load | Access::Store => self.data_mode,
+        };
+        if mode == PrivilegeMode::Machine {
+            return Ok(address);
+        }
+        let fault = || access.page_fault(address);
+        // Sv39 addresses are the sign extension of their 39 bits
+        if self.scheme == Scheme::Sv39 && ((address as i64) << 25 >> 25) as u64 != address {
+            return Err(fault());
+        }
+        let bits = self.scheme.vpn_bits();
+        let vpn = |level: u32| address >> (12 + bits * level) & ((1 << bits) - 1);
+
+        let mut table = self.root;
+        for level in (0..self.scheme.levels()).rev() {
+            let pte_address = table + vpn(level) * self.scheme.pte_size() as u64;
+            let pte = memory
+                .load_bytes(pte_address, self.scheme.pte_size())
+                .map_err(|_| access.access_fault(address))?;
+            let reserved = self.scheme == Scheme::Sv39 && pte & PTE_SV39_UPPER != 0;
+            if pte & PTE_V == 0 || pte & (PTE_R | PTE_W) == PTE_W || reserved {
+                return Err(fault());
+            }
+            let ppn = self.scheme.ppn(pte);
+            // a pointer to the table of the next level
+            if pte & (PTE_R | PTE_X) == 0 {
+                table = ppn * PAGE_SIZE;
+                continue;
+            }
+
+            let permitted = match access {
+                Access::Fetch => pte & PTE_X != 0,
+                Access::Load => pte & PTE_R != 0 || self.mxr && pte & PTE_X != 0,
+                Access::Store => pte & PTE_W != 0,
+            };
+            let privileged = match mode {
+                PrivilegeMode::User => pte & PTE_U != 0,
+                _ => pte & PTE_U == 0 || self.sum && access != Access::Fetch,
+            };
+            // a superpage is aligned to its size
+            let offset_bits = 12 + bits * level;
+            let misaligned = ppn & ((1 << (bits * level)) - 1) != 0;
+            if !permitted || !privileged || misaligned {
+                return Err(fault());
+            }
+
+            let updated = pte | PTE_A | if access == Access::Store { PTE_D } else { 0 };
+            if updated != pte {
+                memory
+                    .store_bytes(pte_address, self.scheme.pte_size(), updated)
+                    .map_err(|_| access.access_fault(address))?;
+            }
+            // the page offset of a superpage takes in the lower parts of the virtual page number
+            return Ok((ppn * PAGE_SIZE) | address & ((1 << offset_bits) - 1));
+        }
+        Err(fault())
+    }
+}
@@ -1,23 +1,19 @@
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Default)]
 pub enum PrivilegeMode {
     User = 0b00,
     Supervisor = 0b01,
+    #[default]
     Machine = 0b11,
 }
 
-impl Default for PrivilegeMode {
-    fn default() -> Self {
-        PrivilegeMode::Machine
-    }
-}
-
 impl PrivilegeMode {
-    pub fn from_primitive(mode: u64) -> Self {
+    // The mode of an encoding, or None for the reserved one.
+    pub fn from_primitive(mode: u64) -> Option<Self> {
         match mode {
-            0 => Self::User,
-            0b01 => Self::Supervisor,
-            0b11 => Self::Machine,
-            _ => panic!(),
+            0 => Some(Self::User),
+            0b01 => Some(Self::Supervisor),
+            0b11 => Some(Self::Machine),
+            _ => None,
         }
     }
 }
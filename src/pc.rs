@@ -1,31 +1,60 @@
-use crate::memory::MEMORY_BASE_ADDRESS;
+use crate::xlen::Xlen;
 
 pub struct ProgramCounter {
     pc: u64,
+    jumped: bool,
+    // the length of the instruction being executed, 2 for a compressed one and 4 otherwise
+    length: u64,
+    // IALIGN in bytes, to which the instructions are aligned: 2 with C and 4 without
+    alignment: u64,
 }
 
-impl Default for ProgramCounter {
-    fn default() -> Self {
+impl ProgramCounter {
+    pub fn new(address: u64, compressed: bool) -> Self {
         Self {
-            pc: MEMORY_BASE_ADDRESS,
+            pc: address,
+            jumped: false,
+            length: 4,
+            alignment: if compressed { 2 } else { 4 },
         }
     }
-}
 
-impl ProgramCounter {
     pub fn read(&self) -> u64 {
         self.pc
     }
 
-    pub fn increment(&mut self) {
-        self.pc += 4;
+    // Continues at `address` rather than after the last instruction, as a reset or a return
+    // from a trap does.
+    pub fn reset(&mut self, address: u64) {
+        self.pc = address;
+        self.jumped = false;
     }
 
     pub fn jump(&mut self, address: u64) {
         self.pc = address;
+        self.jumped = true;
+    }
+
+    // Whether an instruction may start at `address`, as the target of a jump or a branch must.
+    pub fn aligned(&self, address: u64) -> bool {
+        address.is_multiple_of(self.alignment)
     }
 
-    pub fn jumpr(&mut self, relative_address: i64) {
-        self.pc = (self.pc as i64 + relative_address) as u64;
+    pub fn set_length(&mut self, length: u64) {
+        self.length = length;
+    }
+
+    // The address of the instruction after the one being executed, which jal and jalr link.
+    pub fn next_address(&self) -> u64 {
+        self.pc.wrapping_add(self.length)
+    }
+
+    // Moves to the next instruction unless the one just executed has jumped,
+    // which may have been to itself. The pc wraps around at XLEN.
+    pub fn advance(&mut self, xlen: Xlen) {
+        if !self.jumped {
+            self.pc = xlen.zero_extend(self.next_address());
+        }
+        self.jumped = false;
     }
 }
@@ -0,0 +1,229 @@
+use crate::{
+    bus::{BusError, Device, Platform},
+    cause::Interrupt,
+};
+
+// Where QEMU's virt machine places its PLIC, and the number of sources it has.
+pub const DEFAULT_PLIC_BASE: u64 = 0x0c00_0000;
+pub const DEFAULT_PLIC_SOURCES: u32 = 95;
+// The sources are numbered from 1, as 0 means that there is no interrupt to claim.
+pub const MAX_PLIC_SOURCES: u32 = 1023;
+
+pub const PLIC_SIZE: u64 = 0x400_0000;
+
+// The layout of SiFive's PLIC. The priorities and pending bits are indexed by source, and the
+// enable bits, thresholds and claim/complete registers by context.
+const PRIORITY_BASE: u64 = 0x0;
+const PENDING_BASE: u64 = 0x1000;
+const ENABLE_BASE: u64 = 0x2000;
+const ENABLE_STRIDE: u64 = 0x80;
+const CONTEXT_BASE: u64 = 0x20_0000;
+const CONTEXT_STRIDE: u64 = 0x1000;
+const THRESHOLD: u64 = 0x0;
+const CLAIM_COMPLETE: u64 = 0x4;
+
+// priorities and thresholds range from 0 to 7
+const PRIORITY_MASK: u32 = 0b111;
+
+// The platform-level interrupt controller: it routes the interrupt lines of the devices, as
+// sources, to the contexts of the harts, where each hart has a context for M-mode and one for
+// S-mode, as in QEMU's virt machine. A context is interrupted by the enabled pending sources with
+// a priority above its threshold.
+pub struct Plic {
+    sources: u32,
+    // by source, where source 0 is unused
+    priority: Vec<u32>,
+    // bit sets of sources, 32 to a word
+    pending: Vec<u32>,
+    claimed: Vec<u32>,
+    // by context
+    enable: Vec<Vec<u32>>,
+    threshold: Vec<u32>,
+    // whether each context has an interrupt to claim, kept up to date as the state changes
+    interrupt: Vec<bool>,
+    // the lines asserted at the last step
+    lines: Vec<u32>,
+}
+
+impl Plic {
+    pub fn new(harts: usize, sources: u32) -> Self {
+        let sources = sources.min(MAX_PLIC_SOURCES);
+        let words = sources as usize / 32 + 1;
+        let contexts = 2 * harts;
+        Self {
+            sources,
+            priority: vec![0; sources as usize + 1],
+            pending: vec![0; words],
+            claimed: vec![0; words],
+            enable: vec![vec![0; words]; contexts],
+            threshold: vec![0; contexts],
+            interrupt: vec![false; contexts],
+            lines: Vec::new(),
+        }
+    }
+
+    fn bit(set: &[u32], source: u32) -> bool {
+        (set[source as usize / 32] >> (source % 32)) & 1 == 1
+    }
+
+    fn set_bit(set: &mut [u32], source: u32, value: bool) {
+        let mask = 1 << (source % 32);
+        let word = &mut set[source as usize / 32];
+        *word = if value { *word | mask } else { *word & !mask };
+    }
+
+    // The enabled pending source of the highest priority above the threshold of the context, the
+    // lowest numbered one among equals.
+    fn best_source(&self, context: usize) -> Option<u32> {
+        (1..=self.sources)
+            .filter(|&source| {
+                Self::bit(&self.pending, source)
+                    && Self::bit(&self.enable[context], source)
+                    && self.priority[source as usize] > self.threshold[context]
+            })
+            .min_by_key(|&source| std::cmp::Reverse(self.priority[source as usize]))
+    }
+
+    // The gateways are level-triggered: a source is pending while its line is asserted, unless
+    // it has been claimed and not yet completed.
+    fn update(&mut self) {
+        for word in &mut self.pending {
+            *word = 0;
+        }
+        for &line in &self.lines {
+            if (1..=self.sources).contains(&line) && !Self::bit(&self.claimed, line) {
+                Self::set_bit(&mut self.pending, line, true);
+            }
+        }
+        for context in 0..self.interrupt.len() {
+            self.interrupt[context] = self.best_source(context).is_some();
+        }
+    }
+
+    fn claim(&mut self, context: usize) -> u32 {
+        let Some(source) = self.best_source(context) else {
+            return 0;
+        };
+        Self::set_bit(&mut self.pending, source, false);
+        Self::set_bit(&mut self.claimed, source, true);
+        self.update();
+        source
+    }
+
+    // The bits of a word of sources that exist.
+    fn implemented(&self, word: usize) -> u32 {
+        (0..32)
+            .filter(|bit| (1..=self.sources).contains(&(32 * word as u32 + bit)))
+            .fold(0, |mask, bit| mask | 1 << bit)
+    }
+
+    // A completion for a source that the context has not enabled is ignored.
+    fn complete(&mut self, context: usize, source: u32) {
+        if (1..=self.sources).contains(&source) && Self::bit(&self.enable[context], source) {
+            Self::set_bit(&mut self.claimed, source, false);
+        }
+    }
+
+    // The register at `offset`, which are all 32 bits wide.
+    fn register(&self, offset: u64) -> Option<Register> {
+        let words = self.pending.len() as u64;
+        let contexts = self.interrupt.len() as u64;
+        match offset {
+            PRIORITY_BASE.. if offset < PRIORITY_BASE + 4 * (self.sources as u64 + 1) => {
+                Some(Register::Priority((offset - PRIORITY_BASE) as u32 / 4))
+            }
+            PENDING_BASE.. if offset < PENDING_BASE + 4 * words => {
+                Some(Register::Pending((offset - PENDING_BASE) as usize / 4))
+            }
+            ENABLE_BASE.. if offset < ENABLE_BASE + ENABLE_STRIDE * contexts => {
+                let context = (offset - ENABLE_BASE) / ENABLE_STRIDE;
+                let word = (offset - ENABLE_BASE) % ENABLE_STRIDE / 4;
+                (word < words).then_some(Register::Enable(context as usize, word as usize))
+            }
+            CONTEXT_BASE.. if offset < CONTEXT_BASE + CONTEXT_STRIDE * contexts => {
+                let context = ((offset - CONTEXT_BASE) / CONTEXT_STRIDE) as usize;
+                match (offset - CONTEXT_BASE) % CONTEXT_STRIDE {
+                    THRESHOLD => Some(Register::Threshold(context)),
+                    CLAIM_COMPLETE => Some(Register::ClaimComplete(context)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+enum Register {
+    Priority(u32),
+    Pending(usize),
+    Enable(usize, usize),
+    Threshold(usize),
+    ClaimComplete(usize),
+}
+
+impl Device for Plic {
+    fn size(&self) -> u64 {
+        PLIC_SIZE
+    }
+
+    // The registers are accessed as 32-bit words, at the aligned address of the access.
+    fn read(&mut self, offset: u64, _: usize) -> Result<u64, BusError> {
+        let Some(register) = self.register(offset & !0b11) else {
+            // the reserved addresses read as zero
+            return Ok(0);
+        };
+        let value = match register {
+            Register::Priority(source) => self.priority[source as usize],
+            Register::Pending(word) => self.pending[word],
+            Register::Enable(context, word) => self.enable[context][word],
+            Register::Threshold(context) => self.threshold[context],
+            Register::ClaimComplete(context) => self.claim(context),
+        };
+        Ok(value as u64)
+    }
+
+    fn write(&mut self, offset: u64, _: usize, value: u64) -> Result<(), BusError> {
+        let Some(register) = self.register(offset & !0b11) else {
+            return Ok(());
+        };
+        let value = value as u32;
+        match register {
+            // source 0 does not exist
+            Register::Priority(0) => {}
+            Register::Priority(source) => self.priority[source as usize] = value & PRIORITY_MASK,
+            // the pending bits are read-only
+            Register::Pending(_) => {}
+            Register::Enable(context, word) => {
+                self.enable[context][word] = value & self.implemented(word)
+            }
+            Register::Threshold(context) => self.threshold[context] = value & PRIORITY_MASK,
+            Register::ClaimComplete(context) => self.complete(context, value),
+        }
+        self.update();
+        Ok(())
+    }
+}
+
+impl Platform for Plic {
+    fn set_interrupt_lines(&mut self, lines: &[u32]) {
+        // on most steps, no line is asserted
+        if lines.is_empty() && self.lines.is_empty() {
+            return;
+        }
+        if self.lines != lines {
+            self.lines = lines.to_vec();
+            self.update();
+        }
+    }
+
+    fn hart_interrupts(&self, hart: usize) -> u64 {
+        let (Some(&machine), Some(&supervisor)) = (
+            self.interrupt.get(2 * hart),
+            self.interrupt.get(2 * hart + 1),
+        ) else {
+            return 0;
+        };
+        (machine as u64) << Interrupt::MachineExternal.to_primitive()
+            | (supervisor as u64) << Interrupt::SupervisorExternal.to_primitive()
+    }
+}
@@ -0,0 +1,198 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Result,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::{
+    config::Config,
+    htif::{Htif, DEFAULT_TOHOST_ADDRESS},
+    simulator::{Exit, ImageFormat, Simulator},
+    xlen::Xlen,
+};
+
+// Well above the length of any test, so that a timeout means the test is stuck.
+pub const DEFAULT_STEP_LIMIT: u64 = 1_000_000;
+
+// The suites of the implemented extensions, both in the environment without virtual memory and
+// in the one that runs the tests in U-mode under Sv32 or Sv39. The others, such as rv64ssvnapot,
+// are not run.
+pub const SUITES: &[&str] = &[
+    "rv32ui", "rv64ui", "rv32um", "rv64um", "rv32ua", "rv64ua", "rv32uc", "rv64uc", "rv32uf",
+    "rv64uf", "rv32ud", "rv64ud", "rv32mi", "rv64mi", "rv32si", "rv64si",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Pass,
+    // the number of the failing test
+    Fail(u64),
+    Timeout,
+    // the test could not be loaded
+    Error(String),
+}
+
+pub struct TestResult {
+    // the file name without the extension, such as rv64ui-p-add
+    pub name: String,
+    pub outcome: Outcome,
+}
+
+impl TestResult {
+    // The suite that the test belongs to, such as rv64ui.
+    pub fn suite(&self) -> &str {
+        self.name.split('-').next().unwrap_or_default()
+    }
+}
+
+// Runs a test of riscv-tests in a fresh simulator. The XLEN is taken from the name of the test
+// and the extensions from `config`, whose memory grows to hold the image. The test writes its
+// result to `tohost`, or else to its tohost symbol, or else to the address found in its code.
+pub fn run_test(path: &Path, config: &Config, limit: u64, tohost: Option<u64>) -> TestResult {
+    let name = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let mut config = config.clone();
+    config.isa.xlen = if name.starts_with("rv32") {
+        Xlen::Rv32
+    } else {
+        Xlen::Rv64
+    };
+    let outcome = match fs::read(path) {
+        Ok(image) => run_image(&image, &config, limit, tohost),
+        Err(error) => Outcome::Error(error.to_string()),
+    };
+    TestResult { name, outcome }
+}
+
+fn run_image(image: &[u8], config: &Config, limit: u64, tohost: Option<u64>) -> Outcome {
+    let mut config = config.clone();
+    config.memory_size = config
+        .memory_size
+        .max((image.len() as u64).next_power_of_two());
+    let mut simulator = match Simulator::new(&config) {
+        Ok(simulator) => simulator,
+        Err(error) => return Outcome::Error(error),
+    };
+    if let Err(error) = simulator.load_bytes(image, ImageFormat::Auto) {
+        return Outcome::Error(error.to_string());
+    }
+    let tohost = tohost
+        .or(simulator.symbol("tohost"))
+        .or_else(|| find_tohost(image, config.memory_base))
+        .unwrap_or(DEFAULT_TOHOST_ADDRESS);
+    simulator.set_htif(Htif::new(tohost, None, Vec::new()));
+    match simulator.run(Some(limit)) {
+        Exit::Code(0) => Outcome::Pass,
+        Exit::Code(test) => Outcome::Fail(test),
+        Exit::Limit => Outcome::Timeout,
+    }
+}
+
+// The binaries made by objcopy have no symbols, but the write_tohost of the environment without
+// virtual memory writes gp to tohost with an auipc t5 followed by a sw gp, offset(t5), which
+// gives the address of tohost. The linker places tohost on the page after the code, so the tests
+// with more than a page of code have it at a later address than the default one, where that of
+// the environment with virtual memory always is. The pair may follow compressed instructions, so
+// it is looked for at every halfword.
+fn find_tohost(image: &[u8], base: u64) -> Option<u64> {
+    let word = |i: usize| u32::from_le_bytes(image[i..i + 4].try_into().unwrap());
+    (0..image.len().saturating_sub(7)).step_by(2).find_map(|i| {
+        let (auipc, sw) = (word(i), word(i + 4));
+        // auipc t5 and sw gp, offset(t5)
+        if auipc & 0xfff != 0xf17 || sw & 0x01fff07f != 0x003f2023 {
+            return None;
+        }
+        let upper = (auipc & 0xffff_f000) as i32 as i64;
+        let offset = ((sw >> 25) << 5 | (sw >> 7) & 0b11111) as i32;
+        // sign-extend the 12-bit offset
+        let offset = (offset << 20 >> 20) as i64;
+        Some((base + i as u64).wrapping_add((upper + offset) as u64))
+    })
+}
+
+// Runs every test of the suites in `directory`, spread over the cores of the host, and returns the
+// results in the order of the names.
+pub fn run_directory(
+    directory: &Path,
+    config: &Config,
+    limit: u64,
+    tohost: Option<u64>,
+) -> Result<Vec<TestResult>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        let suite = path.file_name().map(|name| {
+            let name = name.to_string_lossy();
+            name.split('-').next().unwrap_or_default().to_string()
+        });
+        if path.is_file() && suite.is_some_and(|suite| SUITES.contains(&suite.as_str())) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::new());
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                while let Some(path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let result = run_test(path, config, limit, tohost);
+                    results.lock().unwrap().push(result);
+                }
+            });
+        }
+    });
+    let mut results = results.into_inner().unwrap();
+    results.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(results)
+}
+
+// Tabulates the passes, failures and timeouts of each suite, followed by the tests that have not
+// passed.
+pub fn report(results: &[TestResult]) -> String {
+    let mut suites: BTreeMap<&str, [usize; 3]> = BTreeMap::new();
+    for result in results {
+        let counts = suites.entry(result.suite()).or_default();
+        match result.outcome {
+            Outcome::Pass => counts[0] += 1,
+            Outcome::Fail(_) | Outcome::Error(_) => counts[1] += 1,
+            Outcome::Timeout => counts[2] += 1,
+        }
+    }
+    let total = suites.values().fold([0; 3], |acc, counts| {
+        [acc[0] + counts[0], acc[1] + counts[1], acc[2] + counts[2]]
+    });
+
+    let mut report = format!(
+        "{:<16}{:>8}{:>8}{:>8}\n",
+        "suite", "pass", "fail", "timeout"
+    );
+    for (suite, [pass, fail, timeout]) in suites
+        .iter()
+        .map(|(s, c)| (*s, *c))
+        .chain([("total", total)])
+    {
+        report += &format!("{:<16}{:>8}{:>8}{:>8}\n", suite, pass, fail, timeout);
+    }
+    for result in results {
+        let line = match &result.outcome {
+            Outcome::Pass => continue,
+            Outcome::Fail(test) => format!("FAIL     {} (test {})", result.name, test),
+            Outcome::Timeout => format!("TIMEOUT  {}", result.name),
+            Outcome::Error(error) => format!("ERROR    {}: {}", result.name, error),
+        };
+        report += &format!("\n{}", line);
+    }
+    report
+}
@@ -0,0 +1,270 @@
+use std::{
+    io::{self, Write},
+    sync::mpsc::Receiver,
+};
+
+use crate::{
+    cause::Interrupt,
+    config::Config,
+    console::spawn_stdin_reader,
+    csr::{MCOUNTEREN, MEDELEG, MENVCFG, MENVCFG_STCE, MIDELEG, MSTATUS, STATUS_SIE, STIMECMP},
+    hart::Hart,
+    memory::Memory,
+    mode::PrivilegeMode,
+    xlen::Xlen,
+};
+
+// The version of the SBI specification implemented, 2.0, and the implementation, which has no
+// number in the registry of implementation IDs.
+const SPEC_VERSION: u64 = 2 << 24;
+const IMPL_ID: u64 = 0x4d35;
+const IMPL_VERSION: u64 = 1;
+
+// The extensions, by EID.
+const EXT_BASE: u64 = 0x10;
+const EXT_TIME: u64 = 0x5449_4d45;
+const EXT_IPI: u64 = 0x73_5049;
+const EXT_RFENCE: u64 = 0x5246_4e43;
+const EXT_HSM: u64 = 0x48_534d;
+const EXT_SRST: u64 = 0x5352_5354;
+const EXT_DBCN: u64 = 0x4442_434e;
+const EXTENSIONS: [u64; 7] = [
+    EXT_BASE, EXT_TIME, EXT_IPI, EXT_RFENCE, EXT_HSM, EXT_SRST, EXT_DBCN,
+];
+
+// The errors returned in a0.
+const SBI_SUCCESS: i64 = 0;
+const SBI_ERR_NOT_SUPPORTED: i64 = -2;
+const SBI_ERR_INVALID_PARAM: i64 = -3;
+const SBI_ERR_ALREADY_AVAILABLE: i64 = -6;
+
+// The states of a hart, as hart_get_status reports them.
+const HSM_STARTED: u64 = 0;
+const HSM_STOPPED: u64 = 1;
+
+// The suspend types of hart_suspend.
+const SUSPEND_RETENTIVE: u64 = 0;
+const SUSPEND_NON_RETENTIVE: u64 = 0x8000_0000;
+
+// The reset reasons of system_reset, of which a system failure makes the simulator fail.
+const RESET_REASON_SYSTEM_FAILURE: u64 = 1;
+
+// What firmware delegates to S-mode: the misaligned fetches, breakpoints, environment calls from
+// U-mode and page faults, and the supervisor interrupts.
+const DELEGATED_EXCEPTIONS: u64 = 1 << 0 | 1 << 3 | 1 << 8 | 1 << 12 | 1 << 13 | 1 << 15;
+const DELEGATED_INTERRUPTS: u64 = 1 << 1 | 1 << 5 | 1 << 9;
+
+// The registers of a call: the arguments in a0 to a5, the FID in a6 and the EID in a7, and the
+// error and value returned in a0 and a1.
+const A0: usize = 10;
+const A1: usize = 11;
+const A6: usize = 16;
+const A7: usize = 17;
+
+// Serves the environment calls that S-mode makes to the SBI in place of M-mode firmware, such as
+// OpenSBI, so that a kernel runs on the harts from the start. The harts start in S-mode, the
+// first one at the entry point of the payload, and the others once it starts them through HSM.
+pub struct Sbi {
+    started: Vec<bool>,
+    // the time at which the supervisor timer interrupt of each hart becomes pending, without Sstc
+    deadlines: Vec<Option<u64>>,
+    sstc: bool,
+    // the time without a CLINT, which counts the steps as TimeSource::Instructions does
+    steps: u64,
+    // the characters typed for the debug console, once it has been read
+    input: Option<Receiver<u8>>,
+}
+
+impl Sbi {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            started: (0..config.harts).map(|id| id == 0).collect(),
+            deadlines: vec![None; config.harts],
+            sstc: config.isa.sstc,
+            steps: 0,
+            input: None,
+        }
+    }
+
+    pub fn started(&self, hart: usize) -> bool {
+        self.started[hart]
+    }
+
+    // The time of the timer and of rdtime when no CLINT keeps it.
+    pub fn time(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn step(&mut self) {
+        self.steps = self.steps.wrapping_add(1);
+    }
+
+    // Starts a hart in S-mode at `address` with `opaque` in a1, as it would come from M-mode
+    // firmware: with the supervisor interrupts and the usual exceptions delegated, the counters
+    // readable, and, with Sstc, stimecmp left to S-mode.
+    pub fn start(&mut self, id: usize, hart: &mut Hart, address: u64, opaque: u64) {
+        self.started[id] = true;
+        self.deadlines[id] = None;
+        let csr = hart.csr();
+        csr.csrrw(MEDELEG, DELEGATED_EXCEPTIONS);
+        csr.csrrw(MIDELEG, DELEGATED_INTERRUPTS);
+        csr.csrrw(MCOUNTEREN, u64::MAX);
+        csr.write_field(MSTATUS, &STATUS_SIE, 0);
+        if self.sstc {
+            csr.write_field(MENVCFG, &MENVCFG_STCE, 1);
+        }
+        hart.resume(PrivilegeMode::Supervisor, address);
+        hart.set_boot_arguments(id as u64, opaque);
+    }
+
+    // Raises the supervisor timer interrupt of a hart once its deadline has passed.
+    pub fn tick(&mut self, id: usize, hart: &mut Hart, time: u64) {
+        if self.deadlines[id].is_some_and(|deadline| time >= deadline) {
+            self.deadlines[id] = None;
+            hart.csr().set_pending(Interrupt::SupervisorTimer, true);
+        }
+    }
+
+    // Serves the call that hart `id` has made, and returns the exit code once the system has
+    // been shut down or reset.
+    pub fn call(&mut self, id: usize, harts: &mut [Hart], memory: &mut Memory) -> Option<u64> {
+        let hart = &harts[id];
+        let xlen = hart.xlen();
+        let (extension, function) = (hart.register(A7), hart.register(A6));
+        let a: Vec<u64> = (A0..A6)
+            .map(|r| xlen.zero_extend(hart.register(r)))
+            .collect();
+        // a 64-bit argument, which RV32 splits across two registers
+        let wide = |low: u64, high: u64| match xlen {
+            Xlen::Rv32 => high << 32 | low,
+            Xlen::Rv64 => low,
+        };
+        let result = match (extension, function) {
+            (EXT_BASE, 0) => Ok(SPEC_VERSION),
+            (EXT_BASE, 1) => Ok(IMPL_ID),
+            (EXT_BASE, 2) => Ok(IMPL_VERSION),
+            (EXT_BASE, 3) => Ok(EXTENSIONS.contains(&a[0]) as u64),
+            // mvendorid, marchid and mimpid, which the harts leave at zero
+            (EXT_BASE, 4..=6) => Ok(0),
+            (EXT_TIME, 0) => {
+                self.set_timer(id, &mut harts[id], wide(a[0], a[1]));
+                Ok(0)
+            }
+            (EXT_IPI, 0) => self.harts_in_mask(a[0], a[1], xlen).map(|targets| {
+                for target in targets {
+                    harts[target]
+                        .csr()
+                        .set_pending(Interrupt::SupervisorSoftware, true);
+                }
+                0
+            }),
+            // without caches or a TLB, as the page tables are walked on every access, there is
+            // nothing to fence
+            (EXT_RFENCE, 0..=2) => self.harts_in_mask(a[0], a[1], xlen).map(|_| 0),
+            (EXT_HSM, 0) => self.hart_start(harts, a[0], a[1], a[2]),
+            (EXT_HSM, 1) => {
+                self.started[id] = false;
+                Ok(0)
+            }
+            (EXT_HSM, 2) => match self.started.get(a[0] as usize) {
+                Some(true) => Ok(HSM_STARTED),
+                Some(false) => Ok(HSM_STOPPED),
+                None => Err(SBI_ERR_INVALID_PARAM),
+            },
+            // the interrupts are checked before each instruction, so a retentive suspend returns
+            // at once, as wfi does, and a non-retentive one resumes at once
+            (EXT_HSM, 3) => match a[0] {
+                SUSPEND_RETENTIVE => Ok(0),
+                SUSPEND_NON_RETENTIVE => {
+                    self.start(id, &mut harts[id], a[1], a[2]);
+                    return None;
+                }
+                _ => Err(SBI_ERR_INVALID_PARAM),
+            },
+            // every reset stops the simulator, which has nothing to reboot
+            (EXT_SRST, 0) if a[0] <= 2 => {
+                return Some((a[1] == RESET_REASON_SYSTEM_FAILURE) as u64);
+            }
+            (EXT_SRST, 0) => Err(SBI_ERR_INVALID_PARAM),
+            (EXT_DBCN, 0) => memory
+                .load_slice(wide(a[1], a[2]), a[0] as usize)
+                .map(|data| {
+                    write_console(data);
+                    data.len() as u64
+                })
+                .map_err(|_| SBI_ERR_INVALID_PARAM),
+            (EXT_DBCN, 1) => self.read_console(memory, wide(a[1], a[2]), a[0] as usize),
+            (EXT_DBCN, 2) => {
+                write_console(&[a[0] as u8]);
+                Ok(0)
+            }
+            _ => Err(SBI_ERR_NOT_SUPPORTED),
+        };
+        let (error, value) = match result {
+            Ok(value) => (SBI_SUCCESS, value),
+            Err(error) => (error, 0),
+        };
+        let hart = &mut harts[id];
+        hart.set_register(A0, error as u64);
+        hart.set_register(A1, value);
+        None
+    }
+
+    // With Sstc, the timer is stimecmp, as S-mode could write it itself.
+    fn set_timer(&mut self, id: usize, hart: &mut Hart, time: u64) {
+        if self.sstc {
+            hart.csr().csrrw(STIMECMP, time);
+            return;
+        }
+        self.deadlines[id] = Some(time);
+        hart.csr().set_pending(Interrupt::SupervisorTimer, false);
+    }
+
+    // The harts that a mask selects, from hart `base`, or all of them for a base of -1.
+    fn harts_in_mask(&self, mask: u64, base: u64, xlen: Xlen) -> Result<Vec<usize>, i64> {
+        let count = self.started.len() as u64;
+        if base == xlen.zero_extend(u64::MAX) {
+            return Ok((0..self.started.len()).collect());
+        }
+        (0..xlen.bits() as u64)
+            .filter(|bit| mask >> bit & 1 == 1)
+            .map(|bit| match base.checked_add(bit) {
+                Some(hart) if hart < count => Ok(hart as usize),
+                _ => Err(SBI_ERR_INVALID_PARAM),
+            })
+            .collect()
+    }
+
+    fn hart_start(
+        &mut self,
+        harts: &mut [Hart],
+        id: u64,
+        address: u64,
+        opaque: u64,
+    ) -> Result<u64, i64> {
+        let Some(hart) = harts.get_mut(id as usize) else {
+            return Err(SBI_ERR_INVALID_PARAM);
+        };
+        if self.started[id as usize] {
+            return Err(SBI_ERR_ALREADY_AVAILABLE);
+        }
+        self.start(id as usize, hart, address, opaque);
+        Ok(0)
+    }
+
+    // Stores the characters typed so far, up to `size`, and returns how many there were.
+    fn read_console(&mut self, memory: &mut Memory, address: u64, size: usize) -> Result<u64, i64> {
+        let input = self.input.get_or_insert_with(spawn_stdin_reader);
+        let data: Vec<u8> = input.try_iter().take(size).collect();
+        memory
+            .store_slice(address, &data)
+            .map_err(|_| SBI_ERR_INVALID_PARAM)?;
+        Ok(data.len() as u64)
+    }
+}
+
+fn write_console(data: &[u8]) {
+    let mut stdout = io::stdout();
+    let _ = stdout.write_all(data);
+    let _ = stdout.flush();
+}
@@ -0,0 +1,359 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Error, ErrorKind, Read, Result},
+};
+
+use crate::{
+    aplic::Aplic,
+    boot_rom::{boot_rom, BOOT_ROM_BASE},
+    bus::{Bus, Device},
+    clint::Clint,
+    config::Config,
+    elf::{Elf, ELF_MAGIC},
+    hart::Hart,
+    htif::Htif,
+    imsic::Imsic,
+    memory::Memory,
+    plic::Plic,
+    sbi::Sbi,
+    virtio::VirtioMmio,
+    xlen::Xlen,
+};
+
+// Where OpenSBI's fw_jump jumps to on RV64, 2 MiB into the memory.
+pub const DEFAULT_KERNEL_OFFSET: u64 = 0x20_0000;
+
+// The furthest into the memory that the initrd goes.
+const INITRD_OFFSET: u64 = 128 << 20;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    // an ELF file if it starts with the ELF magic number, and a raw image otherwise
+    Auto,
+    Elf,
+    Binary,
+}
+
+// Why a run ended.
+pub enum Exit {
+    // the program exited through HTIF
+    Code(u64),
+    // the instruction limit was reached
+    Limit,
+}
+
+pub struct Simulator {
+    config: Config,
+    harts: Vec<Hart>,
+    bus: Bus,
+    symbols: HashMap<String, u64>,
+    htif: Option<Htif>,
+    // in RV32, whether the low word of tohost has been written since the last command
+    tohost_low: bool,
+    sbi: Option<Sbi>,
+    trace: bool,
+    // where the harts start, and the addresses of the device tree and the kernel passed to
+    // them, if any
+    entry: u64,
+    fdt: u64,
+    kernel: u64,
+}
+
+impl Simulator {
+    // Fails if the configuration is invalid, such as when two regions overlap.
+    pub fn new(config: &Config) -> std::result::Result<Self, String> {
+        config.validate()?;
+        let regions = config.memory_map()?;
+        Ok(Self {
+            config: config.clone(),
+            harts: (0..config.harts)
+                .map(|id| Hart::new(id as u64, config))
+                .collect::<std::result::Result<_, _>>()?,
+            bus: Bus::new(Memory::new(regions, config.cache_block_size)?),
+            symbols: HashMap::new(),
+            htif: None,
+            tohost_low: false,
+            sbi: config.sbi.then(|| Sbi::new(config)),
+            trace: false,
+            entry: config.memory_base,
+            fdt: 0,
+            kernel: 0,
+        })
+    }
+
+    pub fn load(&mut self, mut file: File, format: ImageFormat) -> Result<()> {
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        self.load_bytes(&bytes, format)
+    }
+
+    // Loads an ELF executable, or a raw binary image at the start of the memory.
+    pub fn load_bytes(&mut self, bytes: &[u8], format: ImageFormat) -> Result<()> {
+        if format == ImageFormat::Binary
+            || format == ImageFormat::Auto && !bytes.starts_with(ELF_MAGIC)
+        {
+            self.store(self.config.memory_base, bytes)?;
+            self.entry = self.config.memory_base;
+            return self.boot();
+        }
+
+        let elf = Elf::parse(bytes)?;
+        for segment in &elf.segments {
+            self.store(segment.address, &segment.data)?;
+            // zero the BSS, whose size the parser has checked against the file size
+            let filesz = segment.data.len() as u64;
+            self.bus
+                .memory_mut()
+                .zero_image(segment.address + filesz, segment.size - filesz)
+                .map_err(|_| outside(segment.address))?;
+        }
+        self.entry = elf.entry;
+        self.boot()?;
+        self.symbols = elf.symbols;
+        Ok(())
+    }
+
+    // Places a device tree blob at the end of the memory, on an 8-byte boundary as Linux requires,
+    // and passes its address to the harts in a1. Returns the address.
+    pub fn set_device_tree(&mut self, dtb: &[u8]) -> Result<u64> {
+        let end = self.config.memory_base + self.config.memory_size;
+        let address = end
+            .checked_sub(dtb.len() as u64)
+            .filter(|&address| address >= self.config.memory_base)
+            .ok_or(Error::new(
+                ErrorKind::InvalidData,
+                "the device tree does not fit in the memory",
+            ))?
+            & !7;
+        self.store(address, dtb)?;
+        self.fdt = address;
+        self.boot()?;
+        Ok(address)
+    }
+
+    // Loads the image of a kernel at `offset` into the memory, for firmware such as OpenSBI to
+    // jump to, and passes its address to fw_dynamic. Returns the address.
+    pub fn load_kernel(&mut self, image: &[u8], offset: u64) -> Result<u64> {
+        let address = self.config.memory_base + offset;
+        self.store(address, image)?;
+        self.kernel = address;
+        self.boot()?;
+        Ok(address)
+    }
+
+    // Loads an initial ramdisk halfway into the memory, or 128 MiB into a larger one, as QEMU
+    // does, far enough from the kernel to survive its decompression. Returns its start and end,
+    // which the device tree gives to the kernel.
+    pub fn load_initrd(&mut self, image: &[u8]) -> Result<(u64, u64)> {
+        let start = self.config.memory_base + (self.config.memory_size / 2).min(INITRD_OFFSET);
+        let end = start + image.len() as u64;
+        if end > self.config.memory_base + self.config.memory_size {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "the initrd does not fit in the memory",
+            ));
+        }
+        self.store(start, image)?;
+        Ok((start, end))
+    }
+
+    // Every hart starts at the entry point, and tells itself apart by mhartid. Without the boot
+    // ROM, the registers that it would set are set directly. With the SBI of the simulator, the
+    // first hart starts at the entry point in S-mode, and the others wait to be started.
+    fn boot(&mut self) -> Result<()> {
+        if let Some(sbi) = &mut self.sbi {
+            sbi.start(0, &mut self.harts[0], self.entry, self.fdt);
+            return Ok(());
+        }
+        if self.config.boot_rom {
+            return self.store(
+                BOOT_ROM_BASE,
+                &boot_rom(self.config.isa.xlen, self.entry, self.fdt, self.kernel),
+            );
+        }
+        for (id, hart) in self.harts.iter_mut().enumerate() {
+            hart.reset_pc(self.entry);
+            hart.set_boot_arguments(id as u64, self.fdt);
+        }
+        Ok(())
+    }
+
+    fn store(&mut self, address: u64, data: &[u8]) -> Result<()> {
+        self.bus
+            .memory_mut()
+            .load_image(address, data)
+            .map_err(|_| outside(address))
+    }
+
+    // Maps a device at `base`, with its interrupt line wired to `irq`.
+    pub fn attach(
+        &mut self,
+        base: u64,
+        device: Box<dyn Device>,
+        irq: Option<u32>,
+    ) -> std::result::Result<(), String> {
+        self.bus.attach(base, device, irq)
+    }
+
+    // Map the devices of the platform, which reach past their registers: the CLINT and the
+    // interrupt controllers interrupt the harts directly, and the virtio transports access the
+    // memory.
+    pub fn attach_clint(&mut self, base: u64, clint: Clint) -> std::result::Result<(), String> {
+        self.bus.attach_platform(base, Box::new(clint), None)
+    }
+
+    pub fn attach_plic(&mut self, base: u64, plic: Plic) -> std::result::Result<(), String> {
+        self.bus.attach_platform(base, Box::new(plic), None)
+    }
+
+    pub fn attach_aplic(&mut self, base: u64, aplic: Aplic) -> std::result::Result<(), String> {
+        self.bus.attach_platform(base, Box::new(aplic), None)
+    }
+
+    pub fn attach_imsic(&mut self, base: u64, imsic: Imsic) -> std::result::Result<(), String> {
+        self.bus.attach_platform(base, Box::new(imsic), None)
+    }
+
+    pub fn attach_virtio(
+        &mut self,
+        base: u64,
+        transport: VirtioMmio,
+        irq: u32,
+    ) -> std::result::Result<(), String> {
+        self.bus
+            .attach_platform(base, Box::new(transport), Some(irq))
+    }
+
+    pub fn set_htif(&mut self, htif: Htif) {
+        if self.config.isa.xlen == Xlen::Rv32 {
+            self.bus.watch(htif.tohost());
+        }
+        self.htif = Some(htif);
+    }
+
+    // Prints each executed instruction to stderr, with the symbols of the program as labels.
+    pub fn set_trace(&mut self, trace: bool) {
+        self.trace = trace;
+    }
+
+    // Looks up the address of a symbol of the loaded ELF executable.
+    pub fn symbol(&self, name: &str) -> Option<u64> {
+        self.symbols.get(name).copied()
+    }
+
+    // The memory from begin_signature to end_signature in the format of the architectural tests:
+    // one hexadecimal number of `granularity` bytes per line, most significant digit first.
+    pub fn signature(&self, granularity: usize) -> Result<String> {
+        let symbol = |name| {
+            self.symbol(name).ok_or(Error::new(
+                ErrorKind::NotFound,
+                format!("the program has no {} symbol", name),
+            ))
+        };
+        let (begin, end) = (symbol("begin_signature")?, symbol("end_signature")?);
+        let data = self
+            .bus
+            .memory()
+            .load_slice(begin, end.saturating_sub(begin) as usize)
+            .map_err(|_| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("the signature at {:#x} is outside the memory", begin),
+                )
+            })?;
+        Ok(data
+            .chunks(granularity)
+            .map(|word| {
+                let digits: String = word.iter().rev().map(|b| format!("{:02x}", b)).collect();
+                digits + "\n"
+            })
+            .collect())
+    }
+
+    // Runs the harts in turn, one instruction at a time, until the program exits through HTIF
+    // or each hart has run `limit` instructions.
+    pub fn run(&mut self, limit: Option<u64>) -> Exit {
+        let labels: HashMap<u64, &str> = match self.trace {
+            true => self
+                .symbols
+                .iter()
+                .map(|(name, &address)| (address, name.as_str()))
+                .collect(),
+            false => HashMap::new(),
+        };
+        let mut steps = 0;
+        loop {
+            if limit.is_some_and(|limit| steps >= limit) {
+                return Exit::Limit;
+            }
+            let time = self.bus.time().or_else(|| self.sbi.as_ref().map(Sbi::time));
+            let mut calls = Vec::new();
+            for (id, hart) in self.harts.iter_mut().enumerate() {
+                if let Some(sbi) = &mut self.sbi {
+                    if !sbi.started(id) {
+                        continue;
+                    }
+                    sbi.tick(id, hart, time.unwrap_or(0));
+                }
+                hart.set_interrupts(self.bus.hart_interrupts(id));
+                if let Some(time) = time {
+                    hart.set_time(time);
+                }
+                let pc = hart.pc();
+                let instruction = hart.step(&mut self.bus);
+                if self.trace {
+                    if let Some(label) = labels.get(&pc) {
+                        eprintln!("{}:", label);
+                    }
+                    match instruction {
+                        Some(instruction) => {
+                            eprintln!("core {:>3}: {:#018x} ({:#010x})", id, pc, instruction)
+                        }
+                        None => eprintln!("core {:>3}: {:#018x} (trap)", id, pc),
+                    }
+                }
+                if hart.take_environment_call() {
+                    calls.push(id);
+                }
+            }
+            if let Some(sbi) = &mut self.sbi {
+                for id in calls {
+                    if let Some(code) = sbi.call(id, &mut self.harts, self.bus.memory_mut()) {
+                        return Exit::Code(code);
+                    }
+                }
+                sbi.step();
+            }
+            steps += 1;
+            self.bus.tick();
+
+            // in RV32 the target writes tohost in halves, the low one first, and the command is
+            // complete once the high one is written. The older riscv-tests only write the low one,
+            // over and over, which completes the command the second time.
+            let written = match self.config.isa.xlen {
+                Xlen::Rv64 => true,
+                Xlen::Rv32 => {
+                    let (low, high) = self.bus.take_watched();
+                    let written = high || low && self.tohost_low;
+                    self.tohost_low = !written && (self.tohost_low || low);
+                    written
+                }
+            };
+            if let Some(code) = self
+                .htif
+                .as_mut()
+                .and_then(|h| h.tick(self.bus.memory_mut(), written))
+            {
+                return Exit::Code(code);
+            }
+        }
+    }
+}
+
+fn outside(address: u64) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("{:#x} is outside the memory", address),
+    )
+}
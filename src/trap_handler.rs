@@ -1,12 +1,12 @@
 use std::ops::Range;
 
 use crate::{
-    cause::{Cause, Exception},
+    cause::{Cause, Exception, ExceptionReturn, Interrupt},
     csr::{
         ControlAndStatusRegister, MCAUSE, MEDELEG, MEPC, MIDELEG, MSTATUS, MTVAL, MTVEC, SCAUSE,
-        SEDELEG, SEPC, SIDELEG, SSTATUS, STATUS_MIE, STATUS_MPIE, STATUS_MPP, STATUS_SIE,
-        STATUS_SPIE, STATUS_SPP, STATUS_UIE, STATUS_UPIE, STVAL, STVEC, UCAUSE, UEPC, USTATUS,
-        UTVAL, UTVEC,
+        SEDELEG, SEPC, SIDELEG, SSTATUS, STATUS_MIE, STATUS_MPIE, STATUS_MPP, STATUS_MPRV,
+        STATUS_SIE, STATUS_SPIE, STATUS_SPP, STATUS_UIE, STATUS_UPIE, STVAL, STVEC, UCAUSE, UEPC,
+        USTATUS, UTVAL, UTVEC,
     },
     mode::PrivilegeMode,
 };
@@ -58,39 +58,47 @@ fn select_status_field(
     }
 }
 
-fn read_status_field(
-    csr: &mut ControlAndStatusRegister,
-    address: u64,
-    field: &Range<usize>,
-) -> u64 {
-    let status = csr.csrrs(address, 0);
-    let length = (field.end - field.start) as u64;
-    let mask = !((2 ^ length) - 1);
-    (status >> field.start) & mask
-}
-
-fn update_status_field(
-    csr: &mut ControlAndStatusRegister,
-    address: u64,
-    field: &Range<usize>,
-    value: u64,
-) {
-    let length = (field.end - field.start) as u64;
-    let mask = !((2 ^ length) - 1);
-    let shifted_value = value << field.start;
-    csr.csrrc(address, mask);
-    csr.csrrs(address, shifted_value);
+fn select_tval(cause: &Cause, pc_address: u64, faulting_instruction: u32) -> u64 {
+    match cause {
+        Cause::Exception(Exception::IllegalInstruction) => faulting_instruction as u64,
+        Cause::Exception(Exception::Breakpoint) => pc_address,
+        Cause::Exception(Exception::InstructionAddressMisaligned(address))
+        | Cause::Exception(Exception::InstructionAccessFault(address))
+        | Cause::Exception(Exception::LoadAddressMisaligned(address))
+        | Cause::Exception(Exception::LoadAccessFault(address))
+        | Cause::Exception(Exception::StoreAmoAddressMisaligned(address))
+        | Cause::Exception(Exception::StoreAmoAccessFault(address))
+        | Cause::Exception(Exception::InstructionPageFault(address))
+        | Cause::Exception(Exception::LoadPageFault(address))
+        | Cause::Exception(Exception::StoreAmoPageFault(address)) => *address,
+        _ => 0,
+    }
 }
 
-fn select_tval(cause: &Cause, faulting_instruction: u32) -> u64 {
-    if let Cause::Exception(exception) = cause {
-        match exception {
-            Exception::IllegalInstruction => faulting_instruction as u64,
-            _ => 0,
-        }
-    } else {
-        0
+// The interrupt to take before the next instruction, if any: the pending and enabled one of the
+// highest priority among those that the privilege mode does not mask. An interrupt handled in a
+// more privileged mode is always taken, one handled in the current mode only when its xIE bit
+// allows it, and one handled in a less privileged mode never.
+pub fn pending_interrupt(prv: PrivilegeMode, csr: &ControlAndStatusRegister) -> Option<Cause> {
+    let pending = csr.enabled_interrupts();
+    if pending == 0 {
+        return None;
+    }
+    let delegated = csr.read_field(MIDELEG, &(0..63));
+    let enabled = |mode: PrivilegeMode, ie: Range<usize>| {
+        (prv as u64) < mode as u64 || prv == mode && csr.read_field(MSTATUS, &ie) == 1
+    };
+    let mut enabled_interrupts = 0;
+    if enabled(PrivilegeMode::Machine, STATUS_MIE) {
+        enabled_interrupts |= pending & !delegated;
+    }
+    if enabled(PrivilegeMode::Supervisor, STATUS_SIE) {
+        enabled_interrupts |= pending & delegated;
     }
+    Interrupt::PRIORITY
+        .into_iter()
+        .find(|interrupt| (enabled_interrupts >> interrupt.to_primitive()) & 1 == 1)
+        .map(Cause::Interrupt)
 }
 
 fn handle_trap(
@@ -103,7 +111,8 @@ fn handle_trap(
     let next_privilege_mode = delegated_privilege_mode(csr, cause);
     // set cause register
     let cause_address = select_address(&next_privilege_mode, MCAUSE, SCAUSE, UCAUSE);
-    csr.csrrw(cause_address, cause.to_primitive());
+    let interrupt = (cause.is_interrupt() as u64) << (csr.xlen(&next_privilege_mode).bits() - 1);
+    csr.csrrw(cause_address, interrupt | cause.to_primitive());
 
     // set exception program counter
     let epc_address = select_address(&next_privilege_mode, MEPC, SEPC, UEPC);
@@ -111,36 +120,30 @@ fn handle_trap(
 
     // set trap value register
     let tval_address = select_address(&next_privilege_mode, MTVAL, STVAL, UTVAL);
-    let tval = select_tval(cause, instruction);
+    let tval = select_tval(cause, pc_address, instruction);
     csr.csrrw(tval_address, tval);
 
     // set previous privilege
     let status_address = select_address(&next_privilege_mode, MSTATUS, SSTATUS, USTATUS);
     match next_privilege_mode {
-        PrivilegeMode::Machine => update_status_field(
-            csr,
-            status_address,
-            &STATUS_MPP,
-            current_privilege_mode as u64,
-        ),
-        PrivilegeMode::Supervisor => update_status_field(
-            csr,
-            status_address,
-            &STATUS_SPP,
-            current_privilege_mode as u64,
-        ),
+        PrivilegeMode::Machine => {
+            csr.write_field(status_address, &STATUS_MPP, current_privilege_mode as u64)
+        }
+        PrivilegeMode::Supervisor => {
+            csr.write_field(status_address, &STATUS_SPP, current_privilege_mode as u64)
+        }
         PrivilegeMode::User => {}
     }
 
     // set previous interrupt enable
     let ie_field = select_status_field(&next_privilege_mode, STATUS_MIE, STATUS_SIE, STATUS_UIE);
-    let ie = read_status_field(csr, status_address, &ie_field);
+    let ie = csr.read_field(status_address, &ie_field);
     let pie_field =
         select_status_field(&next_privilege_mode, STATUS_MPIE, STATUS_SPIE, STATUS_UPIE);
-    update_status_field(csr, status_address, &pie_field, ie);
+    csr.write_field(status_address, &pie_field, ie);
 
     // disable interrupt enable
-    update_status_field(csr, status_address, &ie_field, 0);
+    csr.write_field(status_address, &ie_field, 0);
 
     // set pc to trap-vector base-address register
     let tvec_address = select_address(&next_privilege_mode, MTVEC, STVEC, UTVEC);
@@ -162,30 +165,33 @@ fn handle_exception_return(
         STATUS_UPIE,
     );
     let ie_field = select_status_field(&current_privilege_mode, STATUS_MIE, STATUS_SIE, STATUS_UIE);
-    let pie = read_status_field(csr, status_address, &pie_field);
-    update_status_field(csr, status_address, &ie_field, pie);
+    let pie = csr.read_field(status_address, &pie_field);
+    csr.write_field(status_address, &ie_field, pie);
 
     // set 1 to previous interrupt enable
-    update_status_field(csr, status_address, &pie_field, 1);
+    csr.write_field(status_address, &pie_field, 1);
 
     // read previous privilege
     let pp = match current_privilege_mode {
-        PrivilegeMode::Machine => {
-            PrivilegeMode::from_primitive(read_status_field(csr, status_address, &STATUS_MPP))
-        }
-        PrivilegeMode::Supervisor => {
-            PrivilegeMode::from_primitive(read_status_field(csr, status_address, &STATUS_SPP))
-        }
-        PrivilegeMode::User => PrivilegeMode::User,
+        PrivilegeMode::Machine => csr.read_field(status_address, &STATUS_MPP),
+        PrivilegeMode::Supervisor => csr.read_field(status_address, &STATUS_SPP),
+        PrivilegeMode::User => 0,
     };
+    // the writes of mstatus keep MPP from holding the reserved mode
+    let pp = PrivilegeMode::from_primitive(pp).unwrap_or(PrivilegeMode::User);
 
     // set 0 to previous privilege
     match current_privilege_mode {
-        PrivilegeMode::Machine => update_status_field(csr, status_address, &STATUS_MPP, 0),
-        PrivilegeMode::Supervisor => update_status_field(csr, status_address, &STATUS_SPP, 0),
+        PrivilegeMode::Machine => csr.write_field(status_address, &STATUS_MPP, 0),
+        PrivilegeMode::Supervisor => csr.write_field(status_address, &STATUS_SPP, 0),
         PrivilegeMode::User => {}
     };
 
+    // returning to a less privileged mode ends the loads and stores that MPRV makes at MPP
+    if pp != PrivilegeMode::Machine {
+        csr.write_field(MSTATUS, &STATUS_MPRV, 0);
+    }
+
     // read exception program counter
     let epc_address = select_address(&current_privilege_mode, MEPC, SEPC, UEPC);
     let epc = csr.csrrs(epc_address, 0);
@@ -201,7 +207,13 @@ pub fn handle_cause(
     csr: &mut ControlAndStatusRegister,
 ) -> (PrivilegeMode, u64) {
     match cause {
-        Cause::ExceptionReturn(_) => handle_exception_return(current_privilege_mode, csr),
+        // sret returns from S-mode even when M-mode runs it
+        Cause::ExceptionReturn(ExceptionReturn::Machine) => {
+            handle_exception_return(PrivilegeMode::Machine, csr)
+        }
+        Cause::ExceptionReturn(ExceptionReturn::Supervisor) => {
+            handle_exception_return(PrivilegeMode::Supervisor, csr)
+        }
         _ => handle_trap(cause, pc_address, instruction, current_privilege_mode, csr),
     }
 }
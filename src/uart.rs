@@ -0,0 +1,210 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+    sync::mpsc::Receiver,
+};
+
+use crate::{
+    bus::{BusError, Device},
+    console::{spawn_stdin_reader, RawMode},
+};
+
+// Where QEMU's virt machine places its UART, and the interrupt line it is wired to.
+pub const DEFAULT_UART_BASE: u64 = 0x1000_0000;
+pub const UART_IRQ: u32 = 10;
+
+pub const UART_SIZE: u64 = 0x100;
+
+// Registers, one byte apart. DLL and DLM replace RBR/THR and IER while LCR.DLAB is set.
+const RBR_THR_DLL: u64 = 0;
+const IER_DLM: u64 = 1;
+const IIR_FCR: u64 = 2;
+const LCR: u64 = 3;
+const MCR: u64 = 4;
+const LSR: u64 = 5;
+const MSR: u64 = 6;
+const SCR: u64 = 7;
+
+const IER_RDI: u8 = 1 << 0;
+const IER_THRI: u8 = 1 << 1;
+
+// IIR identifies the pending interrupt of the highest priority.
+const IIR_NO_INTERRUPT: u8 = 0x01;
+const IIR_THRI: u8 = 0x02;
+const IIR_RDI: u8 = 0x04;
+const IIR_FIFO_ENABLED: u8 = 0xc0;
+
+const FCR_ENABLE_FIFO: u8 = 1 << 0;
+const FCR_CLEAR_RECEIVER: u8 = 1 << 1;
+
+const LCR_DLAB: u8 = 1 << 7;
+
+const MCR_LOOP: u8 = 1 << 4;
+
+const LSR_DATA_READY: u8 = 1 << 0;
+// the transmitter is always empty, since each byte is sent as soon as it is written
+const LSR_TRANSMITTER_EMPTY: u8 = 0x60;
+
+// DCD, DSR and CTS: a modem is connected and ready
+const MSR_CONNECTED: u8 = 0xb0;
+
+// An NS16550A, which receives from `input` and transmits to `output` at no particular baud rate.
+pub struct Uart {
+    input: Receiver<u8>,
+    output: Box<dyn Write>,
+    receiver: VecDeque<u8>,
+    ier: u8,
+    fcr: u8,
+    lcr: u8,
+    mcr: u8,
+    scr: u8,
+    dll: u8,
+    dlm: u8,
+    // the transmitter holding register has emptied since IIR last reported it
+    thr_empty: bool,
+    _raw_mode: Option<RawMode>,
+}
+
+impl Uart {
+    pub fn new(input: Receiver<u8>, output: Box<dyn Write>) -> Self {
+        Self {
+            input,
+            output,
+            receiver: VecDeque::new(),
+            ier: 0,
+            fcr: 0,
+            lcr: 0,
+            mcr: 0,
+            scr: 0,
+            dll: 0,
+            dlm: 0,
+            thr_empty: false,
+            _raw_mode: None,
+        }
+    }
+
+    // Wired to the terminal, which stays in raw mode as long as the UART lives.
+    pub fn stdio() -> Self {
+        let raw_mode = RawMode::enable();
+        Self {
+            _raw_mode: raw_mode,
+            ..Self::new(spawn_stdin_reader(), Box::new(io::stdout()))
+        }
+    }
+
+    fn dlab(&self) -> bool {
+        self.lcr & LCR_DLAB != 0
+    }
+
+    fn pending_interrupt(&self) -> Option<u8> {
+        if self.ier & IER_RDI != 0 && !self.receiver.is_empty() {
+            Some(IIR_RDI)
+        } else if self.ier & IER_THRI != 0 && self.thr_empty {
+            Some(IIR_THRI)
+        } else {
+            None
+        }
+    }
+
+    fn transmit(&mut self, byte: u8) {
+        if self.mcr & MCR_LOOP != 0 {
+            self.receiver.push_back(byte);
+        } else {
+            let _ = self.output.write_all(&[byte]);
+            let _ = self.output.flush();
+        }
+        self.thr_empty = true;
+    }
+
+    // In loopback mode, the modem outputs of MCR come back as the inputs of MSR.
+    fn msr(&self) -> u8 {
+        if self.mcr & MCR_LOOP == 0 {
+            return MSR_CONNECTED;
+        }
+        let (dtr, rts, out1, out2) = (
+            self.mcr & 1,
+            self.mcr >> 1 & 1,
+            self.mcr >> 2 & 1,
+            self.mcr >> 3 & 1,
+        );
+        out2 << 7 | out1 << 6 | dtr << 5 | rts << 4
+    }
+}
+
+impl Device for Uart {
+    fn size(&self) -> u64 {
+        UART_SIZE
+    }
+
+    // Accesses wider than a byte read or write the register at their address.
+    fn read(&mut self, offset: u64, _: usize) -> Result<u64, BusError> {
+        let value = match offset {
+            RBR_THR_DLL if self.dlab() => self.dll,
+            RBR_THR_DLL => self.receiver.pop_front().unwrap_or(0),
+            IER_DLM if self.dlab() => self.dlm,
+            IER_DLM => self.ier,
+            IIR_FCR => {
+                let fifo = match self.fcr & FCR_ENABLE_FIFO {
+                    0 => 0,
+                    _ => IIR_FIFO_ENABLED,
+                };
+                let pending = self.pending_interrupt();
+                // reading IIR acknowledges an empty transmitter
+                if pending == Some(IIR_THRI) {
+                    self.thr_empty = false;
+                }
+                fifo | pending.unwrap_or(IIR_NO_INTERRUPT)
+            }
+            LCR => self.lcr,
+            MCR => self.mcr,
+            LSR => {
+                let ready = match self.receiver.is_empty() {
+                    true => 0,
+                    false => LSR_DATA_READY,
+                };
+                LSR_TRANSMITTER_EMPTY | ready
+            }
+            MSR => self.msr(),
+            SCR => self.scr,
+            _ => 0,
+        };
+        Ok(value as u64)
+    }
+
+    fn write(&mut self, offset: u64, _: usize, value: u64) -> Result<(), BusError> {
+        let value = value as u8;
+        match offset {
+            RBR_THR_DLL if self.dlab() => self.dll = value,
+            RBR_THR_DLL => self.transmit(value),
+            IER_DLM if self.dlab() => self.dlm = value,
+            IER_DLM => {
+                // enabling the interrupt reports the transmitter, which is empty
+                if value & IER_THRI != 0 && self.ier & IER_THRI == 0 {
+                    self.thr_empty = true;
+                }
+                self.ier = value & 0x0f;
+            }
+            IIR_FCR => {
+                if value & FCR_CLEAR_RECEIVER != 0 {
+                    self.receiver.clear();
+                }
+                self.fcr = value & !(FCR_CLEAR_RECEIVER | 1 << 2);
+            }
+            LCR => self.lcr = value,
+            MCR => self.mcr = value & 0x1f,
+            SCR => self.scr = value,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn tick(&mut self) {
+        while let Ok(byte) = self.input.try_recv() {
+            self.receiver.push_back(byte);
+        }
+    }
+
+    fn interrupt(&self) -> bool {
+        self.pending_interrupt().is_some()
+    }
+}
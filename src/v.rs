@@ -0,0 +1,86 @@
+pub const DEFAULT_VLEN: usize = 128;
+pub const DEFAULT_ELEN: usize = 64;
+// The largest VLEN that the V extension allows.
+pub const MAX_VLEN: usize = 65536;
+
+pub struct VectorRegister {
+    vlen: usize,
+    elen: usize,
+    v: Vec<u8>,
+}
+
+impl Default for VectorRegister {
+    fn default() -> Self {
+        Self::new(DEFAULT_VLEN, DEFAULT_ELEN).unwrap()
+    }
+}
+
+impl VectorRegister {
+    pub fn new(vlen: usize, elen: usize) -> Result<Self, String> {
+        Self::validate(vlen, elen)?;
+        Ok(Self {
+            vlen,
+            elen,
+            v: vec![0; 32 * vlen / 8],
+        })
+    }
+
+    // An ELEN of 32 or 64, and a VLEN that is a power of two from ELEN up to the limit of the V
+    // extension.
+    pub fn validate(vlen: usize, elen: usize) -> Result<(), String> {
+        if elen != 32 && elen != 64 {
+            return Err(format!("unsupported ELEN {}", elen));
+        }
+        if !vlen.is_power_of_two() || !(elen..=MAX_VLEN).contains(&vlen) {
+            return Err(format!(
+                "unsupported VLEN {}, which must be a power of two from ELEN to {}",
+                vlen, MAX_VLEN
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn vlen(&self) -> usize {
+        self.vlen
+    }
+
+    pub fn elen(&self) -> usize {
+        self.elen
+    }
+
+    pub fn vlenb(&self) -> usize {
+        self.vlen / 8
+    }
+
+    // Reads element `index` of width `eew` bits from the register group starting at `register`.
+    pub fn read(&self, register: usize, index: usize, eew: usize) -> u64 {
+        let bytes = eew / 8;
+        let offset = register * self.vlenb() + index * bytes;
+        self.v[offset..offset + bytes]
+            .iter()
+            .rev()
+            .fold(0, |acc, &b| acc << 8 | b as u64)
+    }
+
+    pub fn write(&mut self, register: usize, index: usize, eew: usize, value: u64) {
+        let bytes = eew / 8;
+        let offset = register * self.vlenb() + index * bytes;
+        for (i, byte) in self.v[offset..offset + bytes].iter_mut().enumerate() {
+            *byte = (value >> (8 * i)) as u8;
+        }
+    }
+
+    pub fn read_mask(&self, register: usize, index: usize) -> bool {
+        (self.v[register * self.vlenb() + index / 8] >> (index % 8)) & 1 == 1
+    }
+
+    pub fn write_mask(&mut self, register: usize, index: usize, value: bool) {
+        let offset = register * self.vlenb() + index / 8;
+        let byte = &mut self.v[offset];
+        if value {
+            *byte |= 1 << (index % 8);
+        } else {
+            *byte &= !(1 << (index % 8));
+        }
+    }
+}
@@ -0,0 +1,494 @@
+use std::{
+    fs, io,
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::Path,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crate::{
+    bus::{BusError, Device, Platform},
+    memory::Memory,
+};
+
+mod blk;
+mod console;
+mod net;
+mod p9;
+mod rng;
+
+pub use blk::Block;
+pub use console::{Console, Port};
+pub use net::{Backend, Loopback, Net, Socket, DEFAULT_MAC};
+pub use p9::{Filesystem, DEFAULT_MOUNT_TAG};
+pub use rng::Rng;
+
+// Where QEMU's virt machine places its virtio-mmio transports, a page apart, and the interrupt
+// line of the first, which the others follow.
+pub const VIRTIO_MMIO_BASE: u64 = 0x1000_1000;
+pub const VIRTIO_MMIO_STRIDE: u64 = 0x1000;
+pub const VIRTIO_MMIO_IRQ: u32 = 1;
+pub const VIRTIO_MMIO_SLOTS: usize = 8;
+
+const MAGIC: u32 = 0x7472_6976;
+const VERSION: u32 = 2;
+// "QEMU", as its devices have
+const VENDOR_ID: u32 = 0x554d_4551;
+
+// The registers of the transport, which are 32 bits wide, followed by the configuration space of
+// the device.
+const MAGIC_VALUE: u64 = 0x000;
+const VERSION_REGISTER: u64 = 0x004;
+const DEVICE_ID: u64 = 0x008;
+const VENDOR_ID_REGISTER: u64 = 0x00c;
+const DEVICE_FEATURES: u64 = 0x010;
+const DEVICE_FEATURES_SEL: u64 = 0x014;
+const DRIVER_FEATURES: u64 = 0x020;
+const DRIVER_FEATURES_SEL: u64 = 0x024;
+const QUEUE_SEL: u64 = 0x030;
+const QUEUE_NUM_MAX: u64 = 0x034;
+const QUEUE_NUM: u64 = 0x038;
+const QUEUE_READY: u64 = 0x044;
+const QUEUE_NOTIFY: u64 = 0x050;
+const INTERRUPT_STATUS: u64 = 0x060;
+const INTERRUPT_ACK: u64 = 0x064;
+const STATUS: u64 = 0x070;
+const QUEUE_DESC_LOW: u64 = 0x080;
+const QUEUE_DESC_HIGH: u64 = 0x084;
+const QUEUE_DRIVER_LOW: u64 = 0x090;
+const QUEUE_DRIVER_HIGH: u64 = 0x094;
+const QUEUE_DEVICE_LOW: u64 = 0x0a0;
+const QUEUE_DEVICE_HIGH: u64 = 0x0a4;
+const SHM_LEN_LOW: u64 = 0x0b0;
+const SHM_LEN_HIGH: u64 = 0x0b4;
+const SHM_BASE_LOW: u64 = 0x0b8;
+const SHM_BASE_HIGH: u64 = 0x0bc;
+const CONFIG_GENERATION: u64 = 0x0fc;
+const CONFIG: u64 = 0x100;
+
+const STATUS_FEATURES_OK: u32 = 1 << 3;
+const STATUS_DRIVER_OK: u32 = 1 << 2;
+const STATUS_DEVICE_NEEDS_RESET: u32 = 1 << 6;
+
+const INTERRUPT_USED_BUFFER: u32 = 1 << 0;
+const INTERRUPT_CONFIG_CHANGE: u32 = 1 << 1;
+
+// the features of the transport, which the devices offer besides their own
+const VIRTIO_F_VERSION_1: u64 = 1 << 32;
+
+const QUEUE_SIZE_MAX: u16 = 256;
+
+const DESCRIPTOR_SIZE: u64 = 16;
+const VIRTQ_DESC_F_NEXT: u16 = 1 << 0;
+const VIRTQ_DESC_F_WRITE: u16 = 1 << 1;
+const VIRTQ_AVAIL_F_NO_INTERRUPT: u16 = 1 << 0;
+
+// The peer connected to a Unix socket that a device listens on, if any, to which it writes.
+type Peer = Arc<Mutex<Option<UnixStream>>>;
+
+// Listens at `path` for a peer, which may connect again once it has gone, and hands each
+// connection to `serve`, which reads from it until the peer closes it, and returns whether the
+// device still takes what comes. A socket left there by an earlier run is replaced.
+fn listen(path: &Path, serve: impl Fn(UnixStream) -> bool + Send + 'static) -> io::Result<Peer> {
+    if fs::metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    let peer = Arc::new(Mutex::new(None));
+    let connected = peer.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming().map_while(Result::ok) {
+            let Ok(writer) = stream.try_clone() else {
+                continue;
+            };
+            *connected.lock().unwrap() = Some(writer);
+            if !serve(stream) {
+                break;
+            }
+            *connected.lock().unwrap() = None;
+        }
+    });
+    Ok(peer)
+}
+
+// A queue holds a descriptor that is outside the memory, or a chain that is malformed, which
+// leaves the device needing a reset.
+#[derive(Debug)]
+pub struct QueueError;
+
+fn load(memory: &Memory, address: u64, size: usize) -> Result<u64, QueueError> {
+    memory.load_bytes(address, size).map_err(|_| QueueError)
+}
+
+fn store(memory: &mut Memory, address: u64, size: usize, value: u64) -> Result<(), QueueError> {
+    memory
+        .store_bytes(address, size, value)
+        .map_err(|_| QueueError)
+}
+
+// A chain of descriptors that the driver has made available: the buffers that the device reads,
+// followed by those that it writes, as addresses and lengths.
+pub struct Chain {
+    head: u16,
+    readable: Vec<(u64, u32)>,
+    writable: Vec<(u64, u32)>,
+}
+
+impl Chain {
+    // The bytes of the readable buffers, one after the other.
+    pub fn read(&self, memory: &Memory) -> Result<Vec<u8>, QueueError> {
+        let mut data = Vec::new();
+        for &(address, length) in &self.readable {
+            let bytes = memory
+                .load_slice(address, length as usize)
+                .map_err(|_| QueueError)?;
+            data.extend_from_slice(bytes);
+        }
+        Ok(data)
+    }
+
+    // The number of bytes that the writable buffers hold.
+    pub fn writable_len(&self) -> usize {
+        self.writable
+            .iter()
+            .map(|&(_, length)| length as usize)
+            .sum()
+    }
+
+    // Writes `data` at `offset` within the writable buffers, as if they were one, where the bytes
+    // past their end are dropped.
+    pub fn write_at(
+        &self,
+        memory: &mut Memory,
+        mut offset: usize,
+        mut data: &[u8],
+    ) -> Result<(), QueueError> {
+        for &(address, length) in &self.writable {
+            let length = length as usize;
+            if offset >= length {
+                offset -= length;
+                continue;
+            }
+            let count = data.len().min(length - offset);
+            memory
+                .store_slice(address + offset as u64, &data[..count])
+                .map_err(|_| QueueError)?;
+            data = &data[count..];
+            offset = 0;
+            if data.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+// A split virtqueue in the memory: a descriptor table, a driver area with the available ring and
+// a device area with the used ring.
+#[derive(Default)]
+pub struct Queue {
+    size: u16,
+    ready: bool,
+    desc: u64,
+    driver: u64,
+    device: u64,
+    // the free-running indices of the next chain to take from the available ring, and of the
+    // next one to put in the used ring
+    next_available: u16,
+    next_used: u16,
+    // chains have been used since the last interrupt, which the driver has not suppressed
+    interrupt: bool,
+}
+
+impl Queue {
+    // Takes the next chain that the driver has made available, if any.
+    pub fn pop(&mut self, memory: &Memory) -> Result<Option<Chain>, QueueError> {
+        if !self.ready || self.size == 0 {
+            return Ok(None);
+        }
+        let available = load(memory, self.driver + 2, 2)? as u16;
+        if available == self.next_available {
+            return Ok(None);
+        }
+        let slot = (self.next_available % self.size) as u64;
+        let head = load(memory, self.driver + 4 + 2 * slot, 2)? as u16;
+        self.next_available = self.next_available.wrapping_add(1);
+
+        let mut chain = Chain {
+            head,
+            readable: Vec::new(),
+            writable: Vec::new(),
+        };
+        let mut index = head;
+        // a chain longer than the table loops
+        for _ in 0..self.size {
+            if index >= self.size {
+                return Err(QueueError);
+            }
+            let descriptor = self.desc + DESCRIPTOR_SIZE * index as u64;
+            let address = load(memory, descriptor, 8)?;
+            let length = load(memory, descriptor + 8, 4)? as u32;
+            let flags = load(memory, descriptor + 12, 2)? as u16;
+            if flags & VIRTQ_DESC_F_WRITE != 0 {
+                chain.writable.push((address, length));
+            } else if chain.writable.is_empty() {
+                chain.readable.push((address, length));
+            } else {
+                // the readable buffers come first
+                return Err(QueueError);
+            }
+            if flags & VIRTQ_DESC_F_NEXT == 0 {
+                return Ok(Some(chain));
+            }
+            index = load(memory, descriptor + 14, 2)? as u16;
+        }
+        Err(QueueError)
+    }
+
+    // Gives a chain back to the driver, with the number of bytes written to its buffers.
+    pub fn push(
+        &mut self,
+        memory: &mut Memory,
+        chain: Chain,
+        written: usize,
+    ) -> Result<(), QueueError> {
+        let element = self.device + 4 + 8 * (self.next_used % self.size) as u64;
+        store(memory, element, 4, chain.head as u64)?;
+        store(memory, element + 4, 4, written as u64)?;
+        self.next_used = self.next_used.wrapping_add(1);
+        store(memory, self.device + 2, 2, self.next_used as u64)?;
+        let flags = load(memory, self.driver, 2)? as u16;
+        self.interrupt |= flags & VIRTQ_AVAIL_F_NO_INTERRUPT == 0;
+        Ok(())
+    }
+}
+
+// A device type of virtio, behind a virtio-mmio transport.
+pub trait VirtioDevice {
+    fn device_id(&self) -> u32;
+
+    // The feature bits of the device type that it offers to the driver.
+    fn features(&self) -> u64;
+
+    fn queues(&self) -> usize;
+
+    // The configuration space, in little-endian order.
+    fn config(&self) -> Vec<u8>;
+
+    // Processes the chains that the driver has made available in `queue` and notified.
+    fn notify(
+        &mut self,
+        queue: usize,
+        queues: &mut [Queue],
+        memory: &mut Memory,
+    ) -> Result<(), QueueError>;
+
+    // Moves what has come from the host into the queues, after each step once the driver is
+    // ready.
+    fn poll(&mut self, _queues: &mut [Queue], _memory: &mut Memory) -> Result<(), QueueError> {
+        Ok(())
+    }
+
+    // Returns the device to its initial state, as the driver does when it resets it.
+    fn reset(&mut self) {}
+}
+
+// The virtio-mmio transport, version 2, of a device.
+pub struct VirtioMmio {
+    device: Box<dyn VirtioDevice>,
+    device_features_sel: u32,
+    driver_features_sel: u32,
+    driver_features: u64,
+    queue_sel: u32,
+    queues: Vec<Queue>,
+    status: u32,
+    interrupt_status: u32,
+    // a bit set of the queues notified since the last step
+    notified: u64,
+}
+
+impl VirtioMmio {
+    pub fn new(device: Box<dyn VirtioDevice>) -> Self {
+        let queues = (0..device.queues()).map(|_| Queue::default()).collect();
+        Self {
+            device,
+            device_features_sel: 0,
+            driver_features_sel: 0,
+            driver_features: 0,
+            queue_sel: 0,
+            queues,
+            status: 0,
+            interrupt_status: 0,
+            notified: 0,
+        }
+    }
+
+    fn offered_features(&self) -> u64 {
+        VIRTIO_F_VERSION_1 | self.device.features()
+    }
+
+    fn reset(&mut self) {
+        self.device.reset();
+        self.device_features_sel = 0;
+        self.driver_features_sel = 0;
+        self.driver_features = 0;
+        self.queue_sel = 0;
+        self.queues.fill_with(Queue::default);
+        self.status = 0;
+        self.interrupt_status = 0;
+        self.notified = 0;
+    }
+
+    fn queue(&mut self) -> Option<&mut Queue> {
+        self.queues.get_mut(self.queue_sel as usize)
+    }
+
+    fn read_register(&mut self, offset: u64) -> u32 {
+        let half = |value: u64, high| (value >> (32 * high as u32)) as u32;
+        match offset {
+            MAGIC_VALUE => MAGIC,
+            VERSION_REGISTER => VERSION,
+            DEVICE_ID => self.device.device_id(),
+            VENDOR_ID_REGISTER => VENDOR_ID,
+            DEVICE_FEATURES => match self.device_features_sel {
+                0 => half(self.offered_features(), false),
+                1 => half(self.offered_features(), true),
+                _ => 0,
+            },
+            QUEUE_NUM_MAX => match self.queue() {
+                Some(_) => QUEUE_SIZE_MAX as u32,
+                None => 0,
+            },
+            QUEUE_READY => self.queue().is_some_and(|queue| queue.ready) as u32,
+            INTERRUPT_STATUS => self.interrupt_status,
+            STATUS => self.status,
+            // there are no shared memory regions, which a length of all ones tells
+            SHM_LEN_LOW | SHM_LEN_HIGH => u32::MAX,
+            SHM_BASE_LOW | SHM_BASE_HIGH => u32::MAX,
+            // the configuration spaces of the devices do not change
+            CONFIG_GENERATION => 0,
+            _ => 0,
+        }
+    }
+
+    fn write_register(&mut self, offset: u64, value: u32) {
+        let set_half = |field: &mut u64, high: bool| {
+            let shift = 32 * high as u32;
+            *field = *field & !(0xffff_ffff << shift) | (value as u64) << shift;
+        };
+        match offset {
+            DEVICE_FEATURES_SEL => self.device_features_sel = value,
+            DRIVER_FEATURES => match self.driver_features_sel {
+                0 => set_half(&mut self.driver_features, false),
+                1 => set_half(&mut self.driver_features, true),
+                _ => {}
+            },
+            DRIVER_FEATURES_SEL => self.driver_features_sel = value,
+            QUEUE_SEL => self.queue_sel = value,
+            QUEUE_NUM => {
+                if let Some(queue) = self.queue() {
+                    queue.size = (value as u16).min(QUEUE_SIZE_MAX);
+                }
+            }
+            QUEUE_READY => {
+                if let Some(queue) = self.queue() {
+                    queue.ready = value & 1 == 1;
+                }
+            }
+            QUEUE_NOTIFY if (value as usize) < self.queues.len() => self.notified |= 1 << value,
+            INTERRUPT_ACK => self.interrupt_status &= !value,
+            STATUS => self.write_status(value),
+            QUEUE_DESC_LOW | QUEUE_DESC_HIGH => {
+                if let Some(queue) = self.queue() {
+                    set_half(&mut queue.desc, offset == QUEUE_DESC_HIGH);
+                }
+            }
+            QUEUE_DRIVER_LOW | QUEUE_DRIVER_HIGH => {
+                if let Some(queue) = self.queue() {
+                    set_half(&mut queue.driver, offset == QUEUE_DRIVER_HIGH);
+                }
+            }
+            QUEUE_DEVICE_LOW | QUEUE_DEVICE_HIGH => {
+                if let Some(queue) = self.queue() {
+                    set_half(&mut queue.device, offset == QUEUE_DEVICE_HIGH);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Writing 0 resets the device. The driver sets FEATURES_OK to accept the features it has
+    // written, which stays clear if they were not offered.
+    fn write_status(&mut self, value: u32) {
+        if value == 0 {
+            self.reset();
+            return;
+        }
+        self.status = value;
+        if self.driver_features & !self.offered_features() != 0 {
+            self.status &= !STATUS_FEATURES_OK;
+        }
+    }
+
+    // Processes the queues that the driver has notified, reading and writing their buffers in the
+    // memory directly, as the bus does after each step.
+    pub fn process(&mut self, memory: &mut Memory) {
+        if self.status & STATUS_DRIVER_OK == 0 || self.status & STATUS_DEVICE_NEEDS_RESET != 0 {
+            return;
+        }
+        let notified = std::mem::take(&mut self.notified);
+        let processed = (0..self.queues.len())
+            .filter(|queue| notified >> queue & 1 == 1)
+            .try_for_each(|queue| self.device.notify(queue, &mut self.queues, memory))
+            .and_then(|()| self.device.poll(&mut self.queues, memory));
+        if processed.is_err() {
+            self.status |= STATUS_DEVICE_NEEDS_RESET;
+            self.interrupt_status |= INTERRUPT_CONFIG_CHANGE;
+            return;
+        }
+        for queue in &mut self.queues {
+            if std::mem::take(&mut queue.interrupt) {
+                self.interrupt_status |= INTERRUPT_USED_BUFFER;
+            }
+        }
+    }
+}
+
+impl Device for VirtioMmio {
+    fn size(&self) -> u64 {
+        VIRTIO_MMIO_STRIDE
+    }
+
+    // The registers are read as 32-bit words, and the configuration space in any size.
+    fn read(&mut self, offset: u64, size: usize) -> Result<u64, BusError> {
+        if offset < CONFIG {
+            return Ok(self.read_register(offset & !0b11) as u64);
+        }
+        let config = self.device.config();
+        let start = (offset - CONFIG) as usize;
+        Ok((0..size).rev().fold(0, |value, i| {
+            value << 8 | config.get(start + i).copied().unwrap_or(0) as u64
+        }))
+    }
+
+    // The configuration spaces of the devices are read-only.
+    fn write(&mut self, offset: u64, _: usize, value: u64) -> Result<(), BusError> {
+        if offset < CONFIG {
+            self.write_register(offset & !0b11, value as u32);
+        }
+        Ok(())
+    }
+
+    fn interrupt(&self) -> bool {
+        self.interrupt_status != 0
+    }
+}
+
+impl Platform for VirtioMmio {
+    fn dma(&mut self, memory: &mut Memory) {
+        self.process(memory);
+    }
+}
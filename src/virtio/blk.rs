@@ -0,0 +1,161 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use super::{Queue, QueueError, VirtioDevice};
+use crate::memory::Memory;
+
+const DEVICE_ID: u32 = 2;
+const SECTOR_SIZE: usize = 512;
+
+const VIRTIO_BLK_F_RO: u64 = 1 << 5;
+const VIRTIO_BLK_F_FLUSH: u64 = 1 << 9;
+
+// A request starts with its type, a reserved word and the sector it starts at, and ends with a
+// status byte that the device writes.
+const HEADER_SIZE: usize = 16;
+const VIRTIO_BLK_T_IN: u32 = 0;
+const VIRTIO_BLK_T_OUT: u32 = 1;
+const VIRTIO_BLK_T_FLUSH: u32 = 4;
+const VIRTIO_BLK_T_GET_ID: u32 = 8;
+const VIRTIO_BLK_S_OK: u8 = 0;
+const VIRTIO_BLK_S_IOERR: u8 = 1;
+const VIRTIO_BLK_S_UNSUPP: u8 = 2;
+
+// the serial number of the device, up to 20 bytes
+const ID: &[u8] = b"minimum-five";
+const ID_SIZE: usize = 20;
+
+// A block device on a host disk image, which the driver may only read, or may also write without
+// changing the image, as the sectors written are kept in memory until the simulator exits.
+pub struct Block {
+    image: File,
+    sectors: u64,
+    read_only: bool,
+    // the sectors written, by number
+    written: HashMap<u64, Vec<u8>>,
+}
+
+impl Block {
+    // Opens the image, whose size is rounded down to whole sectors.
+    pub fn open(path: impl AsRef<Path>, read_only: bool) -> io::Result<Self> {
+        let image = File::open(path)?;
+        let sectors = image.metadata()?.len() / SECTOR_SIZE as u64;
+        Ok(Self {
+            image,
+            sectors,
+            read_only,
+            written: HashMap::new(),
+        })
+    }
+
+    // Whether the `length` bytes from `sector` are whole sectors of the disk.
+    fn contains(&self, sector: u64, length: usize) -> bool {
+        length.is_multiple_of(SECTOR_SIZE)
+            && sector
+                .checked_add((length / SECTOR_SIZE) as u64)
+                .is_some_and(|end| end <= self.sectors)
+    }
+
+    fn read_sectors(&mut self, sector: u64, data: &mut [u8]) -> io::Result<()> {
+        self.image
+            .seek(SeekFrom::Start(sector * SECTOR_SIZE as u64))?;
+        self.image.read_exact(data)?;
+        for (i, chunk) in data.chunks_mut(SECTOR_SIZE).enumerate() {
+            if let Some(written) = self.written.get(&(sector + i as u64)) {
+                chunk.copy_from_slice(written);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_sectors(&mut self, sector: u64, data: &[u8]) {
+        for (i, chunk) in data.chunks(SECTOR_SIZE).enumerate() {
+            self.written.insert(sector + i as u64, chunk.to_vec());
+        }
+    }
+
+    // Performs a request, whose readable buffers hold `request`, and returns the data and status
+    // to write back, where `space` bytes may be written before the status.
+    fn perform(&mut self, request: &[u8], space: usize) -> (Vec<u8>, u8) {
+        if request.len() < HEADER_SIZE {
+            return (Vec::new(), VIRTIO_BLK_S_IOERR);
+        }
+        let kind = u32::from_le_bytes(request[0..4].try_into().unwrap());
+        let sector = u64::from_le_bytes(request[8..16].try_into().unwrap());
+        let data = &request[HEADER_SIZE..];
+        match kind {
+            VIRTIO_BLK_T_IN => {
+                if !self.contains(sector, space) {
+                    return (Vec::new(), VIRTIO_BLK_S_IOERR);
+                }
+                let mut data = vec![0; space];
+                match self.read_sectors(sector, &mut data) {
+                    Ok(()) => (data, VIRTIO_BLK_S_OK),
+                    Err(_) => (Vec::new(), VIRTIO_BLK_S_IOERR),
+                }
+            }
+            VIRTIO_BLK_T_OUT => {
+                if self.read_only || !self.contains(sector, data.len()) {
+                    return (Vec::new(), VIRTIO_BLK_S_IOERR);
+                }
+                self.write_sectors(sector, data);
+                (Vec::new(), VIRTIO_BLK_S_OK)
+            }
+            // the image is never written, so there is nothing to flush
+            VIRTIO_BLK_T_FLUSH => (Vec::new(), VIRTIO_BLK_S_OK),
+            VIRTIO_BLK_T_GET_ID => {
+                let mut id = ID.to_vec();
+                id.resize(ID_SIZE.min(space), 0);
+                (id, VIRTIO_BLK_S_OK)
+            }
+            _ => (Vec::new(), VIRTIO_BLK_S_UNSUPP),
+        }
+    }
+}
+
+impl VirtioDevice for Block {
+    fn device_id(&self) -> u32 {
+        DEVICE_ID
+    }
+
+    fn features(&self) -> u64 {
+        match self.read_only {
+            true => VIRTIO_BLK_F_RO | VIRTIO_BLK_F_FLUSH,
+            false => VIRTIO_BLK_F_FLUSH,
+        }
+    }
+
+    fn queues(&self) -> usize {
+        1
+    }
+
+    // the capacity in sectors
+    fn config(&self) -> Vec<u8> {
+        self.sectors.to_le_bytes().to_vec()
+    }
+
+    // The requests are performed in order, each as soon as it is notified.
+    fn notify(
+        &mut self,
+        queue: usize,
+        queues: &mut [Queue],
+        memory: &mut Memory,
+    ) -> Result<(), QueueError> {
+        let queue = &mut queues[queue];
+        while let Some(chain) = queue.pop(memory)? {
+            // the status byte is the last one of the writable buffers
+            let Some(space) = chain.writable_len().checked_sub(1) else {
+                return Err(QueueError);
+            };
+            let (data, status) = self.perform(&chain.read(memory)?, space);
+            chain.write_at(memory, 0, &data)?;
+            chain.write_at(memory, space, &[status])?;
+            queue.push(memory, chain, data.len() + 1)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind, Read, Write},
+    os::unix::net::UnixStream,
+    path::Path,
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+use super::{listen, Peer, Queue, QueueError, VirtioDevice};
+use crate::{
+    console::{spawn_stdin_reader, RawMode},
+    memory::Memory,
+};
+
+const DEVICE_ID: u32 = 3;
+
+const VIRTIO_CONSOLE_F_MULTIPORT: u64 = 1 << 1;
+
+// The queues of port 0, then the control queues, then those of the other ports, each receiving
+// then transmitting.
+const CONTROL_RECEIVE_QUEUE: usize = 2;
+const CONTROL_TRANSMIT_QUEUE: usize = 3;
+
+// A control message is a port, an event and a value, which may be followed by a name.
+const CONTROL_SIZE: usize = 8;
+const VIRTIO_CONSOLE_DEVICE_READY: u16 = 0;
+const VIRTIO_CONSOLE_DEVICE_ADD: u16 = 1;
+const VIRTIO_CONSOLE_PORT_READY: u16 = 3;
+const VIRTIO_CONSOLE_CONSOLE_PORT: u16 = 4;
+const VIRTIO_CONSOLE_PORT_OPEN: u16 = 6;
+const VIRTIO_CONSOLE_PORT_NAME: u16 = 7;
+
+// A port of a console, which receives from `input` and transmits to `output`.
+pub struct Port {
+    // under which the guest finds the port, as /dev/virtio-ports/<name> on Linux
+    name: String,
+    input: Receiver<u8>,
+    output: Box<dyn Write>,
+    // the bytes that have come while the driver had no chain to receive them in
+    waiting: VecDeque<u8>,
+    _raw_mode: Option<RawMode>,
+}
+
+impl Port {
+    pub fn new(name: &str, input: Receiver<u8>, output: Box<dyn Write>) -> Self {
+        Self {
+            name: name.to_string(),
+            input,
+            output,
+            waiting: VecDeque::new(),
+            _raw_mode: None,
+        }
+    }
+
+    // Wired to the terminal, which stays in raw mode as long as the port lives.
+    pub fn stdio(name: &str) -> Self {
+        Self {
+            _raw_mode: RawMode::enable(),
+            ..Self::new(name, spawn_stdin_reader(), Box::new(io::stdout()))
+        }
+    }
+
+    // Listens at `path` for a peer, such as socat, which may connect again once it has gone.
+    // What is transmitted while no peer is connected is lost.
+    pub fn socket(name: &str, path: impl AsRef<Path>) -> io::Result<Self> {
+        let (sender, input) = mpsc::channel();
+        let peer = listen(path.as_ref(), move |stream| forward_bytes(stream, &sender))?;
+        Ok(Self::new(name, input, Box::new(PeerWriter(peer))))
+    }
+}
+
+// Sends the bytes that come from `stream` until the peer closes it, and returns whether the
+// port still takes them.
+fn forward_bytes(mut stream: UnixStream, sender: &Sender<u8>) -> bool {
+    let mut buffer = [0; 4096];
+    loop {
+        let count = match stream.read(&mut buffer) {
+            Ok(0) => return true,
+            Ok(count) => count,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(_) => return true,
+        };
+        if buffer[..count]
+            .iter()
+            .any(|&byte| sender.send(byte).is_err())
+        {
+            return false;
+        }
+    }
+}
+
+struct PeerWriter(Peer);
+
+impl Write for PeerWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let mut peer = self.0.lock().unwrap();
+        if let Some(stream) = peer.as_mut() {
+            if stream.write_all(data).is_err() {
+                *peer = None;
+            }
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// A console with several ports, where port 0 is the console of the guest, such as hvc0 on
+// Linux. The driver learns of the other ports through the control queues once it has negotiated
+// VIRTIO_CONSOLE_F_MULTIPORT.
+pub struct Console {
+    ports: Vec<Port>,
+    // the control messages for the driver
+    control: VecDeque<Vec<u8>>,
+}
+
+impl Console {
+    pub fn new(ports: Vec<Port>) -> Self {
+        Self {
+            ports,
+            control: VecDeque::new(),
+        }
+    }
+
+    // The queue in which port `port` receives, followed by the one in which it transmits.
+    fn receive_queue(port: usize) -> usize {
+        match port {
+            0 => 0,
+            port => 2 + 2 * port,
+        }
+    }
+
+    // The port whose transmit queue `queue` is.
+    fn transmitting_port(queue: usize) -> Option<usize> {
+        match queue {
+            1 => Some(0),
+            CONTROL_TRANSMIT_QUEUE => None,
+            queue if queue > CONTROL_TRANSMIT_QUEUE && queue % 2 == 1 => Some((queue - 3) / 2),
+            _ => None,
+        }
+    }
+
+    fn send_control(&mut self, port: usize, event: u16, value: u16, name: &[u8]) {
+        let mut message = Vec::with_capacity(CONTROL_SIZE + name.len());
+        message.extend_from_slice(&(port as u32).to_le_bytes());
+        message.extend_from_slice(&event.to_le_bytes());
+        message.extend_from_slice(&value.to_le_bytes());
+        message.extend_from_slice(name);
+        self.control.push_back(message);
+    }
+
+    // Once the driver is ready, the ports are added, and once a port is ready, it is named and
+    // opened, as a peer may connect to it at any time.
+    fn receive_control(&mut self, message: &[u8]) {
+        if message.len() < CONTROL_SIZE {
+            return;
+        }
+        let port = u32::from_le_bytes(message[0..4].try_into().unwrap()) as usize;
+        let event = u16::from_le_bytes(message[4..6].try_into().unwrap());
+        let value = u16::from_le_bytes(message[6..8].try_into().unwrap());
+        match event {
+            VIRTIO_CONSOLE_DEVICE_READY if value == 1 => {
+                for port in 0..self.ports.len() {
+                    self.send_control(port, VIRTIO_CONSOLE_DEVICE_ADD, 0, &[]);
+                }
+            }
+            VIRTIO_CONSOLE_PORT_READY if value == 1 && port < self.ports.len() => {
+                if port == 0 {
+                    self.send_control(port, VIRTIO_CONSOLE_CONSOLE_PORT, 1, &[]);
+                }
+                let name = self.ports[port].name.clone();
+                if !name.is_empty() {
+                    self.send_control(port, VIRTIO_CONSOLE_PORT_NAME, 1, name.as_bytes());
+                }
+                self.send_control(port, VIRTIO_CONSOLE_PORT_OPEN, 1, &[]);
+            }
+            // whether the guest has opened a port does not matter
+            _ => {}
+        }
+    }
+}
+
+impl VirtioDevice for Console {
+    fn device_id(&self) -> u32 {
+        DEVICE_ID
+    }
+
+    fn features(&self) -> u64 {
+        VIRTIO_CONSOLE_F_MULTIPORT
+    }
+
+    fn queues(&self) -> usize {
+        2 + 2 * self.ports.len()
+    }
+
+    // no size, the number of ports and no emergency write
+    fn config(&self) -> Vec<u8> {
+        let mut config = vec![0; 4];
+        config.extend_from_slice(&(self.ports.len() as u32).to_le_bytes());
+        config.extend_from_slice(&[0; 4]);
+        config
+    }
+
+    fn notify(
+        &mut self,
+        queue: usize,
+        queues: &mut [Queue],
+        memory: &mut Memory,
+    ) -> Result<(), QueueError> {
+        let port = Self::transmitting_port(queue);
+        if port.is_none() && queue != CONTROL_TRANSMIT_QUEUE {
+            return Ok(());
+        }
+        let queue = &mut queues[queue];
+        while let Some(chain) = queue.pop(memory)? {
+            let data = chain.read(memory)?;
+            match port {
+                Some(port) => {
+                    let output = &mut self.ports[port].output;
+                    let _ = output.write_all(&data);
+                    let _ = output.flush();
+                }
+                None => self.receive_control(&data),
+            }
+            queue.push(memory, chain, 0)?;
+        }
+        Ok(())
+    }
+
+    fn poll(&mut self, queues: &mut [Queue], memory: &mut Memory) -> Result<(), QueueError> {
+        while !self.control.is_empty() {
+            let Some(chain) = queues[CONTROL_RECEIVE_QUEUE].pop(memory)? else {
+                break;
+            };
+            let message = self.control.pop_front().unwrap();
+            chain.write_at(memory, 0, &message)?;
+            let written = message.len().min(chain.writable_len());
+            queues[CONTROL_RECEIVE_QUEUE].push(memory, chain, written)?;
+        }
+        for (i, port) in self.ports.iter_mut().enumerate() {
+            port.waiting.extend(port.input.try_iter());
+            let queue = &mut queues[Self::receive_queue(i)];
+            while !port.waiting.is_empty() {
+                let Some(chain) = queue.pop(memory)? else {
+                    break;
+                };
+                let count = port.waiting.len().min(chain.writable_len());
+                let data: Vec<u8> = port.waiting.drain(..count).collect();
+                chain.write_at(memory, 0, &data)?;
+                queue.push(memory, chain, count)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.control.clear();
+    }
+}
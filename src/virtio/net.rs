@@ -0,0 +1,260 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    os::unix::net::UnixStream,
+    path::Path,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{listen, Peer, Queue, QueueError, VirtioDevice};
+use crate::memory::Memory;
+
+const DEVICE_ID: u32 = 1;
+const RECEIVE_QUEUE: usize = 0;
+const TRANSMIT_QUEUE: usize = 1;
+
+const VIRTIO_NET_F_MAC: u64 = 1 << 5;
+const VIRTIO_NET_F_STATUS: u64 = 1 << 16;
+const VIRTIO_NET_S_LINK_UP: u16 = 1;
+
+// Each frame in a queue follows a virtio_net_hdr, which asks for no offloads. With
+// VIRTIO_F_VERSION_1 it ends with the number of chains that a received frame takes, always one.
+const HEADER_SIZE: usize = 12;
+const NUM_BUFFERS: usize = 10;
+
+// QEMU's first MAC address.
+pub const DEFAULT_MAC: [u8; 6] = [0x52, 0x54, 0x00, 0x12, 0x34, 0x56];
+
+// the largest frame that a socket may carry, which is larger than any the device sends
+const MAX_FRAME_SIZE: usize = 65536;
+
+// Where the frames that the guest transmits go, and those that it receives come from.
+pub trait Backend {
+    fn send(&mut self, frame: &[u8]);
+
+    // The next frame for the guest, if one has come.
+    fn receive(&mut self) -> Option<Vec<u8>>;
+}
+
+// Gives the frames back to the guest that sent them.
+#[derive(Default)]
+pub struct Loopback {
+    frames: VecDeque<Vec<u8>>,
+}
+
+impl Backend for Loopback {
+    fn send(&mut self, frame: &[u8]) {
+        self.frames.push_back(frame.to_vec());
+    }
+
+    fn receive(&mut self) -> Option<Vec<u8>> {
+        self.frames.pop_front()
+    }
+}
+
+// Connects to another simulator, or to QEMU's stream netdev, through a Unix socket that carries
+// each frame after its length, as a 32-bit big-endian number. The frames sent while no peer is
+// connected are lost, as on an unplugged cable.
+pub struct Socket {
+    peer: Peer,
+    frames: Receiver<Vec<u8>>,
+}
+
+impl Socket {
+    // Listens at `path` for a peer.
+    pub fn listen(path: impl AsRef<Path>) -> io::Result<Self> {
+        let (sender, frames) = mpsc::channel();
+        let peer = listen(path.as_ref(), move |stream| forward_frames(stream, &sender))?;
+        Ok(Self { peer, frames })
+    }
+
+    // Connects to a peer that listens at `path`.
+    pub fn connect(path: impl AsRef<Path>) -> io::Result<Self> {
+        let stream = UnixStream::connect(path)?;
+        let peer = Arc::new(Mutex::new(Some(stream.try_clone()?)));
+        let (sender, frames) = mpsc::channel();
+        thread::spawn(move || forward_frames(stream, &sender));
+        Ok(Self { peer, frames })
+    }
+}
+
+// Sends the frames that come from `stream` until the peer closes it, and returns whether the
+// device still takes them.
+fn forward_frames(mut stream: UnixStream, sender: &Sender<Vec<u8>>) -> bool {
+    let mut length = [0; 4];
+    while stream.read_exact(&mut length).is_ok() {
+        let length = u32::from_be_bytes(length) as usize;
+        if length > MAX_FRAME_SIZE {
+            break;
+        }
+        let mut frame = vec![0; length];
+        if stream.read_exact(&mut frame).is_err() {
+            break;
+        }
+        if sender.send(frame).is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+impl Backend for Socket {
+    fn send(&mut self, frame: &[u8]) {
+        let mut peer = self.peer.lock().unwrap();
+        let Some(stream) = peer.as_mut() else {
+            return;
+        };
+        let length = (frame.len() as u32).to_be_bytes();
+        if stream.write_all(&length).is_err() || stream.write_all(frame).is_err() {
+            *peer = None;
+        }
+    }
+
+    fn receive(&mut self) -> Option<Vec<u8>> {
+        self.frames.try_recv().ok()
+    }
+}
+
+// Records the frames that a device sends and receives in a pcap file, with the time of the host.
+struct Capture {
+    file: BufWriter<File>,
+}
+
+impl Capture {
+    fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        // the magic number, version 2.4, UTC, a snapshot length of MAX_FRAME_SIZE and Ethernet
+        file.write_all(&0xa1b2_c3d4u32.to_le_bytes())?;
+        file.write_all(&2u16.to_le_bytes())?;
+        file.write_all(&4u16.to_le_bytes())?;
+        file.write_all(&[0; 8])?;
+        file.write_all(&(MAX_FRAME_SIZE as u32).to_le_bytes())?;
+        file.write_all(&1u32.to_le_bytes())?;
+        file.flush()?;
+        Ok(Self { file })
+    }
+
+    fn record(&mut self, frame: &[u8]) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut record = Vec::with_capacity(16 + frame.len());
+        record.extend_from_slice(&(time.as_secs() as u32).to_le_bytes());
+        record.extend_from_slice(&time.subsec_micros().to_le_bytes());
+        record.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        record.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        record.extend_from_slice(frame);
+        // the capture is readable as the simulator runs
+        let _ = self
+            .file
+            .write_all(&record)
+            .and_then(|()| self.file.flush());
+    }
+}
+
+// A network device with a MAC address, whose link is always up, on a backend.
+pub struct Net {
+    backend: Box<dyn Backend>,
+    mac: [u8; 6],
+    capture: Option<Capture>,
+    // a frame that has come while the driver had no chain to receive it in
+    waiting: Option<Vec<u8>>,
+}
+
+impl Net {
+    pub fn new(backend: Box<dyn Backend>, mac: [u8; 6]) -> Self {
+        Self {
+            backend,
+            mac,
+            capture: None,
+            waiting: None,
+        }
+    }
+
+    // Records the frames in a pcap file at `path`.
+    pub fn capture(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.capture = Some(Capture::create(path)?);
+        Ok(())
+    }
+
+    fn record(&mut self, frame: &[u8]) {
+        if let Some(capture) = &mut self.capture {
+            capture.record(frame);
+        }
+    }
+}
+
+impl VirtioDevice for Net {
+    fn device_id(&self) -> u32 {
+        DEVICE_ID
+    }
+
+    fn features(&self) -> u64 {
+        VIRTIO_NET_F_MAC | VIRTIO_NET_F_STATUS
+    }
+
+    fn queues(&self) -> usize {
+        2
+    }
+
+    // the MAC address and the status
+    fn config(&self) -> Vec<u8> {
+        let mut config = self.mac.to_vec();
+        config.extend_from_slice(&VIRTIO_NET_S_LINK_UP.to_le_bytes());
+        config
+    }
+
+    // The frames are sent as soon as the transmit queue is notified. The receive queue is
+    // filled as they come, which polling does.
+    fn notify(
+        &mut self,
+        queue: usize,
+        queues: &mut [Queue],
+        memory: &mut Memory,
+    ) -> Result<(), QueueError> {
+        if queue != TRANSMIT_QUEUE {
+            return Ok(());
+        }
+        let queue = &mut queues[queue];
+        while let Some(chain) = queue.pop(memory)? {
+            let data = chain.read(memory)?;
+            if let Some(frame) = data.get(HEADER_SIZE..) {
+                self.record(frame);
+                self.backend.send(frame);
+            }
+            queue.push(memory, chain, 0)?;
+        }
+        Ok(())
+    }
+
+    fn poll(&mut self, queues: &mut [Queue], memory: &mut Memory) -> Result<(), QueueError> {
+        let queue = &mut queues[RECEIVE_QUEUE];
+        while let Some(frame) = self.waiting.take().or_else(|| self.backend.receive()) {
+            let Some(chain) = queue.pop(memory)? else {
+                self.waiting = Some(frame);
+                break;
+            };
+            let mut data = vec![0; HEADER_SIZE];
+            data[NUM_BUFFERS] = 1;
+            data.extend_from_slice(&frame);
+            // a frame larger than the chain is cut short
+            let written = data.len().min(chain.writable_len());
+            chain.write_at(memory, 0, &data)?;
+            queue.push(memory, chain, written)?;
+            if let Some(capture) = &mut self.capture {
+                capture.record(&frame);
+            }
+        }
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.waiting = None;
+    }
+}
@@ -0,0 +1,705 @@
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fs::{self, File, FileTimes, Metadata, OpenOptions, Permissions},
+    io::{self, ErrorKind},
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{
+            self as unix_fs, DirBuilderExt, FileExt, MetadataExt, OpenOptionsExt, PermissionsExt,
+        },
+    },
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{Queue, QueueError, VirtioDevice};
+use crate::memory::Memory;
+
+const DEVICE_ID: u32 = 9;
+
+const VIRTIO_9P_MOUNT_TAG: u64 = 1 << 0;
+
+// The tag under which the guest mounts the directory, as in
+// mount -t 9p -o trans=virtio,version=9p2000.L host /mnt
+pub const DEFAULT_MOUNT_TAG: &str = "host";
+
+const VERSION: &str = "9P2000.L";
+// the largest message, whatever the driver asks for
+const MAX_MESSAGE_SIZE: u32 = 128 * 1024;
+// A message starts with its size, its type and its tag. A reply has the type of its request
+// plus one.
+const HEADER_SIZE: usize = 7;
+// A read or a write reply holds its count besides the data.
+const IO_HEADER_SIZE: u32 = HEADER_SIZE as u32 + 4;
+
+const RLERROR: u8 = 7;
+const TSTATFS: u8 = 8;
+const TLOPEN: u8 = 12;
+const TLCREATE: u8 = 14;
+const TSYMLINK: u8 = 16;
+const TRENAME: u8 = 20;
+const TREADLINK: u8 = 22;
+const TGETATTR: u8 = 24;
+const TSETATTR: u8 = 26;
+const TREADDIR: u8 = 40;
+const TFSYNC: u8 = 50;
+const TLOCK: u8 = 52;
+const TGETLOCK: u8 = 54;
+const TLINK: u8 = 70;
+const TMKDIR: u8 = 72;
+const TRENAMEAT: u8 = 74;
+const TUNLINKAT: u8 = 76;
+const TVERSION: u8 = 100;
+const TATTACH: u8 = 104;
+const TFLUSH: u8 = 108;
+const TWALK: u8 = 110;
+const TREAD: u8 = 116;
+const TWRITE: u8 = 118;
+const TCLUNK: u8 = 120;
+const TREMOVE: u8 = 122;
+
+// The errors of Linux, which the guest expects whatever the host is.
+const ENOENT: u32 = 2;
+const EIO: u32 = 5;
+const EBADF: u32 = 9;
+const EACCES: u32 = 13;
+const EEXIST: u32 = 17;
+const ENOTDIR: u32 = 20;
+const EISDIR: u32 = 21;
+const EINVAL: u32 = 22;
+const ENOSPC: u32 = 28;
+const EROFS: u32 = 30;
+const ENOTEMPTY: u32 = 39;
+const ELOOP: u32 = 40;
+const EPROTO: u32 = 71;
+const EOPNOTSUPP: u32 = 95;
+
+// the flags of Tlopen and Tlcreate, as open(2) has them on Linux
+const O_ACCMODE: u32 = 0o3;
+const O_RDONLY: u32 = 0o0;
+const O_WRONLY: u32 = 0o1;
+const O_TRUNC: u32 = 0o1000;
+const O_APPEND: u32 = 0o2000;
+
+const QID_DIR: u8 = 0x80;
+const QID_SYMLINK: u8 = 0x02;
+const QID_FILE: u8 = 0x00;
+
+const DT_DIR: u8 = 4;
+const DT_REG: u8 = 8;
+const DT_LNK: u8 = 10;
+
+// the fields of Tgetattr that are answered: all those of stat(2)
+const P9_GETATTR_BASIC: u64 = 0x7ff;
+
+const P9_SETATTR_MODE: u32 = 1 << 0;
+const P9_SETATTR_UID: u32 = 1 << 1;
+const P9_SETATTR_GID: u32 = 1 << 2;
+const P9_SETATTR_SIZE: u32 = 1 << 3;
+const P9_SETATTR_ATIME: u32 = 1 << 4;
+const P9_SETATTR_MTIME: u32 = 1 << 5;
+const P9_SETATTR_ATIME_SET: u32 = 1 << 7;
+const P9_SETATTR_MTIME_SET: u32 = 1 << 8;
+
+const AT_REMOVEDIR: u32 = 0x200;
+const P9_LOCK_SUCCESS: u8 = 0;
+const F_UNLCK: u8 = 2;
+// the type of v9fs, which Tstatfs reports
+const V9FS_MAGIC: u32 = 0x0102_1997;
+
+type Errno = u32;
+
+fn errno(error: io::Error) -> Errno {
+    match error.kind() {
+        ErrorKind::NotFound => ENOENT,
+        ErrorKind::PermissionDenied => EACCES,
+        ErrorKind::AlreadyExists => EEXIST,
+        ErrorKind::NotADirectory => ENOTDIR,
+        ErrorKind::IsADirectory => EISDIR,
+        ErrorKind::DirectoryNotEmpty => ENOTEMPTY,
+        ErrorKind::InvalidInput => EINVAL,
+        ErrorKind::StorageFull => ENOSPC,
+        ErrorKind::ReadOnlyFilesystem => EROFS,
+        _ => EIO,
+    }
+}
+
+// The fields of a message, in little-endian order, where strings are preceded by their length.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], Errno> {
+        if count > self.data.len() {
+            return Err(EPROTO);
+        }
+        let (bytes, rest) = self.data.split_at(count);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, Errno> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, Errno> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, Errno> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, Errno> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<&'a [u8], Errno> {
+        let length = self.u16()? as usize;
+        self.bytes(length)
+    }
+
+    // A name within a directory, which may not lead out of it.
+    fn name(&mut self) -> Result<&'a OsStr, Errno> {
+        let name = self.string()?;
+        if name.is_empty() || name == b"." || name == b".." || name.contains(&b'/') {
+            return Err(EINVAL);
+        }
+        Ok(OsStr::from_bytes(name))
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    data: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) -> &mut Self {
+        self.data.push(value);
+        self
+    }
+
+    fn u16(&mut self, value: u16) -> &mut Self {
+        self.data.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn u32(&mut self, value: u32) -> &mut Self {
+        self.data.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn u64(&mut self, value: u64) -> &mut Self {
+        self.data.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.data.extend_from_slice(bytes);
+        self
+    }
+
+    fn string(&mut self, string: &[u8]) -> &mut Self {
+        self.u16(string.len() as u16).bytes(string)
+    }
+
+    // The type, the version and the path of a file, where the inode number stands for the path.
+    fn qid(&mut self, metadata: &Metadata) -> &mut Self {
+        let kind = if metadata.is_dir() {
+            QID_DIR
+        } else if metadata.is_symlink() {
+            QID_SYMLINK
+        } else {
+            QID_FILE
+        };
+        self.u8(kind).u32(0).u64(metadata.ino())
+    }
+}
+
+// A file that the driver refers to by a number of its own choosing.
+struct Fid {
+    path: PathBuf,
+    // the file once it has been opened, which directories never are
+    file: Option<File>,
+}
+
+// A 9P2000.L server on a directory of the host, which the guest mounts by its tag. The guest
+// cannot walk out of the directory, nor through the symbolic links in it, which it follows by
+// reading them instead.
+pub struct Filesystem {
+    root: PathBuf,
+    tag: String,
+    message_size: u32,
+    fids: HashMap<u32, Fid>,
+}
+
+impl Filesystem {
+    pub fn new(root: impl AsRef<Path>, tag: &str) -> io::Result<Self> {
+        let root = root.as_ref().canonicalize()?;
+        if !root.is_dir() {
+            return Err(io::Error::new(ErrorKind::NotADirectory, "not a directory"));
+        }
+        Ok(Self {
+            root,
+            tag: tag.to_string(),
+            message_size: MAX_MESSAGE_SIZE,
+            fids: HashMap::new(),
+        })
+    }
+
+    fn fid(&self, fid: u32) -> Result<&Fid, Errno> {
+        self.fids.get(&fid).ok_or(EBADF)
+    }
+
+    // The path of a fid, whose directories must still be directories and not symbolic links,
+    // which are not followed: the guest may have moved one since the fid was walked, and linked
+    // anywhere in its place.
+    fn path(&self, fid: u32) -> Result<&Path, Errno> {
+        let path = &self.fid(fid)?.path;
+        if let Some(parent) = path.parent().filter(|_| *path != self.root) {
+            self.check_links(parent)?;
+        }
+        Ok(path)
+    }
+
+    // The path of a fid in which names are looked up, which may not be a symbolic link either.
+    fn directory(&self, fid: u32) -> Result<&Path, Errno> {
+        let path = self.path(fid)?;
+        self.check_links(path)?;
+        Ok(path)
+    }
+
+    // Fails if `path` or a directory above it, up to the root, is a symbolic link.
+    fn check_links(&self, path: &Path) -> Result<(), Errno> {
+        let linked = path
+            .ancestors()
+            .take_while(|ancestor| *ancestor != self.root)
+            .any(|ancestor| fs::symlink_metadata(ancestor).is_ok_and(|m| m.is_symlink()));
+        match linked {
+            true => Err(ELOOP),
+            false => Ok(()),
+        }
+    }
+
+    fn file(&self, fid: u32) -> Result<&File, Errno> {
+        self.fid(fid)?.file.as_ref().ok_or(EBADF)
+    }
+
+    fn add_fid(&mut self, fid: u32, path: PathBuf, file: Option<File>) {
+        self.fids.insert(fid, Fid { path, file });
+    }
+
+    // Serves a request and returns the reply.
+    fn serve(&mut self, request: &[u8]) -> Vec<u8> {
+        let mut reader = Reader { data: request };
+        let (Ok(_), Ok(kind), Ok(tag)) = (reader.u32(), reader.u8(), reader.u16()) else {
+            return Vec::new();
+        };
+        let mut reply = Writer::default();
+        let (kind, body) = match self.perform(kind, &mut reader, &mut reply) {
+            Ok(()) => (kind + 1, reply.data),
+            Err(errno) => (RLERROR, errno.to_le_bytes().to_vec()),
+        };
+        let mut message = Writer::default();
+        message
+            .u32((HEADER_SIZE + body.len()) as u32)
+            .u8(kind)
+            .u16(tag)
+            .bytes(&body);
+        message.data
+    }
+
+    fn perform(&mut self, kind: u8, r: &mut Reader, w: &mut Writer) -> Result<(), Errno> {
+        match kind {
+            TVERSION => {
+                self.message_size = r.u32()?.min(MAX_MESSAGE_SIZE);
+                let version = r.string()?;
+                self.fids.clear();
+                w.u32(self.message_size);
+                match version.starts_with(VERSION.as_bytes()) {
+                    true => w.string(VERSION.as_bytes()),
+                    false => w.string(b"unknown"),
+                };
+            }
+            TATTACH => {
+                let fid = r.u32()?;
+                let root = self.root.clone();
+                w.qid(&fs::symlink_metadata(&root).map_err(errno)?);
+                self.add_fid(fid, root, None);
+            }
+            TWALK => self.walk(r, w)?,
+            TLOPEN => {
+                let fid = r.u32()?;
+                let flags = r.u32()?;
+                let path = self.path(fid)?.to_path_buf();
+                let metadata = fs::symlink_metadata(&path).map_err(errno)?;
+                let file = match metadata.file_type() {
+                    kind if kind.is_dir() => None,
+                    kind if kind.is_symlink() => return Err(ELOOP),
+                    _ => Some(open_options(flags).open(&path).map_err(errno)?),
+                };
+                w.qid(&metadata).u32(0);
+                self.add_fid(fid, path, file);
+            }
+            TLCREATE => {
+                let fid = r.u32()?;
+                let path = self.directory(fid)?.join(r.name()?);
+                let flags = r.u32()?;
+                let mode = r.u32()?;
+                let file = open_options(flags)
+                    .write(true)
+                    .create_new(true)
+                    .mode(mode & 0o7777)
+                    .open(&path)
+                    .map_err(errno)?;
+                w.qid(&file.metadata().map_err(errno)?).u32(0);
+                self.add_fid(fid, path, Some(file));
+            }
+            TREAD => {
+                let fid = r.u32()?;
+                let offset = r.u64()?;
+                let count = r
+                    .u32()?
+                    .min(self.message_size.saturating_sub(IO_HEADER_SIZE));
+                let file = self.file(fid)?;
+                let mut data = vec![0; count as usize];
+                let mut read = 0;
+                while read < data.len() {
+                    match file.read_at(&mut data[read..], offset + read as u64) {
+                        Ok(0) => break,
+                        Ok(count) => read += count,
+                        Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                        Err(error) => return Err(errno(error)),
+                    }
+                }
+                w.u32(read as u32).bytes(&data[..read]);
+            }
+            TWRITE => {
+                let fid = r.u32()?;
+                let offset = r.u64()?;
+                let count = r.u32()? as usize;
+                let data = r.bytes(count)?;
+                self.file(fid)?.write_all_at(data, offset).map_err(errno)?;
+                w.u32(count as u32);
+            }
+            TCLUNK => {
+                self.fids.remove(&r.u32()?).ok_or(EBADF)?;
+            }
+            TREMOVE => {
+                let fid = self.fids.remove(&r.u32()?).ok_or(EBADF)?;
+                remove(
+                    &fid.path,
+                    fs::symlink_metadata(&fid.path).map_err(errno)?.is_dir(),
+                )?;
+            }
+            TGETATTR => {
+                let fid = r.u32()?;
+                let metadata = fs::symlink_metadata(self.path(fid)?).map_err(errno)?;
+                w.u64(P9_GETATTR_BASIC)
+                    .qid(&metadata)
+                    .u32(metadata.mode())
+                    .u32(metadata.uid())
+                    .u32(metadata.gid())
+                    .u64(metadata.nlink())
+                    .u64(metadata.rdev())
+                    .u64(metadata.size())
+                    .u64(metadata.blksize())
+                    .u64(metadata.blocks())
+                    .u64(metadata.atime() as u64)
+                    .u64(metadata.atime_nsec() as u64)
+                    .u64(metadata.mtime() as u64)
+                    .u64(metadata.mtime_nsec() as u64)
+                    .u64(metadata.ctime() as u64)
+                    .u64(metadata.ctime_nsec() as u64)
+                    // no birth time, generation or data version
+                    .u64(0)
+                    .u64(0)
+                    .u64(0)
+                    .u64(0);
+            }
+            TSETATTR => self.set_attributes(r)?,
+            TREADDIR => self.read_directory(r, w)?,
+            TSTATFS => {
+                // the space of the host is not reported
+                self.path(r.u32()?)?;
+                w.u32(V9FS_MAGIC)
+                    .u32(4096)
+                    .u64(0)
+                    .u64(0)
+                    .u64(0)
+                    .u64(0)
+                    .u64(0)
+                    .u64(0)
+                    .u32(255);
+            }
+            TMKDIR => {
+                let fid = r.u32()?;
+                let path = self.directory(fid)?.join(r.name()?);
+                let mode = r.u32()?;
+                fs::DirBuilder::new()
+                    .mode(mode & 0o7777)
+                    .create(&path)
+                    .map_err(errno)?;
+                w.qid(&fs::symlink_metadata(&path).map_err(errno)?);
+            }
+            TSYMLINK => {
+                let fid = r.u32()?;
+                let path = self.directory(fid)?.join(r.name()?);
+                let target = OsStr::from_bytes(r.string()?);
+                unix_fs::symlink(target, &path).map_err(errno)?;
+                w.qid(&fs::symlink_metadata(&path).map_err(errno)?);
+            }
+            TREADLINK => {
+                let target = fs::read_link(self.path(r.u32()?)?).map_err(errno)?;
+                w.string(target.as_os_str().as_bytes());
+            }
+            TLINK => {
+                let directory = self.directory(r.u32()?)?.to_path_buf();
+                let source = self.path(r.u32()?)?;
+                fs::hard_link(source, directory.join(r.name()?)).map_err(errno)?;
+            }
+            TRENAME => {
+                let fid = r.u32()?;
+                let directory = self.directory(r.u32()?)?;
+                let path = directory.join(r.name()?);
+                fs::rename(self.path(fid)?, &path).map_err(errno)?;
+                self.fids.get_mut(&fid).ok_or(EBADF)?.path = path;
+            }
+            TRENAMEAT => {
+                let old = self.directory(r.u32()?)?.join(r.name()?);
+                let new = self.directory(r.u32()?)?.join(r.name()?);
+                fs::rename(old, new).map_err(errno)?;
+            }
+            TUNLINKAT => {
+                let path = self.directory(r.u32()?)?.join(r.name()?);
+                remove(&path, r.u32()? & AT_REMOVEDIR != 0)?;
+            }
+            TFSYNC => {
+                let fid = r.u32()?;
+                if let Some(file) = &self.fid(fid)?.file {
+                    file.sync_all().map_err(errno)?;
+                }
+            }
+            // the files are not locked, as the guest is the only client
+            TLOCK => {
+                self.fid(r.u32()?)?;
+                w.u8(P9_LOCK_SUCCESS);
+            }
+            TGETLOCK => {
+                self.fid(r.u32()?)?;
+                r.u8()?;
+                let (start, length, process) = (r.u64()?, r.u64()?, r.u32()?);
+                let client = r.string()?;
+                w.u8(F_UNLCK)
+                    .u64(start)
+                    .u64(length)
+                    .u32(process)
+                    .string(client);
+            }
+            // the requests are served as they come, so there is none left to flush
+            TFLUSH => {}
+            // extended attributes, device files and the rest are not supported
+            _ => return Err(EOPNOTSUPP),
+        }
+        Ok(())
+    }
+
+    // Walks from a fid through names to a new fid, which is only made when all of them are
+    // walked. The qids of the names walked are returned all the same.
+    fn walk(&mut self, r: &mut Reader, w: &mut Writer) -> Result<(), Errno> {
+        let fid = r.u32()?;
+        let new_fid = r.u32()?;
+        let count = r.u16()?;
+        let mut path = self.path(fid)?.to_path_buf();
+        let mut qids = Writer::default();
+        let mut walked = 0;
+        for _ in 0..count {
+            let name = r.string()?;
+            if !fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.is_dir()) {
+                break;
+            }
+            let next = match name {
+                b".." if path == self.root => path.clone(),
+                b".." => path.parent().unwrap_or(&self.root).to_path_buf(),
+                name if name.is_empty() || name == b"." || name.contains(&b'/') => break,
+                name => path.join(OsStr::from_bytes(name)),
+            };
+            let Ok(metadata) = fs::symlink_metadata(&next) else {
+                break;
+            };
+            qids.qid(&metadata);
+            path = next;
+            walked += 1;
+        }
+        if walked == 0 && count > 0 {
+            return Err(ENOENT);
+        }
+        if walked == count {
+            self.add_fid(new_fid, path, None);
+        }
+        w.u16(walked).bytes(&qids.data);
+        Ok(())
+    }
+
+    fn set_attributes(&mut self, r: &mut Reader) -> Result<(), Errno> {
+        let fid = r.u32()?;
+        let valid = r.u32()?;
+        let (mode, uid, gid, size) = (r.u32()?, r.u32()?, r.u32()?, r.u64()?);
+        let atime = time(r.u64()?, r.u64()?);
+        let mtime = time(r.u64()?, r.u64()?);
+        let path = self.path(fid)?;
+        // the mode, the size and the times would be those of the target of a symbolic link, which
+        // the guest may have linked to anywhere, while the owner is that of the link itself
+        let followed = P9_SETATTR_MODE | P9_SETATTR_SIZE | P9_SETATTR_ATIME | P9_SETATTR_MTIME;
+        if valid & followed != 0 && fs::symlink_metadata(path).map_err(errno)?.is_symlink() {
+            return Err(ELOOP);
+        }
+        if valid & P9_SETATTR_MODE != 0 {
+            fs::set_permissions(path, Permissions::from_mode(mode & 0o7777)).map_err(errno)?;
+        }
+        if valid & (P9_SETATTR_UID | P9_SETATTR_GID) != 0 {
+            let uid = (valid & P9_SETATTR_UID != 0).then_some(uid);
+            let gid = (valid & P9_SETATTR_GID != 0).then_some(gid);
+            unix_fs::lchown(path, uid, gid).map_err(errno)?;
+        }
+        if valid & P9_SETATTR_SIZE != 0 {
+            let file = OpenOptions::new().write(true).open(path).map_err(errno)?;
+            file.set_len(size).map_err(errno)?;
+        }
+        if valid & (P9_SETATTR_ATIME | P9_SETATTR_MTIME) != 0 {
+            let now = SystemTime::now();
+            let mut times = FileTimes::new();
+            if valid & P9_SETATTR_ATIME != 0 {
+                let set = valid & P9_SETATTR_ATIME_SET != 0;
+                times = times.set_accessed(if set { atime } else { now });
+            }
+            if valid & P9_SETATTR_MTIME != 0 {
+                let set = valid & P9_SETATTR_MTIME_SET != 0;
+                times = times.set_modified(if set { mtime } else { now });
+            }
+            File::open(path)
+                .and_then(|file| file.set_times(times))
+                .map_err(errno)?;
+        }
+        Ok(())
+    }
+
+    // The entries of a directory from an offset, which is the index of the entry, as many as fit
+    // in the count. They are sorted by name, so that the offsets stay put between the requests.
+    fn read_directory(&mut self, r: &mut Reader, w: &mut Writer) -> Result<(), Errno> {
+        let fid = r.u32()?;
+        let offset = r.u64()?;
+        let count = r
+            .u32()?
+            .min(self.message_size.saturating_sub(IO_HEADER_SIZE)) as usize;
+        let path = self.directory(fid)?;
+        let parent = match path == self.root {
+            true => path,
+            false => path.parent().unwrap_or(&self.root),
+        };
+        let mut entries = vec![
+            (b".".to_vec(), fs::symlink_metadata(path).map_err(errno)?),
+            (b"..".to_vec(), fs::symlink_metadata(parent).map_err(errno)?),
+        ];
+        let mut names: Vec<_> = fs::read_dir(path)
+            .map_err(errno)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.file_name())
+            .collect();
+        names.sort();
+        entries.extend(names.into_iter().filter_map(|name| {
+            let metadata = fs::symlink_metadata(path.join(&name)).ok()?;
+            Some((name.as_bytes().to_vec(), metadata))
+        }));
+
+        let mut data = Writer::default();
+        for (index, (name, metadata)) in entries.iter().enumerate().skip(offset as usize) {
+            let mut entry = Writer::default();
+            let kind = if metadata.is_dir() {
+                DT_DIR
+            } else if metadata.is_symlink() {
+                DT_LNK
+            } else {
+                DT_REG
+            };
+            entry
+                .qid(metadata)
+                .u64(index as u64 + 1)
+                .u8(kind)
+                .string(name);
+            if data.data.len() + entry.data.len() > count {
+                break;
+            }
+            data.bytes(&entry.data);
+        }
+        w.u32(data.data.len() as u32).bytes(&data.data);
+        Ok(())
+    }
+}
+
+fn open_options(flags: u32) -> OpenOptions {
+    let mut options = OpenOptions::new();
+    let access = flags & O_ACCMODE;
+    options
+        .read(access != O_WRONLY)
+        .write(access != O_RDONLY)
+        .truncate(access != O_RDONLY && flags & O_TRUNC != 0)
+        .append(flags & O_APPEND != 0);
+    options
+}
+
+fn remove(path: &Path, directory: bool) -> Result<(), Errno> {
+    match directory {
+        true => fs::remove_dir(path).map_err(errno),
+        false => fs::remove_file(path).map_err(errno),
+    }
+}
+
+fn time(seconds: u64, nanoseconds: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::new(seconds, nanoseconds.min(999_999_999) as u32)
+}
+
+impl VirtioDevice for Filesystem {
+    fn device_id(&self) -> u32 {
+        DEVICE_ID
+    }
+
+    fn features(&self) -> u64 {
+        VIRTIO_9P_MOUNT_TAG
+    }
+
+    fn queues(&self) -> usize {
+        1
+    }
+
+    // the tag, after its length
+    fn config(&self) -> Vec<u8> {
+        let mut config = (self.tag.len() as u16).to_le_bytes().to_vec();
+        config.extend_from_slice(self.tag.as_bytes());
+        config
+    }
+
+    // Each chain holds a request, followed by room for its reply.
+    fn notify(
+        &mut self,
+        queue: usize,
+        queues: &mut [Queue],
+        memory: &mut Memory,
+    ) -> Result<(), QueueError> {
+        let queue = &mut queues[queue];
+        while let Some(chain) = queue.pop(memory)? {
+            let reply = self.serve(&chain.read(memory)?);
+            chain.write_at(memory, 0, &reply)?;
+            let written = reply.len().min(chain.writable_len());
+            queue.push(memory, chain, written)?;
+        }
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.fids.clear();
+    }
+}
@@ -0,0 +1,55 @@
+use super::{Queue, QueueError, VirtioDevice};
+use crate::{entropy::EntropySource, memory::Memory};
+
+const DEVICE_ID: u32 = 4;
+
+// An entropy device that fills the buffers of the driver from a seeded generator, so that the
+// runs of a guest are reproducible.
+pub struct Rng {
+    source: EntropySource,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            source: EntropySource::new(seed),
+        }
+    }
+}
+
+impl VirtioDevice for Rng {
+    fn device_id(&self) -> u32 {
+        DEVICE_ID
+    }
+
+    fn features(&self) -> u64 {
+        0
+    }
+
+    fn queues(&self) -> usize {
+        1
+    }
+
+    fn config(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn notify(
+        &mut self,
+        queue: usize,
+        queues: &mut [Queue],
+        memory: &mut Memory,
+    ) -> Result<(), QueueError> {
+        let queue = &mut queues[queue];
+        while let Some(chain) = queue.pop(memory)? {
+            let length = chain.writable_len();
+            let data: Vec<u8> = (0..length.div_ceil(8))
+                .flat_map(|_| self.source.next().to_le_bytes())
+                .take(length)
+                .collect();
+            chain.write_at(memory, 0, &data)?;
+            queue.push(memory, chain, length)?;
+        }
+        Ok(())
+    }
+}
@@ -1,16 +1,39 @@
+use crate::xlen::Xlen;
+
 #[derive(Default)]
 pub struct IntegerRegister {
     x: [u64; 32],
+    xlen: Xlen,
 }
 
 impl IntegerRegister {
+    pub fn new(xlen: Xlen) -> Self {
+        Self { x: [0; 32], xlen }
+    }
+
+    pub fn xlen(&self) -> Xlen {
+        self.xlen
+    }
+
+    // Switches to the XLEN of a new privilege mode. The upper bits that a narrower XLEN leaves
+    // behind are kept, but are ignored until the XLEN is widened again.
+    pub fn set_xlen(&mut self, xlen: Xlen) {
+        self.xlen = xlen;
+    }
+
     pub fn read(&self, register: usize) -> u64 {
-        self.x[register]
+        self.xlen.sign_extend(self.x[register])
     }
 
     pub fn write(&mut self, register: usize, value: u64) {
         if register != 0 {
-            self.x[register] = value;
+            self.x[register] = self.xlen.sign_extend(value);
         }
     }
+
+    // Computes a load/store effective address, which wraps around at XLEN.
+    pub fn address(&self, register: usize, offset: u64) -> u64 {
+        self.xlen
+            .zero_extend(self.read(register).wrapping_add(offset))
+    }
 }
@@ -0,0 +1,48 @@
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum Xlen {
+    Rv32 = 32,
+    #[default]
+    Rv64 = 64,
+}
+
+impl Xlen {
+    // Decodes the MXL, SXL and UXL fields.
+    pub fn from_primitive(xl: u64) -> Option<Self> {
+        match xl {
+            1 => Some(Self::Rv32),
+            2 => Some(Self::Rv64),
+            _ => None,
+        }
+    }
+
+    pub fn to_primitive(self) -> u64 {
+        match self {
+            Self::Rv32 => 1,
+            Self::Rv64 => 2,
+        }
+    }
+
+    pub fn bits(self) -> u32 {
+        self as u32
+    }
+
+    // Registers hold narrower values sign-extended to 64 bits, as the RV64 *W instructions do,
+    // so only the operations that look at the upper bits need to know the XLEN.
+    pub fn sign_extend(self, value: u64) -> u64 {
+        match self {
+            Self::Rv32 => value as i32 as u64,
+            Self::Rv64 => value,
+        }
+    }
+
+    pub fn zero_extend(self, value: u64) -> u64 {
+        match self {
+            Self::Rv32 => value as u32 as u64,
+            Self::Rv64 => value,
+        }
+    }
+
+    pub fn shamt_mask(self) -> u64 {
+        self.bits() as u64 - 1
+    }
+}
@@ -0,0 +1,34 @@
+// Shared by the test crates, each of which uses some of it.
+#![allow(dead_code)]
+
+use minimum_five::{
+    config::Config,
+    htif::Htif,
+    simulator::{Exit, ImageFormat, Simulator},
+};
+
+// Where the programs of the tests write their exit code, in the page after them.
+pub const TOHOST: u64 = 0x8000_1000;
+
+pub fn image(program: &[u32]) -> Vec<u8> {
+    program.iter().flat_map(|i| i.to_le_bytes()).collect()
+}
+
+// A simulator that runs `program` from the start of the memory until it writes to tohost.
+pub fn simulator(config: &Config, program: &[u32]) -> Simulator {
+    let mut simulator = Simulator::new(config).unwrap();
+    simulator
+        .load_bytes(&image(program), ImageFormat::Binary)
+        .unwrap();
+    simulator.set_htif(Htif::new(TOHOST, None, Vec::new()));
+    simulator
+}
+
+// Runs `program` until it writes to tohost, and returns the number of the check that failed, or
+// 0 once all of them have passed.
+pub fn run(config: &Config, program: &[u32]) -> u64 {
+    match simulator(config, program).run(Some(100_000)) {
+        Exit::Code(code) => code,
+        Exit::Limit => panic!("the program did not finish"),
+    }
+}
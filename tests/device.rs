@@ -0,0 +1,1463 @@
+use std::{
+    cell::{Cell, RefCell},
+    env, fs,
+    io::{self, Write},
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    process,
+    rc::Rc,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+mod common;
+
+use common::{image, simulator, TOHOST};
+use minimum_five::{
+    aplic::{Aplic, DEFAULT_APLIC_BASE},
+    bus::{BusError, Device},
+    cli::Command,
+    clint::{Clint, TimeSource, DEFAULT_CLINT_BASE},
+    config::Config,
+    fdt::device_tree,
+    htif::Htif,
+    imsic::{Imsic, DEFAULT_IMSIC_BASE},
+    isa::Isa,
+    memory::{Memory, Region},
+    plic::{Plic, DEFAULT_PLIC_BASE},
+    simulator::{Exit, Simulator},
+    uart::{Uart, DEFAULT_UART_BASE, UART_IRQ},
+    virtio::{
+        Block, Console, Filesystem, Loopback, Net, Port, Rng, Socket, VirtioDevice, VirtioMmio,
+    },
+};
+
+const DEVICE_BASE: u64 = 0x1000_0000;
+const DEVICE_IRQ: u32 = 3;
+
+// Answers a read at 4 with the last value written at 0, plus one.
+struct Incrementer {
+    value: u64,
+    ticks: Rc<Cell<u64>>,
+}
+
+impl Device for Incrementer {
+    fn size(&self) -> u64 {
+        8
+    }
+
+    fn read(&mut self, offset: u64, _: usize) -> Result<u64, BusError> {
+        match offset {
+            4 => Ok(self.value + 1),
+            _ => Err(BusError),
+        }
+    }
+
+    fn write(&mut self, offset: u64, _: usize, value: u64) -> Result<(), BusError> {
+        if offset != 0 {
+            return Err(BusError);
+        }
+        self.value = value;
+        Ok(())
+    }
+
+    fn tick(&mut self) {
+        self.ticks.set(self.ticks.get() + 1);
+    }
+}
+
+#[test]
+fn device() {
+    let program: [u32; 9] = [
+        0x100002b7, // lui t0, 0x10000
+        0x02a00313, // li t1, 42
+        0x0062a023, // sw t1, 0(t0)
+        0x0042a383, // lw t2, 4(t0)
+        0x00139393, // slli t2, t2, 1
+        0x0013e393, // ori t2, t2, 1
+        0x00001e17, // auipc t3, 1
+        0xfe7e3423, // sd t2, -24(t3), to tohost at 0x80001000
+        0x0000006f, // j .
+    ];
+
+    let mut simulator = simulator(&Config::default(), &program);
+    let ticks = Rc::new(Cell::new(0));
+    let incrementer = || {
+        Box::new(Incrementer {
+            value: 0,
+            ticks: ticks.clone(),
+        })
+    };
+    simulator.attach(DEVICE_BASE, incrementer(), None).unwrap();
+    // a device may not overlap the memory
+    assert!(simulator.attach(0x8000_0000, incrementer(), None).is_err());
+
+    assert!(matches!(simulator.run(Some(100)), Exit::Code(43)));
+    assert!(ticks.get() > 0);
+}
+
+// Asserts its interrupt line from the start.
+struct Doorbell;
+
+impl Device for Doorbell {
+    fn size(&self) -> u64 {
+        4
+    }
+
+    fn read(&mut self, _: u64, _: usize) -> Result<u64, BusError> {
+        Ok(0)
+    }
+
+    fn write(&mut self, _: u64, _: usize, _: u64) -> Result<(), BusError> {
+        Ok(())
+    }
+
+    fn interrupt(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn plic() {
+    let program: [u32; 21] = [
+        0x00000297, // auipc t0, 0
+        0x03828293, // addi t0, t0, 56
+        0x30529073, // csrw mtvec, t0
+        0x0c0004b7, // lui s1, 0xc000
+        0x00100293, // li t0, 1
+        0x0054a623, // sw t0, 12(s1), the priority of source 3
+        0x0c002337, // lui t1, 0xc002
+        0x00800293, // li t0, 8
+        0x00532023, // sw t0, 0(t1), enabling source 3 in the M-mode context of hart 0
+        0x000012b7, // lui t0, 1
+        0x8002829b, // addiw t0, t0, -2048
+        0x30429073, // csrw mie, t0
+        0x30046073, // csrsi mstatus, 8
+        0x0000006f, // j .
+        0x0c200337, // lui t1, 0xc200, where the external interrupt is handled
+        0x00432283, // lw t0, 4(t1), claiming the source
+        0x00129293, // slli t0, t0, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00001e17, // auipc t3, 1
+        0xfa5e3c23, // sd t0, -72(t3), to tohost at 0x80001000
+        0x0000006f, // j .
+    ];
+
+    let mut simulator = simulator(&Config::default(), &program);
+    let plic = Plic::new(1, 31);
+    simulator.attach_plic(DEFAULT_PLIC_BASE, plic).unwrap();
+    simulator
+        .attach(DEVICE_BASE, Box::new(Doorbell), Some(DEVICE_IRQ))
+        .unwrap();
+
+    assert!(matches!(simulator.run(Some(100)), Exit::Code(3)));
+}
+
+#[test]
+fn aia() {
+    let program: [u32; 37] = [
+        0x00000297, // auipc t0, 0
+        0x07828293, // addi t0, t0, 120
+        0x30529073, // csrw mtvec, t0
+        0x07000293, // li t0, 0x70
+        0x35029073, // csrw miselect, t0
+        0x00100293, // li t0, 1
+        0x35129073, // csrw mireg, t0, enabling the delivery of the M-level interrupt file
+        0x0c000293, // li t0, 0xc0
+        0x35029073, // csrw miselect, t0
+        0x08000293, // li t0, 0x80
+        0x35129073, // csrw mireg, t0, enabling identity 7 in eie0
+        0x0c0004b7, // lui s1, 0xc000
+        0x000242b7, // lui t0, 0x24
+        0x0c002337, // lui t1, 0xc002
+        0xbc532023, // sw t0, -1088(t1), the page of the interrupt file of hart 0 in mmsiaddrcfg
+        0x10400293, // li t0, 0x104
+        0x0054a023, // sw t0, 0(s1), enabling the domain in MSI delivery mode
+        0x00600293, // li t0, 6
+        0x0054a623, // sw t0, 12(s1), making source 3 level-triggered
+        0x00700293, // li t0, 7
+        0x0c003337, // lui t1, 0xc003
+        0x00532623, // sw t0, 12(t1), sending source 3 to hart 0 as identity 7
+        0x00300293, // li t0, 3
+        0x0c002337, // lui t1, 0xc002
+        0xec532e23, // sw t0, -292(t1), enabling source 3 in setienum
+        0x000012b7, // lui t0, 1
+        0x8002829b, // addiw t0, t0, -2048
+        0x30429073, // csrw mie, t0
+        0x30046073, // csrsi mstatus, 8
+        0x0000006f, // j .
+        0x35c012f3, // csrrw t0, mtopei, zero, claiming the interrupt
+        0x0102d293, // srli t0, t0, 16
+        0x00129293, // slli t0, t0, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00001e17, // auipc t3, 1
+        0xf65e3c23, // sd t0, -136(t3), to tohost at 0x80001000
+        0x0000006f, // j .
+    ];
+
+    let mut simulator = simulator(&Config::default(), &program);
+    let aplic = Aplic::new(1, 31);
+    simulator.attach_aplic(DEFAULT_APLIC_BASE, aplic).unwrap();
+    let imsic = Imsic::new(1, 63);
+    simulator.attach_imsic(DEFAULT_IMSIC_BASE, imsic).unwrap();
+    simulator
+        .attach(DEVICE_BASE, Box::new(Doorbell), Some(DEVICE_IRQ))
+        .unwrap();
+
+    assert!(matches!(simulator.run(Some(100)), Exit::Code(7)));
+}
+
+#[test]
+fn uart() {
+    let program: [u32; 120] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x17cd0d13, // la s10, expected
+        0x00000297, 0x14c28293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x10000437, // li s0, 0x10000000
+        0x06800293, // li t0, 104
+        0x00540023, // sb t0, 0(s0), h
+        0x06900293, // li t0, 105
+        0x00540023, // sb t0, 0(s0), i
+        0x08000293, // li t0, 0x80
+        0x005401a3, // sb t0, 3(s0), LCR.DLAB
+        0x00300293, // li t0, 3
+        0x00540023, // sb t0, 0(s0), a divisor of 3 in DLL
+        0x000400a3, // sb zero, 1(s0), and DLM
+        0x00300293, // li t0, 3
+        0x005401a3, // sb t0, 3(s0), 8 data bits, clearing DLAB
+        0x00144283, // lbu t0, 1(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x0ff29463, // bne t0, t6, fail, IER, not DLM
+        0x00344283, // lbu t0, 3(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x0df29c63, // bne t0, t6, fail, LCR
+        0x00100293, // li t0, 1
+        0x00540123, // sb t0, 2(s0), FCR enables the FIFOs
+        0x00544283, // lbu t0, 5(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x0df29063, // bne t0, t6, fail, LSR: data ready, and the transmitter empty
+        0x00244283, // lbu t0, 2(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x0bf29863, // bne t0, t6, fail, IIR: no interrupt while IER disables them
+        0x0c000337, // li t1, 0xc000000
+        0x00100293, // li t0, 1
+        0x02532423, // sw t0, 40(t1), the priority of source 10
+        0x0c002337, // li t1, 0xc002000
+        0x40000293, // li t0, 0x400
+        0x00532023, // sw t0, 0(t1), enabling source 10 in the M-mode context of hart 0
+        0x000012b7, 0x8002829b, // li t0, 0x800
+        0x30429073, // csrw mie, t0
+        0x30046073, // csrsi mstatus, 8
+        0x00100293, // li t0, 1
+        0x005400a3, // sb t0, 1(s0), IER enables the interrupt of the received data
+        0x30047073, // csrci mstatus, 8
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x07fc9863, // bne s9, t6, fail, a machine external interrupt
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x07f49263, // bne s1, t6, fail, claimed from the PLIC
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x05f91c63, // bne s2, t6, fail, IIR: received data
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x05f99663, // bne s3, t6, fail, x
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x05fa1063, // bne s4, t6, fail, LSR: no more data
+        0x001d8d93, // addi s11, s11, 1
+        0x048d3f83, // ld t6, 72(s10)
+        0x03fa9a63, // bne s5, t6, fail, IIR: no interrupt
+        0x00200293, // li t0, 2
+        0x005400a3, // sb t0, 1(s0), IER enables the interrupt of the empty transmitter
+        0x00244283, // lbu t0, 2(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x050d3f83, // ld t6, 80(s10)
+        0x01f29e63, // bne t0, t6, fail, IIR: the transmitter is empty
+        0x00244283, // lbu t0, 2(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x058d3f83, // ld t6, 88(s10)
+        0x01f29663, // bne t0, t6, fail, which reading IIR acknowledged
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x0c200337, 0x0043031b, // li t1, 0xc200004
+        0x00032483, // lw s1, 0(t1), claims the source
+        0x00244903, // lbu s2, 2(s0)
+        0x00044983, // lbu s3, 0(s0)
+        0x00544a03, // lbu s4, 5(s0)
+        0x00244a83, // lbu s5, 2(s0)
+        0x00932023, // sw s1, 0(t1), completes it
+        0x30200073, // mret
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000003, 0x00000000, // expected 0x3
+        0x00000061, 0x00000000, // expected 0x61
+        0x000000c1, 0x00000000, // expected 0xc1
+        0x0000000b, 0x80000000, // expected 0x800000000000000b
+        0x0000000a, 0x00000000, // expected 0xa
+        0x000000c4, 0x00000000, // expected 0xc4
+        0x00000078, 0x00000000, // expected 0x78
+        0x00000060, 0x00000000, // expected 0x60
+        0x000000c1, 0x00000000, // expected 0xc1
+        0x000000c2, 0x00000000, // expected 0xc2
+        0x000000c1, 0x00000000, // expected 0xc1
+    ];
+
+    // the program prints "hi", checks the divisor latch, and takes the interrupt of the byte
+    // received from the host through the PLIC, and then that of the empty transmitter
+    let (input, receiver) = mpsc::channel();
+    input.send(b'x').unwrap();
+    let output = Output::default();
+    let mut simulator = simulator(&Config::default(), &program);
+    let uart = Uart::new(receiver, Box::new(output.clone()));
+    simulator
+        .attach(DEFAULT_UART_BASE, Box::new(uart), Some(UART_IRQ))
+        .unwrap();
+    simulator
+        .attach_plic(DEFAULT_PLIC_BASE, Plic::new(1, 31))
+        .unwrap();
+    assert!(matches!(simulator.run(Some(1000)), Exit::Code(0)));
+    assert_eq!(*output.0.borrow(), b"hi");
+}
+
+#[test]
+fn clint() {
+    let program: [u32; 72] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x0f4d0d13, // la s10, expected
+        0x00000297, 0x0d028293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x02000437, // li s0, 0x2000000
+        0x00800293, // li t0, 8
+        0x30429073, // csrw mie, t0
+        0x30046073, // csrsi mstatus, 8
+        0x00100293, // li t0, 1
+        0x00542023, // sw t0, 0(s0), msip of hart 0
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x09fc9463, // bne s9, t6, fail, a machine software interrupt
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x07f49e63, // bne s1, t6, fail, which clearing msip acknowledged
+        0x08000293, // li t0, 0x80
+        0x30429073, // csrw mie, t0
+        0x0200c337, 0xff83031b, // li t1, 0x200bff8
+        0x00033903, // ld s2, 0(t1), mtime
+        0x03290293, // addi t0, s2, 50
+        0x02004337, // li t1, 0x2004000
+        0x00533023, // sd t0, 0(t1), mtimecmp of hart 0, 50 ticks ahead
+        0x00000c93, // li s9, 0
+        0x000c8063, // beqz s9, wait
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x05fc9463, // bne s9, t6, fail, a machine timer interrupt
+        0x0200c337, 0xff83031b, // li t1, 0x200bff8
+        0x00033283, // ld t0, 0(t1)
+        0x412282b3, // sub t0, t0, s2
+        0x0322b293, // sltiu t0, t0, 50
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x03f29463, // bne t0, t6, fail, not before mtimecmp
+        0xc01022f3, // csrr t0, time
+        0x00033303, // ld t1, 0(t1)
+        0x405302b3, // sub t0, t1, t0
+        0x0042b293, // sltiu t0, t0, 4
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x01f29663, // bne t0, t6, fail, the time CSR follows mtime
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x00042023, // sw zero, 0(s0), clears msip
+        0xfff00293, // li t0, -1
+        0x02004337, // li t1, 0x2004000
+        0x00533023, // sd t0, 0(t1), and mtimecmp
+        0x344024f3, // csrr s1, mip
+        0x30200073, // mret
+        0x00000003, 0x80000000, // expected 0x8000000000000003
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000007, 0x80000000, // expected 0x8000000000000007
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000001, 0x00000000, // expected 0x1
+    ];
+
+    // the program interrupts itself through msip, and then through mtimecmp
+    let mut simulator = simulator(&Config::default(), &program);
+    let clint = Clint::new(1, TimeSource::Instructions);
+    simulator.attach_clint(DEFAULT_CLINT_BASE, clint).unwrap();
+    assert!(matches!(simulator.run(Some(1000)), Exit::Code(0)));
+}
+
+#[test]
+fn sstc() {
+    let program: [u32; 142] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x1f4d0d13, // la s10, expected
+        0x00000297, 0x1bc28293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x30a01073, // csrw menvcfg, zero
+        0x00200293, // li t0, 2
+        0x30629073, // csrw mcounteren, t0
+        0x02000293, // li t0, 32
+        0x3032a073, // csrs mideleg, t0, shows STIP in sip
+        0x000022b7, 0x8002829b, // li t0, 0x1800
+        0x3002b073, // csrc mstatus, t0
+        0x000012b7, 0x8002829b, // li t0, 0x800
+        0x3002a073, // csrs mstatus, t0, mstatus.MPP is S-mode
+        0x00000297, 0x01028293, // la t0, first
+        0x34129073, // csrw mepc, t0
+        0x30200073, // mret
+        0x00000c93, // li s9, 0
+        0x14d022f3, // csrr t0, stimecmp
+        0x000c8b93, // mv s7, s9
+        0x00000073, // ecall
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x15fb9063, // bne s7, t6, fail, illegal without menvcfg.STCE
+        0x00100293, // li t0, 1
+        0x03f29293, // slli t0, t0, 63
+        0x30a2a073, // csrs menvcfg, t0
+        0x30601073, // csrw mcounteren, zero
+        0x000022b7, 0x8002829b, // li t0, 0x1800
+        0x3002b073, // csrc mstatus, t0
+        0x000012b7, 0x8002829b, // li t0, 0x800
+        0x3002a073, // csrs mstatus, t0, mstatus.MPP is S-mode
+        0x00000297, 0x01028293, // la t0, second
+        0x34129073, // csrw mepc, t0
+        0x30200073, // mret
+        0x00000c93, // li s9, 0
+        0x14d022f3, // csrr t0, stimecmp
+        0x000c8b93, // mv s7, s9
+        0x00000073, // ecall
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x0ffb9663, // bne s7, t6, fail, illegal without mcounteren.TM
+        0x00200293, // li t0, 2
+        0x3062a073, // csrs mcounteren, t0
+        0x000022b7, 0x8002829b, // li t0, 0x1800
+        0x3002b073, // csrc mstatus, t0
+        0x000012b7, 0x8002829b, // li t0, 0x800
+        0x3002a073, // csrs mstatus, t0, mstatus.MPP is S-mode
+        0x00000297, 0x01028293, // la t0, third
+        0x34129073, // csrw mepc, t0
+        0x30200073, // mret
+        0x00000c93, // li s9, 0
+        0xfff00293, // li t0, -1
+        0x14d29073, // csrw stimecmp, t0
+        0x14402373, // csrr t1, sip
+        0x14d01073, // csrw stimecmp, zero, reached by time
+        0x144023f3, // csrr t2, sip
+        0x000c8b93, // mv s7, s9
+        0x00000073, // ecall
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x09fb9863, // bne s7, t6, fail, allowed
+        0x02037313, // andi t1, t1, 32
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x09f31063, // bne t1, t6, fail, STIP clear until stimecmp is reached
+        0x0203f393, // andi t2, t2, 32
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x07f39863, // bne t2, t6, fail, and set once it is
+        0x02000293, // li t0, 32
+        0x3442b073, // csrc mip, t0
+        0x344022f3, // csrr t0, mip
+        0x0202f293, // andi t0, t0, 32
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x05f29a63, // bne t0, t6, fail, which M-mode cannot clear
+        0x02000293, // li t0, 32
+        0x3032b073, // csrc mideleg, t0
+        0x3042a073, // csrs mie, t0
+        0x30046073, // csrsi mstatus, 8
+        0x00000013, // nop
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x03fc9a63, // bne s9, t6, fail, a supervisor timer interrupt
+        0x00100293, // li t0, 1
+        0x03f29293, // slli t0, t0, 63
+        0x30a2b073, // csrc menvcfg, t0
+        0x02000293, // li t0, 32
+        0x3442b073, // csrc mip, t0
+        0x344022f3, // csrr t0, mip
+        0x0202f293, // andi t0, t0, 32
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x01f29663, // bne t0, t6, fail, which M-mode clears once STCE no longer drives it
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x30401073, // csrw mie, zero, stops the interrupts
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x00900f13, // li t5, 9, an ecall from S-mode returns to M-mode
+        0x01ec9863, // bne s9, t5, back
+        0x00002f37, 0x800f0f1b, // li t5, 0x1800
+        0x300f2073, // csrs mstatus, t5
+        0x30200073, // mret
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000020, 0x00000000, // expected 0x20
+        0x00000020, 0x00000000, // expected 0x20
+        0x00000005, 0x80000000, // expected 0x8000000000000005
+        0x00000000, 0x00000000, // expected 0x0
+    ];
+
+    // S-mode reaches stimecmp once menvcfg and mcounteren allow it, and stimecmp then drives STIP,
+    // against the time of the CLINT
+    let mut simulator = simulator(&Config::default(), &program);
+    let clint = Clint::new(1, TimeSource::Instructions);
+    simulator.attach_clint(DEFAULT_CLINT_BASE, clint).unwrap();
+    assert!(matches!(simulator.run(Some(1000)), Exit::Code(0)));
+}
+
+// Drives a virtio-mmio transport as a driver does, with a queue of 8 descriptors in each page
+// from 0x80010000, which has its available ring at 0x80 and its used ring at 0x100.
+struct Driver {
+    transport: VirtioMmio,
+    memory: Memory,
+    available: Vec<u16>,
+}
+
+impl Driver {
+    fn new(device: Box<dyn VirtioDevice>, queues: usize) -> Self {
+        let mut transport = VirtioMmio::new(device);
+        // ACKNOWLEDGE and DRIVER, then VIRTIO_F_VERSION_1 and FEATURES_OK
+        transport.write(0x70, 4, 3).unwrap();
+        transport.write(0x24, 4, 1).unwrap();
+        transport.write(0x20, 4, 1).unwrap();
+        transport.write(0x70, 4, 11).unwrap();
+        assert_eq!(transport.read(0x70, 4).unwrap(), 11);
+        for queue in 0..queues as u64 {
+            let page = 0x8001_0000 + 0x1000 * queue;
+            transport.write(0x30, 4, queue).unwrap();
+            transport.write(0x38, 4, 8).unwrap();
+            for (register, address) in [(0x80, page), (0x90, page + 0x80), (0xa0, page + 0x100)] {
+                transport.write(register, 4, address).unwrap();
+            }
+            transport.write(0x44, 4, 1).unwrap();
+        }
+        // DRIVER_OK
+        transport.write(0x70, 4, 15).unwrap();
+        Self {
+            transport,
+            memory: Memory::default(),
+            available: vec![0; queues],
+        }
+    }
+
+    fn store(&mut self, address: u64, size: usize, value: u64) {
+        assert!(self.memory.store_bytes(address, size, value).is_ok());
+    }
+
+    // Makes a chain of the buffers available in `queue`, as addresses, lengths and whether the
+    // device writes them, and notifies it.
+    fn queue(&mut self, queue: usize, buffers: &[(u64, u64, bool)]) {
+        let page = 0x8001_0000 + 0x1000 * queue as u64;
+        for (i, &(address, length, writable)) in buffers.iter().enumerate() {
+            let descriptor = page + 16 * i as u64;
+            let next = if i + 1 < buffers.len() { 1 } else { 0 };
+            self.store(descriptor, 8, address);
+            self.store(descriptor + 8, 4, length);
+            self.store(descriptor + 12, 2, next | (writable as u64) << 1);
+            self.store(descriptor + 14, 2, i as u64 + 1);
+        }
+        let index = self.available[queue];
+        self.store(page + 0x84 + 2 * (index % 8) as u64, 2, 0);
+        self.available[queue] = index.wrapping_add(1);
+        self.store(page + 0x82, 2, index as u64 + 1);
+        self.transport.write(0x50, 4, queue as u64).unwrap();
+        self.transport.process(&mut self.memory);
+    }
+
+    // The index of the used ring of `queue`, and the length of the last chain in it.
+    fn used(&self, queue: usize) -> (u64, u64) {
+        let page = 0x8001_0000 + 0x1000 * queue as u64;
+        let index = self.memory.load_bytes(page + 0x102, 2).ok().unwrap();
+        let element = page + 0x104 + 8 * ((index + 7) % 8);
+        (index, self.memory.load_bytes(element + 4, 4).ok().unwrap())
+    }
+}
+
+// The header of a request of virtio-blk is at 0x80020000, its data at 0x80020100 and its status
+// at 0x80020400.
+fn queue_request(driver: &mut Driver, kind: u64, sector: u64) {
+    driver.store(0x8002_0000, 4, kind);
+    driver.store(0x8002_0008, 8, sector);
+    driver.queue(
+        0,
+        &[
+            (0x8002_0000, 16, false),
+            (0x8002_0100, 512, kind == 0),
+            (0x8002_0400, 1, true),
+        ],
+    );
+}
+
+#[test]
+fn virtio_blk() {
+    let path = env::temp_dir().join(format!("minimum-five-blk-{}", process::id()));
+    let image: Vec<u8> = (0..2048).map(|i| (i / 512) as u8).collect();
+    fs::write(&path, &image).unwrap();
+    let mut transport = VirtioMmio::new(Box::new(Block::open(&path, false).unwrap()));
+    assert_eq!(transport.read(0x0, 4).unwrap(), 0x7472_6976);
+    assert_eq!(transport.read(0x8, 4).unwrap(), 2);
+    // the capacity, in sectors
+    assert_eq!(transport.read(0x100, 8).unwrap(), 4);
+
+    let mut driver = Driver::new(Box::new(Block::open(&path, false).unwrap()), 1);
+    // write sector 2, then read it back
+    assert!(driver.memory.store_slice(0x8002_0100, &[0xab; 512]).is_ok());
+    queue_request(&mut driver, 1, 2);
+    assert!(driver.transport.interrupt());
+    driver.transport.write(0x64, 4, 1).unwrap();
+    assert!(!driver.transport.interrupt());
+    assert!(driver.memory.store_slice(0x8002_0100, &[0; 512]).is_ok());
+    queue_request(&mut driver, 0, 2);
+    assert!(driver.transport.interrupt());
+    // the used length counts the data and the status
+    assert_eq!(driver.used(0), (2, 513));
+    assert_eq!(driver.memory.load_bytes(0x8002_0400, 1).ok(), Some(0));
+    let data = driver.memory.load_slice(0x8002_0100, 512).ok();
+    assert_eq!(data, Some(&[0xab; 512][..]));
+
+    // a request past the end of the disk fails
+    queue_request(&mut driver, 0, 4);
+    assert_eq!(driver.memory.load_bytes(0x8002_0400, 1).ok(), Some(1));
+
+    // the image is left as it was
+    assert_eq!(fs::read(&path).unwrap(), image);
+    // a read-only device offers VIRTIO_BLK_F_RO
+    let mut transport = VirtioMmio::new(Box::new(Block::open(&path, true).unwrap()));
+    assert_eq!(transport.read(0x10, 4).unwrap() & 1 << 5, 1 << 5);
+    fs::remove_file(&path).unwrap();
+}
+
+// Transmits a frame at 0x80020000, after its header, and gives a buffer at 0x80030000 to receive
+// one.
+fn exchange_frame(driver: &mut Driver, frame: &[u8]) {
+    assert!(driver.memory.store_slice(0x8002_0000, &[0; 12]).is_ok());
+    assert!(driver.memory.store_slice(0x8002_000c, frame).is_ok());
+    driver.queue(0, &[(0x8003_0000, 1526, true)]);
+    driver.queue(1, &[(0x8002_0000, 12 + frame.len() as u64, false)]);
+}
+
+// Waits for the device to receive `frame` from the other end of its socket.
+fn poll_frame(driver: &mut Driver, frame: &[u8]) {
+    for _ in 0..1000 {
+        driver.transport.process(&mut driver.memory);
+        if driver.used(0).0 != 0 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    let received = driver.memory.load_slice(0x8003_000c, frame.len()).ok();
+    assert_eq!(received, Some(frame));
+}
+
+#[test]
+fn virtio_net() {
+    let frame: Vec<u8> = (0..60).collect();
+    let capture = env::temp_dir().join(format!("minimum-five-net-{}.pcap", process::id()));
+    let mut net = Net::new(Box::<Loopback>::default(), [2, 0, 0, 0, 0, 1]);
+    net.capture(&capture).unwrap();
+    let mut driver = Driver::new(Box::new(net), 2);
+    assert_eq!(driver.transport.read(0x8, 4).unwrap(), 1);
+    // the MAC address, and the link is up
+    assert_eq!(driver.transport.read(0x100, 4).unwrap(), 2);
+    assert_eq!(driver.transport.read(0x105, 1).unwrap(), 1);
+    assert_eq!(driver.transport.read(0x106, 2).unwrap(), 1);
+
+    // the loopback gives the frame back, after the header with one buffer
+    exchange_frame(&mut driver, &frame);
+    assert_eq!(driver.used(1), (1, 0));
+    assert_eq!(driver.used(0), (1, 72));
+    assert_eq!(driver.memory.load_bytes(0x8003_000a, 2).ok(), Some(1));
+    let received = driver.memory.load_slice(0x8003_000c, 60).ok();
+    assert_eq!(received, Some(&frame[..]));
+    // the capture holds the frame sent and the frame received
+    let pcap = fs::read(&capture).unwrap();
+    assert_eq!(pcap.len(), 24 + 2 * (16 + 60));
+    assert_eq!(pcap[..4], [0xd4, 0xc3, 0xb2, 0xa1]);
+    assert_eq!(pcap[24 + 16..24 + 16 + 60], frame[..]);
+    fs::remove_file(&capture).unwrap();
+
+    // two devices exchange frames through a socket
+    let path = env::temp_dir().join(format!("minimum-five-net-{}.sock", process::id()));
+    let listener = Socket::listen(&path).unwrap();
+    let mut first = Driver::new(
+        Box::new(Net::new(Box::new(listener), [2, 0, 0, 0, 0, 1])),
+        2,
+    );
+    let connector = Socket::connect(&path).unwrap();
+    let mut second = Driver::new(
+        Box::new(Net::new(Box::new(connector), [2, 0, 0, 0, 0, 2])),
+        2,
+    );
+    // the frame waits in the socket until the listener has accepted the connection
+    first.queue(0, &[(0x8003_0000, 1526, true)]);
+    exchange_frame(&mut second, &frame);
+    poll_frame(&mut first, &frame);
+    exchange_frame(&mut first, &frame);
+    poll_frame(&mut second, &frame);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn virtio_rng() {
+    let bytes = |seed| {
+        let mut driver = Driver::new(Box::new(Rng::new(seed)), 1);
+        driver.queue(0, &[(0x8002_0000, 20, true)]);
+        assert_eq!(driver.used(0), (1, 20));
+        driver
+            .memory
+            .load_slice(0x8002_0000, 21)
+            .unwrap_or_default()
+            .to_vec()
+    };
+    let first = bytes(1);
+    assert_eq!(first, bytes(1));
+    assert_ne!(first, bytes(2));
+    // only the buffer is written
+    assert_eq!(first[20], 0);
+}
+
+// Collects what is written to it.
+#[derive(Clone, Default)]
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn virtio_console() {
+    let (console_input, console_receiver) = mpsc::channel();
+    let (_, port_receiver) = mpsc::channel();
+    let (console_output, port_output) = (Output::default(), Output::default());
+    let ports = vec![
+        Port::new("", console_receiver, Box::new(console_output.clone())),
+        Port::new("agent", port_receiver, Box::new(port_output.clone())),
+    ];
+    let mut driver = Driver::new(Box::new(Console::new(ports)), 6);
+    // the number of ports
+    assert_eq!(driver.transport.read(0x104, 4).unwrap(), 2);
+
+    // port 0 transmits to its output and receives from its input
+    assert!(driver.memory.store_slice(0x8002_0000, b"hello").is_ok());
+    driver.queue(1, &[(0x8002_0000, 5, false)]);
+    assert_eq!(*console_output.0.borrow(), b"hello");
+    for &byte in b"hi" {
+        console_input.send(byte).unwrap();
+    }
+    driver.queue(0, &[(0x8003_0000, 16, true)]);
+    assert_eq!(driver.used(0), (1, 2));
+    assert_eq!(
+        driver.memory.load_slice(0x8003_0000, 2).ok(),
+        Some(&b"hi"[..])
+    );
+
+    // the ports are added once the driver is ready, on the control queues
+    driver.store(0x8002_0000, 8, 0x0001_0000_0000_0000);
+    driver.queue(3, &[(0x8002_0000, 8, false)]);
+    for _ in 0..2 {
+        driver.queue(2, &[(0x8003_0000, 64, true)]);
+    }
+    assert_eq!(driver.used(2), (2, 8));
+    // port 1, VIRTIO_CONSOLE_DEVICE_ADD
+    assert_eq!(
+        driver.memory.load_bytes(0x8003_0000, 8).ok(),
+        Some(0x0000_0001_0000_0001)
+    );
+    // and it transmits to its output
+    assert!(driver.memory.store_slice(0x8002_0000, b"ping").is_ok());
+    driver.queue(5, &[(0x8002_0000, 4, false)]);
+    assert_eq!(*port_output.0.borrow(), b"ping");
+}
+
+// Sends a request to a virtio-9p device and returns the reply.
+fn transact(driver: &mut Driver, kind: u8, body: &[u8]) -> Vec<u8> {
+    let mut request = ((7 + body.len()) as u32).to_le_bytes().to_vec();
+    request.push(kind);
+    request.extend_from_slice(&1u16.to_le_bytes());
+    request.extend_from_slice(body);
+    assert!(driver.memory.store_slice(0x8002_0000, &request).is_ok());
+    let length = request.len() as u64;
+    driver.queue(
+        0,
+        &[(0x8002_0000, length, false), (0x8003_0000, 8192, true)],
+    );
+    let (_, written) = driver.used(0);
+    let reply = driver.memory.load_slice(0x8003_0000, written as usize).ok();
+    reply.unwrap().to_vec()
+}
+
+// The fields of a request of 9P: the little-endian numbers, and the strings after their lengths.
+fn fields(fields: &[&[u8]]) -> Vec<u8> {
+    fields.concat()
+}
+
+// A string of 9P, after its length.
+fn string(string: &str) -> Vec<u8> {
+    let mut bytes = (string.len() as u16).to_le_bytes().to_vec();
+    bytes.extend_from_slice(string.as_bytes());
+    bytes
+}
+
+#[test]
+fn virtio_9p() {
+    let directory = env::temp_dir().join(format!("minimum-five-9p-{}", process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("hello.txt"), b"hello from the host").unwrap();
+    let filesystem = Filesystem::new(&directory, "share").unwrap();
+    let mut driver = Driver::new(Box::new(filesystem), 1);
+    // the tag, after its length
+    assert_eq!(driver.transport.read(0x100, 2).unwrap(), 5);
+    assert_eq!(driver.transport.read(0x102, 1).unwrap(), b's' as u64);
+
+    let version = transact(
+        &mut driver,
+        100,
+        &fields(&[&8192u32.to_le_bytes(), &string("9P2000.L")]),
+    );
+    assert_eq!(version[4], 101);
+    assert_eq!(version[7..11], 8192u32.to_le_bytes());
+    let attach = fields(&[
+        &0u32.to_le_bytes(),
+        &u32::MAX.to_le_bytes(),
+        &string("root"),
+        &string(""),
+        &0u32.to_le_bytes(),
+    ]);
+    assert_eq!(transact(&mut driver, 104, &attach)[4], 105);
+
+    // walking up from the root stays there, and then down to the file
+    let walk = fields(&[
+        &0u32.to_le_bytes(),
+        &1u32.to_le_bytes(),
+        &2u16.to_le_bytes(),
+        &string(".."),
+        &string("hello.txt"),
+    ]);
+    let walked = transact(&mut driver, 110, &walk);
+    assert_eq!(walked[4], 111);
+    assert_eq!(walked[7..9], 2u16.to_le_bytes());
+    // a directory, then a file
+    assert_eq!((walked[9], walked[22]), (0x80, 0));
+    let missing = fields(&[
+        &0u32.to_le_bytes(),
+        &2u32.to_le_bytes(),
+        &1u16.to_le_bytes(),
+        &string("missing"),
+    ]);
+    let error = transact(&mut driver, 110, &missing);
+    assert_eq!((error[4], &error[7..11]), (7, &2u32.to_le_bytes()[..]));
+
+    // open the file and read it
+    assert_eq!(
+        transact(
+            &mut driver,
+            12,
+            &fields(&[&1u32.to_le_bytes(), &0u32.to_le_bytes()])
+        )[4],
+        13
+    );
+    let read = fields(&[
+        &1u32.to_le_bytes(),
+        &6u64.to_le_bytes(),
+        &100u32.to_le_bytes(),
+    ]);
+    let data = transact(&mut driver, 116, &read);
+    assert_eq!(data[7..11], 13u32.to_le_bytes());
+    assert_eq!(&data[11..], b"from the host");
+
+    // create a file from the root, which is written through to the host
+    let walk = fields(&[
+        &0u32.to_le_bytes(),
+        &3u32.to_le_bytes(),
+        &0u16.to_le_bytes(),
+    ]);
+    assert_eq!(transact(&mut driver, 110, &walk)[4], 111);
+    let create = fields(&[
+        &3u32.to_le_bytes(),
+        &string("new.txt"),
+        &2u32.to_le_bytes(),
+        &0o644u32.to_le_bytes(),
+        &0u32.to_le_bytes(),
+    ]);
+    assert_eq!(transact(&mut driver, 14, &create)[4], 15);
+    let write = fields(&[
+        &3u32.to_le_bytes(),
+        &0u64.to_le_bytes(),
+        &5u32.to_le_bytes(),
+        b"guest",
+    ]);
+    assert_eq!(
+        transact(&mut driver, 118, &write)[7..11],
+        5u32.to_le_bytes()
+    );
+    assert_eq!(fs::read(directory.join("new.txt")).unwrap(), b"guest");
+
+    // the directory lists ".", "..", then the files by name
+    let open = fields(&[&0u32.to_le_bytes(), &0u32.to_le_bytes()]);
+    assert_eq!(transact(&mut driver, 12, &open)[4], 13);
+    let entries = transact(
+        &mut driver,
+        40,
+        &fields(&[
+            &0u32.to_le_bytes(),
+            &2u64.to_le_bytes(),
+            &4096u32.to_le_bytes(),
+        ]),
+    );
+    assert_eq!(entries[4], 41);
+    // each entry is a qid, an offset, a type and a name
+    let name = |at: usize| {
+        String::from_utf8_lossy(&entries[at + 24..at + 24 + entries[at + 22] as usize]).into_owned()
+    };
+    assert_eq!(name(11), "hello.txt");
+    assert_eq!(name(11 + 24 + 9), "new.txt");
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+// The error of an Rlerror reply, if it is one.
+fn error(reply: &[u8]) -> Option<u32> {
+    (reply[4] == 7).then(|| u32::from_le_bytes(reply[7..11].try_into().unwrap()))
+}
+
+#[test]
+fn virtio_9p_symlinks() {
+    const ELOOP: u32 = 40;
+    let base = env::temp_dir().join(format!("minimum-five-9p-links-{}", process::id()));
+    let (directory, outside) = (base.join("shared"), base.join("outside"));
+    fs::create_dir_all(directory.join("sub")).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+    fs::write(directory.join("sub/file"), b"inside").unwrap();
+    fs::write(outside.join("file"), b"outside").unwrap();
+    let filesystem = Filesystem::new(&directory, "share").unwrap();
+    let mut driver = Driver::new(Box::new(filesystem), 1);
+    let version = fields(&[&8192u32.to_le_bytes(), &string("9P2000.L")]);
+    assert_eq!(transact(&mut driver, 100, &version)[4], 101);
+    let attach = fields(&[
+        &0u32.to_le_bytes(),
+        &u32::MAX.to_le_bytes(),
+        &string("root"),
+        &string(""),
+        &0u32.to_le_bytes(),
+    ]);
+    assert_eq!(transact(&mut driver, 104, &attach)[4], 105);
+
+    let walk = |fid: u32, names: &[&str]| {
+        let mut walk = fields(&[
+            &0u32.to_le_bytes(),
+            &fid.to_le_bytes(),
+            &(names.len() as u16).to_le_bytes(),
+        ]);
+        for name in names {
+            walk.extend(string(name));
+        }
+        walk
+    };
+    let symlink = |name: &str, target: &Path| {
+        fields(&[
+            &0u32.to_le_bytes(),
+            &string(name),
+            &string(target.to_str().unwrap()),
+            &0u32.to_le_bytes(),
+        ])
+    };
+    // truncates a file and makes it writable by all
+    let setattr = |fid: u32| {
+        fields(&[
+            &fid.to_le_bytes(),
+            &0x9u32.to_le_bytes(),
+            &0o777u32.to_le_bytes(),
+            &[0; 8],
+            &[0; 40],
+        ])
+    };
+
+    // the guest may link to anywhere, and walk to the link, but not through it
+    let links = [("file", outside.join("file")), ("dir", outside.clone())];
+    for (name, target) in &links {
+        assert_eq!(
+            error(&transact(&mut driver, 16, &symlink(name, target))),
+            None
+        );
+    }
+    assert_eq!(
+        error(&transact(&mut driver, 110, &walk(1, &["file"]))),
+        None
+    );
+    assert_eq!(error(&transact(&mut driver, 110, &walk(2, &["dir"]))), None);
+    // the walk stops at the link rather than going through it
+    let walked = transact(&mut driver, 110, &walk(3, &["dir", "file"]));
+    assert_eq!((walked[4], &walked[7..9]), (111, &1u16.to_le_bytes()[..]));
+    assert_eq!(error(&transact(&mut driver, 26, &setattr(1))), Some(ELOOP));
+    let create = fields(&[
+        &2u32.to_le_bytes(),
+        &string("new"),
+        &2u32.to_le_bytes(),
+        &0o644u32.to_le_bytes(),
+        &0u32.to_le_bytes(),
+    ]);
+    assert_eq!(error(&transact(&mut driver, 14, &create)), Some(ELOOP));
+    let read_directory = fields(&[
+        &2u32.to_le_bytes(),
+        &0u64.to_le_bytes(),
+        &4096u32.to_le_bytes(),
+    ]);
+    assert_eq!(
+        error(&transact(&mut driver, 40, &read_directory)),
+        Some(ELOOP)
+    );
+
+    // nor through a directory that it has replaced with a link since it walked through it
+    assert_eq!(
+        error(&transact(&mut driver, 110, &walk(4, &["sub", "file"]))),
+        None
+    );
+    let rename = fields(&[
+        &0u32.to_le_bytes(),
+        &string("sub"),
+        &0u32.to_le_bytes(),
+        &string("moved"),
+    ]);
+    assert_eq!(error(&transact(&mut driver, 74, &rename)), None);
+    assert_eq!(
+        error(&transact(&mut driver, 16, &symlink("sub", &outside))),
+        None
+    );
+    assert_eq!(error(&transact(&mut driver, 26, &setattr(4))), Some(ELOOP));
+    let open = fields(&[&4u32.to_le_bytes(), &2u32.to_le_bytes()]);
+    assert_eq!(error(&transact(&mut driver, 12, &open)), Some(ELOOP));
+
+    let file = outside.join("file");
+    assert_eq!(fs::read(&file).unwrap(), b"outside");
+    assert_ne!(
+        fs::metadata(&file).unwrap().permissions().mode() & 0o777,
+        0o777
+    );
+    assert!(!outside.join("new").exists());
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn fdt() {
+    let args = "--uart 0x10000000 --plic 0xc000000 --virtio-rng 1 --bootargs console=ttyS0 x";
+    let Ok(Command::Run(options)) = Command::parse(args.split(' ').map(String::from)) else {
+        panic!("invalid options");
+    };
+    let dtb = device_tree(&options, Some((0x8008_0000, 0x8008_1000)));
+    assert_eq!(dtb[0..4], 0xd00d_feedu32.to_be_bytes());
+    assert_eq!(dtb[4..8], (dtb.len() as u32).to_be_bytes());
+    let contains = |string: &[u8]| dtb.windows(string.len()).any(|window| window == string);
+    assert!(contains(b"virtio_mmio@10001000\0"));
+    assert!(contains(b"console=ttyS0\0"));
+    assert!(contains(&[0, 0, 0, 0, 0x80, 0x08, 0x10, 0]));
+
+    // the program finds the magic number of the device tree at a1
+    let program: [u32; 9] = [
+        0x0005a283, // lw t0, 0(a1)
+        0xedfe1337, // lui t1, 0xedfe1
+        0xdd030313, // addi t1, t1, -560, the magic number read in little endian
+        0x406282b3, // sub t0, t0, t1
+        0x00129293, // slli t0, t0, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00001e17, // auipc t3, 1
+        0xfe5e3423, // sd t0, -24(t3), to tohost at 0x80001000
+        0x0000006f, // j .
+    ];
+    let mut simulator = simulator(&options.config, &program);
+    let address = simulator.set_device_tree(&dtb).unwrap();
+    assert_eq!(address % 8, 0);
+    assert!(matches!(simulator.run(Some(100)), Exit::Code(0)));
+}
+
+#[test]
+fn sbi() {
+    let program: [u32; 33] = [
+        0x01000893, // li a7, 0x10, the base extension
+        0x00000813, // li a6, 0
+        0x00000073, // ecall, get_spec_version
+        0x020002b7, // lui t0, 0x2000
+        0x40558433, // sub s0, a1, t0
+        0x004858b7, // lui a7, 0x485
+        0x34d88893, // addi a7, a7, 0x34d
+        0x00200813, // li a6, 2
+        0x00100513, // li a0, 1
+        0x00000073, // ecall, hart_get_status of hart 1
+        0xfff58313, // addi t1, a1, -1
+        0x00646433, // or s0, s0, t1
+        0x00a46433, // or s0, s0, a0
+        0x02041263, // bnez s0, fail
+        0x00000813, // li a6, 0
+        0x00100513, // li a0, 1
+        0x00000597, // auipc a1, 0
+        0x02058593, // addi a1, a1, 32
+        0x02a00613, // li a2, 42
+        0x00000073, // ecall, hart_start of hart 1 at 24 with 42
+        0x00051463, // bnez a0, fail
+        0x0000006f, // j .
+        0x00100593, // li a1, 1
+        0x0140006f, // j reset
+        0xfff50293, // addi t0, a0, -1, where hart 1 starts
+        0xfd658313, // addi t1, a1, -42
+        0x0062e2b3, // or t0, t0, t1
+        0x005035b3, // sltu a1, zero, t0
+        0x535258b7, // lui a7, 0x53525
+        0x35488893, // addi a7, a7, 0x354
+        0x00000813, // li a6, 0
+        0x00000513, // li a0, 0
+        0x00000073, // ecall
+    ];
+
+    // hart 0 checks the SBI version and that hart 1 is stopped, then starts it, and hart 1 checks
+    // its arguments and shuts the system down, with a system failure should a check fail
+    let config = Config {
+        harts: 2,
+        sbi: true,
+        ..Config::default()
+    };
+    let mut simulator = simulator(&config, &program);
+    assert!(matches!(simulator.run(Some(100)), Exit::Code(0)));
+}
+
+#[test]
+fn sbi_timer() {
+    let program: [u32; 30] = [
+        0x00000297, 0x04428293, // la t0, handler
+        0x10529073, // csrw stvec, t0
+        0x02000293, // li t0, 0x20
+        0x1042a073, // csrs sie, t0, STIE
+        0x10016073, // csrsi sstatus, 2, SIE
+        0xc0102473, // rdtime s0
+        0x00000013, // nop
+        0xc01024f3, // rdtime s1
+        0x02940e63, // beq s0, s1, fail
+        0x544958b7, 0xd458889b, // li a7, 0x54494d45, the TIME extension
+        0x00000813, // li a6, 0
+        0x01448513, // addi a0, s1, 20
+        0x00000073, // ecall, set_timer
+        0x10500073, // wfi
+        0xffdff06f, // j -4
+        0x142022f3, // csrr t0, scause, the handler
+        0xfff00313, // li t1, -1
+        0x03f31313, // slli t1, t1, 63
+        0x00530313, // addi t1, t1, 5
+        0x406282b3, // sub t0, t0, t1
+        0x005035b3, // snez a1, t0
+        0x0080006f, // j reset
+        0x00100593, // li a1, 1, fail
+        0x535258b7, 0x3548889b, // li a7, 0x53525354, reset
+        0x00000813, // li a6, 0
+        0x00000513, // li a0, 0
+        0x00000073, // ecall, system_reset
+    ];
+
+    // without a CLINT, time moves on, and the timer set through the SBI interrupts the hart, with
+    // and without Sstc, or the system is shut down with a system failure
+    for isa in ["rv64i_zicsr", "rv64i_zicsr_sstc"] {
+        let config = Config {
+            isa: Isa::parse(isa).unwrap(),
+            sbi: true,
+            ..Config::default()
+        };
+        let mut simulator = simulator(&config, &program);
+        assert!(matches!(simulator.run(Some(100)), Exit::Code(0)), "{isa}");
+    }
+}
+
+#[test]
+fn fw_dynamic() {
+    // the firmware finds the magic number of fw_dynamic_info at a2, and jumps to the next stage
+    let firmware: [u32; 7] = [
+        0x00062283, // lw t0, 0(a2)
+        0x494253b7, // lui t2, 0x49425
+        0x34f38393, // addi t2, t2, 0x34f
+        0x00729663, // bne t0, t2, 12
+        0x01063303, // ld t1, 16(a2), the address of the kernel
+        0x00030067, // jr t1
+        0x0000006f, // j .
+    ];
+    let kernel: [u32; 4] = [
+        0x00100293, // li t0, 1
+        0xfffffe17, // auipc t3, 0xfffff
+        0xfe5e3e23, // sd t0, -4(t3), to tohost at 0x80001000
+        0x0000006f, // j .
+    ];
+
+    let mut simulator = simulator(&Config::default(), &firmware);
+    let address = simulator.load_kernel(&image(&kernel), 0x2000).unwrap();
+    assert_eq!(address, 0x8000_2000);
+    assert!(matches!(simulator.run(Some(100)), Exit::Code(0)));
+}
+
+#[test]
+fn memory_map() {
+    let program: [u32; 88] = [
+        0x00000417, // auipc s0, 0
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x108d0d13, // la s10, expected
+        0x00000297, 0x0e828293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0xf14022f3, // csrr t0, mhartid
+        0x40550333, // sub t1, a0, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x0bf31663, // bne t1, t6, fail, a0 holds mhartid
+        0x0c029263, // bnez t0, park
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x09f41e63, // bne s0, t6, fail, the boot ROM jumps to the base of the memory
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x09f59863, // bne a1, t6, fail, a1 holds the device tree
+        0x00066283, // lwu t0, 0(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x09f29063, // bne t0, t6, fail, a2 holds fw_dynamic_info
+        0x00863283, // ld t0, 8(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x07f29863, // bne t0, t6, fail, its version
+        0x30000337, // li t1, 0x30000000
+        0x00036283, // lwu t0, 0(t1)
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x05f29e63, // bne t0, t6, fail, read from the ROM
+        0x00032023, // sw zero, 0(t1)
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x05fc9663, // bne s9, t6, fail, a store access fault
+        0x00036283, // lwu t0, 0(t1)
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x03f29e63, // bne t0, t6, fail, the ROM is unchanged
+        0x20000337, // li t1, 0x20000000
+        0x02a00293, // li t0, 42
+        0x00533423, // sd t0, 8(t1)
+        0x00833383, // ld t2, 8(t1)
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x03f39063, // bne t2, t6, fail, read back from the RAM
+        0x40010337, // li t1, 0x40010000
+        0x00033283, // ld t0, 0(t1)
+        0x001d8d93, // addi s11, s11, 1
+        0x048d3f83, // ld t6, 72(s10)
+        0x01fc9663, // bne s9, t6, fail, a load access fault past the end of the memory
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x0000006f, // j park
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000000, 0x00000000, // expected 0x0
+        0x40000000, 0x00000000, // expected 0x40000000
+        0x4000fff0, 0x00000000, // expected 0x4000fff0
+        0x4942534f, 0x00000000, // expected 0x4942534f
+        0x00000002, 0x00000000, // expected 0x2
+        0xdeadbeef, 0x00000000, // expected 0xdeadbeef
+        0x00000007, 0x00000000, // expected 0x7
+        0xdeadbeef, 0x00000000, // expected 0xdeadbeef
+        0x0000002a, 0x00000000, // expected 0x2a
+        0x00000005, 0x00000000, // expected 0x5
+    ];
+
+    // the harts start in the boot ROM, which passes them their ID, the device tree and
+    // fw_dynamic_info, and jumps to the program at the base of a memory moved to 0x40000000, next
+    // to a RAM, with tohost, and a ROM
+    let config = Config {
+        harts: 2,
+        memory_base: 0x4000_0000,
+        memory_size: 0x1_0000,
+        regions: vec![
+            Region::ram(0x2000_0000, 0x1000),
+            Region::rom(0x3000_0000, vec![0xef, 0xbe, 0xad, 0xde]),
+            Region::ram(TOHOST & !0xfff, 0x1000),
+        ],
+        ..Config::default()
+    };
+    let mut simulator = simulator(&config, &program);
+    assert_eq!(simulator.set_device_tree(&[0; 16]).unwrap(), 0x4000_fff0);
+    assert!(matches!(simulator.run(Some(1000)), Exit::Code(0)));
+
+    // the regions may not overlap
+    let config = Config {
+        regions: vec![Region::ram(0x8008_0000, 0x10_0000)],
+        ..Config::default()
+    };
+    assert!(config.memory_map().is_err());
+    assert!(Simulator::new(&config).is_err());
+    let config = Config {
+        cache_block_size: 48,
+        ..Config::default()
+    };
+    assert!(Simulator::new(&config).is_err());
+}
+
+#[test]
+fn isa_strings() {
+    // G is IMAFD with Zicsr and Zifencei, F and V imply Zicsr, D implies F, and the first
+    // multi-letter extension may follow the single letters without an underscore
+    for (isa, expected) in [
+        ("rv64gc", "rv64imafdc_zicsr_zifencei"),
+        ("rv32gc", "rv32imafdc_zicsr_zifencei"),
+        ("rv64imafdc", "rv64imafdc_zicsr"),
+        ("RV64IMAFDCZicsr_Zifencei", "rv64imafdc_zicsr_zifencei"),
+        ("rv64imaczicsr", "rv64imac_zicsr"),
+        ("rv32id", "rv32ifd_zicsr"),
+        ("rv64iv_zfh", "rv64ifv_zicsr_zfh"),
+        ("rv64i__zicond", "rv64i_zicond"),
+    ] {
+        assert_eq!(Isa::parse(isa).unwrap().to_string(), expected, "{}", isa);
+    }
+    for isa in [
+        "rv128i",
+        "rv64e",
+        "rv64ig",
+        "rv64iq",
+        "rv64g_zba",
+        "rv64i_zicsrx",
+    ] {
+        assert!(Isa::parse(isa).is_err(), "{}", isa);
+    }
+}
+
+#[test]
+fn command_line() {
+    let parse = |args: &str| Command::parse(args.split(' ').map(String::from));
+    let args = "--vlen 256 --elen 32 --entropy-seed 0x1234 --cache-block-size 128 x";
+    let Ok(Command::Run(options)) = parse(args) else {
+        panic!("invalid options");
+    };
+    assert_eq!(options.config.vlen, 256);
+    assert_eq!(options.config.elen, 32);
+    assert_eq!(options.config.entropy_seed, 0x1234);
+    assert_eq!(options.config.cache_block_size, 128);
+
+    for args in [
+        "--vlen 96 x",
+        "--vlen 32 x",
+        "--vlen 131072 x",
+        "--elen 16 x",
+        "--cache-block-size 48 x",
+        "--cache-block-size 8192 x",
+        "--entropy-seed seed x",
+        "--plic 0xc000000 --aplic 0xd000000 x",
+        "--plic-sources 0 x",
+        "--plic-sources 1024 x",
+        "--plic-sources 4294967297 x",
+    ] {
+        assert!(parse(args).is_err(), "{}", args);
+    }
+}
+
+#[test]
+fn htif_host_files() {
+    let program: [u32; 27] = [
+        0x00002417, // auipc s0, 2, the system call at 0x80002000
+        0x03800293, // li t0, 56
+        0x00543023, // sd t0, 0(s0), openat
+        0xf9c00293, // li t0, -100
+        0x00543423, // sd t0, 8(s0), AT_FDCWD
+        0x10040293, // addi t0, s0, 0x100
+        0x00543823, // sd t0, 16(s0), the path
+        0x00b00293, // li t0, 11
+        0x00543c23, // sd t0, 24(s0), its length
+        0x02043023, // sd zero, 32(s0), O_RDONLY
+        0x677262b7, // lui t0, 0x67726
+        0x14328293, // addi t0, t0, 0x143
+        0x10542023, // sw t0, 0x100(s0), Carg
+        0x6f7432b7, // lui t0, 0x6f743
+        0xe6f28293, // addi t0, t0, -0x191
+        0x10542223, // sw t0, 0x104(s0), o.to
+        0x000072b7, // lui t0, 7
+        0xc6d28293, // addi t0, t0, -0x393
+        0x10542423, // sw t0, 0x108(s0), ml
+        0x00001317, // auipc t1, 1
+        0xfa833a23, // sd s0, -76(t1), to tohost at 0x80001000
+        0x00043283, // ld t0, 0(s0), the result
+        0x01028293, // addi t0, t0, 16
+        0x00129293, // slli t0, t0, 1
+        0x0012e293, // ori t0, t0, 1
+        0xfa533a23, // sd t0, -76(t1), exits with the result plus 16
+        0x0000006f, // j .
+    ];
+
+    // the program opens Cargo.toml, in the working directory of the tests, through the proxied
+    // openat, which fails with EACCES (-13) unless the host files are allowed, and then returns
+    // the first free file descriptor, 3
+    for (allowed, code) in [(false, 3), (true, 19)] {
+        let mut simulator = simulator(&Config::default(), &program);
+        let mut htif = Htif::new(TOHOST, None, Vec::new());
+        htif.set_host_files(allowed);
+        simulator.set_htif(htif);
+        assert!(matches!(simulator.run(Some(100)), Exit::Code(exit) if exit == code));
+    }
+}
+
+#[test]
+fn htif_rv32() {
+    let program: [u32; 9] = [
+        0x80001337, // lui t1, 0x80001, tohost
+        0x00300293, // li t0, 3
+        0x00532023, // sw t0, 0(t1), an odd payload, as of an exit
+        0x01ff02b7, // lui t0, 0x1ff0
+        0x00532223, // sw t0, 4(t1), of a command of the console, which is ignored
+        0x00100293, // li t0, 1
+        0x00532023, // sw t0, 0(t1)
+        0x00032223, // sw zero, 4(t1), exits
+        0x0000006f, // j .
+    ];
+
+    // in RV32 the command is only complete once its high word is written
+    let config = Config {
+        isa: Isa::parse("rv32i_zicsr").unwrap(),
+        ..Config::default()
+    };
+    let mut simulator = simulator(&config, &program);
+    assert!(matches!(simulator.run(Some(100)), Exit::Code(0)));
+}
@@ -0,0 +1,193 @@
+mod common;
+
+use std::io::ErrorKind;
+
+use common::{image, TOHOST};
+use minimum_five::{
+    config::Config,
+    htif::Htif,
+    simulator::{Exit, ImageFormat, Simulator},
+};
+
+const PROGRAM: [u32; 4] = [
+    0x00100293, // li t0, 1
+    0x00001317, // auipc t1, 1
+    0xfe533e23, // sd t0, -4(t1), to tohost
+    0x0000006f, // j 0
+];
+
+const SIGNATURE: [u32; 11] = [
+    0x00000317, // auipc t1, 0
+    0x123452b7, 0x6782829b, // li t0, 0x12345678
+    0x04532023, // sw t0, 64(t1), at begin_signature
+    0xffe00293, // li t0, -2
+    0x04532223, // sw t0, 68(t1)
+    0x00100293, // li t0, 1
+    0x000013b7, // lui t2, 1
+    0x006383b3, // add t2, t2, t1
+    0x0053b023, // sd t0, 0(t2), to tohost
+    0x0000006f, // j 0
+];
+
+// The fields of the single program header of an ELF64 file.
+struct Segment {
+    offset: u64,
+    address: u64,
+    filesz: u64,
+    memsz: u64,
+}
+
+// An ELF64 executable holding `program` in one segment after its headers, which `segment` may
+// describe otherwise.
+fn elf(program: &[u32], phoff: u64, segment: impl FnOnce(&mut Segment)) -> Vec<u8> {
+    let code = image(program);
+    let mut fields = Segment {
+        offset: 120,
+        address: 0x8000_0000,
+        filesz: code.len() as u64,
+        memsz: code.len() as u64 + 0x100,
+    };
+    segment(&mut fields);
+
+    let mut bytes = b"\x7fELF\x02\x01\x01".to_vec();
+    bytes.resize(16, 0);
+    bytes.extend(2u16.to_le_bytes()); // ET_EXEC
+    bytes.extend(243u16.to_le_bytes()); // EM_RISCV
+    bytes.extend(1u32.to_le_bytes());
+    bytes.extend(0x8000_0000u64.to_le_bytes()); // the entry
+    bytes.extend(phoff.to_le_bytes());
+    bytes.extend(0u64.to_le_bytes()); // no section headers
+    bytes.extend(0u32.to_le_bytes());
+    bytes.extend(64u16.to_le_bytes());
+    bytes.extend(56u16.to_le_bytes()); // phentsize
+    bytes.extend(1u16.to_le_bytes()); // phnum
+    bytes.extend([0; 6]);
+
+    bytes.extend(1u32.to_le_bytes()); // PT_LOAD
+    bytes.extend(7u32.to_le_bytes());
+    bytes.extend(fields.offset.to_le_bytes());
+    bytes.extend(fields.address.to_le_bytes());
+    bytes.extend(fields.address.to_le_bytes());
+    bytes.extend(fields.filesz.to_le_bytes());
+    bytes.extend(fields.memsz.to_le_bytes());
+    bytes.extend(4u64.to_le_bytes());
+    bytes.extend(code);
+    bytes
+}
+
+// Appends a symbol table, its string table and their section headers to an executable.
+fn with_symbols(mut bytes: Vec<u8>, symbols: &[(&str, u64)]) -> Vec<u8> {
+    let mut strtab = vec![0];
+    let mut symtab = vec![0; 24];
+    for (name, value) in symbols {
+        symtab.extend((strtab.len() as u32).to_le_bytes());
+        symtab.extend([0x10, 0]); // STB_GLOBAL
+        symtab.extend(1u16.to_le_bytes());
+        symtab.extend(value.to_le_bytes());
+        symtab.extend(0u64.to_le_bytes());
+        strtab.extend(name.bytes().chain([0]));
+    }
+    bytes.resize(bytes.len().next_multiple_of(8), 0);
+    let symoff = bytes.len() as u64;
+    bytes.extend(&symtab);
+    let stroff = bytes.len() as u64;
+    bytes.extend(&strtab);
+    bytes.resize(bytes.len().next_multiple_of(8), 0);
+    let shoff = bytes.len() as u64;
+
+    // the null section, .symtab linked to .strtab, and .strtab
+    bytes.extend([0; 64]);
+    let sections = [
+        (2u32, symoff, symtab.len(), 2u32, 24u64),
+        (3, stroff, strtab.len(), 0, 0),
+    ];
+    for (kind, offset, size, link, entsize) in sections {
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend(kind.to_le_bytes());
+        bytes.extend([0; 16]);
+        bytes.extend(offset.to_le_bytes());
+        bytes.extend((size as u64).to_le_bytes());
+        bytes.extend(link.to_le_bytes());
+        bytes.extend([0; 12]);
+        bytes.extend(entsize.to_le_bytes());
+    }
+    bytes[40..48].copy_from_slice(&shoff.to_le_bytes());
+    bytes[58..60].copy_from_slice(&64u16.to_le_bytes()); // shentsize
+    bytes[60..62].copy_from_slice(&3u16.to_le_bytes()); // shnum
+    bytes
+}
+
+fn load(bytes: &[u8]) -> std::io::Result<Simulator> {
+    let mut simulator = Simulator::new(&Config::default()).unwrap();
+    simulator.load_bytes(bytes, ImageFormat::Elf)?;
+    Ok(simulator)
+}
+
+fn rejected(bytes: &[u8]) -> bool {
+    load(bytes).is_err_and(|error| error.kind() == ErrorKind::InvalidData)
+}
+
+#[test]
+fn elf_loads() {
+    let mut simulator = load(&elf(&PROGRAM, 64, |_| {})).unwrap();
+    simulator.set_htif(Htif::new(TOHOST, None, Vec::new()));
+    assert!(matches!(simulator.run(Some(100)), Exit::Code(0)));
+}
+
+#[test]
+fn truncated_headers() {
+    let bytes = elf(&PROGRAM, 64, |_| {});
+    // in the ELF header, in the program header and in the segment
+    for size in [40, 100, 124] {
+        assert!(rejected(&bytes[..size]));
+    }
+}
+
+#[test]
+fn malformed_headers() {
+    // program headers past the end of the file, or of the address space
+    assert!(rejected(&elf(&PROGRAM, 0x1000, |_| {})));
+    assert!(rejected(&elf(&PROGRAM, u64::MAX - 8, |_| {})));
+    // a segment past the end of the file
+    assert!(rejected(
+        &elf(&PROGRAM, 64, |segment| segment.offset = u64::MAX - 8)
+    ));
+    // a segment larger in the file than in memory
+    assert!(rejected(&elf(&PROGRAM, 64, |segment| segment.memsz = 0)));
+    // a BSS outside the memory, which is not allocated to be zeroed
+    assert!(rejected(
+        &elf(&PROGRAM, 64, |segment| segment.memsz = 1 << 62)
+    ));
+    assert!(rejected(
+        &elf(&PROGRAM, 64, |segment| segment.memsz = u64::MAX)
+    ));
+    assert!(rejected(
+        &elf(&PROGRAM, 64, |segment| segment.address = 0x1_0000_0000)
+    ));
+}
+
+#[test]
+fn signature() {
+    let symbols = [
+        ("begin_signature", 0x8000_0040),
+        ("end_signature", 0x8000_0050),
+    ];
+    let mut simulator = load(&with_symbols(elf(&SIGNATURE, 64, |_| {}), &symbols)).unwrap();
+    simulator.set_htif(Htif::new(TOHOST, None, Vec::new()));
+    assert!(matches!(simulator.run(Some(100)), Exit::Code(0)));
+    // a word or a doubleword per line, most significant digit first, up to the zeroed BSS
+    assert_eq!(
+        simulator.signature(4).unwrap(),
+        "12345678\nfffffffe\n00000000\n00000000\n"
+    );
+    assert_eq!(
+        simulator.signature(8).unwrap(),
+        "fffffffe12345678\n0000000000000000\n"
+    );
+
+    // without the symbols, there is no signature
+    let simulator = load(&elf(&SIGNATURE, 64, |_| {})).unwrap();
+    assert!(simulator
+        .signature(4)
+        .is_err_and(|error| error.kind() == ErrorKind::NotFound));
+}
@@ -0,0 +1,2741 @@
+mod common;
+
+use common::run;
+use minimum_five::{config::Config, isa::Isa, memory::Region};
+
+// The programs below count their checks in s11 and fail with the number of the first one whose
+// register differs from the table of expected values at s10. Their trap handler records mcause in
+// s9 and mtval in s8, and skips the instruction that trapped.
+
+#[test]
+fn vector_configuration() {
+    let program: [u32; 102] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x134d0d13, // la s10, expected
+        0x00000297, 0x11028293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x20000293, // li t0, 0x200, mstatus.VS
+        0x3002a073, // csrs mstatus, t0
+        0x00a00513, // li a0, 10
+        0x0d057357, // vsetvli t1, a0, e32, m1, ta, ma, vl is VLMAX, 4
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x0df31863, // bne t1, t6, fail
+        0xc20023f3, // csrr t2, vl
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x0df39063, // bne t2, t6, fail
+        0x0c307357, // vsetvli t1, zero, e8, m8, ta, ma, rs1 = x0 asks for VLMAX
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x0bf31863, // bne t1, t6, fail
+        0x00300513, // li a0, 3
+        0x0d957357, // vsetvli t1, a0, e64, m2, ta, ma
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x09f31e63, // bne t1, t6, fail
+        0xc21023f3, // csrr t2, vtype
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x09f39663, // bne t2, t6, fail
+        0xc0fff357, // vsetivli t1, 31, e16, mf2, tu, mu
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x07f31e63, // bne t1, t6, fail
+        0xc21023f3, // csrr t2, vtype
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x07f39663, // bne t2, t6, fail
+        0x00400593, // li a1, 4, the reserved LMUL
+        0x80b57357, // vsetvl t1, a0, a1
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x05f31c63, // bne t1, t6, fail
+        0xc21023f3, // csrr t2, vtype
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x05f39463, // bne t2, t6, fail
+        0xc22023f3, // csrr t2, vlenb
+        0x001d8d93, // addi s11, s11, 1
+        0x048d3f83, // ld t6, 72(s10)
+        0x03f39c63, // bne t2, t6, fail
+        0x60000293, // li t0, 0x600
+        0x3002b073, // csrc mstatus, t0, with VS off, vector instructions are illegal
+        0x00000c93, // li s9, 0
+        0x00700313, // li t1, 7
+        0x0d057357, // vsetvli t1, a0, e32, m1, ta, ma
+        0x001d8d93, // addi s11, s11, 1
+        0x050d3f83, // ld t6, 80(s10)
+        0x01fc9c63, // bne s9, t6, fail
+        0x001d8d93, // addi s11, s11, 1
+        0x058d3f83, // ld t6, 88(s10)
+        0x01f31663, // bne t1, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0x00000004, 0x00000000, // expected 0x4
+        0x00000004, 0x00000000, // expected 0x4
+        0x00000080, 0x00000000, // expected 0x80
+        0x00000003, 0x00000000, // expected 0x3
+        0x000000d9, 0x00000000, // expected 0xd9
+        0x00000004, 0x00000000, // expected 0x4
+        0x0000000f, 0x00000000, // expected 0xf
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000000, 0x80000000, // expected 0x8000000000000000
+        0x00000010, 0x00000000, // expected 0x10
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000007, 0x00000000, // expected 0x7
+    ];
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn vector_loads_and_stores() {
+    let program: [u32; 162] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x20cd0d13, // la s10, expected
+        0x00000297, 0x1b828293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x20000293, // li t0, 0x200, mstatus.VS
+        0x3002a073, // csrs mstatus, t0
+        0x00000517, 0x1c050513, // la a0, words
+        0x00800593, // li a1, 8
+        0x00040637, 0x0016061b, 0x00d61613, // li a2, 0x80002000, a buffer
+        0xcd027357, // vsetivli t1, 4, e32, m1, ta, ma
+        0x02056087, // vle32.v v1, (a0), unit stride
+        0x021530d7, // vadd.vi v1, v1, 10
+        0x020660a7, // vse32.v v1, (a2)
+        0x00063383, // ld t2, 0(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x15f39a63, // bne t2, t6, fail
+        0x00863383, // ld t2, 8(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x15f39263, // bne t2, t6, fail
+        0x0ab56107, // vlse32.v v2, (a0), a1, every other word
+        0x02066127, // vse32.v v2, (a2)
+        0x00063383, // ld t2, 0(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x13f39663, // bne t2, t6, fail
+        0x00863383, // ld t2, 8(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x11f39e63, // bne t2, t6, fail
+        0x00000697, 0x17068693, // la a3, offsets
+        0x0206e187, // vle32.v v3, (a3)
+        0x06356207, // vluxei32.v v4, (a0), v3, words 8, 1, 3 and 2
+        0x02066227, // vse32.v v4, (a2)
+        0x00063383, // ld t2, 0(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x0ff39c63, // bne t2, t6, fail
+        0x00863383, // ld t2, 8(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x0ff39463, // bne t2, t6, fail
+        0x22056287, // vlseg2e32.v v5, (a0), the odd words in v5 and the even ones in v6
+        0x020662a7, // vse32.v v5, (a2)
+        0x00063383, // ld t2, 0(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x0df39863, // bne t2, t6, fail
+        0x00863383, // ld t2, 8(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x0df39063, // bne t2, t6, fail
+        0x02066327, // vse32.v v6, (a2)
+        0x00063383, // ld t2, 0(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x0bf39663, // bne t2, t6, fail
+        0x00863383, // ld t2, 8(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x048d3f83, // ld t6, 72(s10)
+        0x09f39e63, // bne t2, t6, fail
+        0x00063023, // sd zero, 0(a2)
+        0x00063423, // sd zero, 8(a2)
+        0x0ab66327, // vsse32.v v6, (a2), a1, every other word
+        0x00063383, // ld t2, 0(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x050d3f83, // ld t6, 80(s10)
+        0x09f39063, // bne t2, t6, fail
+        0x00863383, // ld t2, 8(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x058d3f83, // ld t6, 88(s10)
+        0x07f39863, // bne t2, t6, fail
+        0xcd817357, // vsetivli t1, 2, e64, m1, ta, ma
+        0x02057387, // vle64.v v7, (a0)
+        0x427023d7, // vmv.x.s t2, v7
+        0x001d8d93, // addi s11, s11, 1
+        0x060d3f83, // ld t6, 96(s10)
+        0x05f39c63, // bne t2, t6, fail
+        0x00001737, 0x8017071b, 0x01471713,
+        0xff870713, // li a4, 0x800ffff8, two words before the end of the memory
+        0x01100e13, // li t3, 0x11
+        0x01c72023, // sw t3, 0(a4)
+        0x02200e13, // li t3, 0x22
+        0x01c72223, // sw t3, 4(a4)
+        0xcd027357, // vsetivli t1, 4, e32, m1, ta, ma
+        0x03076407, // vle32ff.v v8, (a4), trims vl at the first element past the end
+        0xc20023f3, // csrr t2, vl
+        0x001d8d93, // addi s11, s11, 1
+        0x068d3f83, // ld t6, 104(s10)
+        0x03f39063, // bne t2, t6, fail
+        0x3e80b4d7, // vslidedown.vi v9, v8, 1
+        0x429023d7, // vmv.x.s t2, v9
+        0x001d8d93, // addi s11, s11, 1
+        0x070d3f83, // ld t6, 112(s10)
+        0x01f39663, // bne t2, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0x00000001, // .word 1
+        0x00000002, // .word 2
+        0x00000003, // .word 3
+        0x00000004, // .word 4
+        0x00000005, // .word 5
+        0x00000006, // .word 6
+        0x00000007, // .word 7
+        0x00000008, // .word 8
+        0x0000001c, // .word 28
+        0x00000000, // .word 0
+        0x00000008, // .word 8
+        0x00000004, // .word 4
+        0x0000000b, 0x0000000c, // expected 0xc0000000b
+        0x0000000d, 0x0000000e, // expected 0xe0000000d
+        0x00000001, 0x00000003, // expected 0x300000001
+        0x00000005, 0x00000007, // expected 0x700000005
+        0x00000008, 0x00000001, // expected 0x100000008
+        0x00000003, 0x00000002, // expected 0x200000003
+        0x00000001, 0x00000003, // expected 0x300000001
+        0x00000005, 0x00000007, // expected 0x700000005
+        0x00000002, 0x00000004, // expected 0x400000002
+        0x00000006, 0x00000008, // expected 0x800000006
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000004, 0x00000000, // expected 0x4
+        0x00000001, 0x00000002, // expected 0x200000001
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000022, 0x00000000, // expected 0x22
+    ];
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn vector_masks() {
+    let program: [u32; 128] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x194d0d13, // la s10, expected
+        0x00000297, 0x16428293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x20000293, // li t0, 0x200, mstatus.VS
+        0x3002a073, // csrs mstatus, t0
+        0x00000517, 0x16850513, // la a0, words
+        0x00040637, 0x0016061b, 0x00d61613, // li a2, 0x80002000, a buffer
+        0xc5027357, // vsetivli t1, 4, e32, m1, ta, mu
+        0x02056087, // vle32.v v1, (a0)
+        0x7e113057, // vmsgt.vi v0, v1, 2, elements 2 and 3
+        0x420823d7, // vcpop.m t2, v0
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x11f39463, // bne t2, t6, fail
+        0x4208a3d7, // vfirst.m t2, v0
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x0ff39c63, // bne t2, t6, fail
+        0x5e003157, // vmv.v.i v2, 0
+        0x00108157, // vadd.vv v2, v1, v1, v0.t, leaves the inactive elements undisturbed
+        0x02066127, // vse32.v v2, (a2)
+        0x00063383, // ld t2, 0(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x0df39e63, // bne t2, t6, fail
+        0x00863383, // ld t2, 8(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x0df39663, // bne t2, t6, fail
+        0x760021d7, // vmnand.mm v3, v0, v0
+        0x4238a3d7, // vfirst.m t2, v3
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x0bf39c63, // bne t2, t6, fail
+        0x623021d7, // vmandn.mm v3, v3, v0
+        0x423823d7, // vcpop.m t2, v3
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x0bf39263, // bne t2, t6, fail
+        0x5208a257, // vid.v v4
+        0x02066227, // vse32.v v4, (a2)
+        0x00863383, // ld t2, 8(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x09f39663, // bne t2, t6, fail
+        0x520822d7, // viota.m v5, v0
+        0x020662a7, // vse32.v v5, (a2)
+        0x00863383, // ld t2, 8(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x07f39a63, // bne t2, t6, fail
+        0x5e102357, // vcompress.vm v6, v1, v0
+        0x426023d7, // vmv.x.s t2, v6
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x07f39063, // bne t2, t6, fail
+        0x5c1fb3d7, // vmerge.vim v7, v1, -1
+        0x020663a7, // vse32.v v7, (a2)
+        0x00063383, // ld t2, 0(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x048d3f83, // ld t6, 72(s10)
+        0x05f39463, // bne t2, t6, fail
+        0x00863383, // ld t2, 8(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x050d3f83, // ld t6, 80(s10)
+        0x03f39c63, // bne t2, t6, fail
+        0x00063023, // sd zero, 0(a2)
+        0x00063423, // sd zero, 8(a2)
+        0x000660a7, // vse32.v v1, (a2), v0.t, stores only the active elements
+        0x00063383, // ld t2, 0(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x058d3f83, // ld t6, 88(s10)
+        0x01f39e63, // bne t2, t6, fail
+        0x00863383, // ld t2, 8(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x060d3f83, // ld t6, 96(s10)
+        0x01f39663, // bne t2, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000001, // .word 1
+        0x00000002, // .word 2
+        0x00000003, // .word 3
+        0x00000004, // .word 4
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000006, 0x00000008, // expected 0x800000006
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000002, 0x00000003, // expected 0x300000002
+        0x00000000, 0x00000001, // expected 0x100000000
+        0x00000003, 0x00000000, // expected 0x3
+        0x00000001, 0x00000002, // expected 0x200000001
+        0xffffffff, 0xffffffff, // expected 0xffffffffffffffff
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000003, 0x00000004, // expected 0x400000003
+    ];
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn vector_arithmetic() {
+    let program: [u32; 110] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x16cd0d13, // la s10, expected
+        0x00000297, 0x13828293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x20000293, // li t0, 0x200, mstatus.VS
+        0x3002a073, // csrs mstatus, t0
+        0x00000517, 0x14050513, // la a0, words
+        0x00040637, 0x0016061b, 0x00d61613, // li a2, 0x80002000, a buffer
+        0xcd027357, // vsetivli t1, 4, e32, m1, ta, ma
+        0x02056087, // vle32.v v1, (a0)
+        0x06400e13, // li t3, 100
+        0x420e6157, // vmv.s.x v2, t3
+        0x021121d7, // vredsum.vs v3, v1, v2
+        0x423023d7, // vmv.x.s t2, v3
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x0df39a63, // bne t2, t6, fail
+        0x161121d7, // vredmin.vs v3, v1, v2
+        0x423023d7, // vmv.x.s t2, v3
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x0df39063, // bne t2, t6, fail
+        0xffd00e13, // li t3, -3
+        0xee1e6257, // vwmul.vx v4, v1, t3, into 64-bit elements in v4 and v5
+        0xcd927357, // vsetivli t1, 4, e64, m2, ta, ma
+        0x02067227, // vse64.v v4, (a2)
+        0x00063383, // ld t2, 0(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x0bf39063, // bne t2, t6, fail
+        0x01863383, // ld t2, 24(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x09f39863, // bne t2, t6, fail
+        0xccf27357, // vsetivli t1, 4, e16, mf2, ta, ma
+        0xb210b357, // vnsrl.wi v6, v1, 1, from the 32-bit elements of v1
+        0x02065327, // vse16.v v6, (a2)
+        0x00063383, // ld t2, 0(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x07f39a63, // bne t2, t6, fail
+        0xcc027357, // vsetivli t1, 4, e8, m1, ta, ma
+        0x0fa00e13, // li t3, 250
+        0x5e0e43d7, // vmv.v.x v7, t3
+        0x827533d7, // vsaddu.vi v7, v7, 10
+        0x427023d7, // vmv.x.s t2, v7
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x05f39a63, // bne t2, t6, fail
+        0x009023f3, // csrr t2, vxsat
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x05f39263, // bne t2, t6, fail
+        0x5e02b457, // vmv.v.i v8, 5
+        0x5e0134d7, // vmv.v.i v9, 2
+        0x00a05073, // csrwi vxrm, 0, round to nearest, up
+        0x2284a557, // vaaddu.vv v10, v8, v9
+        0x42a023d7, // vmv.x.s t2, v10
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x03f39263, // bne t2, t6, fail
+        0x00a15073, // csrwi vxrm, 2, round down
+        0x2284a557, // vaaddu.vv v10, v8, v9
+        0x42a023d7, // vmv.x.s t2, v10
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x01f39663, // bne t2, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0x00000001, // .word 1
+        0x00000002, // .word 2
+        0x00000003, // .word 3
+        0x00000004, // .word 4
+        0x0000006e, 0x00000000, // expected 0x6e
+        0x00000001, 0x00000000, // expected 0x1
+        0xfffffffd, 0xffffffff, // expected 0xfffffffffffffffd
+        0xfffffff4, 0xffffffff, // expected 0xfffffffffffffff4
+        0x00010000, 0x00020001, // expected 0x2000100010000
+        0xffffffff, 0xffffffff, // expected 0xffffffffffffffff
+        0x00000001, 0x00000000, // expected 0x1
+        0x00000004, 0x00000000, // expected 0x4
+        0x00000003, 0x00000000, // expected 0x3
+    ];
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn vector_floating_point() {
+    let program: [u32; 124] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x18cd0d13, // la s10, expected
+        0x00000297, 0x16828293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x000022b7, 0x2002829b, // li t0, 0x2200, mstatus.VS and FS
+        0x3002a073, // csrs mstatus, t0
+        0xcd017357, // vsetivli t1, 2, e32, m1, ta, ma
+        0x3fc00e37, // li t3, 0x3fc00000, 1.5
+        0x5e0e40d7, // vmv.v.x v1, t3
+        0x40100e37, // li t3, 0x40100000, 2.25
+        0x5e0e4157, // vmv.v.x v2, t3
+        0x021111d7, // vfadd.vv v3, v1, v2
+        0x423023d7, // vmv.x.s t2, v3
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x11f39863, // bne t2, t6, fail
+        0x92111257, // vfmul.vv v4, v1, v2
+        0x424023d7, // vmv.x.s t2, v4
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x0ff39e63, // bne t2, t6, fail
+        0xb2209257, // vfmacc.vv v4, v1, v2
+        0x424023d7, // vmv.x.s t2, v4
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x0ff39463, // bne t2, t6, fail
+        0x6e111057, // vmflt.vv v0, v1, v2
+        0x420823d7, // vcpop.m t2, v0
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x0df39a63, // bne t2, t6, fail
+        0x4a3092d7, // vfcvt.x.f.v v5, v3, to the nearest even
+        0x425023d7, // vmv.x.s t2, v5
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x0df39063, // bne t2, t6, fail
+        0x3f800e37, // li t3, 0x3f800000, 1.0
+        0x5e0e40d7, // vmv.v.x v1, t3
+        0x40400e37, // li t3, 0x40400000, 3.0
+        0x5e0e4157, // vmv.v.x v2, t3
+        0x00101073, // csrw fflags, zero
+        0x82111357, // vfdiv.vv v6, v1, v2, rounded to the nearest
+        0x426023d7, // vmv.x.s t2, v6
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x09f39c63, // bne t2, t6, fail
+        0x001023f3, // csrr t2, fflags
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x09f39463, // bne t2, t6, fail, inexact
+        0x0020d073, // csrwi frm, 1, towards zero
+        0x82111357, // vfdiv.vv v6, v1, v2
+        0x426023d7, // vmv.x.s t2, v6
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x07f39863, // bne t2, t6, fail
+        0x00205073, // csrwi frm, 0
+        0x00101073, // csrw fflags, zero
+        0x40800e37, // li t3, 0x40800000, 4.0
+        0x5e0e40d7, // vmv.v.x v1, t3
+        0x4e101357, // vfsqrt.v v6, v1
+        0x426023d7, // vmv.x.s t2, v6
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x05f39663, // bne t2, t6, fail
+        0x001023f3, // csrr t2, fflags
+        0x001d8d93, // addi s11, s11, 1
+        0x048d3f83, // ld t6, 72(s10)
+        0x03f39e63, // bne t2, t6, fail, exact
+        0x17f00e13, 0x017e1e13, // li t3, 0xbf800000, -1.0
+        0x5e0e40d7, // vmv.v.x v1, t3
+        0x4e101357, // vfsqrt.v v6, v1
+        0x426023d7, // vmv.x.s t2, v6
+        0x001d8d93, // addi s11, s11, 1
+        0x050d3f83, // ld t6, 80(s10)
+        0x01f39e63, // bne t2, t6, fail, the canonical NaN
+        0x001023f3, // csrr t2, fflags
+        0x001d8d93, // addi s11, s11, 1
+        0x058d3f83, // ld t6, 88(s10)
+        0x01f39663, // bne t2, t6, fail, invalid
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0x40700000, 0x00000000, // expected 0x40700000
+        0x40580000, 0x00000000, // expected 0x40580000
+        0x40d80000, 0x00000000, // expected 0x40d80000
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000004, 0x00000000, // expected 0x4
+        0x3eaaaaab, 0x00000000, // expected 0x3eaaaaab
+        0x00000001, 0x00000000, // expected 0x1
+        0x3eaaaaaa, 0x00000000, // expected 0x3eaaaaaa
+        0x40000000, 0x00000000, // expected 0x40000000
+        0x00000000, 0x00000000, // expected 0x0
+        0x7fc00000, 0x00000000, // expected 0x7fc00000
+        0x00000010, 0x00000000, // expected 0x10
+    ];
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn vector_register_groups() {
+    let program: [u32; 76] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x0f4d0d13, // la s10, expected
+        0x00000297, 0x0d428293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x000022b7, 0x2002829b, // li t0, 0x2200, mstatus.VS and FS
+        0x3002a073, // csrs mstatus, t0
+        0xcd227357, // vsetivli t1, 4, e32, m4, ta, ma
+        0x00000c93, // li s9, 0
+        0x6e831057, // vmflt.vv v0, v8, v6
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x09fc9663, // bne s9, t6, fail, vs1 is not aligned to LMUL
+        0x00000c93, // li s9, 0
+        0xc2411457, // vfwadd.vv v8, v4, v2
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x07fc9c63, // bne s9, t6, fail
+        0x00000c93, // li s9, 0
+        0x02810257, // vadd.vv v4, v8, v2
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x07fc9263, // bne s9, t6, fail
+        0x00000c93, // li s9, 0
+        0x5208a257, // vid.v v4, the vs1 field of the unary operations is not a register
+        0x42402557, // vmv.x.s a0, v4
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x05fc9663, // bne s9, t6, fail
+        0xcd127357, // vsetivli t1, 4, e32, m2, ta, ma
+        0x4248a557, // vfirst.m a0, v4
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x03fc9c63, // bne s9, t6, fail
+        0xcd227357, // vsetivli t1, 4, e32, m4, ta, ma
+        0x4a432457, // vzext.vf2 v8, v4
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x03fc9263, // bne s9, t6, fail
+        0xcd227357, // vsetivli t1, 4, e32, m4, ta, ma
+        0x3e41b457, // vslidedown.vi v8, v4, 3
+        0x42802557, // vmv.x.s a0, v8
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x01f51663, // bne a0, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000003, 0x00000000, // expected 0x3
+    ];
+
+    // a vs1 or vs2 group misaligned to LMUL is an illegal instruction, whichever the operation
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn vector_state() {
+    let program: [u32; 64] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x0d4d0d13, // la s10, expected
+        0x00000297, 0x0b028293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x60000293, // li t0, 0x600
+        0x3002b073, // csrc mstatus, t0, VS is off
+        0x00000c93, // li s9, 0
+        0xc2202573, // csrr a0, vlenb
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x07fc9863, // bne s9, t6, fail
+        0x00000c93, // li s9, 0
+        0x00a0d073, // csrwi vxrm, 1
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x05fc9e63, // bne s9, t6, fail
+        0x20000293, // li t0, 0x200
+        0x3002a073, // csrs mstatus, t0, VS is initial
+        0x00000c93, // li s9, 0
+        0xc2002573, // csrr a0, vl
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x05fc9063, // bne s9, t6, fail
+        0x30002573, // csrr a0, mstatus
+        0x00955513, // srli a0, a0, 9
+        0x00357513, // andi a0, a0, 3
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x03f51463, // bne a0, t6, fail, a read leaves the state as it is
+        0x00a0d073, // csrwi vxrm, 1
+        0x30002573, // csrr a0, mstatus
+        0x00955513, // srli a0, a0, 9
+        0x00357513, // andi a0, a0, 3
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x01f51663, // bne a0, t6, fail, a write makes it dirty
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000001, 0x00000000, // expected 0x1
+        0x00000003, 0x00000000, // expected 0x3
+    ];
+
+    // the vector CSRs are illegal while mstatus.VS is off, and a write to them makes the state
+    // of the unit dirty
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn vector_restart() {
+    let program: [u32; 102] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x14cd0d13, // la s10, expected
+        0x00000297, 0x11c28293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x20000293, // li t0, 0x200, mstatus.VS
+        0x3002a073, // csrs mstatus, t0
+        0x00000517, 0x12050513, // la a0, words
+        0x00040637, 0x0016061b, 0x00d61613, // li a2, 0x80002000, a buffer
+        0xcd027357, // vsetivli t1, 4, e32, m1, ta, ma
+        0x02056087, // vle32.v v1, (a0)
+        0x00815073, // csrwi vstart, 2
+        0x021530d7, // vadd.vi v1, v1, 10, from element 2
+        0x008023f3, // csrr t2, vstart
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x0bf39e63, // bne t2, t6, fail
+        0x020660a7, // vse32.v v1, (a2)
+        0x00063383, // ld t2, 0(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x0bf39463, // bne t2, t6, fail
+        0x00863383, // ld t2, 8(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x09f39c63, // bne t2, t6, fail
+        0x00001737, 0x8017071b, 0x01471713,
+        0xff870713, // li a4, 0x800ffff8, two words before the end of the memory
+        0x01100e13, // li t3, 0x11
+        0x01c72023, // sw t3, 0(a4)
+        0x02200e13, // li t3, 0x22
+        0x01c72223, // sw t3, 4(a4)
+        0x5e003157, // vmv.v.i v2, 0
+        0x00000c93, // li s9, 0
+        0x02076107, // vle32.v v2, (a4), faults at element 2
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x07fc9063, // bne s9, t6, fail
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x05fc1a63, // bne s8, t6, fail
+        0x008023f3, // csrr t2, vstart
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x05f39263, // bne t2, t6, fail
+        0x02056107, // vle32.v v2, (a0), resumes at element 2
+        0x008023f3, // csrr t2, vstart
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x03f39863, // bne t2, t6, fail
+        0x02066127, // vse32.v v2, (a2)
+        0x00063383, // ld t2, 0(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x01f39e63, // bne t2, t6, fail
+        0x00863383, // ld t2, 8(a2)
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x01f39663, // bne t2, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000001, // .word 1
+        0x00000002, // .word 2
+        0x00000003, // .word 3
+        0x00000004, // .word 4
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000001, 0x00000002, // expected 0x200000001
+        0x0000000d, 0x0000000e, // expected 0xe0000000d
+        0x00000005, 0x00000000, // expected 0x5
+        0x80100000, 0x00000000, // expected 0x80100000
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000011, 0x00000022, // expected 0x2200000011
+        0x00000003, 0x00000004, // expected 0x400000003
+    ];
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn aes64_sha512_sha256() {
+    let program: [u32; 328] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x4bcd0d13, // la s10, expected
+        0x00000297, 0x49c28293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x01dda537, 0x9555051b, 0x00e51513, 0x43350513, 0x00c51513, 0x22150513, 0x00c51513,
+        0x10050513, // li a0, 0x7766554433221100, the plaintext of FIPS-197 C.1
+        0xfbb775b7, 0x32f5859b, 0x00d59593, 0xd5558593, 0x00d59593,
+        0x98858593, // li a1, 0xffeeddccbbaa9988
+        0x01c18637, 0x1416061b, 0x01161613, 0x18160613, 0x01161613,
+        0x10060613, // li a2, 0x0706050403020100, its key
+        0x00f0e6b7, 0x0d16869b, 0x00c69693, 0xc0b68693, 0x00c69693, 0x0a168693, 0x00c69693,
+        0x90868693, // li a3, 0x0f0e0d0c0b0a0908
+        0x00040437, 0x0014041b, 0x00d41413, // li s0, 0x80002000, the round keys
+        0x00c43023, // sd a2, 0(s0)
+        0x00d43423, // sd a3, 8(s0)
+        0x31069293, // aes64ks1i t0, a3, 0
+        0x7ec28633, // aes64ks2 a2, t0, a2
+        0x7ed606b3, // aes64ks2 a3, a2, a3
+        0x00c43823, // sd a2, 16(s0)
+        0x00d43c23, // sd a3, 24(s0)
+        0x31169293, // aes64ks1i t0, a3, 1
+        0x7ec28633, // aes64ks2 a2, t0, a2
+        0x7ed606b3, // aes64ks2 a3, a2, a3
+        0x02c43023, // sd a2, 32(s0)
+        0x02d43423, // sd a3, 40(s0)
+        0x31269293, // aes64ks1i t0, a3, 2
+        0x7ec28633, // aes64ks2 a2, t0, a2
+        0x7ed606b3, // aes64ks2 a3, a2, a3
+        0x02c43823, // sd a2, 48(s0)
+        0x02d43c23, // sd a3, 56(s0)
+        0x31369293, // aes64ks1i t0, a3, 3
+        0x7ec28633, // aes64ks2 a2, t0, a2
+        0x7ed606b3, // aes64ks2 a3, a2, a3
+        0x04c43023, // sd a2, 64(s0)
+        0x04d43423, // sd a3, 72(s0)
+        0x31469293, // aes64ks1i t0, a3, 4
+        0x7ec28633, // aes64ks2 a2, t0, a2
+        0x7ed606b3, // aes64ks2 a3, a2, a3
+        0x04c43823, // sd a2, 80(s0)
+        0x04d43c23, // sd a3, 88(s0)
+        0x31569293, // aes64ks1i t0, a3, 5
+        0x7ec28633, // aes64ks2 a2, t0, a2
+        0x7ed606b3, // aes64ks2 a3, a2, a3
+        0x06c43023, // sd a2, 96(s0)
+        0x06d43423, // sd a3, 104(s0)
+        0x31669293, // aes64ks1i t0, a3, 6
+        0x7ec28633, // aes64ks2 a2, t0, a2
+        0x7ed606b3, // aes64ks2 a3, a2, a3
+        0x06c43823, // sd a2, 112(s0)
+        0x06d43c23, // sd a3, 120(s0)
+        0x31769293, // aes64ks1i t0, a3, 7
+        0x7ec28633, // aes64ks2 a2, t0, a2
+        0x7ed606b3, // aes64ks2 a3, a2, a3
+        0x08c43023, // sd a2, 128(s0)
+        0x08d43423, // sd a3, 136(s0)
+        0x31869293, // aes64ks1i t0, a3, 8
+        0x7ec28633, // aes64ks2 a2, t0, a2
+        0x7ed606b3, // aes64ks2 a3, a2, a3
+        0x08c43823, // sd a2, 144(s0)
+        0x08d43c23, // sd a3, 152(s0)
+        0x31969293, // aes64ks1i t0, a3, 9
+        0x7ec28633, // aes64ks2 a2, t0, a2
+        0x7ed606b3, // aes64ks2 a3, a2, a3
+        0x0ac43023, // sd a2, 160(s0)
+        0x0ad43423, // sd a3, 168(s0)
+        0x00043283, // ld t0, 0(s0)
+        0x00554533, // xor a0, a0, t0
+        0x00843283, // ld t0, 8(s0)
+        0x0055c5b3, // xor a1, a1, t0
+        0x36b50333, // aes64esm t1, a0, a1
+        0x36a583b3, // aes64esm t2, a1, a0
+        0x01043283, // ld t0, 16(s0)
+        0x00534533, // xor a0, t1, t0
+        0x01843283, // ld t0, 24(s0)
+        0x0053c5b3, // xor a1, t2, t0
+        0x36b50333, // aes64esm t1, a0, a1
+        0x36a583b3, // aes64esm t2, a1, a0
+        0x02043283, // ld t0, 32(s0)
+        0x00534533, // xor a0, t1, t0
+        0x02843283, // ld t0, 40(s0)
+        0x0053c5b3, // xor a1, t2, t0
+        0x36b50333, // aes64esm t1, a0, a1
+        0x36a583b3, // aes64esm t2, a1, a0
+        0x03043283, // ld t0, 48(s0)
+        0x00534533, // xor a0, t1, t0
+        0x03843283, // ld t0, 56(s0)
+        0x0053c5b3, // xor a1, t2, t0
+        0x36b50333, // aes64esm t1, a0, a1
+        0x36a583b3, // aes64esm t2, a1, a0
+        0x04043283, // ld t0, 64(s0)
+        0x00534533, // xor a0, t1, t0
+        0x04843283, // ld t0, 72(s0)
+        0x0053c5b3, // xor a1, t2, t0
+        0x36b50333, // aes64esm t1, a0, a1
+        0x36a583b3, // aes64esm t2, a1, a0
+        0x05043283, // ld t0, 80(s0)
+        0x00534533, // xor a0, t1, t0
+        0x05843283, // ld t0, 88(s0)
+        0x0053c5b3, // xor a1, t2, t0
+        0x36b50333, // aes64esm t1, a0, a1
+        0x36a583b3, // aes64esm t2, a1, a0
+        0x06043283, // ld t0, 96(s0)
+        0x00534533, // xor a0, t1, t0
+        0x06843283, // ld t0, 104(s0)
+        0x0053c5b3, // xor a1, t2, t0
+        0x36b50333, // aes64esm t1, a0, a1
+        0x36a583b3, // aes64esm t2, a1, a0
+        0x07043283, // ld t0, 112(s0)
+        0x00534533, // xor a0, t1, t0
+        0x07843283, // ld t0, 120(s0)
+        0x0053c5b3, // xor a1, t2, t0
+        0x36b50333, // aes64esm t1, a0, a1
+        0x36a583b3, // aes64esm t2, a1, a0
+        0x08043283, // ld t0, 128(s0)
+        0x00534533, // xor a0, t1, t0
+        0x08843283, // ld t0, 136(s0)
+        0x0053c5b3, // xor a1, t2, t0
+        0x36b50333, // aes64esm t1, a0, a1
+        0x36a583b3, // aes64esm t2, a1, a0
+        0x09043283, // ld t0, 144(s0)
+        0x00534533, // xor a0, t1, t0
+        0x09843283, // ld t0, 152(s0)
+        0x0053c5b3, // xor a1, t2, t0
+        0x32b50333, // aes64es t1, a0, a1
+        0x32a583b3, // aes64es t2, a1, a0
+        0x0a043283, // ld t0, 160(s0)
+        0x00534533, // xor a0, t1, t0
+        0x0a843283, // ld t0, 168(s0)
+        0x0053c5b3, // xor a1, t2, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x23f51063, // bne a0, t6, fail, the ciphertext
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x21f59a63, // bne a1, t6, fail
+        0x0a043283, // ld t0, 160(s0)
+        0x00554533, // xor a0, a0, t0
+        0x0a843283, // ld t0, 168(s0)
+        0x0055c5b3, // xor a1, a1, t0
+        0x3eb50333, // aes64dsm t1, a0, a1
+        0x3ea583b3, // aes64dsm t2, a1, a0
+        0x09043283, // ld t0, 144(s0)
+        0x30029293, // aes64im t0, t0, the equivalent inverse cipher
+        0x00534533, // xor a0, t1, t0
+        0x09843283, // ld t0, 152(s0)
+        0x30029293, // aes64im t0, t0
+        0x0053c5b3, // xor a1, t2, t0
+        0x3eb50333, // aes64dsm t1, a0, a1
+        0x3ea583b3, // aes64dsm t2, a1, a0
+        0x08043283, // ld t0, 128(s0)
+        0x30029293, // aes64im t0, t0, the equivalent inverse cipher
+        0x00534533, // xor a0, t1, t0
+        0x08843283, // ld t0, 136(s0)
+        0x30029293, // aes64im t0, t0
+        0x0053c5b3, // xor a1, t2, t0
+        0x3eb50333, // aes64dsm t1, a0, a1
+        0x3ea583b3, // aes64dsm t2, a1, a0
+        0x07043283, // ld t0, 112(s0)
+        0x30029293, // aes64im t0, t0, the equivalent inverse cipher
+        0x00534533, // xor a0, t1, t0
+        0x07843283, // ld t0, 120(s0)
+        0x30029293, // aes64im t0, t0
+        0x0053c5b3, // xor a1, t2, t0
+        0x3eb50333, // aes64dsm t1, a0, a1
+        0x3ea583b3, // aes64dsm t2, a1, a0
+        0x06043283, // ld t0, 96(s0)
+        0x30029293, // aes64im t0, t0, the equivalent inverse cipher
+        0x00534533, // xor a0, t1, t0
+        0x06843283, // ld t0, 104(s0)
+        0x30029293, // aes64im t0, t0
+        0x0053c5b3, // xor a1, t2, t0
+        0x3eb50333, // aes64dsm t1, a0, a1
+        0x3ea583b3, // aes64dsm t2, a1, a0
+        0x05043283, // ld t0, 80(s0)
+        0x30029293, // aes64im t0, t0, the equivalent inverse cipher
+        0x00534533, // xor a0, t1, t0
+        0x05843283, // ld t0, 88(s0)
+        0x30029293, // aes64im t0, t0
+        0x0053c5b3, // xor a1, t2, t0
+        0x3eb50333, // aes64dsm t1, a0, a1
+        0x3ea583b3, // aes64dsm t2, a1, a0
+        0x04043283, // ld t0, 64(s0)
+        0x30029293, // aes64im t0, t0, the equivalent inverse cipher
+        0x00534533, // xor a0, t1, t0
+        0x04843283, // ld t0, 72(s0)
+        0x30029293, // aes64im t0, t0
+        0x0053c5b3, // xor a1, t2, t0
+        0x3eb50333, // aes64dsm t1, a0, a1
+        0x3ea583b3, // aes64dsm t2, a1, a0
+        0x03043283, // ld t0, 48(s0)
+        0x30029293, // aes64im t0, t0, the equivalent inverse cipher
+        0x00534533, // xor a0, t1, t0
+        0x03843283, // ld t0, 56(s0)
+        0x30029293, // aes64im t0, t0
+        0x0053c5b3, // xor a1, t2, t0
+        0x3eb50333, // aes64dsm t1, a0, a1
+        0x3ea583b3, // aes64dsm t2, a1, a0
+        0x02043283, // ld t0, 32(s0)
+        0x30029293, // aes64im t0, t0, the equivalent inverse cipher
+        0x00534533, // xor a0, t1, t0
+        0x02843283, // ld t0, 40(s0)
+        0x30029293, // aes64im t0, t0
+        0x0053c5b3, // xor a1, t2, t0
+        0x3eb50333, // aes64dsm t1, a0, a1
+        0x3ea583b3, // aes64dsm t2, a1, a0
+        0x01043283, // ld t0, 16(s0)
+        0x30029293, // aes64im t0, t0, the equivalent inverse cipher
+        0x00534533, // xor a0, t1, t0
+        0x01843283, // ld t0, 24(s0)
+        0x30029293, // aes64im t0, t0
+        0x0053c5b3, // xor a1, t2, t0
+        0x3ab50333, // aes64ds t1, a0, a1
+        0x3aa583b3, // aes64ds t2, a1, a0
+        0x00043283, // ld t0, 0(s0)
+        0x00534533, // xor a0, t1, t0
+        0x00843283, // ld t0, 8(s0)
+        0x0053c5b3, // xor a1, t2, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x0df51063, // bne a0, t6, fail
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x0bf59a63, // bne a1, t6, fail
+        0x00d412b7, 0x3cd2829b, 0x00d29293, 0x9fd28293, 0x00e29293, 0xbcd28293, 0x00c29293,
+        0x90828293, // li t0, 0x6a09e667f3bcc908
+        0x10629313, // sha512sig0 t1, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x09f31263, // bne t1, t6, fail
+        0x10729313, // sha512sig1 t1, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x07f31a63, // bne t1, t6, fail
+        0x10429313, // sha512sum0 t1, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x07f31263, // bne t1, t6, fail
+        0x10529313, // sha512sum1 t1, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x05f31a63, // bne t1, t6, fail
+        0x6a09e2b7, 0x6672829b, // li t0, 0x6a09e667
+        0x10229313, // sha256sig0 t1, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x03f31e63, // bne t1, t6, fail, sign-extended
+        0x10329313, // sha256sig1 t1, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x048d3f83, // ld t6, 72(s10)
+        0x03f31663, // bne t1, t6, fail
+        0x10029313, // sha256sum0 t1, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x050d3f83, // ld t6, 80(s10)
+        0x01f31e63, // bne t1, t6, fail
+        0x10129313, // sha256sum1 t1, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x058d3f83, // ld t6, 88(s10)
+        0x01f31663, // bne t1, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0xd8e0c469, 0x30047b6a, // expected 0x30047b6ad8e0c469
+        0x80b7cdd8, 0x5ac5b470, // expected 0x5ac5b47080b7cdd8
+        0x33221100, 0x77665544, // expected 0x7766554433221100
+        0xbbaa9988, 0xffeeddcc, // expected 0xffeeddccbbaa9988
+        0x51caa1df, 0x3dbae919, // expected 0x3dbae91951caa1df
+        0x3ee44510, 0xc8c619e7, // expected 0xc8c619e73ee44510
+        0xaac80c2a, 0x08c4db56, // expected 0x8c4db56aac80c2a
+        0x643336ef, 0x259a6cc1, // expected 0x259a6cc1643336ef
+        0xba0cf582, 0xffffffff, // expected 0xffffffffba0cf582
+        0xcfe5da3c, 0xffffffff, // expected 0xffffffffcfe5da3c
+        0xce20b47e, 0xffffffff, // expected 0xffffffffce20b47e
+        0x55b65510, 0x00000000, // expected 0x55b65510
+    ];
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn aes32_sha512() {
+    let program: [u32; 134] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x1b4d0d13, // la s10, expected
+        0x00000297, 0x19028293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0xbee342b7,
+        0xd1928293, // li t0, 0xbee33d19, the columns of round 1 of FIPS-197 appendix B
+        0x2be2f337, 0x4a030313, // li t1, 0x2be2f4a0
+        0x2a8dc3b7, 0x69a38393, // li t2, 0x2a8dc69a
+        0x08490e37, 0x8e9e0e13, // li t3, 0x0848f8e9
+        0x00000513, // li a0, 0
+        0x26550533, // aes32esmi a0, a0, t0, 0
+        0x66650533, // aes32esmi a0, a0, t1, 1
+        0xa6750533, // aes32esmi a0, a0, t2, 2
+        0xe7c50533, // aes32esmi a0, a0, t3, 3
+        0x001d8d93, // addi s11, s11, 1
+        0x000d2f83, // lw t6, 0(s10)
+        0x13f51863, // bne a0, t6, fail
+        0x1b8b62b7, 0x9eb28293, // li t0, 0x1b8b59eb, the columns of round 10
+        0xc3a13337, 0xe4030313, // li t1, 0xc3a12e40
+        0x421343b7, 0x8f238393, // li t2, 0x421338f2
+        0xd2e78e37, 0x41ee0e13, // li t3, 0xd2e7841e
+        0xa8f91537, 0x4d050513, // li a0, 0xa8f914d0, its round key
+        0x22550533, // aes32esi a0, a0, t0, 0
+        0x62650533, // aes32esi a0, a0, t1, 1
+        0xa2750533, // aes32esi a0, a0, t2, 2
+        0xe3c50533, // aes32esi a0, a0, t3, 3
+        0x001d8d93, // addi s11, s11, 1
+        0x008d2f83, // lw t6, 8(s10)
+        0x0ff51663, // bne a0, t6, fail, the ciphertext
+        0xb57d32b7, 0x1e928293, // li t0, 0xb57d31e9
+        0x00000513, // li a0, 0
+        0x2a550533, // aes32dsi a0, a0, t0, 0
+        0x6a550533, // aes32dsi a0, a0, t0, 1
+        0xaa550533, // aes32dsi a0, a0, t0, 2
+        0xea550533, // aes32dsi a0, a0, t0, 3
+        0x001d8d93, // addi s11, s11, 1
+        0x010d2f83, // lw t6, 16(s10)
+        0x0df51263, // bne a0, t6, fail
+        0xd90c32b7, 0x3f228293, // li t0, 0xd90c33f2
+        0x00000513, // li a0, 0
+        0x2e550533, // aes32dsmi a0, a0, t0, 0
+        0x6e550533, // aes32dsmi a0, a0, t0, 1
+        0xae550533, // aes32dsmi a0, a0, t0, 2
+        0xee550533, // aes32dsmi a0, a0, t0, 3
+        0x001d8d93, // addi s11, s11, 1
+        0x018d2f83, // lw t6, 24(s10)
+        0x09f51e63, // bne a0, t6, fail
+        0xf3bcd537, 0x90850513, // li a0, 0xf3bcc908, 0x6a09e667f3bcc908
+        0x6a09e5b7, 0x66758593, // li a1, 0x6a09e667
+        0x54b50333, // sha512sig0l t1, a0, a1
+        0x001d8d93, // addi s11, s11, 1
+        0x020d2f83, // lw t6, 32(s10)
+        0x07f31e63, // bne t1, t6, fail
+        0x5ca58333, // sha512sig0h t1, a1, a0
+        0x001d8d93, // addi s11, s11, 1
+        0x028d2f83, // lw t6, 40(s10)
+        0x07f31663, // bne t1, t6, fail
+        0x56b50333, // sha512sig1l t1, a0, a1
+        0x001d8d93, // addi s11, s11, 1
+        0x030d2f83, // lw t6, 48(s10)
+        0x05f31e63, // bne t1, t6, fail
+        0x5ea58333, // sha512sig1h t1, a1, a0
+        0x001d8d93, // addi s11, s11, 1
+        0x038d2f83, // lw t6, 56(s10)
+        0x05f31663, // bne t1, t6, fail
+        0x50b50333, // sha512sum0r t1, a0, a1
+        0x001d8d93, // addi s11, s11, 1
+        0x040d2f83, // lw t6, 64(s10)
+        0x03f31e63, // bne t1, t6, fail
+        0x50a58333, // sha512sum0r t1, a1, a0
+        0x001d8d93, // addi s11, s11, 1
+        0x048d2f83, // lw t6, 72(s10)
+        0x03f31663, // bne t1, t6, fail
+        0x52b50333, // sha512sum1r t1, a0, a1
+        0x001d8d93, // addi s11, s11, 1
+        0x050d2f83, // lw t6, 80(s10)
+        0x01f31e63, // bne t1, t6, fail
+        0x52a58333, // sha512sum1r t1, a1, a0
+        0x001d8d93, // addi s11, s11, 1
+        0x058d2f83, // lw t6, 88(s10)
+        0x01f31663, // bne t1, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x80001337, // li t1, 0x80001000
+        0x00532023, // sw t0, 0(t1)
+        0x00032223, // sw zero, 4(t1), the high word completes the command
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0xe5816604, 0xffffffff, // expected 0xffffffffe5816604
+        0x1d842539, 0x00000000, // expected 0x1d842539
+        0xd2132eeb, 0xffffffff, // expected 0xffffffffd2132eeb
+        0x305dbfd4, 0x00000000, // expected 0x305dbfd4
+        0x51caa1df, 0x00000000, // expected 0x51caa1df
+        0x3dbae919, 0x00000000, // expected 0x3dbae919
+        0x3ee44510, 0x00000000, // expected 0x3ee44510
+        0xc8c619e7, 0xffffffff, // expected 0xffffffffc8c619e7
+        0xaac80c2a, 0xffffffff, // expected 0xffffffffaac80c2a
+        0x08c4db56, 0x00000000, // expected 0x8c4db56
+        0x643336ef, 0x00000000, // expected 0x643336ef
+        0x259a6cc1, 0x00000000, // expected 0x259a6cc1
+    ];
+    let config = Config {
+        isa: Isa::parse("rv32i_zicsr_zknd_zkne_zknh").unwrap(),
+        ..Config::default()
+    };
+    assert_eq!(run(&config, &program), 0);
+    // without Zknd, the first check of a decryption fails
+    let config = Config {
+        isa: Isa::parse("rv32i_zicsr_zkne_zknh").unwrap(),
+        ..Config::default()
+    };
+    assert_eq!(run(&config, &program), 3);
+}
+
+#[test]
+fn sm4_sm3() {
+    let program: [u32; 172] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x27cd0d13, // la s10, expected
+        0x00000297, 0x1d828293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x01234537, 0x5675051b, // li a0, 0x01234567, the key of GB/T 32907 A.1, xored with FK
+        0x000292b7, 0xec72829b, 0x00e29293, 0xac628293, // li t0, 0xa3b1bac6
+        0x00554533, // xor a0, a0, t0
+        0x0008a5b7, 0xabd5859b, 0x00c59593, 0xdef58593, // li a1, 0x89abcdef
+        0x56aa32b7, 0x3502829b, // li t0, 0x56aa3350
+        0x0055c5b3, // xor a1, a1, t0
+        0x00040637, 0xb736061b, 0x00e61613, 0xa9860613, // li a2, 0xfedcba98
+        0x677d92b7, 0x1972829b, // li t0, 0x677d9197
+        0x00564633, // xor a2, a2, t0
+        0x765436b7, 0x2106869b, // li a3, 0x76543210
+        0x000592b7, 0x3812829b, 0x00d29293, 0x2dc28293, // li t0, 0xb27022dc
+        0x0056c6b3, // xor a3, a3, t0
+        0x00000497, 0x17448493, // la s1, ck
+        0x00040437, 0x0014041b, 0x00d41413, // li s0, 0x80002000, the round keys
+        0x02000913, // li s2, 32
+        0x0004a283, // lw t0, 0(s1)
+        0x00b2c2b3, // xor t0, t0, a1
+        0x00c2c2b3, // xor t0, t0, a2
+        0x00d2c2b3, // xor t0, t0, a3
+        0x34550533, // sm4ks a0, a0, t0, 0
+        0x74550533, // sm4ks a0, a0, t0, 1
+        0xb4550533, // sm4ks a0, a0, t0, 2
+        0xf4550533, // sm4ks a0, a0, t0, 3
+        0x00a42023, // sw a0, 0(s0)
+        0x00050313, // mv t1, a0
+        0x00058513, // mv a0, a1
+        0x00060593, // mv a1, a2
+        0x00068613, // mv a2, a3
+        0x00030693, // mv a3, t1
+        0x00448493, // addi s1, s1, 4
+        0x00440413, // addi s0, s0, 4
+        0xfff90913, // addi s2, s2, -1
+        0xfa091ee3, // bnez s2, keys
+        0x01234537, 0x5675051b, // li a0, 0x01234567, the plaintext, the same as the key
+        0x0008a5b7, 0xabd5859b, 0x00c59593, 0xdef58593, // li a1, 0x89abcdef
+        0x00040637, 0xb736061b, 0x00e61613, 0xa9860613, // li a2, 0xfedcba98
+        0x765436b7, 0x2106869b, // li a3, 0x76543210
+        0x00040437, 0x0014041b, 0x00d41413, // li s0, 0x80002000
+        0x02000913, // li s2, 32
+        0x00042283, // lw t0, 0(s0)
+        0x00b2c2b3, // xor t0, t0, a1
+        0x00c2c2b3, // xor t0, t0, a2
+        0x00d2c2b3, // xor t0, t0, a3
+        0x30550533, // sm4ed a0, a0, t0, 0
+        0x70550533, // sm4ed a0, a0, t0, 1
+        0xb0550533, // sm4ed a0, a0, t0, 2
+        0xf0550533, // sm4ed a0, a0, t0, 3
+        0x00050313, // mv t1, a0
+        0x00058513, // mv a0, a1
+        0x00060593, // mv a1, a2
+        0x00068613, // mv a2, a3
+        0x00030693, // mv a3, t1
+        0x00440413, // addi s0, s0, 4
+        0xfff90913, // addi s2, s2, -1
+        0xfc0912e3, // bnez s2, rounds
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x05f69c63, // bne a3, t6, fail, the ciphertext, in reverse order
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x05f61663, // bne a2, t6, fail
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x05f59063, // bne a1, t6, fail
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x03f51a63, // bne a0, t6, fail
+        0x738012b7, 0x66f2829b, // li t0, 0x7380166f
+        0x10829313, // sm3p0 t1, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x01f31e63, // bne t1, t6, fail
+        0x10929313, // sm3p1 t1, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x01f31663, // bne t1, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00070e15, // CK0
+        0x1c232a31, // CK1
+        0x383f464d, // CK2
+        0x545b6269, // CK3
+        0x70777e85, // CK4
+        0x8c939aa1, // CK5
+        0xa8afb6bd, // CK6
+        0xc4cbd2d9, // CK7
+        0xe0e7eef5, // CK8
+        0xfc030a11, // CK9
+        0x181f262d, // CK10
+        0x343b4249, // CK11
+        0x50575e65, // CK12
+        0x6c737a81, // CK13
+        0x888f969d, // CK14
+        0xa4abb2b9, // CK15
+        0xc0c7ced5, // CK16
+        0xdce3eaf1, // CK17
+        0xf8ff060d, // CK18
+        0x141b2229, // CK19
+        0x30373e45, // CK20
+        0x4c535a61, // CK21
+        0x686f767d, // CK22
+        0x848b9299, // CK23
+        0xa0a7aeb5, // CK24
+        0xbcc3cad1, // CK25
+        0xd8dfe6ed, // CK26
+        0xf4fb0209, // CK27
+        0x10171e25, // CK28
+        0x2c333a41, // CK29
+        0x484f565d, // CK30
+        0x646b7279, // CK31
+        0x00000013, // nop
+        0x681edf34, 0x00000000, // expected 0x681edf34
+        0xd206965e, 0xffffffff, // expected 0xffffffffd206965e
+        0x86b3e94f, 0xffffffff, // expected 0xffffffff86b3e94f
+        0x536e4246, 0x00000000, // expected 0x536e4246
+        0x5f722f88, 0x00000000, // expected 0x5f722f88
+        0x4f0e6fa4, 0x00000000, // expected 0x4f0e6fa4
+    ];
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn seed() {
+    let program: [u32; 92] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x13cd0d13, // la s10, expected
+        0x00000297, 0x10828293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x015012f3, // csrrw t0, seed, zero
+        0x0102d313, // srli t1, t0, 16
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x0df31863, // bne t1, t6, fail, ES16, with the entropy in the low bits
+        0x00000c93, // li s9, 0
+        0x015022f3, // csrr t0, seed, without a write
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x0bfc9e63, // bne s9, t6, fail
+        0x00000c93, // li s9, 0
+        0x74701073, // csrw mseccfg, zero
+        0x00000297, 0x01c28293, // la t0, user
+        0x34129073, // csrw mepc, t0
+        0x000022b7, 0x8002829b, // li t0, 0x1800, mstatus.MPP
+        0x3002b073, // csrc mstatus, t0
+        0x30200073, // mret
+        0x015012f3, // csrrw t0, seed, zero
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x09fc9463, // bne s9, t6, fail, without mseccfg.USEED
+        0x00000073, // ecall
+        0x00000c93, // li s9, 0
+        0x10000293, // li t0, 0x100, mseccfg.USEED
+        0x7472a073, // csrs mseccfg, t0
+        0x00000297, 0x01c28293, // la t0, granted
+        0x34129073, // csrw mepc, t0
+        0x000022b7, 0x8002829b, // li t0, 0x1800
+        0x3002b073, // csrc mstatus, t0
+        0x30200073, // mret
+        0x015012f3, // csrrw t0, seed, zero
+        0x0102d313, // srli t1, t0, 16
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x05f31463, // bne t1, t6, fail
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x03fc9e63, // bne s9, t6, fail
+        0x00000073, // ecall
+        0x000012b7, 0x8002829b, // li t0, 0x800, S-mode, without mseccfg.SSEED
+        0x3002a073, // csrs mstatus, t0
+        0x00000297, 0x01028293, // la t0, supervisor
+        0x34129073, // csrw mepc, t0
+        0x30200073, // mret
+        0x015012f3, // csrrw t0, seed, zero
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x01fc9663, // bne s9, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x00800f13, // li t5, 8, an ecall from U-mode returns to M-mode
+        0x01ec9863, // bne s9, t5, back
+        0x00002f37, 0x800f0f1b, // li t5, 0x1800
+        0x300f2073, // csrs mstatus, t5
+        0x30200073, // mret
+        0x00008000, 0x00000000, // expected 0x8000
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000002, 0x00000000, // expected 0x2
+        0x00008000, 0x00000000, // expected 0x8000
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000002, 0x00000000, // expected 0x2
+    ];
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn zfh_zfa() {
+    let program: [u32; 282] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x354d0d13, // la s10, expected
+        0x00000297, 0x33028293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x000022b7, // li t0, 0x2000, mstatus.FS
+        0x3002a073, // csrs mstatus, t0
+        0xf0100053, // fli.s ft0, -1.0
+        0xe00003d3, // fmv.x.w t2, ft0
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x2ff39863, // bne t2, t6, fail, NaN-boxed
+        0xf0108053, // fli.s ft0, min
+        0xe00003d3, // fmv.x.w t2, ft0
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x2df39e63, // bne t2, t6, fail
+        0xf01e8053, // fli.s ft0, 2^16
+        0xe00003d3, // fmv.x.w t2, ft0
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x2df39463, // bne t2, t6, fail
+        0xf2180053, // fli.d ft0, 1.0
+        0xe20003d3, // fmv.x.d t2, ft0
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x2bf39a63, // bne t2, t6, fail
+        0xf2108053, // fli.d ft0, min
+        0xe20003d3, // fmv.x.d t2, ft0
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x2bf39063, // bne t2, t6, fail
+        0xf21f8053, // fli.d ft0, nan
+        0xe20003d3, // fmv.x.d t2, ft0
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x29f39663, // bne t2, t6, fail
+        0xf4108053, // fli.h ft0, min
+        0xe40003d3, // fmv.x.h t2, ft0
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x27f39c63, // bne t2, t6, fail
+        0xf0180053, // fli.s ft0, 1.0
+        0xf01f80d3, // fli.s ft1, nan
+        0x28100153, // fmin.s ft2, ft0, ft1
+        0xe00103d3, // fmv.x.w t2, ft2
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x25f39e63, // bne t2, t6, fail, the number
+        0x28102153, // fminm.s ft2, ft0, ft1
+        0xe00103d3, // fmv.x.w t2, ft2
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x25f39463, // bne t2, t6, fail, the NaN
+        0x2800b153, // fmaxm.s ft2, ft1, ft0
+        0xe00103d3, // fmv.x.w t2, ft2
+        0x001d8d93, // addi s11, s11, 1
+        0x048d3f83, // ld t6, 72(s10)
+        0x23f39a63, // bne t2, t6, fail
+        0xf2000053, // fmv.d.x ft0, zero
+        0x220010d3, // fsgnjn.d ft1, ft0, ft0
+        0x2a00b153, // fmaxm.d ft2, ft1, ft0
+        0xe20103d3, // fmv.x.d t2, ft2
+        0x001d8d93, // addi s11, s11, 1
+        0x050d3f83, // ld t6, 80(s10)
+        0x21f39c63, // bne t2, t6, fail, +0.0
+        0x2a102153, // fminm.d ft2, ft0, ft1
+        0xe20103d3, // fmv.x.d t2, ft2
+        0x001d8d93, // addi s11, s11, 1
+        0x058d3f83, // ld t6, 88(s10)
+        0x21f39263, // bne t2, t6, fail, -0.0
+        0x00101073, // csrw fflags, zero
+        0xf01a8053, // fli.s ft0, 2.5
+        0x404000d3, // fround.s ft1, ft0, rne
+        0xe00083d3, // fmv.x.w t2, ft1
+        0x001d8d93, // addi s11, s11, 1
+        0x060d3f83, // ld t6, 96(s10)
+        0x1ff39463, // bne t2, t6, fail, 2.0
+        0x404040d3, // fround.s ft1, ft0, rmm
+        0xe00083d3, // fmv.x.w t2, ft1
+        0x001d8d93, // addi s11, s11, 1
+        0x068d3f83, // ld t6, 104(s10)
+        0x1df39a63, // bne t2, t6, fail, 3.0
+        0x001023f3, // csrr t2, fflags
+        0x001d8d93, // addi s11, s11, 1
+        0x070d3f83, // ld t6, 112(s10)
+        0x1df39263, // bne t2, t6, fail, fround is exact
+        0x405020d3, // froundnx.s ft1, ft0, rdn
+        0xe00083d3, // fmv.x.w t2, ft1
+        0x001d8d93, // addi s11, s11, 1
+        0x078d3f83, // ld t6, 120(s10)
+        0x1bf39863, // bne t2, t6, fail
+        0x001023f3, // csrr t2, fflags
+        0x001d8d93, // addi s11, s11, 1
+        0x080d3f83, // ld t6, 128(s10)
+        0x1bf39063, // bne t2, t6, fail, froundnx is inexact
+        0x00101073, // csrw fflags, zero
+        0x41f00293, 0x02029293, 0x00528293, 0x01429293, // li t0, 0x41f0000000500000, 2^32 + 5
+        0xf2028053, // fmv.d.x ft0, t0
+        0xc28013d3, // fcvtmod.w.d t2, ft0, rtz
+        0x001d8d93, // addi s11, s11, 1
+        0x088d3f83, // ld t6, 136(s10)
+        0x17f39c63, // bne t2, t6, fail, modulo 2^32
+        0x001023f3, // csrr t2, fflags
+        0x001d8d93, // addi s11, s11, 1
+        0x090d3f83, // ld t6, 144(s10)
+        0x17f39463, // bne t2, t6, fail, invalid
+        0x00101073, // csrw fflags, zero
+        0xe0f00293, 0x02029293, 0x00128293,
+        0x01529293, // li t0, 0xc1e0000000200000, -2^31 - 1
+        0xf2028053, // fmv.d.x ft0, t0
+        0xc28013d3, // fcvtmod.w.d t2, ft0, rtz
+        0x001d8d93, // addi s11, s11, 1
+        0x098d3f83, // ld t6, 152(s10)
+        0x15f39063, // bne t2, t6, fail
+        0x00101073, // csrw fflags, zero
+        0xfffff2b7, 0x7ff2829b, 0x03329293, // li t0, 0xbff8000000000000, -1.5
+        0xf2028053, // fmv.d.x ft0, t0
+        0xc28013d3, // fcvtmod.w.d t2, ft0, rtz
+        0x001d8d93, // addi s11, s11, 1
+        0x0a0d3f83, // ld t6, 160(s10)
+        0x11f39e63, // bne t2, t6, fail
+        0x001023f3, // csrr t2, fflags
+        0x001d8d93, // addi s11, s11, 1
+        0x0a8d3f83, // ld t6, 168(s10)
+        0x11f39663, // bne t2, t6, fail, inexact
+        0x00101073, // csrw fflags, zero
+        0xf21f0053, // fli.d ft0, inf
+        0xc28013d3, // fcvtmod.w.d t2, ft0, rtz
+        0x001d8d93, // addi s11, s11, 1
+        0x0b0d3f83, // ld t6, 176(s10)
+        0x0ff39a63, // bne t2, t6, fail
+        0x001023f3, // csrr t2, fflags
+        0x001d8d93, // addi s11, s11, 1
+        0x0b8d3f83, // ld t6, 184(s10)
+        0x0ff39263, // bne t2, t6, fail
+        0x00101073, // csrw fflags, zero
+        0xf0180053, // fli.s ft0, 1.0
+        0xf01f80d3, // fli.s ft1, nan
+        0xa00043d3, // fleq.s t2, ft0, ft0
+        0x001d8d93, // addi s11, s11, 1
+        0x0c0d3f83, // ld t6, 192(s10)
+        0x0df39463, // bne t2, t6, fail
+        0xa01053d3, // fltq.s t2, ft0, ft1
+        0x001d8d93, // addi s11, s11, 1
+        0x0c8d3f83, // ld t6, 200(s10)
+        0x0bf39c63, // bne t2, t6, fail
+        0xa000c3d3, // fleq.s t2, ft1, ft0
+        0x001d8d93, // addi s11, s11, 1
+        0x0d0d3f83, // ld t6, 208(s10)
+        0x0bf39463, // bne t2, t6, fail
+        0x001023f3, // csrr t2, fflags
+        0x001d8d93, // addi s11, s11, 1
+        0x0d8d3f83, // ld t6, 216(s10)
+        0x09f39c63, // bne t2, t6, fail, quiet comparisons
+        0xa01013d3, // flt.s t2, ft0, ft1
+        0x001023f3, // csrr t2, fflags
+        0x001d8d93, // addi s11, s11, 1
+        0x0e0d3f83, // ld t6, 224(s10)
+        0x09f39263, // bne t2, t6, fail, unlike the signaling ones
+        0xf4180053, // fli.h ft0, 1.0
+        0xf41a00d3, // fli.h ft1, 2.0
+        0x04107153, // fadd.h ft2, ft0, ft1
+        0xe40103d3, // fmv.x.h t2, ft2
+        0x001d8d93, // addi s11, s11, 1
+        0x0e8d3f83, // ld t6, 232(s10)
+        0x07f39463, // bne t2, t6, fail, 3.0
+        0x402171d3, // fcvt.s.h ft3, ft2
+        0xe00183d3, // fmv.x.w t2, ft3
+        0x001d8d93, // addi s11, s11, 1
+        0x0f0d3f83, // ld t6, 240(s10)
+        0x05f39a63, // bne t2, t6, fail
+        0x3f8002b7, 0x0012829b, // li t0, 0x3f800001, the float after 1.0
+        0xf0028053, // fmv.w.x ft0, t0
+        0x00101073, // csrw fflags, zero
+        0x440030d3, // fcvt.h.s ft1, ft0, rup
+        0xe40083d3, // fmv.x.h t2, ft1
+        0x001d8d93, // addi s11, s11, 1
+        0x0f8d3f83, // ld t6, 248(s10)
+        0x03f39863, // bne t2, t6, fail
+        0x001023f3, // csrr t2, fflags
+        0x001d8d93, // addi s11, s11, 1
+        0x100d3f83, // ld t6, 256(s10)
+        0x03f39063, // bne t2, t6, fail
+        0xf4100053, // fli.h ft0, -1.0
+        0xe40003d3, // fmv.x.h t2, ft0
+        0x001d8d93, // addi s11, s11, 1
+        0x108d3f83, // ld t6, 264(s10)
+        0x01f39663, // bne t2, t6, fail, 0xbc00, sign-extended
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0xbf800000, 0xffffffff, // expected 0xffffffffbf800000
+        0x00800000, 0x00000000, // expected 0x800000
+        0x47800000, 0x00000000, // expected 0x47800000
+        0x00000000, 0x3ff00000, // expected 0x3ff0000000000000
+        0x00000000, 0x00100000, // expected 0x10000000000000
+        0x00000000, 0x7ff80000, // expected 0x7ff8000000000000
+        0x00000400, 0x00000000, // expected 0x400
+        0x3f800000, 0x00000000, // expected 0x3f800000
+        0x7fc00000, 0x00000000, // expected 0x7fc00000
+        0x7fc00000, 0x00000000, // expected 0x7fc00000
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000000, 0x80000000, // expected 0x8000000000000000
+        0x40000000, 0x00000000, // expected 0x40000000
+        0x40400000, 0x00000000, // expected 0x40400000
+        0x00000000, 0x00000000, // expected 0x0
+        0x40000000, 0x00000000, // expected 0x40000000
+        0x00000001, 0x00000000, // expected 0x1
+        0x00000005, 0x00000000, // expected 0x5
+        0x00000010, 0x00000000, // expected 0x10
+        0x7fffffff, 0x00000000, // expected 0x7fffffff
+        0xffffffff, 0xffffffff, // expected 0xffffffffffffffff
+        0x00000001, 0x00000000, // expected 0x1
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000010, 0x00000000, // expected 0x10
+        0x00000001, 0x00000000, // expected 0x1
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000010, 0x00000000, // expected 0x10
+        0x00004200, 0x00000000, // expected 0x4200
+        0x40400000, 0x00000000, // expected 0x40400000
+        0x00003c01, 0x00000000, // expected 0x3c01
+        0x00000001, 0x00000000, // expected 0x1
+        0xffffbc00, 0xffffffff, // expected 0xffffffffffffbc00
+    ];
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn floating_point_state() {
+    let program: [u32; 70] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x0e4d0d13, // la s10, expected
+        0x00000297, 0x0c428293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x000062b7, // li t0, 0x6000
+        0x3002b073, // csrc mstatus, t0, FS is off
+        0x00000c93, // li s9, 0
+        0x00302573, // csrr a0, fcsr
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x09fc9263, // bne s9, t6, fail
+        0x00000c93, // li s9, 0
+        0x0010d073, // csrwi fflags, 1
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x07fc9863, // bne s9, t6, fail
+        0x000022b7, // li t0, 0x2000
+        0x3002a073, // csrs mstatus, t0, FS is initial
+        0x00000c93, // li s9, 0
+        0x00202573, // csrr a0, frm
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x05fc9a63, // bne s9, t6, fail
+        0x30002573, // csrr a0, mstatus
+        0x00d55513, // srli a0, a0, 13
+        0x00357513, // andi a0, a0, 3
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x03f51e63, // bne a0, t6, fail, a read leaves the state as it is
+        0x0010d073, // csrwi fflags, 1
+        0x30002573, // csrr a0, mstatus
+        0x00d55513, // srli a0, a0, 13
+        0x00357513, // andi a0, a0, 3
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x03f51063, // bne a0, t6, fail, a write makes it dirty
+        0x30002573, // csrr a0, mstatus
+        0x03f55513, // srli a0, a0, 63
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x01f51663, // bne a0, t6, fail, SD
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000001, 0x00000000, // expected 0x1
+        0x00000003, 0x00000000, // expected 0x3
+        0x00000001, 0x00000000, // expected 0x1
+    ];
+
+    // fflags, frm and fcsr are illegal while mstatus.FS is off, and a write to them makes the
+    // state of the unit dirty
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn zicond_zicbo_zihintpause() {
+    let program: [u32; 172] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x22cd0d13, // la s10, expected
+        0x00000297, 0x1f828293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x00700293, // li t0, 7
+        0x00000313, // li t1, 0
+        0x0e62d533, // czero.eqz a0, t0, t1
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x1bf51e63, // bne a0, t6, fail
+        0x0e62f533, // czero.nez a0, t0, t1
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x1bf51663, // bne a0, t6, fail
+        0x00100313, // li t1, 1
+        0x0e62d533, // czero.eqz a0, t0, t1
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x19f51c63, // bne a0, t6, fail
+        0x0e62f533, // czero.nez a0, t0, t1
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x19f51463, // bne a0, t6, fail
+        0x00040437, 0x0014041b, 0x00d41413, // li s0, 0x80002000, two blocks of ones
+        0xfff00293, // li t0, -1
+        0x01000313, // li t1, 16
+        0x00543023, // sd t0, 0(s0)
+        0x00840413, // addi s0, s0, 8
+        0xfff30313, // addi t1, t1, -1
+        0xfe031ae3, // bnez t1, fill
+        0x00040437, 0x0014041b, 0x00d41413, // li s0, 0x80002000
+        0x04840593, // addi a1, s0, 0x48
+        0x0045a00f, // cbo.zero (a1), the second block
+        0x03843503, // ld a0, 0x38(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x15f51063, // bne a0, t6, fail
+        0x04043503, // ld a0, 0x40(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x13f51863, // bne a0, t6, fail
+        0x07843503, // ld a0, 0x78(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x13f51063, // bne a0, t6, fail
+        0x0014200f, // cbo.clean (s0)
+        0x0024200f, // cbo.flush (s0)
+        0x0004200f, // cbo.inval (s0)
+        0x00043503, // ld a0, 0(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x11f51263, // bne a0, t6, fail, the others keep the data
+        0x700005b7, // li a1, 0x70000000, outside the memory
+        0x0045a00f, // cbo.zero (a1)
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x0ffc9863, // bne s9, t6, fail
+        0x001d8d93, // addi s11, s11, 1
+        0x048d3f83, // ld t6, 72(s10)
+        0x0ffc1263, // bne s8, t6, fail
+        0x00000c93, // li s9, 0
+        0x0100000f, // pause
+        0x001d8d93, // addi s11, s11, 1
+        0x050d3f83, // ld t6, 80(s10)
+        0x0dfc9863, // bne s9, t6, fail
+        0x30a01073, // csrw menvcfg, zero
+        0x000022b7, 0x8002829b, // li t0, 0x1800, mstatus.MPP
+        0x3002b073, // csrc mstatus, t0
+        0x000012b7, 0x8002829b, // li t0, 0x800, S-mode
+        0x3002a073, // csrs mstatus, t0
+        0x00000297, 0x01028293, // la t0, disabled
+        0x34129073, // csrw mepc, t0
+        0x30200073, // mret
+        0x0044200f, // cbo.zero (s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x058d3f83, // ld t6, 88(s10)
+        0x09fc9a63, // bne s9, t6, fail, without menvcfg.CBZE
+        0x00000c93, // li s9, 0
+        0x0004200f, // cbo.inval (s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x060d3f83, // ld t6, 96(s10)
+        0x09fc9063, // bne s9, t6, fail, without menvcfg.CBIE
+        0x00000c93, // li s9, 0
+        0x0014200f, // cbo.clean (s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x068d3f83, // ld t6, 104(s10)
+        0x07fc9663, // bne s9, t6, fail, without menvcfg.CBCFE
+        0x00000073, // ecall
+        0x0d000293, // li t0, 0xd0, CBZE, CBCFE and CBIE flushing
+        0x30a2a073, // csrs menvcfg, t0
+        0x000022b7, 0x8002829b, // li t0, 0x1800
+        0x3002b073, // csrc mstatus, t0
+        0x000012b7, 0x8002829b, // li t0, 0x800
+        0x3002a073, // csrs mstatus, t0
+        0x00000297, 0x01428293, // la t0, enabled
+        0x34129073, // csrw mepc, t0
+        0x00000c93, // li s9, 0
+        0x30200073, // mret
+        0x0044200f, // cbo.zero (s0)
+        0x0004200f, // cbo.inval (s0)
+        0x0014200f, // cbo.clean (s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x070d3f83, // ld t6, 112(s10)
+        0x01fc9e63, // bne s9, t6, fail
+        0x00043503, // ld a0, 0(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x078d3f83, // ld t6, 120(s10)
+        0x01f51663, // bne a0, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x00900f13, // li t5, 9, an ecall from S-mode returns to M-mode
+        0x01ec9863, // bne s9, t5, back
+        0x00002f37, 0x800f0f1b, // li t5, 0x1800
+        0x300f2073, // csrs mstatus, t5
+        0x30200073, // mret
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000007, 0x00000000, // expected 0x7
+        0x00000007, 0x00000000, // expected 0x7
+        0x00000000, 0x00000000, // expected 0x0
+        0xffffffff, 0xffffffff, // expected 0xffffffffffffffff
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000000, 0x00000000, // expected 0x0
+        0xffffffff, 0xffffffff, // expected 0xffffffffffffffff
+        0x00000007, 0x00000000, // expected 0x7
+        0x70000000, 0x00000000, // expected 0x70000000
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000000, 0x00000000, // expected 0x0
+    ];
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn atomics() {
+    let program: [u32; 112] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x154d0d13, // la s10, expected
+        0x00000297, 0x13028293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x00040437, 0x0014041b, 0x00d41413, // li s0, 0x80002000
+        0x00500293, // li t0, 5
+        0x00543023, // sd t0, 0(s0)
+        0x1004252f, // lr.w a0, (s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x0ff51463, // bne a0, t6, fail
+        0x00042223, // sw zero, 4(s0), a store to the reserved doubleword
+        0x00700313, // li t1, 7
+        0x186425af, // sc.w a1, t1, (s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x0df59863, // bne a1, t6, fail, fails
+        0x00042503, // lw a0, 0(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x0df51063, // bne a0, t6, fail
+        0x1004252f, // lr.w a0, (s0)
+        0x00043423, // sd zero, 8(s0), a store elsewhere
+        0x186425af, // sc.w a1, t1, (s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x0bf59463, // bne a1, t6, fail
+        0x00042503, // lw a0, 0(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x09f51c63, // bne a0, t6, fail
+        0x186425af, // sc.w a1, t1, (s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x09f59463, // bne a1, t6, fail, the reservation has been used
+        0x1004352f, // lr.d a0, (s0)
+        0x00840393, // addi t2, s0, 8
+        0x1863b5af, // sc.d a1, t1, (t2)
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x07f59863, // bne a1, t6, fail, another address
+        0xffe00313, // li t1, -2
+        0xe064252f, // amomaxu.w a0, t1, (s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x05f51e63, // bne a0, t6, fail
+        0x00042503, // lw a0, 0(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x05f51663, // bne a0, t6, fail
+        0x8054252f, // amomin.w a0, t0, (s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x048d3f83, // ld t6, 72(s10)
+        0x03f51e63, // bne a0, t6, fail
+        0x00042503, // lw a0, 0(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x050d3f83, // ld t6, 80(s10)
+        0x03f51663, // bne a0, t6, fail
+        0x00240393, // addi t2, s0, 2
+        0x0053a52f, // amoadd.w a0, t0, (t2)
+        0x001d8d93, // addi s11, s11, 1
+        0x058d3f83, // ld t6, 88(s10)
+        0x01fc9c63, // bne s9, t6, fail, misaligned
+        0x001d8d93, // addi s11, s11, 1
+        0x060d3f83, // ld t6, 96(s10)
+        0x01fc1663, // bne s8, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0x00000005, 0x00000000, // expected 0x5
+        0x00000001, 0x00000000, // expected 0x1
+        0x00000005, 0x00000000, // expected 0x5
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000007, 0x00000000, // expected 0x7
+        0x00000001, 0x00000000, // expected 0x1
+        0x00000001, 0x00000000, // expected 0x1
+        0x00000007, 0x00000000, // expected 0x7
+        0xfffffffe, 0xffffffff, // expected 0xfffffffffffffffe
+        0xfffffffe, 0xffffffff, // expected 0xfffffffffffffffe
+        0xfffffffe, 0xffffffff, // expected 0xfffffffffffffffe
+        0x00000006, 0x00000000, // expected 0x6
+        0x80002002, 0x00000000, // expected 0x80002002
+    ];
+
+    // a store to the reserved doubleword makes sc fail, as does a use of the reservation, and a
+    // misaligned AMO raises a store address misaligned exception
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn multiply_divide() {
+    let program: [u32; 114] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x154d0d13, // la s10, expected
+        0x00000297, 0x13028293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0xffd00413, // li s0, -3
+        0x00700493, // li s1, 7
+        0x02940533, // mul a0, s0, s1
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x0ff51a63, // bne a0, t6, fail
+        0xfff00913, 0x03f91913, // li s2, 0x8000000000000000
+        0x03291533, // mulh a0, s2, s2
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x0df51e63, // bne a0, t6, fail, the high half of the signed product
+        0x02943533, // mulhu a0, s0, s1
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x0df51663, // bne a0, t6, fail
+        0x02942533, // mulhsu a0, s0, s1
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x0bf51e63, // bne a0, t6, fail
+        0x0284c533, // div a0, s1, s0
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x0bf51663, // bne a0, t6, fail, rounds toward zero
+        0x0284e533, // rem a0, s1, s0
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x09f51e63, // bne a0, t6, fail, takes the sign of the dividend
+        0x0204d533, // divu a0, s1, zero
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x09f51663, // bne a0, t6, fail, a division by zero
+        0x0204e533, // rem a0, s1, zero
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x07f51e63, // bne a0, t6, fail
+        0xfff00293, // li t0, -1
+        0x02594533, // div a0, s2, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x07f51463, // bne a0, t6, fail, an overflow
+        0x02596533, // rem a0, s2, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x048d3f83, // ld t6, 72(s10)
+        0x05f51c63, // bne a0, t6, fail
+        0x0299053b, // mulw a0, s2, s1
+        0x001d8d93, // addi s11, s11, 1
+        0x050d3f83, // ld t6, 80(s10)
+        0x05f51463, // bne a0, t6, fail
+        0x80000337, 0xfff3031b, // li t1, 0x7fffffff
+        0x00200393, // li t2, 2
+        0x0273053b, // mulw a0, t1, t2
+        0x001d8d93, // addi s11, s11, 1
+        0x058d3f83, // ld t6, 88(s10)
+        0x03f51663, // bne a0, t6, fail, the low word, sign-extended
+        0x0294553b, // divuw a0, s0, s1
+        0x001d8d93, // addi s11, s11, 1
+        0x060d3f83, // ld t6, 96(s10)
+        0x01f51e63, // bne a0, t6, fail
+        0x0294653b, // remw a0, s0, s1
+        0x001d8d93, // addi s11, s11, 1
+        0x068d3f83, // ld t6, 104(s10)
+        0x01f51663, // bne a0, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0xffffffeb, 0xffffffff, // expected 0xffffffffffffffeb
+        0x00000000, 0x40000000, // expected 0x4000000000000000
+        0x00000006, 0x00000000, // expected 0x6
+        0xffffffff, 0xffffffff, // expected 0xffffffffffffffff
+        0xfffffffe, 0xffffffff, // expected 0xfffffffffffffffe
+        0x00000001, 0x00000000, // expected 0x1
+        0xffffffff, 0xffffffff, // expected 0xffffffffffffffff
+        0x00000007, 0x00000000, // expected 0x7
+        0x00000000, 0x80000000, // expected 0x8000000000000000
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000000, 0x00000000, // expected 0x0
+        0xfffffffe, 0xffffffff, // expected 0xfffffffffffffffe
+        0x24924924, 0x00000000, // expected 0x24924924
+        0xfffffffd, 0xffffffff, // expected 0xfffffffffffffffd
+    ];
+
+    // a division by zero gives all ones or the dividend, and an overflow the most negative
+    // number, without a trap
+    assert_eq!(run(&Config::default(), &program), 0);
+    // without M, mul is an illegal instruction
+    let config = Config {
+        isa: Isa::parse("rv64i_zicsr").unwrap(),
+        ..Config::default()
+    };
+    assert_eq!(run(&config, &program), 1);
+}
+
+#[test]
+fn compressed() {
+    let program: [u32; 88] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x114d0d13, // la s10, expected
+        0x00000297, 0x0f028293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x00040137, 0x0011011b, 0x00d11113, // li sp, 0x80002000
+        0x051d556d, // c.li a0, -5; c.addi a0, 7
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x0bf51863, // bne a0, t6, fail
+        0x862a65c9, // c.lui a1, 0x12; c.mv a2, a0
+        0x0612962e, // c.add a2, a1; c.slli a2, 4
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x09f61e63, // bne a2, t6, fail
+        0x66a2e432, // c.sdsp a2, 8; c.ldsp a3, 8
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x09f69663, // bne a3, t6, fail
+        0x800006b7, 0xfff6869b, // li a3, 0x7fffffff
+        0x00012685, // c.addiw a3, 1; c.nop, the word is sign-extended
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x07f69a63, // bne a3, t6, fail
+        0x00000413, // li s0, 0
+        0x4505c011, // c.beqz s0, 4; c.li a0, 1, the branch skips c.li
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x07f51063, // bne a0, t6, fail
+        0x07130001, // c.nop; the low half of li a4, 42
+        0x000102a0, // the high half of li a4, 42; c.nop
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x05f71663, // bne a4, t6, fail
+        0x00000297, 0x00c28293, // la t0, target
+        0x00019282, // c.jalr t0; c.nop
+        0x00000317, 0xffc30313, // la t1, back
+        0x406083b3, // sub t2, ra, t1
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x03f39463, // bne t2, t6, fail, the link of c.jalr is the next half
+        0x00010000, // c.illegal; c.nop
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x01fc9c63, // bne s9, t6, fail
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x01fc1663, // bne s8, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0x00000002, 0x00000000, // expected 0x2
+        0x00120020, 0x00000000, // expected 0x120020
+        0x00120020, 0x00000000, // expected 0x120020
+        0x80000000, 0xffffffff, // expected 0xffffffff80000000
+        0x00000002, 0x00000000, // expected 0x2
+        0x0000002a, 0x00000000, // expected 0x2a
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000000, 0x00000000, // expected 0x0
+    ];
+
+    // a 32-bit instruction may start at any half, and the illegal instruction 0 traps with
+    // mtval 0 before the handler skips it and the c.nop after it
+    assert_eq!(run(&Config::default(), &program), 0);
+    // without C, the halves are illegal instructions
+    let config = Config {
+        isa: Isa::parse("rv64imafd_zicsr").unwrap(),
+        ..Config::default()
+    };
+    assert_eq!(run(&config, &program), 1);
+}
+
+#[test]
+fn fence_i() {
+    let program: [u32; 38] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x084d0d13, // la s10, expected
+        0x00000297, 0x06428293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x00000297, 0x02428293, // la t0, patch
+        0x02a00337, 0x5133031b, // li t1, 0x02a00513, li a0, 42
+        0x0062a023, // sw t1, 0(t0)
+        0x0000100f, // fence.i
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x01fc9e63, // bne s9, t6, fail
+        0x00000513, // li a0, 0
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x01f51663, // bne a0, t6, fail, the new instruction
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000000, 0x00000000, // expected 0x0
+        0x0000002a, 0x00000000, // expected 0x2a
+    ];
+    assert_eq!(run(&Config::default(), &program), 0);
+    // without Zifencei, fence.i is an illegal instruction
+    let config = Config {
+        isa: Isa::parse("rv64i_zicsr").unwrap(),
+        ..Config::default()
+    };
+    assert_eq!(run(&config, &program), 1);
+}
+
+#[test]
+fn pc_wraps_around() {
+    let program: [u32; 36] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x084d0d13, // la s10, expected
+        0x00000297, 0x06028293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x007002b7, 0x51328293, // li t0, 0x00700513, li a0, 7
+        0xffc00313, // li t1, -4, 0xfffffffc, the last word of the address space
+        0x00532023, // sw t0, 0(t1)
+        0x00000497, 0x01848493, // la s1, back
+        0x000482b7, 0x06728293, // li t0, 0x00048067, jr s1
+        0x00502023, // sw t0, 0(zero)
+        0x00030067, // jr t1
+        0x001d8d93, // addi s11, s11, 1
+        0x000d2f83, // lw t6, 0(s10)
+        0x01f51663, // bne a0, t6, fail, once the pc has wrapped around to 0
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x80001337, // li t1, 0x80001000
+        0x00532023, // sw t0, 0(t1)
+        0x00032223, // sw zero, 4(t1), the high word completes the command
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0x00000007, 0x00000000, // expected 0x7
+    ];
+    let config = Config {
+        isa: Isa::parse("rv32i_zicsr").unwrap(),
+        regions: vec![Region::ram(0, 0x1000), Region::ram(0xffff_f000, 0x1000)],
+        ..Config::default()
+    };
+    assert_eq!(run(&config, &program), 0);
+}
+
+#[test]
+fn user_xlen() {
+    let program: [u32; 90] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x124d0d13, // la s10, expected
+        0x00000297, 0x0ec28293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x300022f3, // csrr t0, mstatus
+        0x00300313, 0x02031313, // li t1, 0x300000000, flips mstatus.UXL from 64 to 32 bits
+        0x0062c2b3, // xor t0, t0, t1
+        0x30029073, // csrw mstatus, t0
+        0x000022b7, 0x8002829b, // li t0, 0x1800, mstatus.MPP
+        0x3002b073, // csrc mstatus, t0
+        0x00000297, 0x01028293, // la t0, user
+        0x34129073, // csrw mepc, t0
+        0x30200073, // mret
+        0x80000537, // lui a0, 0x80000
+        0xfff50513, // addi a0, a0, -1, 0x7fffffff
+        0x00150593, // addi a1, a0, 1
+        0xfff00613, // addi a2, zero, -1
+        0x00165613, // srli a2, a2, 1
+        0x00151693, // slli a3, a0, 1
+        0x00b58733, // add a4, a1, a1
+        0x00b537b3, // sltu a5, a0, a1
+        0x000d3803, // ld a6, 0(s10)
+        0x000c8b93, // mv s7, s9
+        0x00000073, // ecall
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x07f51063, // bne a0, t6, fail
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x05f59a63, // bne a1, t6, fail, sign-extended
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x05f61463, // bne a2, t6, fail, 32 bits shifted
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x03f69e63, // bne a3, t6, fail
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x03f71863, // bne a4, t6, fail, wrapped at 32 bits
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x03f79263, // bne a5, t6, fail
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x01fb9c63, // bne s7, t6, fail, ld is illegal in RV32
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x01fc9663, // bne s9, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x00800f13, // li t5, 8, an ecall from U-mode returns to M-mode
+        0x01ec9863, // bne s9, t5, back
+        0x00002f37, 0x800f0f1b, // li t5, 0x1800
+        0x300f2073, // csrs mstatus, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0x7fffffff, 0x00000000, // expected 0x7fffffff
+        0x80000000, 0xffffffff, // expected 0xffffffff80000000
+        0x7fffffff, 0x00000000, // expected 0x7fffffff
+        0xfffffffe, 0xffffffff, // expected 0xfffffffffffffffe
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000001, 0x00000000, // expected 0x1
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000008, 0x00000000, // expected 0x8
+    ];
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn reserved_mpp() {
+    let program: [u32; 60] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x0d4d0d13, // la s10, expected
+        0x00000297, 0x0b428293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x000022b7, 0x8002829b, // li t0, 0x1800
+        0x3002b073, // csrc mstatus, t0
+        0x000012b7, // li t0, 0x1000
+        0x3002a073, // csrs mstatus, t0, the reserved mode 2
+        0x30002573, // csrr a0, mstatus
+        0x00b55513, // srli a0, a0, 11
+        0x00357513, // andi a0, a0, 3
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x07f51263, // bne a0, t6, fail, MPP keeps U-mode
+        0x000022b7, 0x8002829b, // li t0, 0x1800
+        0x3002a073, // csrs mstatus, t0
+        0x000012b7, 0x8002829b, // li t0, 0x800
+        0x3002b073, // csrc mstatus, t0, from M-mode to the reserved mode again
+        0x30002573, // csrr a0, mstatus
+        0x00b55513, // srli a0, a0, 11
+        0x00357513, // andi a0, a0, 3
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x03f51a63, // bne a0, t6, fail
+        0x00000297, 0x01028293, // la t0, machine
+        0x34129073, // csrw mepc, t0
+        0x30200073, // mret
+        0x30002573, // csrr a0, mstatus
+        0x00b55513, // srli a0, a0, 11
+        0x00357513, // andi a0, a0, 3
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x01f51663, // bne a0, t6, fail, mret has returned to M-mode, where a CSR of M-mode can be read
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000003, 0x00000000, // expected 0x3
+        0x00000000, 0x00000000, // expected 0x0
+    ];
+
+    // a write of the reserved mode 2 leaves MPP as it was, so that mret returns to a mode
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn sv32() {
+    let program: [u32; 118] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x194d0d13, // la s10, expected
+        0x00000297, 0x17028293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x80004437, // li s0, 0x80004000
+        0x200002b7, 0x0cf28293, // li t0, 0x200000cf
+        0x80005937, 0x80090913, // li s2, 0x80004800
+        0x00592023, // sw t0, 0(s2), the megapage of the program, mapped to itself
+        0x200012b7, 0x40128293, // li t0, 0x20001401
+        0x00542023, // sw t0, 0(s0), a pointer to the table at 0x80005000
+        0x200002b7, 0x40328293, // li t0, 0x20000403
+        0x00542223, // sw t0, 4(s0), a megapage at 0x400000 whose physical page is misaligned
+        0x800054b7, // li s1, 0x80005000
+        0x200012b7, 0xc0728293, // li t0, 0x20000c07
+        0x0054a023, // sw t0, 0(s1), 0x0 to 0x80003000, readable and writable by S-mode
+        0x200012b7, 0x81728293, // li t0, 0x20000817
+        0x0054a223, // sw t0, 4(s1), 0x1000 to 0x80002000, of U-mode
+        0x800802b7, 0x00428293, // li t0, 0x80080004
+        0x18029073, // csrw satp, t0
+        0x18002573, // csrr a0, satp
+        0x001d8d93, // addi s11, s11, 1
+        0x000d2f83, // lw t6, 0(s10)
+        0x0ff51463, // bne a0, t6, fail
+        0x800039b7, // li s3, 0x80003000
+        0x112232b7, 0x34428293, // li t0, 0x11223344
+        0x80004ab7, 0xffca8a93, // li s5, 0x80003ffc
+        0x005aa023, // sw t0, 0(s5)
+        0x80002a37, // li s4, 0x80002000
+        0x556672b7, 0x78828293, // li t0, 0x55667788
+        0x005a2023, // sw t0, 0(s4)
+        0x000212b7, 0x80028293, // li t0, 0x20800
+        0x3002a073, // csrs mstatus, t0, MPRV with MPP set to S-mode
+        0x00001537, 0x23450513, // li a0, 0x1234
+        0x00a02023, // sw a0, 0(zero)
+        0x00002583, // lw a1, 0(zero)
+        0x001d8d93, // addi s11, s11, 1
+        0x008d2f83, // lw t6, 8(s10)
+        0x09f59c63, // bne a1, t6, fail
+        0x000202b7, // li t0, 0x20000
+        0x3002b073, // csrc mstatus, t0
+        0x0009a583, // lw a1, 0(s3)
+        0x001d8d93, // addi s11, s11, 1
+        0x010d2f83, // lw t6, 16(s10)
+        0x09f59063, // bne a1, t6, fail, at the physical address
+        0x0004a603, // lw a2, 0(s1)
+        0x0c067613, // andi a2, a2, 0xc0
+        0x001d8d93, // addi s11, s11, 1
+        0x018d2f83, // lw t6, 24(s10)
+        0x07f61663, // bne a2, t6, fail, the store set A and D
+        0x0044a603, // lw a2, 4(s1)
+        0x0c067613, // andi a2, a2, 0xc0
+        0x001d8d93, // addi s11, s11, 1
+        0x020d2f83, // lw t6, 32(s10)
+        0x05f61c63, // bne a2, t6, fail
+        0x000602b7, // li t0, 0x60000
+        0x3002a073, // csrs mstatus, t0, MPRV and SUM
+        0x00001ab7, 0xffea8a93, // li s5, 0xffe
+        0x000aa583, // lw a1, 0(s5)
+        0x001d8d93, // addi s11, s11, 1
+        0x028d2f83, // lw t6, 40(s10)
+        0x03f59c63, // bne a1, t6, fail, crosses into the page of U-mode
+        0x00400ab7, // li s5, 0x400000
+        0x000aa583, // lw a1, 0(s5)
+        0x000012b7, 0x80028293, // li t0, 0x800
+        0x3002a073, // csrs mstatus, t0, mret has left MPP at U-mode
+        0x001d8d93, // addi s11, s11, 1
+        0x030d2f83, // lw t6, 48(s10)
+        0x01fc9c63, // bne s9, t6, fail, a misaligned megapage
+        0x001d8d93, // addi s11, s11, 1
+        0x038d2f83, // lw t6, 56(s10)
+        0x01fc1663, // bne s8, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x80001337, // li t1, 0x80001000
+        0x00532023, // sw t0, 0(t1)
+        0x00032223, // sw zero, 4(t1), the high word completes the command
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0x80080004, 0xffffffff, // expected 0xffffffff80080004
+        0x00001234, 0x00000000, // expected 0x1234
+        0x00001234, 0x00000000, // expected 0x1234
+        0x000000c0, 0x00000000, // expected 0xc0
+        0x00000000, 0x00000000, // expected 0x0
+        0x77881122, 0x00000000, // expected 0x77881122
+        0x0000000d, 0x00000000, // expected 0xd
+        0x00400000, 0x00000000, // expected 0x400000
+    ];
+
+    // the loads and stores of M-mode go through the page tables with MPRV, and a megapage must
+    // be aligned to its size
+    let config = Config {
+        isa: Isa::parse("rv32ima_zicsr").unwrap(),
+        ..Config::default()
+    };
+    assert_eq!(run(&config, &program), 0);
+}
+
+#[test]
+fn sv39() {
+    let program: [u32; 200] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x2b4d0d13, // la s10, expected
+        0x00000297, 0x27c28293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x00020437, 0x0014041b, 0x00e41413, // li s0, 0x80004000
+        0x200002b7, 0x0cf2829b, // li t0, 0x200000cf
+        0x00543823, // sd t0, 16(s0), the gigapage of the program, mapped to itself
+        0x200012b7, 0x4012829b, // li t0, 0x20001401
+        0x00543023, // sd t0, 0(s0), a pointer to the table at 0x80005000
+        0x000804b7, 0x0054849b, 0x00c49493, // li s1, 0x80005000
+        0x200022b7, 0x8012829b, // li t0, 0x20001801
+        0x0054b023, // sd t0, 0(s1), a pointer to the table at 0x80006000
+        0x00040937, 0x0039091b, 0x00d91913, // li s2, 0x80006000
+        0x200012b7, 0xc072829b, // li t0, 0x20000c07
+        0x00593023, // sd t0, 0(s2), 0x0 to 0x80003000, readable and writable by S-mode
+        0x200012b7, 0x8172829b, // li t0, 0x20000817
+        0x00593423, // sd t0, 8(s2), 0x1000 to 0x80002000, of U-mode
+        0xff900293, 0x02929293, 0x00128293, 0x01329293,
+        0x00428293, // li t0, 0x9000000000080004
+        0x18029073, // csrw satp, t0
+        0x18002573, // csrr a0, satp
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x1df51863, // bne a0, t6, fail, Sv48 is not implemented, so the write has no effect
+        0xfff00293, 0x02c29293, 0x00128293, 0x01329293,
+        0x00428293, // li t0, 0x8000000000080004
+        0x18029073, // csrw satp, t0
+        0x18002573, // csrr a0, satp
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x1bf51463, // bne a0, t6, fail
+        0x000809b7, 0x0039899b, 0x00c99993, // li s3, 0x80003000
+        0x004492b7, 0x8cd2829b, 0x00e29293, 0x45528293, 0x00c29293, 0x66728293, 0x00c29293,
+        0x78828293, // li t0, 0x1122334455667788
+        0x00020ab7, 0x001a8a9b, 0x00ea9a93, 0xff8a8a93, // li s5, 0x80003ff8
+        0x005ab023, // sd t0, 0(s5)
+        0x00040a37, 0x001a0a1b, 0x00da1a13, // li s4, 0x80002000
+        0xffe672b7, 0xaaf2829b, 0x00d29293, 0xe6728293, 0x00d29293, 0xdef28293, 0x01029293,
+        0xf0028293, // li t0, 0x99aabbccddeeff00
+        0x005a3023, // sd t0, 0(s4)
+        0x000212b7, 0x8002829b, // li t0, 0x20800
+        0x3002a073, // csrs mstatus, t0, MPRV with MPP set to S-mode
+        0x00001537, 0x2345051b, // li a0, 0x1234
+        0x00a02023, // sw a0, 0(zero)
+        0x00002583, // lw a1, 0(zero)
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x11f59863, // bne a1, t6, fail
+        0x000202b7, // li t0, 0x20000
+        0x3002b073, // csrc mstatus, t0
+        0x0009a583, // lw a1, 0(s3)
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x0ff59c63, // bne a1, t6, fail, at the physical address
+        0x00093603, // ld a2, 0(s2)
+        0x0c067613, // andi a2, a2, 0xc0
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x0ff61263, // bne a2, t6, fail, the store set A and D
+        0x3002a073, // csrs mstatus, t0
+        0x00001ab7, // li s5, 0x1000
+        0x000aa583, // lw a1, 0(s5)
+        0x000012b7, 0x8002829b, // li t0, 0x800
+        0x3002a073, // csrs mstatus, t0, mret has left MPP at U-mode
+        0x001d8d93, // addi s11, s11, 1
+        0x028d3f83, // ld t6, 40(s10)
+        0x0dfc9063, // bne s9, t6, fail, S-mode cannot load from U-mode without SUM
+        0x001d8d93, // addi s11, s11, 1
+        0x030d3f83, // ld t6, 48(s10)
+        0x0bfc1a63, // bne s8, t6, fail
+        0x000402b7, // li t0, 0x40000
+        0x3002a073, // csrs mstatus, t0, SUM
+        0x00001ab7, 0xffca8a9b, // li s5, 0xffc
+        0x000ab583, // ld a1, 0(s5)
+        0x001d8d93, // addi s11, s11, 1
+        0x038d3f83, // ld t6, 56(s10)
+        0x09f59a63, // bne a1, t6, fail, crosses into the next page
+        0x00002ab7, // li s5, 0x2000
+        0x00baa023, // sw a1, 0(s5)
+        0x000012b7, 0x8002829b, // li t0, 0x800
+        0x3002a073, // csrs mstatus, t0
+        0x001d8d93, // addi s11, s11, 1
+        0x040d3f83, // ld t6, 64(s10)
+        0x07fc9a63, // bne s9, t6, fail, not mapped
+        0x001d8d93, // addi s11, s11, 1
+        0x048d3f83, // ld t6, 72(s10)
+        0x07fc1463, // bne s8, t6, fail
+        0x001002b7, // li t0, 0x100000
+        0x3002a073, // csrs mstatus, t0, TVM
+        0x00000297, 0x01028293, // la t0, supervisor
+        0x34129073, // csrw mepc, t0
+        0x30200073, // mret
+        0x00000c93, // li s9, 0
+        0x12000073, // sfence.vma
+        0x001d8d93, // addi s11, s11, 1
+        0x050d3f83, // ld t6, 80(s10)
+        0x03fc9e63, // bne s9, t6, fail
+        0x00000c93, // li s9, 0
+        0x18002573, // csrr a0, satp
+        0x001d8d93, // addi s11, s11, 1
+        0x058d3f83, // ld t6, 88(s10)
+        0x03fc9463, // bne s9, t6, fail
+        0x00000073, // ecall
+        0x30002573, // csrr a0, mstatus
+        0x01155513, // srli a0, a0, 17
+        0x00157513, // andi a0, a0, 1
+        0x001d8d93, // addi s11, s11, 1
+        0x060d3f83, // ld t6, 96(s10)
+        0x01f51663, // bne a0, t6, fail, returning to S-mode has cleared MPRV
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x00900f13, // li t5, 9, an ecall from S-mode returns to M-mode
+        0x01ec9863, // bne s9, t5, back
+        0x00002f37, 0x800f0f1b, // li t5, 0x1800
+        0x300f2073, // csrs mstatus, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0x00000000, 0x00000000, // expected 0x0
+        0x00080004, 0x80000000, // expected 0x8000000000080004
+        0x00001234, 0x00000000, // expected 0x1234
+        0x00001234, 0x00000000, // expected 0x1234
+        0x000000c0, 0x00000000, // expected 0xc0
+        0x0000000d, 0x00000000, // expected 0xd
+        0x00001000, 0x00000000, // expected 0x1000
+        0x11223344, 0xddeeff00, // expected 0xddeeff0011223344
+        0x0000000f, 0x00000000, // expected 0xf
+        0x00002000, 0x00000000, // expected 0x2000
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000002, 0x00000000, // expected 0x2
+        0x00000000, 0x00000000, // expected 0x0
+    ];
+    assert_eq!(run(&Config::default(), &program), 0);
+}
+
+#[test]
+fn zfhmin() {
+    let program: [u32; 64] = [
+        0x00000d93, // li s11, 0
+        0x00000d17, 0x0d4d0d13, // la s10, expected
+        0x00000297, 0x0b028293, // la t0, handler
+        0x30529073, // csrw mtvec, t0
+        0x000022b7, // li t0, 0x2000, mstatus.FS
+        0x3002a073, // csrs mstatus, t0
+        0x00040437, 0x0014041b, 0x00d41413, // li s0, 0x80002000
+        0x000042b7, 0xc002829b, // li t0, 0x3c00, 1.0
+        0x00541023, // sh t0, 0(s0)
+        0x00041007, // flh ft0, 0(s0)
+        0x402070d3, // fcvt.s.h ft1, ft0
+        0xe00083d3, // fmv.x.w t2, ft1
+        0x001d8d93, // addi s11, s11, 1
+        0x000d3f83, // ld t6, 0(s10)
+        0x05f39a63, // bne t2, t6, fail
+        0x0010f0d3, // fadd.s ft1, ft1, ft1
+        0x4400f153, // fcvt.h.s ft2, ft1
+        0xe40103d3, // fmv.x.h t2, ft2
+        0x001d8d93, // addi s11, s11, 1
+        0x008d3f83, // ld t6, 8(s10)
+        0x03f39e63, // bne t2, t6, fail, 2.0
+        0x00241127, // fsh ft2, 2(s0)
+        0x00245383, // lhu t2, 2(s0)
+        0x001d8d93, // addi s11, s11, 1
+        0x010d3f83, // ld t6, 16(s10)
+        0x03f39463, // bne t2, t6, fail
+        0x001d8d93, // addi s11, s11, 1
+        0x018d3f83, // ld t6, 24(s10)
+        0x01fc9e63, // bne s9, t6, fail
+        0x042171d3, // fadd.h ft3, ft2, ft2, only in Zfh
+        0x001d8d93, // addi s11, s11, 1
+        0x020d3f83, // ld t6, 32(s10)
+        0x01fc9663, // bne s9, t6, fail
+        0x00100293, // li t0, 1
+        0x00c0006f, // j report
+        0x001d9293, // slli t0, s11, 1
+        0x0012e293, // ori t0, t0, 1
+        0x00080337, 0x0013031b, 0x00c31313, // li t1, 0x80001000
+        0x00533023, // sd t0, 0(t1)
+        0x0000006f, // j hang
+        0x34202cf3, // csrr s9, mcause
+        0x34302c73, // csrr s8, mtval
+        0x34102f73, // csrr t5, mepc
+        0x004f0f13, // addi t5, t5, 4
+        0x341f1073, // csrw mepc, t5
+        0x30200073, // mret
+        0x00000013, // nop
+        0x3f800000, 0x00000000, // expected 0x3f800000
+        0x00004000, 0x00000000, // expected 0x4000
+        0x00004000, 0x00000000, // expected 0x4000
+        0x00000000, 0x00000000, // expected 0x0
+        0x00000002, 0x00000000, // expected 0x2
+    ];
+    let config = Config {
+        isa: Isa::parse("rv64if_zicsr_zfhmin").unwrap(),
+        ..Config::default()
+    };
+    assert_eq!(run(&config, &program), 0);
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use std::{
+    env, fs,
+    io::Read,
+    process::{self, Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::image;
+
+// How long the boots may take to reach the prompt.
+const LINUX_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const BOOT_FLOW_TIMEOUT: Duration = Duration::from_secs(60);
+const PROMPT: &str = "# ";
+// The environment variables of the images of the Linux boot.
+const IMAGES: [&str; 3] = [
+    "MINIMUM_FIVE_OPENSBI",
+    "MINIMUM_FIVE_KERNEL",
+    "MINIMUM_FIVE_INITRD",
+];
+
+// Boots Linux through OpenSBI to the prompt of a shell, on images built locally and given by
+//   MINIMUM_FIVE_OPENSBI  fw_jump.bin or fw_dynamic.bin of OpenSBI for PLATFORM=generic
+//   MINIMUM_FIVE_KERNEL   arch/riscv/boot/Image of Linux
+//   MINIMUM_FIVE_INITRD   an initramfs whose init starts a shell on the console
+// as the README describes. As the images are not part of the repository, the boot is opt-in: the
+// test passes without booting when none of them is set.
+#[test]
+fn linux() {
+    let images = IMAGES.map(|name| env::var(name).ok());
+    if images.iter().all(Option::is_none) {
+        eprintln!("not booting Linux, as {} are not set", IMAGES.join(", "));
+        return;
+    }
+    let [opensbi, kernel, initrd] = images
+        .map(|image| image.unwrap_or_else(|| panic!("{} must all be set", IMAGES.join(", "))));
+    let options = ["--memory-size", "256M"];
+    boot(&options, &opensbi, &kernel, &initrd, LINUX_TIMEOUT);
+}
+
+// Runs the same boot flow on images small enough to run on every test: the boot ROM passes
+// fw_dynamic_info to a firmware that enters the kernel in S-mode, which checks the device tree,
+// turns on Sv39 and prints the prompt that the initrd holds on the UART.
+#[test]
+fn boot_flow() {
+    let firmware: [u32; 13] = [
+        0x00066283, // lwu t0, 0(a2)
+        0x49425337, 0x34f3031b, // li t1, 0x4942534f
+        0x02629263, // bne t0, t1, hang, a2 holds fw_dynamic_info
+        0x01063283, // ld t0, 16(a2)
+        0x34129073, // csrw mepc, t0, the address of the kernel
+        0x000012b7, // li t0, 0x1000
+        0x3002b073, // csrc mstatus, t0
+        0x000012b7, 0x8002829b, // li t0, 0x800
+        0x3002a073, // csrs mstatus, t0, which runs in S-mode
+        0x30200073, // mret
+        0x0000006f, // j hang
+    ];
+    let kernel: [u32; 30] = [
+        0x0005e283, // lwu t0, 0(a1)
+        0x000ee337, 0xfe13031b, 0x00c31313, 0xdd030313, // li t1, 0xedfe0dd0
+        0x04629e63, // bne t0, t1, hang, a1 holds the device tree
+        0x00001417, // auipc s0, 1
+        0x00c45413, // srli s0, s0, 12
+        0x00c41413, // slli s0, s0, 12, the next page, for the root table
+        0x0cf00293, // li t0, 0xcf
+        0x00543023, // sd t0, 0(s0), the devices, mapped to themselves by a gigapage
+        0x200002b7, 0x0cf2829b, // li t0, 0x200000cf
+        0x00543823, // sd t0, 16(s0), and the memory
+        0x00c45313, // srli t1, s0, 12
+        0xfff00293, 0x03f29293, // li t0, 0x8000000000000000
+        0x0062e2b3, // or t0, t0, t1
+        0x18029073, // csrw satp, t0, Sv39
+        0x12000073, // sfence.vma
+        0x20100493, 0x01649493, // li s1, 0x80400000
+        0x10000937, // li s2, 0x10000000
+        0x0004c283, // lbu t0, 0(s1), the prompt that the initrd holds
+        0x00028863, // beqz t0, hang
+        0x00590023, // sb t0, 0(s2)
+        0x00148493, // addi s1, s1, 1
+        0xff1ff06f, // j print
+        0x10500073, // wfi
+        0xffdff06f, // j hang
+    ];
+
+    let directory = env::temp_dir().join(format!("minimum-five-boot-{}", process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let path = |name: &str| directory.join(name).to_str().unwrap().to_string();
+    fs::write(path("firmware.bin"), image(&firmware)).unwrap();
+    fs::write(path("Image"), image(&kernel)).unwrap();
+    fs::write(path("initrd"), PROMPT).unwrap();
+    // the initrd is halfway through the memory, at 0x80400000
+    boot(
+        &["--memory-size", "8M"],
+        &path("firmware.bin"),
+        &path("Image"),
+        &path("initrd"),
+        BOOT_FLOW_TIMEOUT,
+    );
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+// Boots the firmware, kernel and initrd on the platform of the README, and waits for the prompt
+// on the console until the timeout.
+fn boot(options: &[&str], firmware: &str, kernel: &str, initrd: &str, timeout: Duration) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_minimum-five"))
+        .args(options)
+        .args(["--uart", "0x10000000"])
+        .args(["--clint", "0x2000000"])
+        .args(["--plic", "0xc000000"])
+        .args(["--kernel", kernel, "--initrd", initrd])
+        .args(["--bootargs", "console=ttyS0 earlycon=sbi"])
+        .arg(firmware)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdout = child.stdout.take().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = [0; 4096];
+        while let Ok(count @ 1..) = stdout.read(&mut buffer) {
+            if sender.send(buffer[..count].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+    let deadline = Instant::now() + timeout;
+    let mut console = Vec::new();
+    let booted = loop {
+        if String::from_utf8_lossy(&console).contains(PROMPT) {
+            break true;
+        }
+        match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(data) => console.extend(data),
+            Err(_) => break false,
+        }
+    };
+    let _ = child.kill();
+    let _ = child.wait();
+    assert!(
+        booted,
+        "no prompt on the console:\n{}",
+        String::from_utf8_lossy(&console)
+    );
+}
@@ -0,0 +1,20 @@
+use std::path::Path;
+
+use minimum_five::{
+    config::Config,
+    riscv_tests::{report, run_directory, Outcome, DEFAULT_STEP_LIMIT},
+};
+
+#[test]
+fn riscv_tests() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/isa");
+    let results = run_directory(&directory, &Config::default(), DEFAULT_STEP_LIMIT, None).unwrap();
+    println!("{}", report(&results));
+
+    let failures: Vec<_> = results
+        .iter()
+        .filter(|result| result.outcome != Outcome::Pass)
+        .map(|result| format!("{} {:?}", result.name, result.outcome))
+        .collect();
+    assert!(failures.is_empty(), "{:#?}", failures);
+}